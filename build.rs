@@ -4,7 +4,9 @@ fn main() {
     println!("slint build");
     slint_build::compile_with_config(
         "ui/image_sieve.slint",
-        slint_build::CompilerConfiguration::new().with_style(String::from("fluent")),
+        slint_build::CompilerConfiguration::new()
+            .with_style(String::from("fluent"))
+            .with_bundled_translations("lang"),
     )
     .unwrap();
     if cfg!(target_os = "windows") {
@@ -6,8 +6,9 @@ use std::{
 use slint::{Model, SharedString};
 
 use crate::{
-    item_sort_list::{self, parse_date, ItemList},
+    item_sort_list::{self, parse_date, ItemList, EVENT_DATE_FORMAT},
     main_window,
+    misc::translations::{tr, tr_format},
 };
 
 use super::helper;
@@ -15,6 +16,9 @@ use super::helper;
 pub struct EventsController {
     item_list: Arc<Mutex<ItemList>>,
     events_model: Rc<slint::VecModel<main_window::Event>>,
+    histogram_model: Rc<slint::VecModel<main_window::HistogramBar>>,
+    /// Keeps the events sorted by their start date, otherwise they keep the order they were arranged in
+    sort_events: bool,
 }
 
 impl EventsController {
@@ -22,29 +26,80 @@ impl EventsController {
         Self {
             item_list,
             events_model: Rc::new(slint::VecModel::<main_window::Event>::default()),
+            histogram_model: Rc::new(slint::VecModel::<main_window::HistogramBar>::default()),
+            sort_events: true,
         }
     }
 
     /// Synchronize the event list with the GUI model
     pub fn synchronize(&mut self) {
+        {
+            let item_list = self.item_list.lock().unwrap();
+            let model_count = self.events_model.row_count();
+            // Event model
+            for (index, event) in item_list.events.iter().enumerate() {
+                let _event = main_window::Event {
+                    name: SharedString::from(event.name.clone()),
+                    start_date: SharedString::from(event.start_date_as_string()),
+                    end_date: SharedString::from(event.end_date_as_string()),
+                    target_directory: SharedString::from(event.target_directory.clone()),
+                    folder: SharedString::from(event.folder.clone()),
+                };
+                if index >= model_count {
+                    self.events_model.push(_event);
+                } else {
+                    self.events_model.set_row_data(index, _event);
+                }
+            }
+        }
+        self.synchronize_histogram();
+    }
+
+    /// Synchronize the number of items per day with the histogram model
+    pub fn synchronize_histogram(&mut self) {
         let item_list = self.item_list.lock().unwrap();
-        let model_count = self.events_model.row_count();
-        // Event model
-        for (index, event) in item_list.events.iter().enumerate() {
-            let _event = main_window::Event {
-                name: SharedString::from(event.name.clone()),
-                start_date: SharedString::from(event.start_date_as_string()),
-                end_date: SharedString::from(event.end_date_as_string()),
-            };
-            if index >= model_count {
-                self.events_model.push(_event);
-            } else {
-                self.events_model.set_row_data(index, _event);
+        let counts = item_list.count_per_day();
+        let max_count = counts.iter().map(|(_, count)| *count).max().unwrap_or(1);
+        let bars: Vec<main_window::HistogramBar> = counts
+            .iter()
+            .map(|(date, count)| main_window::HistogramBar {
+                date: SharedString::from(date.format(EVENT_DATE_FORMAT).to_string()),
+                count: *count as i32,
+                height: *count as f32 / max_count as f32,
+                in_event: item_list.events.iter().any(|event| event.contains(date)),
+            })
+            .collect();
+        self.histogram_model.set_vec(bars);
+    }
+
+    /// Turns sorting the events by their start date on or off, the events are sorted right away when turned on
+    pub fn set_sort_events(&mut self, sort_events: bool) {
+        self.sort_events = sort_events;
+        if sort_events {
+            self.item_list.lock().unwrap().events.sort_unstable();
+            self.synchronize();
+        }
+    }
+
+    /// Moves an event to another position in the list of events, which is only possible if the events are not
+    /// sorted by their start date
+    pub fn move_event(&mut self, index: i32, new_index: i32) {
+        if self.sort_events || index < 0 || new_index < 0 {
+            return;
+        }
+        let (index, new_index) = (index as usize, new_index as usize);
+        {
+            let mut item_list = self.item_list.lock().unwrap();
+            if index >= item_list.events.len() || new_index >= item_list.events.len() {
+                return;
             }
+            let event = item_list.events.remove(index);
+            item_list.events.insert(new_index, event);
         }
+        self.synchronize();
     }
 
-    /// Add an event to the item list and to the events model and sorts the lists
+    /// Add an event to the item list and to the events model and sorts the lists if the events are kept sorted
     pub fn add_event(&mut self, name: &str, start_date: &str, end_date: &str) -> SharedString {
         if let Err(error) = self.check_event(start_date, end_date, None) {
             error
@@ -53,7 +108,9 @@ impl EventsController {
             {
                 let mut item_list = self.item_list.lock().unwrap();
                 item_list.events.push(event);
-                item_list.events.sort_unstable();
+                if self.sort_events {
+                    item_list.events.sort_unstable();
+                }
             }
             self.synchronize();
             SharedString::from("")
@@ -75,18 +132,104 @@ impl EventsController {
             {
                 let mut item_list = self.item_list.lock().unwrap();
                 assert!(item_list.events[index].update(name, start_date, end_date));
-                item_list.events.sort_unstable();
+                if self.sort_events {
+                    item_list.events.sort_unstable();
+                }
             };
             self.synchronize();
             SharedString::from("")
         }
     }
 
+    /// Sets the directory the items of an event are sieved to, an empty string uses the target directory
+    pub fn set_target_directory(&mut self, index: i32, target_directory: &str) {
+        self.item_list.lock().unwrap().events[index as usize].target_directory =
+            String::from(target_directory);
+        self.synchronize();
+    }
+
     /// Removes an event from the item list and the events model
     pub fn remove_event(&mut self, index: i32) {
-        let mut item_list = self.item_list.lock().unwrap();
-        item_list.events.remove(index as usize);
-        self.events_model.remove(index as usize);
+        {
+            let mut item_list = self.item_list.lock().unwrap();
+            item_list.events.remove(index as usize);
+            self.events_model.remove(index as usize);
+        }
+        self.synchronize_histogram();
+    }
+
+    /// Merges two events into one spanning both date ranges. The merged event keeps the name of the first event and
+    /// its target directory, if set. Returns an error if the merged event overlaps with another event.
+    /// Creates an event for each folder of the items without one and returns the number of created events
+    pub fn create_folder_events(&mut self) -> usize {
+        let created = {
+            let mut item_list = self.item_list.lock().unwrap();
+            let created = item_list.create_folder_events();
+            if self.sort_events {
+                item_list.events.sort_unstable();
+            }
+            created
+        };
+        self.synchronize();
+        created
+    }
+
+    pub fn merge_events(&mut self, first: i32, second: i32) -> SharedString {
+        let (first, second) = (first as usize, second as usize);
+        {
+            let mut item_list = self.item_list.lock().unwrap();
+            if first == second
+                || first >= item_list.events.len()
+                || second >= item_list.events.len()
+            {
+                return SharedString::from(tr("Select two different events to merge"));
+            }
+            let second_event = item_list.events[second].clone();
+            let mut merged_event = item_list.events[first].clone();
+            merged_event.start_date = merged_event.start_date.min(second_event.start_date);
+            merged_event.end_date = merged_event.end_date.max(second_event.end_date);
+            if merged_event.target_directory.is_empty() {
+                merged_event.target_directory = second_event.target_directory;
+            }
+
+            // Events between the merged ones would be contained in the merged event
+            if let Some((_, event)) = item_list.events.iter().enumerate().find(|(index, event)| {
+                *index != first
+                    && *index != second
+                    && !event.is_folder_event()
+                    && !merged_event.is_folder_event()
+                    && event.start_date <= merged_event.end_date
+                    && merged_event.start_date <= event.end_date
+            }) {
+                return SharedString::from(tr_format(
+                    "Merged event overlaps with {}",
+                    &[&event.name],
+                ));
+            }
+
+            item_list.events[first] = merged_event;
+            item_list.events.remove(second);
+            if self.sort_events {
+                item_list.events.sort_unstable();
+            }
+            self.events_model.remove(second);
+        }
+        self.synchronize();
+        SharedString::from("")
+    }
+
+    /// Keeps or discards all items of an event and returns a message how many items were changed
+    pub fn set_take_over(&mut self, index: i32, take_over: bool) -> SharedString {
+        let count = self
+            .item_list
+            .lock()
+            .unwrap()
+            .set_take_over_of_event(index as usize, take_over);
+        SharedString::from(if take_over {
+            tr_format("✔ Kept {} images and videos", &[&count])
+        } else {
+            tr_format("🗑 Discarded {} images and videos", &[&count])
+        })
     }
 
     /// Returns the contained slint VecModel
@@ -94,9 +237,15 @@ impl EventsController {
         self.events_model.clone()
     }
 
-    /// Clear the events model
+    /// Returns the slint VecModel with the number of items per day
+    pub fn get_histogram_model(&self) -> Rc<slint::VecModel<main_window::HistogramBar>> {
+        self.histogram_model.clone()
+    }
+
+    /// Clear the events and histogram model
     pub fn clear(&mut self) {
         helper::clear_model(self.events_model.clone());
+        helper::clear_model(self.histogram_model.clone());
     }
 
     /// Check the validity of an event
@@ -108,18 +257,21 @@ impl EventsController {
     ) -> Result<(), SharedString> {
         let start_date = parse_date(start_date);
         if let Err(start_date) = start_date {
-            return Err(SharedString::from(format!("Start date: {}", start_date)));
+            return Err(SharedString::from(tr_format(
+                "Start date: {}",
+                &[&start_date],
+            )));
         }
         let start_date = start_date.unwrap();
 
         let end_date = parse_date(end_date);
         if let Err(end_date) = end_date {
-            return Err(SharedString::from(format!("End date: {}", end_date)));
+            return Err(SharedString::from(tr_format("End date: {}", &[&end_date])));
         }
         let end_date = end_date.unwrap();
 
         if start_date > end_date {
-            return Err(SharedString::from("Start date must be before end date"));
+            return Err(SharedString::from(tr("Start date must be before end date")));
         }
 
         let item_list = self.item_list.lock().unwrap();
@@ -127,10 +279,14 @@ impl EventsController {
             if event_index.is_some() && index == event_index.unwrap() {
                 continue;
             }
-            if event.contains(&start_date) || event.contains(&end_date) {
-                return Err(SharedString::from(
-                    String::from("Event overlaps with ") + &event.name,
-                ));
+            // Events of folders match their items by the folder, so they may overlap other events
+            if !event.is_folder_event()
+                && (event.contains(&start_date) || event.contains(&end_date))
+            {
+                return Err(SharedString::from(tr_format(
+                    "Event overlaps with {}",
+                    &[&event.name],
+                )));
             }
         }
         Ok(())
@@ -142,6 +298,7 @@ mod tests {
     use chrono::Datelike;
 
     use super::*;
+    use crate::item_sort_list::FileItem;
 
     #[test]
     fn test_synchronize() {
@@ -183,6 +340,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_histogram() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut events_controller = EventsController::new(item_list.clone());
+        {
+            let mut item_list = item_list.lock().unwrap();
+            item_list.items.push(FileItem::dummy("test1.jpg", 0, true));
+            item_list
+                .items
+                .push(FileItem::dummy("test2.jpg", 3600, true));
+            item_list
+                .items
+                .push(FileItem::dummy("test3.jpg", 24 * 3600, true));
+        }
+        events_controller.synchronize();
+        let histogram_model = events_controller.get_histogram_model();
+        assert_eq!(histogram_model.row_count(), 2);
+        let bar = histogram_model.row_data(0).unwrap();
+        assert_eq!(bar.date.as_str(), "1970-01-01");
+        assert_eq!(bar.count, 2);
+        assert_eq!(bar.height, 1.0);
+        assert!(!bar.in_event);
+        let bar = histogram_model.row_data(1).unwrap();
+        assert_eq!(bar.date.as_str(), "1970-01-02");
+        assert_eq!(bar.count, 1);
+        assert_eq!(bar.height, 0.5);
+
+        events_controller.add_event("Event", "1970-01-02", "1970-01-02");
+        assert!(histogram_model.row_data(1).unwrap().in_event);
+
+        assert_eq!(
+            events_controller.set_take_over(0, false).as_str(),
+            "🗑 Discarded 1 images and videos"
+        );
+        assert!(!item_list.lock().unwrap().items[2].get_take_over());
+        assert_eq!(
+            events_controller.set_take_over(0, true).as_str(),
+            "✔ Kept 1 images and videos"
+        );
+        events_controller.remove_event(0);
+        assert!(!histogram_model.row_data(1).unwrap().in_event);
+
+        events_controller.clear();
+        assert_eq!(histogram_model.row_count(), 0);
+    }
+
     #[test]
     fn test_update() {
         let item_list = Arc::new(Mutex::new(ItemList::new()));
@@ -263,6 +466,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut events_controller = EventsController::new(item_list.clone());
+        item_list
+            .lock()
+            .unwrap()
+            .items
+            .push(FileItem::dummy("test1.jpg", 2 * 24 * 3600, true));
+        events_controller.add_event("Event 1", "1970-01-01", "1970-01-01");
+        events_controller.add_event("Event 2", "1970-01-04", "1970-01-05");
+        events_controller.add_event("Event 3", "1970-01-10", "1970-01-10");
+        events_controller.set_target_directory(1, "target");
+
+        assert_eq!(
+            events_controller.merge_events(0, 0).as_str(),
+            "Select two different events to merge"
+        );
+        assert_eq!(
+            events_controller.merge_events(0, 3).as_str(),
+            "Select two different events to merge"
+        );
+        assert_eq!(
+            events_controller.merge_events(0, 2).as_str(),
+            "Merged event overlaps with Event 2"
+        );
+        assert_eq!(item_list.lock().unwrap().events.len(), 3);
+
+        // The merged event spans both events and contains the days between
+        assert_eq!(events_controller.merge_events(1, 0).as_str(), "");
+        let events_model = events_controller.get_model();
+        assert_eq!(events_model.row_count(), 2);
+        let event = events_model.row_data(0).unwrap();
+        assert_eq!(event.name.as_str(), "Event 2");
+        assert_eq!(event.start_date.as_str(), "1970-01-01");
+        assert_eq!(event.end_date.as_str(), "1970-01-05");
+        assert_eq!(event.target_directory.as_str(), "target");
+        assert_eq!(events_model.row_data(1).unwrap().name.as_str(), "Event 3");
+        {
+            let item_list = item_list.lock().unwrap();
+            assert_eq!(item_list.events.len(), 2);
+            assert_eq!(
+                item_list.get_event(&item_list.items[0]).unwrap().name,
+                "Event 2"
+            );
+        }
+        assert!(
+            events_controller
+                .get_histogram_model()
+                .row_data(0)
+                .unwrap()
+                .in_event
+        );
+
+        // Without a target directory of its own, the merged event takes the one of the second event
+        assert_eq!(events_controller.merge_events(1, 0).as_str(), "");
+        let event = events_model.row_data(0).unwrap();
+        assert_eq!(event.name.as_str(), "Event 3");
+        assert_eq!(event.end_date.as_str(), "1970-01-10");
+        assert_eq!(event.target_directory.as_str(), "target");
+        assert_eq!(events_model.row_count(), 1);
+    }
+
     #[test]
     fn test_add_remove_clear() {
         let item_list = Arc::new(Mutex::new(ItemList::new()));
@@ -335,4 +601,76 @@ mod tests {
         events_controller.clear();
         assert_eq!(events_controller.get_model().row_count(), 0);
     }
+
+    #[test]
+    fn test_move_event() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut events_controller = EventsController::new(item_list.clone());
+        events_controller.add_event("Event 2", "2020-02-01", "2020-02-02");
+        events_controller.add_event("Event 1", "2020-01-01", "2020-01-02");
+        events_controller.add_event("Event 3", "2020-03-01", "2020-03-02");
+        let events_model = events_controller.get_model();
+        let names = |events_model: &slint::VecModel<main_window::Event>| -> Vec<String> {
+            events_model
+                .iter()
+                .map(|event| event.name.to_string())
+                .collect()
+        };
+        assert_eq!(names(&events_model), ["Event 1", "Event 2", "Event 3"]);
+
+        // Sorted events cannot be moved
+        events_controller.move_event(0, 2);
+        assert_eq!(names(&events_model), ["Event 1", "Event 2", "Event 3"]);
+
+        events_controller.set_sort_events(false);
+        events_controller.move_event(0, 2);
+        assert_eq!(names(&events_model), ["Event 2", "Event 3", "Event 1"]);
+        events_controller.move_event(2, 1);
+        assert_eq!(names(&events_model), ["Event 2", "Event 1", "Event 3"]);
+        events_controller.move_event(2, 3);
+        events_controller.move_event(-1, 0);
+        assert_eq!(names(&events_model), ["Event 2", "Event 1", "Event 3"]);
+        assert_eq!(item_list.lock().unwrap().events[0].name, "Event 2");
+
+        // New and updated events keep the order
+        events_controller.add_event("Event 0", "2019-01-01", "2019-01-01");
+        events_controller.update_event(1, "Event 4", "2020-04-01", "2020-04-02");
+        assert_eq!(
+            names(&events_model),
+            ["Event 2", "Event 4", "Event 3", "Event 0"]
+        );
+
+        // Turning sorting on sorts the events right away
+        events_controller.set_sort_events(true);
+        assert_eq!(
+            names(&events_model),
+            ["Event 0", "Event 2", "Event 3", "Event 4"]
+        );
+    }
+
+    #[test]
+    fn test_set_target_directory() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut events_controller = EventsController::new(item_list.clone());
+        events_controller.add_event("Event 1", "2020-01-01", "2020-01-02");
+        let events_model = events_controller.get_model();
+        assert_eq!(events_model.row_data(0).unwrap().target_directory, "");
+
+        events_controller.set_target_directory(0, "other");
+        assert_eq!(events_model.row_data(0).unwrap().target_directory, "other");
+        assert_eq!(
+            item_list.lock().unwrap().events[0].target_directory,
+            "other"
+        );
+
+        // Updating the event keeps the target directory
+        events_controller.update_event(0, "Event 2", "2020-01-01", "2020-01-02");
+        assert_eq!(
+            item_list.lock().unwrap().events[0].target_directory,
+            "other"
+        );
+
+        events_controller.set_target_directory(0, "");
+        assert_eq!(events_model.row_data(0).unwrap().target_directory, "");
+    }
 }
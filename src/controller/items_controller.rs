@@ -1,44 +1,89 @@
 use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
     rc::Rc,
     sync::{Arc, Mutex},
 };
 
-use slint::Model;
+use slint::{ComponentHandle, Model};
 
 use crate::{
-    item_sort_list::{timestamp_to_string, FileItem, Format, ItemList},
+    item_sort_list::{
+        get_exif_metadata, parse_date, timestamp_to_string, FileItem, Format, ItemList,
+    },
     main_window,
-    misc::image_cache,
+    misc::{
+        combo_values::{text_of, SORT_BY},
+        image_cache,
+        images::{adjust_image, crop_loupe, get_image_buffer, get_sharpness, ImageBuffer},
+        translations::{tr, tr_format},
+        video_to_image,
+    },
 };
 
 use super::helper;
+use super::list_model::ListModel;
+use super::overview_model::OverviewModel;
 
 pub struct ItemsController {
     item_list: Arc<Mutex<ItemList>>,
-    list_model: Rc<slint::VecModel<main_window::ListItem>>,
+    list_model: Rc<ListModel>,
+    overview_model: Rc<OverviewModel>,
     similar_items_model: Rc<slint::VecModel<main_window::SortItem>>,
-    image_cache: image_cache::ImageCache,
+    image_cache: Rc<image_cache::ImageCache>,
+    /// Sharpness of the images that were already loaded, as it is expensive to calculate
+    sharpness_cache: RefCell<HashMap<PathBuf, f64>>,
+    /// Resolution in pixels of the images, None if it cannot be determined like for videos
+    resolution_cache: RefCell<HashMap<PathBuf, Option<u64>>>,
+    /// Image at native resolution of the item last shown in the loupe
+    loupe_cache: RefCell<Option<(PathBuf, ImageBuffer)>>,
+    /// EXIF metadata of the current item, read when the current item changes instead of whenever the details are shown
+    exif_metadata_cache: RefCell<Option<(PathBuf, Vec<(String, String)>)>>,
+}
+
+/// Width and height of the square shown in the loupe in pixels of the original image
+const LOUPE_SIZE: u32 = 256;
+/// Maximum width and height of the displayed images
+const IMAGE_SIZE: (u32, u32) = (1600, 1000);
+
+/// Properties of an item to compare it with similar items
+struct ItemMetrics {
+    sharpness: Option<f64>,
+    resolution: Option<u64>,
+    size: u64,
 }
 
 impl ItemsController {
     /// Create a new items controller instance
     pub fn new(item_list: Arc<Mutex<ItemList>>) -> Self {
         let mut image_cache = image_cache::ImageCache::new();
-        image_cache.restrict_size(1600, 1000);
+        image_cache.restrict_size(IMAGE_SIZE.0, IMAGE_SIZE.1);
+        let image_cache = Rc::new(image_cache);
 
         Self {
+            list_model: Rc::new(ListModel::new(item_list.clone())),
+            overview_model: Rc::new(OverviewModel::new(item_list.clone(), image_cache.clone())),
             item_list,
-            list_model: Rc::new(slint::VecModel::<main_window::ListItem>::default()),
             similar_items_model: Rc::new(slint::VecModel::<main_window::SortItem>::default()),
             image_cache,
+            sharpness_cache: RefCell::new(HashMap::new()),
+            resolution_cache: RefCell::new(HashMap::new()),
+            loupe_cache: RefCell::new(None),
+            exif_metadata_cache: RefCell::new(None),
         }
     }
 
-    /// Gets the slint vec model for the item list
-    pub fn get_list_model(&self) -> Rc<slint::VecModel<main_window::ListItem>> {
+    /// Gets the slint model for the item list
+    pub fn get_list_model(&self) -> Rc<ListModel> {
         self.list_model.clone()
     }
 
+    /// Gets the slint model for the thumbnail overview of the listed items
+    pub fn get_overview_model(&self) -> Rc<OverviewModel> {
+        self.overview_model.clone()
+    }
+
     /// Gets the slint vec model for the similar items
     pub fn get_similar_items_model(&self) -> Rc<slint::VecModel<main_window::SortItem>> {
         self.similar_items_model.clone()
@@ -46,7 +91,8 @@ impl ItemsController {
 
     /// Clear the list model
     pub fn clear_list(&mut self) {
-        helper::clear_model(self.list_model.clone());
+        self.list_model.set_local_indices(vec![]);
+        self.overview_model.set_local_indices(vec![]);
     }
 
     /// Clear the similar items model
@@ -61,23 +107,20 @@ impl ItemsController {
         list_model_index: usize,
         window: slint::Weak<main_window::ImageSieve>,
     ) {
-        if list_model_index >= self.list_model.row_count() {
-            return;
-        }
-        {
+        let items_index = match self.list_model.local_index(list_model_index) {
+            Some(items_index) => items_index,
+            None => return,
+        };
+        let keep_all = {
             // Clear images model
             self.clear_similar_items();
 
-            let items_index = self
-                .list_model
-                .row_data(list_model_index)
-                .unwrap()
-                .local_index as usize;
             let item_list = self.item_list.lock().unwrap();
             let similars = item_list.items[items_index].get_similars();
 
-            // Clear pending commands in the image cache
+            // Clear pending commands in the image cache, the overview requests its thumbnails again
             self.image_cache.purge();
+            self.overview_model.refresh();
 
             // Add the current image
             let item = &item_list.items[items_index];
@@ -89,7 +132,9 @@ impl ItemsController {
                 !similars.is_empty(),
                 window.clone(),
             );
-            let sort_image = sort_item_from_file_item(item, &item_list, image);
+            let thumbnail =
+                self.get_item_thumbnail(item, 0, !similars.is_empty(), &item_list, window.clone());
+            let sort_image = sort_item_from_file_item(item, &item_list, image, thumbnail);
             self.similar_items_model.push(sort_image);
 
             // Now add all similar images
@@ -104,16 +149,23 @@ impl ItemsController {
                     !similars.is_empty(),
                     window.clone(),
                 );
-                let sort_image = sort_item_from_file_item(item, &item_list, image);
+                let thumbnail =
+                    self.get_item_thumbnail(item, model_index, true, &item_list, window.clone());
+                let sort_image = sort_item_from_file_item(item, &item_list, image, thumbnail);
                 self.similar_items_model.push(sort_image);
                 model_index += 1;
             }
-        }
+            !similars.is_empty() && item_list.items[items_index].is_keep_all()
+        };
 
         // Set the data of the current image
         window
             .unwrap()
             .set_current_image(self.similar_items_model.row_data(0).unwrap());
+        window.unwrap().set_group_keep_all(keep_all);
+        self.adjust_current_image(&window.unwrap());
+        self.cache_metadata(items_index as i32);
+        self.update_comparison(&window.unwrap());
 
         // And prefetch the next images
         self.prefetch_images(list_model_index);
@@ -121,11 +173,194 @@ impl ItemsController {
 
     /// Sets the take over state of an item
     pub fn set_take_over(&mut self, local_index: i32, take_over: bool) -> slint::SharedString {
-        let description = {
+        self.change_item(local_index, |item| item.set_take_over(take_over))
+    }
+
+    /// Marks an item as unsure or removes the mark
+    pub fn set_unsure(&mut self, local_index: i32, unsure: bool) -> slint::SharedString {
+        self.change_item(local_index, |item| item.set_unsure(unsure))
+    }
+
+    /// Tags an item for deletion or removes the tag
+    pub fn set_pending_delete(
+        &mut self,
+        local_index: i32,
+        pending_delete: bool,
+    ) -> slint::SharedString {
+        self.change_item(local_index, |item| item.set_pending_delete(pending_delete))
+    }
+
+    /// Marks an item as favorite or removes the mark
+    pub fn set_favorite(&mut self, local_index: i32, favorite: bool) -> slint::SharedString {
+        self.change_item(local_index, |item| item.set_favorite(favorite))
+    }
+
+    /// Cycles the take over state of all items in the shown group through keeping all, keeping only the best and
+    /// discarding all items. The best item is the one that ranks highest in the comparison with its similar images.
+    pub fn cycle_group_take_over(&mut self) {
+        let local_indices = self.get_group_local_indices();
+        let (take_over, best) = {
+            let item_list = self.item_list.lock().unwrap();
+            let items: Vec<&FileItem> = local_indices
+                .iter()
+                .filter_map(|local_index| item_list.items.get(*local_index as usize))
+                .collect();
+            if items.len() != local_indices.len() {
+                return;
+            }
+            let metrics: Vec<ItemMetrics> =
+                items.iter().map(|item| self.get_metrics(item)).collect();
+            let take_over: Vec<bool> = items.iter().map(|item| item.get_take_over()).collect();
+            (take_over, best_item(&metrics))
+        };
+        for (local_index, take_over) in local_indices
+            .iter()
+            .zip(next_group_take_over(&take_over, best))
+        {
+            self.change_item(*local_index, |item| item.set_take_over(take_over));
+        }
+    }
+
+    /// Keeps only the largest or the smallest item of every group of similar items and returns a message how many
+    /// items were changed
+    pub fn keep_by_size_in_groups(&mut self, keep_largest: bool) -> slint::SharedString {
+        let count = self
+            .item_list
+            .lock()
+            .unwrap()
+            .keep_by_size_in_groups(keep_largest);
+        slint::SharedString::from(if keep_largest {
+            tr_format("Kept the largest item of each group, {} changed", &[&count])
+        } else {
+            tr_format(
+                "Kept the smallest item of each group, {} changed",
+                &[&count],
+            )
+        })
+    }
+
+    /// Keeps all items of the shown group and remembers this decision, e.g. for bracketed exposures, or removes it.
+    /// Removing the decision does not change if the items are kept.
+    pub fn set_group_keep_all(&mut self, keep_all: bool) {
+        for local_index in self.get_group_local_indices() {
+            self.change_item(local_index, |item| {
+                item.set_keep_all(keep_all);
+                if keep_all {
+                    item.set_take_over(true);
+                }
+            });
+        }
+    }
+
+    /// Gets the local indices of all items in the shown group
+    pub fn get_group_local_indices(&self) -> Vec<i32> {
+        (0..self.similar_items_model.row_count())
+            .filter_map(|row| self.similar_items_model.row_data(row))
+            .map(|sort_item| sort_item.local_index)
+            .collect()
+    }
+
+    /// Rotates images clockwise by the given degrees on top of their orientation. Their cached images are discarded
+    /// and the shown images are loaded again. Videos are not rotated.
+    pub fn rotate_items(
+        &mut self,
+        local_indices: &[i32],
+        degrees: i32,
+        window: &main_window::ImageSieve,
+    ) {
+        let rotated_indices: Vec<i32> = {
+            let mut item_list = self.item_list.lock().unwrap();
+            local_indices
+                .iter()
+                .copied()
+                .filter(
+                    |local_index| match item_list.items.get_mut(*local_index as usize) {
+                        Some(item) if !item.is_video() => {
+                            item.rotate(degrees);
+                            self.image_cache.invalidate(item);
+                            true
+                        }
+                        _ => false,
+                    },
+                )
+                .collect()
+        };
+        self.reload_similar_items(&rotated_indices, window);
+        self.update_list_model();
+    }
+
+    /// Displays an image with or without applying its orientation, e.g. if an editor already rotated its pixels
+    pub fn set_ignore_orientation(
+        &mut self,
+        local_index: i32,
+        ignore_orientation: bool,
+        window: &main_window::ImageSieve,
+    ) {
+        {
+            let mut item_list = self.item_list.lock().unwrap();
+            match item_list.items.get_mut(local_index as usize) {
+                Some(item) => {
+                    item.set_ignore_orientation(ignore_orientation);
+                    self.image_cache.invalidate(item);
+                }
+                None => return,
+            }
+        }
+        self.reload_similar_items(&[local_index], window);
+        self.update_list_model();
+    }
+
+    /// Loads the images and thumbnails of items in the similar items model and the current image again
+    fn reload_similar_items(&self, local_indices: &[i32], window: &main_window::ImageSieve) {
+        let item_list = self.item_list.lock().unwrap();
+        let current_local_index = window.get_current_image().local_index;
+        let has_similars = self.similar_items_model.row_count() > 1;
+        for row in 0..self.similar_items_model.row_count() {
+            let mut sort_item = self.similar_items_model.row_data(row).unwrap();
+            if !local_indices.contains(&sort_item.local_index) {
+                continue;
+            }
+            let item = &item_list.items[sort_item.local_index as usize];
+            let is_current_image = sort_item.local_index == current_local_index;
+            sort_item.image = self.get_item_image(
+                item,
+                row,
+                current_local_index,
+                is_current_image,
+                has_similars,
+                window.as_weak(),
+            );
+            sort_item.thumbnail =
+                self.get_item_thumbnail(item, row, has_similars, &item_list, window.as_weak());
+            if is_current_image {
+                let mut current_image = window.get_current_image();
+                current_image.image = sort_item.image.clone();
+                window.set_current_image(current_image);
+            }
+            self.similar_items_model.set_row_data(row, sort_item);
+        }
+    }
+
+    /// Changes the state of an item and updates the models, returns the new description of the item
+    fn change_item(
+        &mut self,
+        local_index: i32,
+        change: impl FnOnce(&mut FileItem),
+    ) -> slint::SharedString {
+        let (description, take_over, unsure, pending_delete, favorite) = {
             // Change the item_list state
             let mut item_list = self.item_list.lock().unwrap();
-            item_list.items[local_index as usize].set_take_over(take_over);
-            sort_item_description(&item_list.items[local_index as usize], &item_list)
+            let file_item = &mut item_list.items[local_index as usize];
+            change(file_item);
+            let (take_over, unsure, pending_delete, favorite) = (
+                file_item.get_take_over(),
+                file_item.is_unsure(),
+                file_item.is_pending_delete(),
+                file_item.is_favorite(),
+            );
+            let description =
+                sort_item_description(&item_list.items[local_index as usize], &item_list);
+            (description, take_over, unsure, pending_delete, favorite)
         };
         // Update item list model to reflect change in icons in list
         self.update_list_model();
@@ -134,6 +369,9 @@ impl ItemsController {
             let mut item: main_window::SortItem = self.similar_items_model.row_data(count).unwrap();
             if item.local_index == local_index {
                 item.take_over = take_over;
+                item.unsure = unsure;
+                item.pending_delete = pending_delete;
+                item.favorite = favorite;
                 item.text = description.clone();
                 self.similar_items_model.set_row_data(count, item);
                 break;
@@ -145,35 +383,157 @@ impl ItemsController {
     /// Update the texts for all entries in the list model and returns true if the list contains more than one item
     /// Should be called when the underlying data (i.e. the item list) has changed
     pub fn update_list_model(&mut self) -> bool {
-        let item_list = self.item_list.lock().unwrap();
-        for count in 0..self.list_model.row_count() {
-            let mut list_item = self.list_model.row_data(count).unwrap();
-            let file_item = &item_list.items[list_item.local_index as usize];
-            list_item.text = list_item_title(file_item, &item_list);
-            self.list_model.set_row_data(count, list_item);
+        // The texts are created when the rows are displayed, so only notify the change
+        self.list_model.refresh();
+        self.overview_model.refresh();
+        !self.item_list.lock().unwrap().items.is_empty()
+    }
+
+    /// Applies the preview brightness and contrast to the current image if it is already loaded.
+    /// Only the displayed image is changed, never the file.
+    pub fn adjust_current_image(&self, window: &main_window::ImageSieve) {
+        let mut current_image = window.get_current_image();
+        let buffer = {
+            let item_list = self.item_list.lock().unwrap();
+            match item_list.items.get(current_image.local_index as usize) {
+                Some(item) => self.image_cache.get_buffer(item),
+                None => None,
+            }
+        };
+        if let Some(buffer) = buffer {
+            current_image.image = crate::misc::images::get_slint_image(&adjust_image(
+                &buffer,
+                window.get_preview_brightness() as i32,
+                window.get_preview_contrast(),
+            ));
+            window.set_current_image(current_image);
         }
-        !item_list.items.is_empty()
     }
 
-    /// Fills the list of found items from the internal data structure to the slint VecModel
-    pub fn populate_list_model(&mut self, filters: &main_window::Filters) -> usize {
-        self.clear_list();
+    /// Compares the current image with its similar images by sharpness, resolution and file size and shows the result.
+    /// The sharpness is only compared once all images of the group are loaded.
+    pub fn update_comparison(&self, window: &main_window::ImageSieve) {
+        let current_index = window.get_current_image().local_index;
+        let local_indices: Vec<i32> = (0..self.similar_items_model.row_count())
+            .filter_map(|row| self.similar_items_model.row_data(row))
+            .map(|sort_item| sort_item.local_index)
+            .collect();
+        let comparison = if local_indices.len() > 1 && local_indices.contains(&current_index) {
+            let item_list = self.item_list.lock().unwrap();
+            let metrics = |local_index: i32| match item_list.items.get(local_index as usize) {
+                Some(item) => self.get_metrics(item),
+                None => ItemMetrics {
+                    sharpness: None,
+                    resolution: None,
+                    size: 0,
+                },
+            };
+            let others: Vec<ItemMetrics> = local_indices
+                .iter()
+                .filter(|local_index| **local_index != current_index)
+                .map(|local_index| metrics(*local_index))
+                .collect();
+            comparison_text(&metrics(current_index), &others)
+        } else {
+            String::new()
+        };
+        window.set_comparison(slint::SharedString::from(comparison));
+    }
 
+    /// Gets a magnified crop of the current image around the given position relative to the image width and height,
+    /// or an empty image for videos. The image in full resolution is cached, as the loupe follows the mouse cursor.
+    pub fn get_loupe(&self, local_index: i32, x: f32, y: f32) -> slint::Image {
         let item_list = self.item_list.lock().unwrap();
-        let mut filtered_list: Vec<&FileItem> = item_list
-            .items
-            .iter()
-            .filter(|item| filter_file_items(item, filters))
-            .collect();
-        filtered_list.sort_unstable_by(|a, b| compare_file_items(a, b, filters));
-        if filters.direction == "Desc" {
-            filtered_list.reverse();
+        let item = match item_list.items.get(local_index as usize) {
+            Some(item) if item.is_image() || item.is_raw_image() => item,
+            _ => return crate::misc::images::get_empty_image(),
+        };
+        let mut loupe_cache = self.loupe_cache.borrow_mut();
+        if loupe_cache.as_ref().map(|(path, _)| path) != Some(&item.path) {
+            *loupe_cache = Some((item.path.clone(), get_image_buffer(item, 0, 0)));
         }
-        let list_len = filtered_list.len();
-        for image in filtered_list {
-            let list_item = list_item_from_file_item(image, &item_list);
-            self.list_model.push(list_item);
+        let (_, buffer) = loupe_cache.as_ref().unwrap();
+        crate::misc::images::get_slint_image(&crop_loupe(buffer, x, y, LOUPE_SIZE))
+    }
+
+    /// Gets the number of frames of a video, 0 for other items or if it cannot be determined
+    pub fn get_video_frame_count(&self, local_index: i32) -> i32 {
+        let item_list = self.item_list.lock().unwrap();
+        match item_list.items.get(local_index as usize) {
+            Some(item) if item.is_video() => {
+                video_to_image::get_frame_count(item).unwrap_or(0) as i32
+            }
+            _ => 0,
         }
+    }
+
+    /// Gets a single frame of a video in the size of the displayed images to scrub through the video
+    pub fn get_video_frame(&self, local_index: i32, frame_index: i32) -> slint::Image {
+        let item_list = self.item_list.lock().unwrap();
+        let buffer = match item_list.items.get(local_index as usize) {
+            Some(item) if item.is_video() => video_to_image::get_frame_buffer(
+                item,
+                frame_index.max(0) as u32,
+                IMAGE_SIZE.0,
+                IMAGE_SIZE.1,
+            ),
+            _ => None,
+        };
+        buffer.map_or_else(crate::misc::images::get_empty_image, |buffer| {
+            crate::misc::images::get_slint_image(&buffer)
+        })
+    }
+
+    /// Gets the properties of an item to compare it with others, the sharpness is only known if the image is loaded
+    fn get_metrics(&self, item: &FileItem) -> ItemMetrics {
+        let mut sharpness_cache = self.sharpness_cache.borrow_mut();
+        let sharpness = match sharpness_cache.get(&item.path) {
+            Some(sharpness) => Some(*sharpness),
+            None if item.is_image() => self.image_cache.get_buffer(item).map(|buffer| {
+                let sharpness = get_sharpness(&buffer);
+                sharpness_cache.insert(item.path.clone(), sharpness);
+                sharpness
+            }),
+            None => None,
+        };
+        let resolution = *self
+            .resolution_cache
+            .borrow_mut()
+            .entry(item.path.clone())
+            .or_insert_with(|| {
+                image::image_dimensions(&item.path)
+                    .ok()
+                    .map(|(width, height)| width as u64 * height as u64)
+            });
+        ItemMetrics {
+            sharpness,
+            resolution,
+            size: item.get_size(),
+        }
+    }
+
+    /// Fills the list of found items from the internal data structure to the slint model
+    pub fn populate_list_model(&mut self, filters: &main_window::Filters) -> usize {
+        let local_indices = {
+            let item_list = self.item_list.lock().unwrap();
+            let mut filtered_list: Vec<(usize, &FileItem)> = item_list
+                .items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| filter_file_items(item, filters))
+                .collect();
+            filtered_list.sort_unstable_by(|(_, a), (_, b)| compare_file_items(a, b, filters));
+            if filters.descending {
+                filtered_list.reverse();
+            }
+            filtered_list
+                .into_iter()
+                .map(|(local_index, _)| local_index)
+                .collect::<Vec<usize>>()
+        };
+        let list_len = local_indices.len();
+        self.overview_model.set_local_indices(local_indices.clone());
+        self.list_model.set_local_indices(local_indices);
         list_len
     }
 
@@ -184,6 +544,164 @@ impl ItemsController {
         slint::SharedString::from(timestamp_to_string(item.get_timestamp(), Format::Date))
     }
 
+    /// Gets the row in the list model of the earliest listed item taken on a day
+    pub fn get_first_list_item_of_day(&self, date: &str) -> Option<usize> {
+        let date = parse_date(date).ok()?;
+        let item_list = self.item_list.lock().unwrap();
+        (0..self.list_model.row_count())
+            .filter_map(|row| {
+                let item = &item_list.items[self.list_model.local_index(row)?];
+                if item_list.get_event_date(item) == date {
+                    Some((item_list.get_event_timestamp(item), row))
+                } else {
+                    None
+                }
+            })
+            .min()
+            .map(|(_, row)| row)
+    }
+
+    /// Gets the row of the next listed item after the given row that was not decided yet.
+    /// The search continues at the start of the list and ends with the given row, None is returned if all listed
+    /// items were decided.
+    pub fn get_next_undecided_row(&self, row: usize) -> Option<usize> {
+        let item_list = self.item_list.lock().unwrap();
+        let row_count = self.list_model.row_count();
+        (1..=row_count)
+            .map(|offset| (row + offset) % row_count)
+            .find(|next_row| {
+                self.list_model
+                    .local_index(*next_row)
+                    .is_some_and(|local_index| !item_list.items[local_index].is_decided())
+            })
+    }
+
+    /// Gets the row of the first item of the next group of similar items after the group of the item in a row.
+    /// Items that do not belong to a group are skipped.
+    pub fn get_next_group_row(&self, row: usize) -> Option<usize> {
+        let group_ids = self.get_group_ids();
+        let current_group_id = *group_ids.get(row)?;
+        (row + 1..group_ids.len()).find(|next_row| {
+            group_ids[*next_row].is_some() && group_ids[*next_row] != current_group_id
+        })
+    }
+
+    /// Gets the row of the first item of the previous group of similar items before the group of the item in a row.
+    /// Items that do not belong to a group are skipped.
+    pub fn get_previous_group_row(&self, row: usize) -> Option<usize> {
+        let group_ids = self.get_group_ids();
+        let current_group_id = *group_ids.get(row)?;
+        let mut previous_row = (0..row).rev().find(|previous_row| {
+            group_ids[*previous_row].is_some() && group_ids[*previous_row] != current_group_id
+        })?;
+        while previous_row > 0 && group_ids[previous_row - 1] == group_ids[previous_row] {
+            previous_row -= 1;
+        }
+        Some(previous_row)
+    }
+
+    /// Gets the group ids of the listed items in the order of the list
+    fn get_group_ids(&self) -> Vec<Option<usize>> {
+        let item_list = self.item_list.lock().unwrap();
+        (0..self.list_model.row_count())
+            .map(|row| {
+                self.list_model
+                    .local_index(row)
+                    .and_then(|local_index| item_list.items[local_index].get_group_id())
+            })
+            .collect()
+    }
+
+    /// Gets all metadata of an item like path, size, similar items and EXIF data
+    pub fn get_metadata(&self, local_index: i32) -> Vec<main_window::MetadataEntry> {
+        let item_list = self.item_list.lock().unwrap();
+        let item = match item_list.items.get(local_index as usize) {
+            Some(item) => item,
+            None => return vec![],
+        };
+
+        let mut metadata = vec![
+            (String::from(tr("Path")), item.path.display().to_string()),
+            (
+                String::from(tr("Size")),
+                format!("{} KB", item.get_size() / 1024),
+            ),
+            (
+                String::from(tr("Date")),
+                timestamp_to_string(item.get_timestamp(), Format::DateTime),
+            ),
+        ];
+        if let Some(invalid_date) = item.get_invalid_date() {
+            metadata.push((String::from(tr("Invalid date")), invalid_date.to_string()));
+        }
+        if let Some(orientation) = item.get_orientation() {
+            metadata.push((
+                String::from(tr("Orientation")),
+                format!("{:?}", orientation),
+            ));
+        }
+        if item.has_orientation_mismatch() {
+            metadata.push((
+                String::from(tr("Orientation mismatch")),
+                String::from(tr(if item.is_orientation_ignored() {
+                    "The pixels are already rotated, the orientation is ignored"
+                } else {
+                    "The pixels are already rotated, the image is likely rotated twice"
+                })),
+            ));
+        }
+        if item.get_user_rotation() != 0 {
+            metadata.push((
+                String::from(tr("Rotation")),
+                format!("{}°", item.get_user_rotation()),
+            ));
+        }
+        if let Some(event) = item_list.get_event(item) {
+            metadata.push((String::from(tr("Event")), event.name.clone()));
+        }
+        if !item.get_similars().is_empty() {
+            let similars: Vec<String> = item
+                .get_similars()
+                .iter()
+                .map(|index| item_list.items[*index].get_item_string(&item_list.path))
+                .collect();
+            metadata.push((String::from(tr("Similar to")), similars.join(", ")));
+        }
+        if let Some(group_id) = item.get_group_id() {
+            metadata.push((String::from(tr("Similarity group")), group_id.to_string()));
+        }
+        // The file is not read here, as the details are evaluated by a binding of the window
+        if let Some((path, exif_metadata)) = self.exif_metadata_cache.borrow().as_ref() {
+            if *path == item.path {
+                metadata.extend(exif_metadata.iter().cloned());
+            }
+        }
+
+        metadata
+            .into_iter()
+            .map(|(name, value)| main_window::MetadataEntry {
+                name: slint::SharedString::from(name),
+                value: slint::SharedString::from(value),
+            })
+            .collect()
+    }
+
+    /// Reads the EXIF metadata of the current item for its details, unless it is already cached
+    pub fn cache_metadata(&self, local_index: i32) {
+        let path = {
+            let item_list = self.item_list.lock().unwrap();
+            match item_list.items.get(local_index as usize) {
+                Some(item) => item.path.clone(),
+                None => return,
+            }
+        };
+        let mut exif_metadata_cache = self.exif_metadata_cache.borrow_mut();
+        if !matches!(&*exif_metadata_cache, Some((cached_path, _)) if *cached_path == path) {
+            let exif_metadata = get_exif_metadata(&path);
+            *exif_metadata_cache = Some((path, exif_metadata));
+        }
+    }
+
     /// Gets the image for an item
     /// This function returns either a cached image or a loading image while the real image is being loaded
     /// in the background. As soon as the process finishes, the image is displayed.
@@ -200,7 +718,24 @@ impl ItemsController {
         if let Some(image) = image {
             image
         } else {
+            let corrupt_files = self.image_cache.get_corrupt_files();
+            let item_list = self.item_list.clone();
+            let path = item.path.clone();
             let f: image_cache::DoneCallback = Box::new(move |image_buffer| {
+                // Remember a corrupt file in the item list and describe it, so that it can be replaced
+                let corrupt_text = if corrupt_files.lock().unwrap().contains(&path) {
+                    let mut item_list = item_list.lock().unwrap();
+                    item_list
+                        .items
+                        .iter()
+                        .position(|item| item.path == path)
+                        .map(|index| {
+                            item_list.items[index].set_corrupt(true);
+                            sort_item_description(&item_list.items[index], &item_list)
+                        })
+                } else {
+                    None
+                };
                 window_weak
                     .clone()
                     .upgrade_in_event_loop(move |handle| {
@@ -216,45 +751,317 @@ impl ItemsController {
                                 handle
                                     .get_similar_images_model()
                                     .set_row_data(model_index, row_data);
+                                // The sharpness can be compared once the image is loaded
+                                handle.invoke_update_comparison();
                             }
                             // If the image is the current image, then we need to also update the current image SortImage
                             if is_current_image {
                                 let mut current_image = handle.get_current_image();
                                 current_image.image =
-                                    crate::misc::images::get_slint_image(&image_buffer);
+                                    crate::misc::images::get_slint_image(&adjust_image(
+                                        &image_buffer,
+                                        handle.get_preview_brightness() as i32,
+                                        handle.get_preview_contrast(),
+                                    ));
+                                if let Some(corrupt_text) = corrupt_text {
+                                    current_image.text = corrupt_text;
+                                }
                                 handle.set_current_image(current_image);
                             }
                         }
                     })
                     .unwrap()
             });
-            self.image_cache.load(
-                item,
-                if is_current_image {
-                    image_cache::Purpose::CurrentImage
-                } else {
-                    image_cache::Purpose::SimilarImage
-                },
-                Some(f),
-            );
-            self.image_cache.get_waiting()
+            // Show the thumbnail embedded in the image until it is loaded, if there is one
+            self.image_cache
+                .load(
+                    item,
+                    if is_current_image {
+                        image_cache::Purpose::CurrentImage
+                    } else {
+                        image_cache::Purpose::SimilarImage
+                    },
+                    Some(f),
+                )
+                .unwrap_or_else(|| self.image_cache.get_waiting())
         }
     }
 
-    /// Prefetch the next images in the model list
-    fn prefetch_images(&self, list_model_index: usize) {
-        // Prefetch next two images
-        for i in list_model_index + 1..list_model_index + 3 {
-            if i < self.list_model.row_count() {
-                let item_list = self.item_list.lock().unwrap();
-                let list_item = &self.list_model.row_data(i).unwrap();
-                let file_item = &item_list.items[list_item.local_index as usize];
-                if file_item.is_image() {
-                    self.image_cache
-                        .load(file_item, image_cache::Purpose::Prefetch, None);
+    /// Gets the thumbnail of an item for the strip of similar items, which is only needed if there are similar items.
+    /// If the thumbnail is not cached, it is loaded in the background and set in the similar items model afterwards.
+    fn get_item_thumbnail(
+        &self,
+        item: &FileItem,
+        model_index: usize,
+        has_similars: bool,
+        item_list: &ItemList,
+        window_weak: slint::Weak<main_window::ImageSieve>,
+    ) -> slint::Image {
+        if !has_similars {
+            return crate::misc::images::get_empty_image();
+        }
+        if let Some(thumbnail) = self.image_cache.get_thumbnail(item) {
+            return thumbnail;
+        }
+        let local_index = item_list.index_of_item(item).unwrap() as i32;
+        let f: image_cache::DoneCallback = Box::new(move |image_buffer| {
+            window_weak
+                .clone()
+                .upgrade_in_event_loop(move |handle| {
+                    // Check if the model still contains the item that caused the thumbnail load
+                    let similar_images_model = handle.get_similar_images_model();
+                    if let Some(mut row_data) = similar_images_model.row_data(model_index) {
+                        if row_data.local_index == local_index {
+                            row_data.thumbnail =
+                                crate::misc::images::get_slint_image(&image_buffer);
+                            similar_images_model.set_row_data(model_index, row_data);
+                        }
+                    }
+                })
+                .unwrap()
+        });
+        self.image_cache
+            .load(item, image_cache::Purpose::Thumbnail, Some(f));
+        self.image_cache.get_waiting()
+    }
+
+    /// Gets the items tagged for deletion with their thumbnails to review them before deleting. Thumbnails that are not
+    /// cached yet are loaded in the background and shown in the pending delete model once loaded.
+    pub fn get_pending_delete_items(
+        &self,
+        window_weak: slint::Weak<main_window::ImageSieve>,
+    ) -> Vec<main_window::PendingDeleteItem> {
+        let item_list = self.item_list.lock().unwrap();
+        item_list
+            .get_pending_deletes()
+            .into_iter()
+            .enumerate()
+            .map(|(row, local_index)| {
+                let item = &item_list.items[local_index];
+                main_window::PendingDeleteItem {
+                    thumbnail: self.get_pending_delete_thumbnail(
+                        item,
+                        row,
+                        local_index as i32,
+                        window_weak.clone(),
+                    ),
+                    text: slint::SharedString::from(item.get_item_string(&item_list.path)),
+                    size: slint::SharedString::from(format!("{} KB", item.get_size() / 1024)),
+                    local_index: local_index as i32,
                 }
+            })
+            .collect()
+    }
+
+    /// Gets the thumbnail of an item tagged for deletion or loads it in the background
+    fn get_pending_delete_thumbnail(
+        &self,
+        item: &FileItem,
+        row: usize,
+        local_index: i32,
+        window_weak: slint::Weak<main_window::ImageSieve>,
+    ) -> slint::Image {
+        if let Some(thumbnail) = self.image_cache.get_thumbnail(item) {
+            return thumbnail;
+        }
+        let f: image_cache::DoneCallback = Box::new(move |image_buffer| {
+            window_weak
+                .clone()
+                .upgrade_in_event_loop(move |handle| {
+                    // Check if the model still contains the item that caused the thumbnail load
+                    let pending_delete_model = handle.get_pending_delete_model();
+                    if let Some(mut row_data) = pending_delete_model.row_data(row) {
+                        if row_data.local_index == local_index {
+                            row_data.thumbnail =
+                                crate::misc::images::get_slint_image(&image_buffer);
+                            pending_delete_model.set_row_data(row, row_data);
+                        }
+                    }
+                })
+                .unwrap()
+        });
+        self.image_cache
+            .load(item, image_cache::Purpose::Thumbnail, Some(f));
+        self.image_cache.get_waiting()
+    }
+
+    /// Prepares the thumbnails of the items with similar items in the background, starting with the given row of the
+    /// list and continuing with the following rows, so that the strip of similar items is shown immediately.
+    /// The progress is shown in the window until all thumbnails are prepared.
+    pub fn warm_thumbnails(&self, row: usize, window_weak: slint::Weak<main_window::ImageSieve>) {
+        let items: Vec<FileItem> = {
+            let item_list = self.item_list.lock().unwrap();
+            let row_count = self.list_model.row_count();
+            (row..row_count)
+                .chain(0..row.min(row_count))
+                .filter_map(|row| self.list_model.local_index(row))
+                .map(|local_index| &item_list.items[local_index])
+                .filter(|item| !item.get_similars().is_empty())
+                .take(image_cache::MAX_WARM_THUMBNAILS)
+                .cloned()
+                .collect()
+        };
+        let progress_window_weak = window_weak.clone();
+        let total = self.image_cache.warm_thumbnails(
+            items,
+            Box::new(move |done, total| {
+                progress_window_weak
+                    .upgrade_in_event_loop(move |handle| {
+                        handle.set_thumbnails_progress(thumbnails_progress(done, total));
+                    })
+                    .unwrap();
+            }),
+        );
+        window_weak
+            .unwrap()
+            .set_thumbnails_progress(thumbnails_progress(0, total));
+    }
+
+    /// Stops preparing thumbnails in the background
+    pub fn cancel_warming(&self) {
+        self.image_cache.cancel_warming();
+    }
+
+    /// Gets the statistics of the image cache
+    pub fn cache_statistics(&self) -> image_cache::CacheStatistics {
+        self.image_cache.statistics()
+    }
+
+    /// Removes all cached images and the stored hashes of the items, e.g. because they are stale. Projects and the
+    /// files themselves are not touched. Returns the number of removed hashes.
+    pub fn clear_cache(&self) -> usize {
+        self.image_cache.clear();
+        self.sharpness_cache.borrow_mut().clear();
+        self.resolution_cache.borrow_mut().clear();
+        self.loupe_cache.replace(None);
+        self.item_list.lock().unwrap().clear_hashes()
+    }
+
+    /// Prefetch the next images in the model list and the images of the next group of similar images
+    fn prefetch_images(&self, list_model_index: usize) {
+        let prefetch_items = self.get_prefetch_items(list_model_index);
+        let item_list = self.item_list.lock().unwrap();
+        for local_index in prefetch_items {
+            let file_item = &item_list.items[local_index];
+            if !self.image_cache.is_cached(file_item) {
+                self.image_cache
+                    .load(file_item, image_cache::Purpose::Prefetch, None);
+            }
+        }
+    }
+
+    /// Gets the indices in the item list of the images to prefetch after the item in a row: the next two listed items
+    /// and all items of the next group of similar items, so that stepping to that group shows all of them at once.
+    /// Each image is contained once and the number of images is limited to not evict the shown images from the cache.
+    fn get_prefetch_items(&self, list_model_index: usize) -> Vec<usize> {
+        let next_group_item = self
+            .get_next_group_row(list_model_index)
+            .and_then(|row| self.list_model.local_index(row));
+        let item_list = self.item_list.lock().unwrap();
+        let mut local_indices: Vec<usize> = (list_model_index + 1..list_model_index + 3)
+            .filter_map(|row| self.list_model.local_index(row))
+            .collect();
+        if let Some(local_index) = next_group_item {
+            local_indices.push(local_index);
+            local_indices.extend(item_list.items[local_index].get_similars());
+        }
+        let mut unique = HashSet::new();
+        local_indices
+            .into_iter()
+            .filter(|local_index| {
+                unique.insert(*local_index) && item_list.items[*local_index].is_image()
+            })
+            .take(image_cache::MAX_PREFETCH)
+            .collect()
+    }
+}
+
+/// Gets the text showing the progress of preparing thumbnails, which is empty when all are prepared
+fn thumbnails_progress(done: usize, total: usize) -> slint::SharedString {
+    if done < total {
+        slint::SharedString::from(tr_format("Preparing thumbnails: {}/{}", &[&done, &total]))
+    } else {
+        slint::SharedString::default()
+    }
+}
+
+/// Describes the rank of an item compared to similar items for each property that is known for all of them
+fn comparison_text(current: &ItemMetrics, others: &[ItemMetrics]) -> String {
+    fn rank<T: PartialOrd>(current: Option<T>, others: Vec<Option<T>>) -> Option<usize> {
+        let current = current?;
+        let mut rank = 1;
+        for other in others {
+            if other? > current {
+                rank += 1;
             }
         }
+        Some(rank)
+    }
+
+    let ranks = [
+        (
+            "🔍 Sharpness",
+            rank(
+                current.sharpness,
+                others.iter().map(|other| other.sharpness).collect(),
+            ),
+        ),
+        (
+            "📐 Resolution",
+            rank(
+                current.resolution,
+                others.iter().map(|other| other.resolution).collect(),
+            ),
+        ),
+        (
+            "💾 Size",
+            rank(
+                Some(current.size),
+                others.iter().map(|other| Some(other.size)).collect(),
+            ),
+        ),
+    ];
+    let ranks: Vec<String> = ranks
+        .iter()
+        .filter_map(|(name, rank)| rank.map(|rank| format!("{} #{}", tr(name), rank)))
+        .collect();
+    tr_format(
+        "{} of {} similar images",
+        &[&ranks.join(" · "), &(others.len() + 1)],
+    )
+}
+
+/// Gets the index of the best item by comparing the sharpness, the resolution and the size in this order.
+/// Like in the comparison text, the sharpness and the resolution are only used if they are known for all items.
+fn best_item(metrics: &[ItemMetrics]) -> usize {
+    let use_sharpness = metrics.iter().all(|metric| metric.sharpness.is_some());
+    let use_resolution = metrics.iter().all(|metric| metric.resolution.is_some());
+    let key = |metric: &ItemMetrics| {
+        (
+            metric.sharpness.filter(|_| use_sharpness),
+            metric.resolution.filter(|_| use_resolution),
+            metric.size,
+        )
+    };
+    let mut best = 0;
+    for (index, metric) in metrics.iter().enumerate().skip(1) {
+        if key(metric) > key(&metrics[best]) {
+            best = index;
+        }
+    }
+    best
+}
+
+/// Gets the next take over states of a group in the cycle keep all, keep only the best item and discard all
+fn next_group_take_over(take_over: &[bool], best: usize) -> Vec<bool> {
+    let keep_all = vec![true; take_over.len()];
+    let keep_best: Vec<bool> = (0..take_over.len()).map(|index| index == best).collect();
+    if take_over == keep_best {
+        vec![false; take_over.len()]
+    } else if take_over == keep_all {
+        keep_best
+    } else {
+        keep_all
     }
 }
 
@@ -267,6 +1074,15 @@ fn filter_file_items(file_item: &FileItem, filters: &main_window::Filters) -> bo
     if !filters.videos && file_item.is_video() {
         visible = false;
     }
+    if !filters.screenshots && file_item.is_screenshot() {
+        visible = false;
+    }
+    if filters.favorites_only && !file_item.is_favorite() {
+        visible = false;
+    }
+    if filters.invalid_dates_only && file_item.get_invalid_date().is_none() {
+        visible = false;
+    }
     if !filters.sorted_out && !file_item.get_take_over() {
         visible = false;
     }
@@ -279,7 +1095,7 @@ fn compare_file_items(
     b: &FileItem,
     filters: &main_window::Filters,
 ) -> std::cmp::Ordering {
-    match filters.sort_by.as_str() {
+    match text_of(SORT_BY, filters.sort_by) {
         "Date" => a.cmp(b),
         "Name" => a.path.cmp(&b.path),
         "Type" => {
@@ -301,11 +1117,20 @@ fn sort_item_from_file_item(
     file_item: &FileItem,
     item_list: &ItemList,
     image: slint::Image,
+    thumbnail: slint::Image,
 ) -> main_window::SortItem {
     main_window::SortItem {
         text: sort_item_description(file_item, item_list),
         image,
+        thumbnail,
         take_over: file_item.get_take_over(),
+        unsure: file_item.is_unsure(),
+        pending_delete: file_item.is_pending_delete(),
+        favorite: file_item.is_favorite(),
+        is_video: file_item.is_video(),
+        orientation_mismatch: file_item.has_orientation_mismatch(),
+        ignore_orientation: file_item.is_orientation_ignored(),
+        long_side: file_item.get_long_side() as i32,
         local_index: item_list.index_of_item(file_item).unwrap() as i32,
     }
 }
@@ -313,34 +1138,25 @@ fn sort_item_from_file_item(
 /// Gets the description of a sort item from a file item
 fn sort_item_description(file_item: &FileItem, item_list: &ItemList) -> slint::SharedString {
     let mut description = format!("{}", file_item);
+    if let Some((width, height)) = file_item.get_dimensions() {
+        description = description + &format!(", 📐 {} × {}", width, height);
+    }
     if let Some(event) = item_list.get_event(file_item) {
         description = description + ", 📅 " + &event.name;
     }
-    slint::SharedString::from(description)
-}
-
-/// Get the list item title for the GUI from a file item
-fn list_item_title(file_item: &FileItem, item_list: &ItemList) -> slint::SharedString {
-    let mut title = file_item.get_item_string(&item_list.path);
-    if item_list.get_event(file_item).is_some() {
-        title = String::from("📅 ") + &title;
-    }
-    slint::SharedString::from(title)
-}
-
-/// Create a list item for the GUI from a file item
-fn list_item_from_file_item(file_item: &FileItem, item_list: &ItemList) -> main_window::ListItem {
-    main_window::ListItem {
-        text: list_item_title(file_item, item_list),
-        local_index: item_list.index_of_item(file_item).unwrap() as i32,
+    if file_item.is_corrupt() {
+        description = description
+            + ", 💔 "
+            + tr("The file is empty or truncated, please download or copy it again");
     }
+    slint::SharedString::from(description)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::main_window::ImageSieve;
     use rusty_fork::rusty_fork_test;
-    use slint::{ComponentHandle, SharedString};
+    use slint::ComponentHandle;
 
     use super::*;
 
@@ -348,9 +1164,12 @@ mod tests {
         main_window::Filters {
             images: true,
             videos: true,
+            screenshots: true,
+            favorites_only: false,
+            invalid_dates_only: false,
             sorted_out: true,
-            sort_by: SharedString::from("Date"),
-            direction: SharedString::from("Asc"),
+            sort_by: 0,
+            descending: false,
         }
     }
 
@@ -374,7 +1193,7 @@ mod tests {
         assert_eq!(list_model.row_data(1).unwrap().local_index, 0);
         assert_eq!(list_model.row_data(1).unwrap().text, "📹 test2.mov");
 
-        filters.direction = SharedString::from("Desc");
+        filters.descending = true;
         items_controller.populate_list_model(&filters);
         assert_eq!(list_model.row_count(), 2);
         assert_eq!(list_model.row_data(1).unwrap().local_index, 1);
@@ -402,10 +1221,282 @@ mod tests {
         assert_eq!(list_model.row_data(0).unwrap().local_index, 0);
         assert_eq!(list_model.row_data(0).unwrap().text, "📹 test2.mov");
 
+        filters.sorted_out = true;
+        filters.invalid_dates_only = true;
+        item_list.lock().unwrap().items[1].set_invalid_date(Some("1970:01:01 00:00:00"));
+        items_controller.populate_list_model(&filters);
+        assert_eq!(list_model.row_count(), 1);
+        assert_eq!(list_model.row_data(0).unwrap().local_index, 1);
+        assert_eq!(list_model.row_data(0).unwrap().text, "🔀 📷 🗑 ⚠ test1.jpg");
+
         items_controller.clear_list();
         assert_eq!(items_controller.get_list_model().row_count(), 0);
     }
 
+    #[test]
+    fn test_first_list_item_of_day() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut items_controller = ItemsController::new(item_list.clone());
+        let mut filters = build_filters();
+        {
+            let mut item_list = item_list.lock().unwrap();
+            item_list.items.push(FileItem::dummy("test1.jpg", 20, true));
+            item_list
+                .items
+                .push(FileItem::dummy("test2.jpg", 10, false));
+            item_list
+                .items
+                .push(FileItem::dummy("test3.jpg", 24 * 3600, true));
+        }
+        items_controller.populate_list_model(&filters);
+        assert_eq!(
+            items_controller.get_first_list_item_of_day("1970-01-01"),
+            Some(0)
+        );
+        assert_eq!(
+            items_controller.get_first_list_item_of_day("1970-01-02"),
+            Some(2)
+        );
+        assert_eq!(
+            items_controller.get_first_list_item_of_day("1970-01-03"),
+            None
+        );
+        assert_eq!(items_controller.get_first_list_item_of_day("invalid"), None);
+
+        filters.descending = true;
+        items_controller.populate_list_model(&filters);
+        assert_eq!(
+            items_controller.get_first_list_item_of_day("1970-01-01"),
+            Some(2)
+        );
+
+        // Only listed items are found
+        filters.sorted_out = false;
+        items_controller.populate_list_model(&filters);
+        assert_eq!(
+            items_controller.get_first_list_item_of_day("1970-01-01"),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_next_undecided_row() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut items_controller = ItemsController::new(item_list.clone());
+        assert_eq!(items_controller.get_next_undecided_row(0), None);
+        {
+            let mut item_list = item_list.lock().unwrap();
+            for (index, name) in ["test1.jpg", "test2.jpg", "test3.jpg"].iter().enumerate() {
+                item_list
+                    .items
+                    .push(FileItem::dummy(name, index as i64, true));
+            }
+            item_list.items[1].set_take_over(true);
+        }
+        items_controller.populate_list_model(&build_filters());
+
+        assert_eq!(items_controller.get_next_undecided_row(0), Some(2));
+        // The search wraps around and ends with the current row
+        assert_eq!(items_controller.get_next_undecided_row(2), Some(0));
+        item_list.lock().unwrap().items[2].set_unsure(true);
+        assert_eq!(items_controller.get_next_undecided_row(0), Some(0));
+        assert_eq!(items_controller.get_next_undecided_row(1), Some(0));
+        item_list.lock().unwrap().items[0].set_take_over(false);
+        assert_eq!(items_controller.get_next_undecided_row(1), None);
+    }
+
+    #[test]
+    fn test_cycle_group_take_over() {
+        let metrics = |sharpness: Option<f64>, resolution: Option<u64>, size: u64| ItemMetrics {
+            sharpness,
+            resolution,
+            size,
+        };
+        assert_eq!(
+            best_item(&[
+                metrics(Some(1.0), Some(100), 30),
+                metrics(Some(2.0), Some(50), 10),
+                metrics(Some(2.0), Some(40), 20),
+            ]),
+            1
+        );
+        // The sharpness is ignored if it is not known for all items
+        assert_eq!(
+            best_item(&[
+                metrics(Some(1.0), Some(100), 30),
+                metrics(None, Some(200), 10),
+            ]),
+            1
+        );
+        assert_eq!(
+            best_item(&[metrics(None, None, 10), metrics(None, None, 20)]),
+            1
+        );
+
+        let keep_all = next_group_take_over(&[true, false, true], 1);
+        assert_eq!(keep_all, vec![true, true, true]);
+        let keep_best = next_group_take_over(&keep_all, 1);
+        assert_eq!(keep_best, vec![false, true, false]);
+        let discard_all = next_group_take_over(&keep_best, 1);
+        assert_eq!(discard_all, vec![false, false, false]);
+        assert_eq!(next_group_take_over(&discard_all, 1), keep_all);
+        // A single item toggles between kept and discarded
+        assert_eq!(next_group_take_over(&[true], 0), vec![false]);
+        assert_eq!(next_group_take_over(&[false], 0), vec![true]);
+    }
+
+    #[test]
+    fn test_group_rows() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut items_controller = ItemsController::new(item_list.clone());
+        assert_eq!(items_controller.get_next_group_row(0), None);
+        {
+            let mut item_list = item_list.lock().unwrap();
+            let group_ids = [
+                Some(1),
+                Some(1),
+                None,
+                Some(2),
+                Some(2),
+                Some(2),
+                None,
+                Some(3),
+            ];
+            for (index, group_id) in group_ids.iter().enumerate() {
+                let mut item = FileItem::dummy(&format!("test{}.jpg", index), index as i64, true);
+                item.set_group_id(*group_id);
+                item_list.items.push(item);
+            }
+        }
+        items_controller.populate_list_model(&build_filters());
+
+        assert_eq!(items_controller.get_next_group_row(0), Some(3));
+        assert_eq!(items_controller.get_next_group_row(1), Some(3));
+        assert_eq!(items_controller.get_next_group_row(2), Some(3));
+        assert_eq!(items_controller.get_next_group_row(4), Some(7));
+        assert_eq!(items_controller.get_next_group_row(7), None);
+
+        assert_eq!(items_controller.get_previous_group_row(7), Some(3));
+        assert_eq!(items_controller.get_previous_group_row(5), Some(0));
+        assert_eq!(items_controller.get_previous_group_row(6), Some(3));
+        assert_eq!(items_controller.get_previous_group_row(1), None);
+        assert_eq!(items_controller.get_previous_group_row(8), None);
+    }
+
+    #[test]
+    fn test_prefetch_items() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let mut items_controller = ItemsController::new(item_list.clone());
+        assert!(items_controller.get_prefetch_items(0).is_empty());
+        {
+            let mut item_list = item_list.lock().unwrap();
+            let group_ids = [None, None, Some(1), Some(1), Some(1), None, Some(2)];
+            for (index, group_id) in group_ids.iter().enumerate() {
+                let extension = if index == 4 { "mov" } else { "jpg" };
+                let mut item =
+                    FileItem::dummy(&format!("test{}.{}", index, extension), index as i64, true);
+                item.set_group_id(*group_id);
+                item_list.items.push(item);
+            }
+            item_list.items[2].add_similar_vec(&[3, 4]);
+            item_list.items[3].add_similar_vec(&[2, 4]);
+            item_list.items[6].add_similar_vec(&[0]);
+        }
+        items_controller.populate_list_model(&build_filters());
+
+        // The next two items and all images of the next group, videos are not prefetched
+        assert_eq!(items_controller.get_prefetch_items(0), vec![1, 2, 3]);
+        // From within a group, the following group is prefetched
+        assert_eq!(items_controller.get_prefetch_items(2), vec![3, 6, 0]);
+        assert_eq!(items_controller.get_prefetch_items(6), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_thumbnails_progress() {
+        assert_eq!(thumbnails_progress(0, 3), "Preparing thumbnails: 0/3");
+        assert_eq!(thumbnails_progress(2, 3), "Preparing thumbnails: 2/3");
+        assert_eq!(thumbnails_progress(3, 3), "");
+        assert_eq!(thumbnails_progress(0, 0), "");
+    }
+
+    #[test]
+    fn test_comparison_text() {
+        let metrics = |sharpness: Option<f64>, resolution: Option<u64>, size: u64| ItemMetrics {
+            sharpness,
+            resolution,
+            size,
+        };
+        assert_eq!(
+            comparison_text(
+                &metrics(Some(2.0), Some(100), 10),
+                &[metrics(Some(1.0), Some(200), 10)]
+            ),
+            "🔍 Sharpness #1 · 📐 Resolution #2 · 💾 Size #1 of 2 similar images"
+        );
+        // Unknown properties like the sharpness of images that are not loaded yet are left out
+        assert_eq!(
+            comparison_text(
+                &metrics(Some(2.0), None, 10),
+                &[
+                    metrics(None, Some(200), 20),
+                    metrics(Some(3.0), Some(200), 30)
+                ]
+            ),
+            "💾 Size #3 of 3 similar images"
+        );
+    }
+
+    #[test]
+    fn test_metadata() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        let items_controller = ItemsController::new(item_list.clone());
+        {
+            let mut item_list = item_list.lock().unwrap();
+            item_list.items.push(FileItem::dummy("test2.mov", 0, true));
+            let mut file_item = FileItem::dummy("test1.jpg", 0, false);
+            file_item.add_similar_range(&(0..1));
+            file_item.set_group_id(Some(1));
+            item_list.items.push(file_item);
+        }
+
+        let metadata = items_controller.get_metadata(1);
+        assert_eq!(metadata.len(), 6);
+        assert_eq!(metadata[0].name, "Path");
+        assert_eq!(metadata[0].value, "test1.jpg");
+        assert_eq!(metadata[3].name, "Orientation");
+        assert_eq!(metadata[4].name, "Similar to");
+        assert_eq!(metadata[4].value, "📹 test2.mov");
+        assert_eq!(metadata[5].name, "Similarity group");
+        assert_eq!(metadata[5].value, "1");
+
+        assert_eq!(items_controller.get_metadata(0).len(), 4);
+        assert!(items_controller.get_metadata(2).is_empty());
+        // Unknown items are not cached
+        items_controller.cache_metadata(2);
+
+        // The raw value of an invalid date is shown after the date
+        item_list.lock().unwrap().items[0].set_invalid_date(Some("0000:00:00 00:00:00"));
+        let metadata = items_controller.get_metadata(0);
+        assert_eq!(metadata.len(), 5);
+        assert_eq!(metadata[3].name, "Invalid date");
+        assert_eq!(metadata[3].value, "0000:00:00 00:00:00");
+
+        // The EXIF metadata is only shown for the cached current item
+        item_list
+            .lock()
+            .unwrap()
+            .items
+            .push(FileItem::dummy("tests/test.jpg", 0, true));
+        let date_taken = |metadata: &[main_window::MetadataEntry]| {
+            metadata.iter().any(|entry| entry.name == "Date taken")
+        };
+        assert!(!date_taken(&items_controller.get_metadata(2)));
+        items_controller.cache_metadata(2);
+        assert!(date_taken(&items_controller.get_metadata(2)));
+        items_controller.cache_metadata(0);
+        assert!(!date_taken(&items_controller.get_metadata(2)));
+    }
+
     rusty_fork_test! {
         #[test]
         fn test_take_over() {
@@ -442,6 +1533,13 @@ mod tests {
             assert_eq!(list_model.row_data(1).unwrap().text, "📹 test2.mov");
             assert!(window.get_current_image().take_over);
             assert!(similar_items_model.row_data(0).unwrap().take_over);
+
+            items_controller.set_take_over(0, false);
+            items_controller.set_unsure(0, true);
+            assert!(item_list.lock().unwrap().items[0].is_unsure());
+            assert_eq!(list_model.row_data(1).unwrap().text, "📹 ❓ test2.mov");
+            assert!(similar_items_model.row_data(0).unwrap().take_over);
+            assert!(similar_items_model.row_data(0).unwrap().unsure);
         }
     }
 
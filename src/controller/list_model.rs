@@ -0,0 +1,153 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    sync::{Arc, Mutex, TryLockError},
+};
+
+use slint::{Model, ModelNotify, ModelTracker};
+
+use crate::{
+    item_sort_list::{FileItem, ItemList},
+    main_window,
+    misc::translations::tr_format,
+};
+
+/// Slint model for the list of items that only stores the indices of the listed items.
+/// The list items are created when a row is requested, so only visible rows are converted.
+/// While the item list is locked by the synchronization, the rows created last are returned instead of waiting.
+pub struct ListModel {
+    item_list: Arc<Mutex<ItemList>>,
+    local_indices: RefCell<Vec<usize>>,
+    cached_rows: RefCell<Vec<Option<main_window::ListItem>>>,
+    notify: ModelNotify,
+}
+
+impl ListModel {
+    /// Create a new, empty list model
+    pub fn new(item_list: Arc<Mutex<ItemList>>) -> Self {
+        Self {
+            item_list,
+            local_indices: RefCell::new(vec![]),
+            cached_rows: RefCell::new(vec![]),
+            notify: ModelNotify::default(),
+        }
+    }
+
+    /// Set the indices in the item list of the items to list
+    pub fn set_local_indices(&self, local_indices: Vec<usize>) {
+        *self.cached_rows.borrow_mut() = vec![None; local_indices.len()];
+        *self.local_indices.borrow_mut() = local_indices;
+        self.notify.reset();
+    }
+
+    /// Gets the index in the item list of the item in a row
+    pub fn local_index(&self, row: usize) -> Option<usize> {
+        self.local_indices.borrow().get(row).copied()
+    }
+
+    /// Notify that the listed items have changed and need to be created again
+    pub fn refresh(&self) {
+        self.notify.reset();
+    }
+}
+
+impl Model for ListModel {
+    type Data = main_window::ListItem;
+
+    fn row_count(&self) -> usize {
+        self.local_indices.borrow().len()
+    }
+
+    fn row_data(&self, row: usize) -> Option<Self::Data> {
+        let local_index = self.local_index(row)?;
+        let item_list = match self.item_list.try_lock() {
+            Ok(item_list) => item_list,
+            Err(TryLockError::WouldBlock) => {
+                // Rows not created yet stay empty until the list is refreshed
+                let cached_row = self.cached_rows.borrow().get(row).cloned().flatten();
+                return Some(cached_row.unwrap_or(main_window::ListItem {
+                    local_index: local_index as i32,
+                    ..Default::default()
+                }));
+            }
+            Err(TryLockError::Poisoned(error)) => panic!("{}", error),
+        };
+        let file_item = item_list.items.get(local_index)?;
+        let list_item = main_window::ListItem {
+            text: list_item_title(file_item, &item_list),
+            tooltip: list_item_tooltip(file_item),
+            local_index: local_index as i32,
+        };
+        if let Some(cached_row) = self.cached_rows.borrow_mut().get_mut(row) {
+            *cached_row = Some(list_item.clone());
+        }
+        Some(list_item)
+    }
+
+    fn model_tracker(&self) -> &dyn ModelTracker {
+        &self.notify
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Get the list item title for the GUI from a file item
+fn list_item_title(file_item: &FileItem, item_list: &ItemList) -> slint::SharedString {
+    let mut title = file_item.get_item_string(&item_list.path);
+    if item_list.get_event(file_item).is_some() {
+        title = String::from("📅 ") + &title;
+    }
+    slint::SharedString::from(title)
+}
+
+/// Get the tooltip of a list item showing the group of similar items
+fn list_item_tooltip(file_item: &FileItem) -> slint::SharedString {
+    match file_item.get_group_id() {
+        Some(group_id) => slint::SharedString::from(tr_format("🔀 Group {}", &[&group_id])),
+        None => slint::SharedString::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_model() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        {
+            let mut item_list = item_list.lock().unwrap();
+            item_list.items.push(FileItem::dummy("test1.jpg", 0, true));
+            item_list.items.push(FileItem::dummy("test2.mov", 1, false));
+        }
+        let list_model = ListModel::new(item_list.clone());
+        assert_eq!(list_model.row_count(), 0);
+        assert!(list_model.row_data(0).is_none());
+
+        list_model.set_local_indices(vec![1, 0]);
+        assert_eq!(list_model.row_count(), 2);
+        assert_eq!(list_model.local_index(0), Some(1));
+        assert_eq!(list_model.row_data(0).unwrap().local_index, 1);
+        assert_eq!(list_model.row_data(0).unwrap().text, "📹 🗑 test2.mov");
+        assert_eq!(list_model.row_data(1).unwrap().text, "📷 test1.jpg");
+        assert_eq!(list_model.row_data(1).unwrap().tooltip, "");
+        assert!(list_model.row_data(2).is_none());
+
+        // Texts are created on request and reflect changes of the item list
+        item_list.lock().unwrap().items[1].set_take_over(true);
+        assert_eq!(list_model.row_data(0).unwrap().text, "📹 test2.mov");
+        item_list.lock().unwrap().items[1].set_group_id(Some(3));
+        assert_eq!(list_model.row_data(0).unwrap().tooltip, "🔀 Group 3");
+
+        // While the item list is locked, the rows created last are returned without waiting
+        {
+            let mut locked_item_list = item_list.lock().unwrap();
+            locked_item_list.items[1].set_take_over(false);
+            assert_eq!(list_model.row_data(0).unwrap().text, "📹 test2.mov");
+            assert_eq!(list_model.row_data(0).unwrap().local_index, 1);
+        }
+        assert_eq!(list_model.row_data(0).unwrap().text, "📹 🗑 test2.mov");
+    }
+}
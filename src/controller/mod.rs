@@ -1,3 +1,5 @@
 pub mod events_controller;
 mod helper;
 pub mod items_controller;
+mod list_model;
+mod overview_model;
@@ -0,0 +1,161 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashSet,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use slint::{Model, ModelNotify, ModelTracker};
+
+use crate::{
+    item_sort_list::ItemList,
+    main_window,
+    misc::image_cache::{self, ImageCache},
+};
+
+/// Slint model for the thumbnail overview of the listed items. Like the list model, it only stores the indices of the
+/// listed items and creates the overview items when a row is requested, so only the thumbnails of visible items are loaded.
+pub struct OverviewModel {
+    item_list: Arc<Mutex<ItemList>>,
+    image_cache: Rc<ImageCache>,
+    local_indices: RefCell<Vec<usize>>,
+    /// Items whose thumbnail is loaded in the background, so that it is only requested once
+    requested: RefCell<HashSet<usize>>,
+    window: RefCell<Option<slint::Weak<main_window::ImageSieve>>>,
+    notify: ModelNotify,
+}
+
+impl OverviewModel {
+    /// Create a new, empty overview model
+    pub fn new(item_list: Arc<Mutex<ItemList>>, image_cache: Rc<ImageCache>) -> Self {
+        Self {
+            item_list,
+            image_cache,
+            local_indices: RefCell::new(vec![]),
+            requested: RefCell::new(HashSet::new()),
+            window: RefCell::new(None),
+            notify: ModelNotify::default(),
+        }
+    }
+
+    /// Set the window the overview is shown in, which is notified when a thumbnail was loaded
+    pub fn set_window(&self, window: slint::Weak<main_window::ImageSieve>) {
+        *self.window.borrow_mut() = Some(window);
+    }
+
+    /// Set the indices in the item list of the items to show
+    pub fn set_local_indices(&self, local_indices: Vec<usize>) {
+        *self.local_indices.borrow_mut() = local_indices;
+        self.requested.borrow_mut().clear();
+        self.notify.reset();
+    }
+
+    /// Notify that the shown items have changed and need to be created again
+    pub fn refresh(&self) {
+        self.requested.borrow_mut().clear();
+        for row in 0..self.row_count() {
+            self.notify.row_changed(row);
+        }
+    }
+
+    /// Notify that the thumbnail of an item in a row was loaded
+    pub fn thumbnail_loaded(&self, row: usize) {
+        if row < self.row_count() {
+            self.notify.row_changed(row);
+        }
+    }
+
+    /// Gets the thumbnail of an item or loads it in the background if it is not cached yet
+    fn get_thumbnail(&self, row: usize, local_index: usize, item_list: &ItemList) -> slint::Image {
+        let item = &item_list.items[local_index];
+        if let Some(thumbnail) = self.image_cache.get_thumbnail(item) {
+            return thumbnail;
+        }
+        if let Some(window_weak) = self.window.borrow().clone() {
+            if self.requested.borrow_mut().insert(local_index) {
+                let f: image_cache::DoneCallback = Box::new(move |_| {
+                    window_weak
+                        .clone()
+                        .upgrade_in_event_loop(move |handle| {
+                            let overview_model = handle.get_overview_model();
+                            if let Some(overview_model) =
+                                overview_model.as_any().downcast_ref::<OverviewModel>()
+                            {
+                                overview_model.thumbnail_loaded(row);
+                            }
+                        })
+                        .unwrap()
+                });
+                self.image_cache
+                    .load(item, image_cache::Purpose::Thumbnail, Some(f));
+            }
+        }
+        self.image_cache.get_waiting()
+    }
+}
+
+impl Model for OverviewModel {
+    type Data = main_window::OverviewItem;
+
+    fn row_count(&self) -> usize {
+        self.local_indices.borrow().len()
+    }
+
+    fn row_data(&self, row: usize) -> Option<Self::Data> {
+        let local_index = self.local_indices.borrow().get(row).copied()?;
+        let item_list = self.item_list.lock().unwrap();
+        let file_item = item_list.items.get(local_index)?;
+        Some(main_window::OverviewItem {
+            thumbnail: self.get_thumbnail(row, local_index, &item_list),
+            take_over: file_item.get_take_over(),
+            unsure: file_item.is_unsure(),
+            pending_delete: file_item.is_pending_delete(),
+            favorite: file_item.is_favorite(),
+            local_index: local_index as i32,
+        })
+    }
+
+    fn model_tracker(&self) -> &dyn ModelTracker {
+        &self.notify
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item_sort_list::FileItem;
+
+    #[test]
+    fn test_overview_model() {
+        let item_list = Arc::new(Mutex::new(ItemList::new()));
+        {
+            let mut item_list = item_list.lock().unwrap();
+            item_list.items.push(FileItem::dummy("test1.jpg", 0, true));
+            item_list.items.push(FileItem::dummy("test2.mov", 1, false));
+        }
+        let overview_model = OverviewModel::new(item_list.clone(), Rc::new(ImageCache::new()));
+        assert_eq!(overview_model.row_count(), 0);
+        assert!(overview_model.row_data(0).is_none());
+
+        overview_model.set_local_indices(vec![1, 0]);
+        assert_eq!(overview_model.row_count(), 2);
+        let row_data = overview_model.row_data(0).unwrap();
+        assert_eq!(row_data.local_index, 1);
+        assert!(!row_data.take_over);
+        assert!(overview_model.row_data(1).unwrap().take_over);
+        assert!(overview_model.row_data(2).is_none());
+        // Without a window, no thumbnails are loaded
+        assert!(overview_model.requested.borrow().is_empty());
+
+        // Items are created on request and reflect changes of the item list
+        item_list.lock().unwrap().items[1].set_pending_delete(true);
+        let row_data = overview_model.row_data(0).unwrap();
+        assert!(row_data.pending_delete);
+        assert!(!row_data.unsure);
+    }
+}
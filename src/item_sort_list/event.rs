@@ -1,6 +1,7 @@
 extern crate chrono;
 
 use std::cmp::Ordering;
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
@@ -21,6 +22,13 @@ pub struct Event {
     #[serde_as(as = "DisplayFromStr")]
     /// Event end date
     pub end_date: NaiveDate,
+    /// Directory to sieve the items of the event to instead of the target directory, empty if not set
+    #[serde(default)]
+    pub target_directory: String,
+    /// Folder relative to the source directory whose items belong to the event regardless of their dates, empty if
+    /// the items are matched by their dates
+    #[serde(default)]
+    pub folder: String,
 }
 
 impl Event {
@@ -32,9 +40,30 @@ impl Event {
             name: String::from(name),
             start_date,
             end_date,
+            target_directory: String::new(),
+            folder: String::new(),
         }
     }
 
+    /// Creates an event for the items of a folder, which covers the days of its items
+    pub fn from_folder(folder: &Path, start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        Self {
+            name: folder
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            start_date,
+            end_date,
+            target_directory: String::new(),
+            folder: folder.to_string_lossy().to_string(),
+        }
+    }
+
+    /// Returns whether the items of the event are matched by their folder instead of their dates
+    pub fn is_folder_event(&self) -> bool {
+        !self.folder.is_empty()
+    }
+
     /// Updates an event with a new name and start and end date. If start or end date have an invalid format,
     /// return false.
     pub fn update(&mut self, name: &str, start_date: &str, end_date: &str) -> bool {
@@ -127,6 +156,16 @@ mod tests {
         assert_eq!(event.end_date_as_string(), "2021-09-15");
     }
 
+    #[test]
+    fn test_from_folder() {
+        let date = parse_date("2021-09-14").unwrap();
+        let event = Event::from_folder(Path::new("2021/Holidays"), date, date);
+
+        assert_eq!(event.name, "Holidays");
+        assert!(event.is_folder_event());
+        assert!(!Event::new("test", "2021-09-14", "2021-09-15").is_folder_event());
+    }
+
     #[test]
     #[should_panic]
     fn test_create_error_start() {
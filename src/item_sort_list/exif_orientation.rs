@@ -0,0 +1,286 @@
+use std::io::{Cursor, Error, ErrorKind};
+
+use image::codecs::jpeg::JpegEncoder;
+
+/// EXIF tag of the orientation
+const ORIENTATION_TAG: u16 = 0x0112;
+
+/// Rotates the pixels of a JPEG image by the given degrees, so that it is displayed upright without an EXIF
+/// orientation. The image is encoded again with the given quality, which is lossy. The EXIF data is kept with the
+/// orientation set to 1, other metadata like the color profile is lost.
+pub fn rotate_jpeg_upright(jpeg: &[u8], rotation: i32, quality: u8) -> Result<Vec<u8>, Error> {
+    let image = image::load_from_memory_with_format(jpeg, image::ImageFormat::Jpeg)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let image = match rotation {
+        90 => image.rotate90(),
+        180 => image.rotate180(),
+        270 => image.rotate270(),
+        _ => image,
+    };
+
+    let mut encoded = vec![];
+    JpegEncoder::new_with_quality(&mut Cursor::new(&mut encoded), quality.clamp(1, 100))
+        .encode_image(&image)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    // The encoder does not write EXIF data, so insert the original one right after the start of image marker
+    match find_exif_segment(jpeg) {
+        Some(exif_segment) => {
+            let mut exif_segment = exif_segment.to_vec();
+            set_exif_orientation(&mut exif_segment, 1);
+            Ok([&encoded[..2], &exif_segment, &encoded[2..]].concat())
+        }
+        None => Ok(encoded),
+    }
+}
+
+/// Gets the EXIF orientation that makes viewers rotate an image clockwise by the given degrees
+pub fn orientation_from_rotation(rotation: i32) -> u16 {
+    match rotation.rem_euclid(360) {
+        90 => 6,
+        180 => 3,
+        270 => 8,
+        _ => 1,
+    }
+}
+
+/// Gets the EXIF orientation of a JPEG image, None if it has no EXIF data or no orientation
+pub fn get_jpeg_orientation(jpeg: &[u8]) -> Option<u16> {
+    let exif_segment = find_exif_segment(jpeg)?;
+    let (tiff_offset, big_endian) = find_orientation_value(exif_segment)?;
+    let value = [exif_segment[tiff_offset], exif_segment[tiff_offset + 1]];
+    Some(if big_endian {
+        u16::from_be_bytes(value)
+    } else {
+        u16::from_le_bytes(value)
+    })
+}
+
+/// Sets the EXIF orientation of a JPEG image without touching the image data, so this is lossless.
+/// All other metadata is kept. If the image has no EXIF data, a segment containing only the orientation is added.
+/// Returns None if the image has EXIF data without an orientation, which cannot be changed in place.
+pub fn set_jpeg_orientation(jpeg: &[u8], orientation: u16) -> Option<Vec<u8>> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    match find_exif_segment(jpeg) {
+        Some(exif_segment) => {
+            let start = exif_segment.as_ptr() as usize - jpeg.as_ptr() as usize;
+            let end = start + exif_segment.len();
+            let mut oriented = jpeg.to_vec();
+            if set_exif_orientation(&mut oriented[start..end], orientation) {
+                Some(oriented)
+            } else {
+                None
+            }
+        }
+        None => {
+            // EXIF data follows the JFIF header, if there is one
+            let position = if jpeg.len() >= 6 && jpeg[2..4] == [0xFF, 0xE0] {
+                4 + u16::from_be_bytes([jpeg[4], jpeg[5]]) as usize
+            } else {
+                2
+            };
+            let position = position.min(jpeg.len());
+            Some(
+                [
+                    &jpeg[..position],
+                    &orientation_segment(orientation),
+                    &jpeg[position..],
+                ]
+                .concat(),
+            )
+        }
+    }
+}
+
+/// Creates an APP1 segment with EXIF data that only contains the orientation
+fn orientation_segment(orientation: u16) -> Vec<u8> {
+    let mut segment = vec![0xFF, 0xE1, 0, 34];
+    segment.extend_from_slice(b"Exif\0\0");
+    // Big endian TIFF header with the first directory right after it
+    segment.extend_from_slice(&[b'M', b'M', 0, 42, 0, 0, 0, 8]);
+    // One entry with the orientation as a single short value and no further directory
+    segment.extend_from_slice(&[0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1]);
+    segment.extend_from_slice(&orientation.to_be_bytes());
+    segment.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+    segment
+}
+
+/// Finds the APP1 segment containing the EXIF data of a JPEG image, including its marker and length
+fn find_exif_segment(jpeg: &[u8]) -> Option<&[u8]> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut position = 2;
+    while position + 4 <= jpeg.len() && jpeg[position] == 0xFF {
+        let marker = jpeg[position + 1];
+        // Image data starts at the start of scan marker, there is no metadata afterwards
+        if marker == 0xDA {
+            break;
+        }
+        let length = u16::from_be_bytes([jpeg[position + 2], jpeg[position + 3]]) as usize;
+        let end = position + 2 + length;
+        if end > jpeg.len() {
+            break;
+        }
+        if marker == 0xE1 && jpeg[position + 4..end].starts_with(b"Exif\0\0") {
+            return Some(&jpeg[position..end]);
+        }
+        position = end;
+    }
+    None
+}
+
+/// Finds the value of the orientation in an APP1 segment with EXIF data.
+/// Returns its offset in the segment and whether it is stored big endian.
+fn find_orientation_value(exif_segment: &[u8]) -> Option<(usize, bool)> {
+    // The TIFF structure starts after the marker, the length and the EXIF header
+    let tiff = exif_segment.get(10..).filter(|tiff| tiff.len() >= 8)?;
+    let big_endian = match &tiff[..2] {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let read_u16 = |bytes: &[u8]| {
+        if big_endian {
+            u16::from_be_bytes([bytes[0], bytes[1]])
+        } else {
+            u16::from_le_bytes([bytes[0], bytes[1]])
+        }
+    };
+    let offset_bytes = [tiff[4], tiff[5], tiff[6], tiff[7]];
+    let ifd_offset = if big_endian {
+        u32::from_be_bytes(offset_bytes)
+    } else {
+        u32::from_le_bytes(offset_bytes)
+    } as usize;
+    if ifd_offset + 2 > tiff.len() {
+        return None;
+    }
+
+    let entry_count = read_u16(&tiff[ifd_offset..]) as usize;
+    for entry in 0..entry_count {
+        let entry_offset = ifd_offset + 2 + entry * 12;
+        if entry_offset + 12 > tiff.len() {
+            return None;
+        }
+        if read_u16(&tiff[entry_offset..]) == ORIENTATION_TAG {
+            return Some((10 + entry_offset + 8, big_endian));
+        }
+    }
+    None
+}
+
+/// Sets the orientation in an APP1 segment with EXIF data. Returns false if the segment contains no orientation.
+fn set_exif_orientation(exif_segment: &mut [u8], orientation: u16) -> bool {
+    match find_orientation_value(exif_segment) {
+        Some((offset, big_endian)) => {
+            let value = if big_endian {
+                orientation.to_be_bytes()
+            } else {
+                orientation.to_le_bytes()
+            };
+            exif_segment[offset..offset + 2].copy_from_slice(&value);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    /// Gets the orientation from the EXIF data of a JPEG image
+    fn get_exif_orientation(jpeg: &[u8]) -> Option<u32> {
+        let exif = exif::Reader::new().read_from_container(&mut Cursor::new(jpeg));
+        exif.ok()?
+            .get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+            .value
+            .get_uint(0)
+    }
+
+    #[test]
+    fn test_set_exif_orientation() {
+        let mut jpeg = std::fs::read("tests/test.jpg").unwrap();
+        assert_eq!(get_exif_orientation(&jpeg), Some(6));
+
+        let exif_segment = find_exif_segment(&jpeg).unwrap();
+        let start = exif_segment.as_ptr() as usize - jpeg.as_ptr() as usize;
+        let end = start + exif_segment.len();
+        assert!(set_exif_orientation(&mut jpeg[start..end], 3));
+        assert_eq!(get_exif_orientation(&jpeg), Some(3));
+
+        assert!(find_exif_segment(b"no jpeg").is_none());
+        let no_exif = std::fs::read("tests/test_no_exif.jpg").unwrap();
+        assert!(find_exif_segment(&no_exif).is_none());
+        assert!(!set_exif_orientation(&mut [0xFF, 0xE1, 0, 8], 1));
+    }
+
+    #[test]
+    fn test_set_jpeg_orientation() {
+        let jpeg = std::fs::read("tests/test.jpg").unwrap();
+        assert_eq!(get_jpeg_orientation(&jpeg), Some(6));
+        for rotation in [0, 90, 180, 270] {
+            let orientation = orientation_from_rotation(rotation);
+            let oriented = set_jpeg_orientation(&jpeg, orientation).unwrap();
+            assert_eq!(get_jpeg_orientation(&oriented), Some(orientation));
+            assert_eq!(get_exif_orientation(&oriented), Some(orientation as u32));
+            // Only the orientation is changed, the image data and all other metadata are kept
+            assert_eq!(oriented.len(), jpeg.len());
+            let changed = jpeg.iter().zip(&oriented).filter(|(a, b)| a != b).count();
+            assert!(changed <= 1);
+        }
+
+        // An orientation is added to images without EXIF data
+        let no_exif = std::fs::read("tests/test_no_exif.jpg").unwrap();
+        assert_eq!(get_jpeg_orientation(&no_exif), None);
+        let oriented = set_jpeg_orientation(&no_exif, 8).unwrap();
+        assert_eq!(get_exif_orientation(&oriented), Some(8));
+        assert_eq!(
+            image::load_from_memory(&oriented).unwrap(),
+            image::load_from_memory(&no_exif).unwrap()
+        );
+
+        assert!(set_jpeg_orientation(b"no jpeg", 1).is_none());
+    }
+
+    #[test]
+    fn test_rotate_jpeg_upright() {
+        let jpeg = std::fs::read("tests/test.jpg").unwrap();
+        let (width, height) = image::load_from_memory(&jpeg).unwrap().dimensions();
+        let exif_segment = find_exif_segment(&jpeg).unwrap();
+        let start = exif_segment.as_ptr() as usize - jpeg.as_ptr() as usize;
+        let end = start + exif_segment.len();
+
+        // Check all orientations, the pixels are rotated and the orientation is reset
+        for (orientation, rotation) in [(6, 90), (3, 180), (8, 270)] {
+            let mut oriented_jpeg = jpeg.clone();
+            set_exif_orientation(&mut oriented_jpeg[start..end], orientation);
+
+            let upright_jpeg = rotate_jpeg_upright(&oriented_jpeg, rotation, 95).unwrap();
+            assert_eq!(get_exif_orientation(&upright_jpeg), Some(1));
+            let upright_image = image::load_from_memory(&upright_jpeg).unwrap();
+            if rotation == 180 {
+                assert_eq!(upright_image.dimensions(), (width, height));
+            } else {
+                assert_eq!(upright_image.dimensions(), (height, width));
+            }
+            // The other EXIF data like the capture date is kept
+            let exif = exif::Reader::new()
+                .read_from_container(&mut Cursor::new(&upright_jpeg))
+                .unwrap();
+            assert!(exif
+                .get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+                .is_some());
+        }
+
+        // A lower quality results in a smaller file
+        let low_quality = rotate_jpeg_upright(&jpeg, 90, 20).unwrap();
+        assert!(low_quality.len() < rotate_jpeg_upright(&jpeg, 90, 95).unwrap().len());
+
+        assert!(rotate_jpeg_upright(b"no jpeg", 90, 95).is_err());
+    }
+}
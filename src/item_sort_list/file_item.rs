@@ -6,6 +6,7 @@ use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
+use chrono::{NaiveDateTime, TimeZone};
 use img_hash::ImageHash;
 use serde::Deserialize;
 use serde::Serialize;
@@ -14,13 +15,64 @@ use serde::Serializer;
 use super::file_types::is_image;
 use super::file_types::is_raw_image;
 use super::file_types::is_video;
+use super::item_traits::GpsPosition;
 use super::item_traits::Orientation;
 use super::item_traits::PropertyResolver;
+use super::sieve::SievedCopy;
 use super::timestamp_to_string;
 use super::Format;
 
 pub type HashType = ImageHash<Vec<u8>>;
 
+/// Default size of the hash grid, the hash of a landscape image has twice as many columns as rows
+pub const DEFAULT_HASH_SIZE: u32 = 8;
+
+fn default_hash_size() -> u32 {
+    DEFAULT_HASH_SIZE
+}
+
+/// Scales a maximum hash difference for the default hash size to a hash size, as the number of hash bits grows with
+/// the square of the hash size
+pub fn scale_hash_max_diff(max_diff: u32, hash_size: u32) -> u32 {
+    (max_diff * hash_size * hash_size / (DEFAULT_HASH_SIZE * DEFAULT_HASH_SIZE)).max(1)
+}
+
+/// Common resolutions of desktop, phone and tablet screens in landscape orientation, used to detect screenshots
+const SCREEN_RESOLUTIONS: [(u32, u32); 32] = [
+    (1280, 720),
+    (1280, 800),
+    (1280, 1024),
+    (1366, 768),
+    (1440, 900),
+    (1536, 864),
+    (1600, 900),
+    (1680, 1050),
+    (1920, 1080),
+    (1920, 1200),
+    (2560, 1080),
+    (2560, 1440),
+    (2560, 1600),
+    (2880, 1800),
+    (3440, 1440),
+    (3840, 2160),
+    (1334, 750),
+    (1792, 828),
+    (2208, 1242),
+    (2280, 1080),
+    (2340, 1080),
+    (2400, 1080),
+    (2436, 1125),
+    (2532, 1170),
+    (2556, 1179),
+    (2688, 1242),
+    (2778, 1284),
+    (2796, 1290),
+    (3088, 1440),
+    (3200, 1440),
+    (2048, 1536),
+    (2732, 2048),
+];
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 enum ItemType {
     Image,
@@ -32,19 +84,80 @@ enum ItemType {
 #[derive(Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct FileItem {
     /// Actual file path
+    #[serde(serialize_with = "serialize_path")]
+    #[serde(deserialize_with = "deserialize_path")]
     pub path: PathBuf,
     /// Time stamp of file creation (either from EXIF or from file system)
     timestamp: i64,
+    /// Flag indicating that the time stamp is the date of the file system, because the file has no capture date
+    #[serde(default)]
+    file_date: bool,
+    /// Offset of the time stamp to UTC in seconds if known
+    #[serde(default)]
+    utc_offset: Option<i32>,
     /// Flag indicating if the file shall be taken over during sieving (true) or be discarded (false)
     take_over: bool,
+    /// Flag indicating that no decision was made yet, unsure items are kept and sieved to a review directory
+    #[serde(default)]
+    unsure: bool,
+    /// Flag indicating if the take over state was set explicitly or is still the default of newly found items
+    #[serde(default)]
+    decided: bool,
+    /// Flag indicating that the file is tagged to be deleted in the source directory once the deletion is confirmed
+    #[serde(default)]
+    pending_delete: bool,
+    /// Flag marking one of the best items, independent of the take over state
+    #[serde(default)]
+    favorite: bool,
+    /// Flag indicating that all items of the group are intentional variations, e.g. bracketed exposures, which are kept
+    #[serde(default)]
+    keep_all: bool,
+    /// Flag indicating that the file is empty or truncated, e.g. by an interrupted download
+    #[serde(default)]
+    corrupt: bool,
     /// List of similar items as indices in the list of file items
     similar: Vec<usize>,
+    /// Number of the group of similar items this item belongs to, if it has similar items
+    #[serde(default)]
+    group_id: Option<usize>,
+    /// Copy of the file made by the last sieve, used to skip unchanged copies when sieving again
+    #[serde(default)]
+    sieved_copy: Option<SievedCopy>,
     /// Orientation of the image
     orientation: Option<Orientation>,
+    /// Rotation in degrees set by the user on top of the orientation, e.g. if the camera did not detect it correctly
+    #[serde(default)]
+    rotation: i32,
+    /// Flag indicating that the orientation disagrees with the pixels, e.g. because an editor rotated them already
+    #[serde(default)]
+    orientation_mismatch: bool,
+    /// Flag indicating that the user chose to display the image without applying its orientation
+    #[serde(default)]
+    ignore_orientation: bool,
+    /// Position at which the item was captured, if stored in its metadata
+    #[serde(default)]
+    gps: Option<GpsPosition>,
+    /// Make and model of the camera, if stored in its metadata
+    #[serde(default)]
+    camera: Option<String>,
+    /// Width and height of the image in pixels, only determined for images
+    #[serde(default)]
+    dimensions: Option<(u32, u32)>,
+    /// Raw date from the metadata that could not be parsed or is implausible, the file date is used instead
+    #[serde(default)]
+    invalid_date: Option<String>,
     /// Hash of the image
     #[serde(serialize_with = "serialize_hash")]
     #[serde(deserialize_with = "deserialize_hash")]
     hash: Option<HashType>,
+    /// Hashes of the image rotated by 90, 180 and 270 degrees
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_hashes")]
+    #[serde(deserialize_with = "deserialize_hashes")]
+    rotated_hashes: Vec<HashType>,
+    /// Size of the hash grid the hashes were calculated with
+    #[serde(default = "default_hash_size")]
+    hash_size: u32,
     /// File item type
     item_type: Option<ItemType>,
 }
@@ -71,6 +184,75 @@ where
     }
 }
 
+pub fn serialize_hashes<S>(hashes: &[HashType], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let hashes: Vec<String> = hashes.iter().map(|hash| hash.to_base64()).collect();
+    hashes.serialize(s)
+}
+
+pub fn deserialize_hashes<'de, D>(deserializer: D) -> Result<Vec<HashType>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let hash_strs = Vec::<String>::deserialize(deserializer)?;
+    Ok(hash_strs
+        .iter()
+        .filter_map(|hash_str| process_encoded_hash(hash_str))
+        .collect())
+}
+
+/// Path as stored in a project file, either as a string or as raw bytes if the path is not valid Unicode
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedPath {
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+pub fn serialize_path<S>(path: &Path, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match path.to_str() {
+        Some(path) => path.serialize(s),
+        None => path_to_bytes(path).serialize(s),
+    }
+}
+
+pub fn deserialize_path<'de, D>(deserializer: D) -> Result<PathBuf, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match SerializedPath::deserialize(deserializer)? {
+        SerializedPath::String(path) => PathBuf::from(path),
+        SerializedPath::Bytes(bytes) => path_from_bytes(bytes),
+    })
+}
+
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).to_string())
+}
+
 fn get_item_type(path: &Path) -> ItemType {
     match (is_image(path), is_video(path), is_raw_image(path)) {
         (true, _, _) => ItemType::Image,
@@ -89,17 +271,43 @@ impl FileItem {
         encoded_hash: &str,
     ) -> Self {
         let timestamp = property_resolver.get_timestamp();
+        let file_date = property_resolver.has_file_date();
+        let utc_offset = property_resolver.get_utc_offset();
         let orientation = property_resolver.get_orientation();
+        let gps = property_resolver.get_gps();
+        let camera = property_resolver.get_camera();
+        let dimensions = property_resolver.get_dimensions();
+        let invalid_date = property_resolver.get_invalid_date();
+        let orientation_mismatch = property_resolver.has_orientation_mismatch();
         let hash = process_encoded_hash(encoded_hash);
         let item_type = get_item_type(&path);
 
         Self {
             path,
             timestamp,
+            file_date,
+            utc_offset,
             take_over,
+            unsure: false,
+            decided: false,
+            pending_delete: false,
+            favorite: false,
+            keep_all: false,
+            corrupt: false,
             similar: Vec::new(),
+            group_id: None,
+            sieved_copy: None,
             orientation,
+            rotation: 0,
+            orientation_mismatch,
+            ignore_orientation: false,
+            gps,
+            camera,
+            dimensions,
+            invalid_date,
             hash,
+            rotated_hashes: Vec::new(),
+            hash_size: DEFAULT_HASH_SIZE,
             item_type: Some(item_type),
         }
     }
@@ -112,10 +320,29 @@ impl FileItem {
         Self {
             path,
             timestamp,
+            file_date: false,
+            utc_offset: None,
             orientation: Some(Orientation::Landscape),
+            rotation: 0,
+            orientation_mismatch: false,
+            ignore_orientation: false,
+            gps: None,
+            camera: None,
+            dimensions: None,
+            invalid_date: None,
             take_over,
+            unsure: false,
+            decided: false,
+            pending_delete: false,
+            favorite: false,
+            keep_all: false,
+            corrupt: false,
             similar: Vec::new(),
+            group_id: None,
+            sieved_copy: None,
             hash: None,
+            rotated_hashes: Vec::new(),
+            hash_size: DEFAULT_HASH_SIZE,
             item_type: Some(item_type),
         }
     }
@@ -130,6 +357,9 @@ impl FileItem {
     /// Set the take over property to make a file item be discarded or taken over in the sieving process
     pub fn set_take_over(&mut self, take_over: bool) {
         self.take_over = take_over;
+        self.unsure = false;
+        self.pending_delete = false;
+        self.decided = true;
     }
 
     /// Get the take over property
@@ -137,11 +367,105 @@ impl FileItem {
         self.take_over
     }
 
+    /// Mark a file item as unsure to review it later. Unsure items are taken over in the sieving process.
+    pub fn set_unsure(&mut self, unsure: bool) {
+        self.unsure = unsure;
+        self.decided = true;
+        if unsure {
+            self.take_over = true;
+            self.pending_delete = false;
+        }
+    }
+
+    /// Get the unsure property
+    pub fn is_unsure(&self) -> bool {
+        self.unsure
+    }
+
+    /// Tag a file item to be deleted in the source directory or remove the tag. Tagged items are discarded.
+    pub fn set_pending_delete(&mut self, pending_delete: bool) {
+        self.pending_delete = pending_delete;
+        self.decided = true;
+        if pending_delete {
+            self.take_over = false;
+            self.unsure = false;
+        }
+    }
+
+    /// Check if the item is tagged to be deleted
+    pub fn is_pending_delete(&self) -> bool {
+        self.pending_delete
+    }
+
+    /// Mark a file item as favorite or remove the mark, which does not change if it is taken over
+    pub fn set_favorite(&mut self, favorite: bool) {
+        self.favorite = favorite;
+    }
+
+    /// Check if the file item is marked as favorite
+    pub fn is_favorite(&self) -> bool {
+        self.favorite
+    }
+
+    /// Mark a file item as part of a group whose items are all kept or remove the mark
+    pub fn set_keep_all(&mut self, keep_all: bool) {
+        self.keep_all = keep_all;
+    }
+
+    /// Check if the file item is part of a group whose items are all kept
+    pub fn is_keep_all(&self) -> bool {
+        self.keep_all
+    }
+
+    /// Mark the file of the item as empty or truncated or remove the mark
+    pub fn set_corrupt(&mut self, corrupt: bool) {
+        self.corrupt = corrupt;
+    }
+
+    /// Check if the file of the item is empty or truncated
+    pub fn is_corrupt(&self) -> bool {
+        self.corrupt
+    }
+
+    /// Check if the item was explicitly kept, discarded or marked as unsure
+    pub fn is_decided(&self) -> bool {
+        self.decided
+    }
+
     /// Get the time stamp of the file item
     pub fn get_timestamp(&self) -> i64 {
         self.timestamp
     }
 
+    /// Check if the time stamp is the date of the file system instead of a capture date
+    pub fn has_file_date(&self) -> bool {
+        self.file_date
+    }
+
+    /// Set if the time stamp is the date of the file system
+    #[cfg(test)]
+    pub fn set_file_date(&mut self, file_date: bool) {
+        self.file_date = file_date;
+    }
+
+    /// Get the time stamp of the file item converted to the wall clock time of a time zone.
+    /// If the offset to UTC is unknown, the time stamp is returned unchanged.
+    pub fn get_timestamp_in<Tz: TimeZone>(&self, time_zone: &Tz) -> i64 {
+        if let Some(utc_offset) = self.utc_offset {
+            let utc = NaiveDateTime::from_timestamp_opt(self.timestamp - utc_offset as i64, 0);
+            if let Some(utc) = utc {
+                return time_zone.from_utc_datetime(&utc).naive_local().timestamp();
+            }
+        }
+        self.timestamp
+    }
+
+    /// Set the offset of the time stamp to UTC in seconds
+    #[cfg(test)]
+    pub fn set_utc_offset(&mut self, utc_offset: Option<i32>) {
+        self.utc_offset = utc_offset;
+    }
+
     /// Get the time stamp of the file item formatted as string
     fn get_date_str(&self) -> String {
         timestamp_to_string(self.timestamp, Format::DateTime)
@@ -171,9 +495,36 @@ impl FileItem {
         &self.similar
     }
 
+    /// Keep only the similar item indices for which the predicate returns true
+    pub fn retain_similars(&mut self, predicate: impl Fn(usize) -> bool) {
+        self.similar
+            .retain(|similar_index| predicate(*similar_index));
+    }
+
     /// Reset the list of similar item indices
     pub fn reset_similars(&mut self) {
-        self.similar.clear()
+        self.similar.clear();
+        self.group_id = None;
+    }
+
+    /// Get the number of the group of similar items this item belongs to
+    pub fn get_group_id(&self) -> Option<usize> {
+        self.group_id
+    }
+
+    /// Set the number of the group of similar items
+    pub fn set_group_id(&mut self, group_id: Option<usize>) {
+        self.group_id = group_id;
+    }
+
+    /// Get the copy of the file made by the last sieve
+    pub fn get_sieved_copy(&self) -> Option<&SievedCopy> {
+        self.sieved_copy.as_ref()
+    }
+
+    /// Set the copy of the file made by a sieve
+    pub fn set_sieved_copy(&mut self, sieved_copy: Option<SievedCopy>) {
+        self.sieved_copy = sieved_copy;
     }
 
     fn has_similars(&self) -> bool {
@@ -193,17 +544,116 @@ impl FileItem {
         self.orientation.as_ref()
     }
 
+    /// Rotates the item clockwise by the given degrees on top of its orientation
+    pub fn rotate(&mut self, degrees: i32) {
+        self.rotation = (self.rotation + degrees).rem_euclid(360);
+    }
+
+    /// Get the rotation in degrees set by the user on top of the orientation
+    pub fn get_user_rotation(&self) -> i32 {
+        self.rotation
+    }
+
+    /// Get the rotation in degrees that is needed to display the item upright, composed of its orientation and the
+    /// rotation set by the user. The orientation is left out if the user chose to ignore it.
+    pub fn get_rotation(&self) -> i32 {
+        let orientation = self
+            .orientation
+            .as_ref()
+            .filter(|_| !self.ignore_orientation)
+            .map_or(0, |orientation| orientation.get_rotation());
+        (orientation + self.rotation) % 360
+    }
+
+    /// Checks if the orientation disagrees with the pixels of the image, so that it is likely rotated twice
+    pub fn has_orientation_mismatch(&self) -> bool {
+        self.orientation_mismatch
+    }
+
+    /// Checks if the image is displayed without applying its orientation
+    pub fn is_orientation_ignored(&self) -> bool {
+        self.ignore_orientation
+    }
+
+    /// Set whether the image is displayed without applying its orientation
+    pub fn set_ignore_orientation(&mut self, ignore_orientation: bool) {
+        self.ignore_orientation = ignore_orientation;
+    }
+
+    /// Get the position at which the item was captured
+    pub fn get_gps(&self) -> Option<GpsPosition> {
+        self.gps
+    }
+
+    /// Set the position at which the item was captured
+    #[cfg(test)]
+    pub fn set_gps(&mut self, gps: Option<GpsPosition>) {
+        self.gps = gps;
+    }
+
+    /// Gets the raw date from the metadata that could not be parsed or is implausible
+    pub fn get_invalid_date(&self) -> Option<&str> {
+        self.invalid_date.as_deref()
+    }
+
+    /// Set the raw date that could not be parsed or is implausible
+    #[cfg(test)]
+    pub fn set_invalid_date(&mut self, invalid_date: Option<&str>) {
+        self.invalid_date = invalid_date.map(String::from);
+    }
+
+    /// Gets the width and height of the original image in pixels, None if they are unknown, e.g. for videos
+    pub fn get_dimensions(&self) -> Option<(u32, u32)> {
+        self.dimensions
+    }
+
+    /// Gets the longer side of the original image in pixels, 0 if it is unknown
+    pub fn get_long_side(&self) -> u32 {
+        self.dimensions
+            .map_or(0, |(width, height)| width.max(height))
+    }
+
+    /// Checks if the item is likely a screenshot: an image without a camera in its metadata and with the size of a
+    /// common screen in either orientation
+    pub fn is_screenshot(&self) -> bool {
+        match (&self.camera, self.dimensions) {
+            (None, Some((width, height))) if self.is_image() => SCREEN_RESOLUTIONS
+                .iter()
+                .any(|&resolution| resolution == (width, height) || resolution == (height, width)),
+            _ => false,
+        }
+    }
+
     /// Gets a string representing the item type and if it has simlar items or not, if it will be discarded and the item path
     pub fn get_item_string(&self, base_path: &Path) -> String {
         let path = self.path.strip_prefix(base_path).unwrap_or(&self.path);
         let similars_str = if !self.has_similars() { "🔀" } else { "" };
         let extension_str = self.extension_to_unicode_icon();
-        let take_over_str = if self.take_over { "" } else { "🗑" };
+        let take_over_str = if self.pending_delete {
+            "❌"
+        } else if self.unsure {
+            "❓"
+        } else if self.take_over {
+            ""
+        } else {
+            "🗑"
+        };
+        let favorite_str = if self.favorite { "⭐" } else { "" };
+        let invalid_date_str = if self.invalid_date.is_some() {
+            "⚠"
+        } else {
+            ""
+        };
+        let corrupt_str = if self.corrupt { "💔" } else { "" };
+        let path_str = path.to_string_lossy();
         let strings: Vec<&str> = [
             similars_str,
             extension_str,
             take_over_str,
-            path.to_str().unwrap(),
+            favorite_str,
+            invalid_date_str,
+            corrupt_str,
+            &path_str,
         ]
         .iter()
         .filter(|&s| !s.is_empty())
@@ -229,7 +679,9 @@ impl FileItem {
 
     /// Get the unicode icon for the extension
     fn extension_to_unicode_icon(&self) -> &str {
-        if self.is_image() || self.is_raw_image() {
+        if self.is_screenshot() {
+            "🖥"
+        } else if self.is_image() || self.is_raw_image() {
             "📷"
         } else if self.is_video() {
             "📹"
@@ -262,13 +714,46 @@ impl FileItem {
         self.hash.is_some()
     }
 
+    /// Removes all stored hashes, so that they are calculated again when checking the similarity
+    pub fn clear_hashes(&mut self) {
+        self.hash = None;
+        self.rotated_hashes.clear();
+    }
+
+    /// Set the hashes of the image rotated by 90, 180 and 270 degrees
+    pub fn set_rotated_hashes(&mut self, rotated_hashes: Vec<HashType>) {
+        self.rotated_hashes = rotated_hashes;
+    }
+
+    /// Check if the file item has the hashes of all rotated variants
+    pub fn has_rotated_hashes(&self) -> bool {
+        !self.rotated_hashes.is_empty()
+    }
+
+    /// Set the size of the hash grid the hashes were calculated with
+    pub fn set_hash_size(&mut self, hash_size: u32) {
+        self.hash_size = hash_size;
+    }
+
+    /// Get the size of the hash grid the hashes were calculated with
+    pub fn get_hash_size(&self) -> u32 {
+        self.hash_size
+    }
+
     /// Get the image hash distance to another file item
+    /// The hash of this item is compared to the upright and all rotated hashes of the other item, so that
+    /// rotated copies of the same image are detected as well. Hashes calculated with different hash sizes
+    /// cannot be compared.
     pub fn get_hash_distance(&self, other: &FileItem) -> u32 {
-        if self.has_hash() && other.has_hash() {
-            self.hash
-                .as_ref()
+        if self.has_hash() && other.has_hash() && self.hash_size == other.hash_size {
+            let hash = self.hash.as_ref().unwrap();
+            other
+                .rotated_hashes
+                .iter()
+                .chain(other.hash.iter())
+                .map(|other_hash| hash.dist(other_hash))
+                .min()
                 .unwrap()
-                .dist(other.hash.as_ref().unwrap())
         } else {
             u32::MAX
         }
@@ -299,8 +784,13 @@ impl PartialOrd for FileItem {
 }
 
 impl Ord for FileItem {
+    /// Items are ordered by their capture time, then by file name and finally by their full path,
+    /// so the order does not depend on the order in which the files were found
     fn cmp(&self, other: &Self) -> Ordering {
-        self.timestamp.cmp(&other.timestamp)
+        self.timestamp
+            .cmp(&other.timestamp)
+            .then_with(|| self.path.file_name().cmp(&other.path.file_name()))
+            .then_with(|| self.path.cmp(&other.path))
     }
 }
 
@@ -324,14 +814,24 @@ mod tests {
 
     struct MockResolver {
         timestamp: i64,
+        utc_offset: Option<i32>,
         orientation: Option<Orientation>,
+        camera: Option<String>,
+        dimensions: Option<(u32, u32)>,
+        invalid_date: Option<String>,
+        orientation_mismatch: bool,
     }
 
     impl MockResolver {
         fn new(timestamp: i64, orientation: Option<Orientation>) -> Self {
             MockResolver {
                 timestamp,
+                utc_offset: None,
                 orientation,
+                camera: None,
+                dimensions: None,
+                invalid_date: None,
+                orientation_mismatch: false,
             }
         }
     }
@@ -341,9 +841,37 @@ mod tests {
             self.timestamp
         }
 
+        fn get_utc_offset(&self) -> Option<i32> {
+            self.utc_offset
+        }
+
         fn get_orientation(&self) -> Option<Orientation> {
             self.orientation.clone()
         }
+
+        fn get_gps(&self) -> Option<GpsPosition> {
+            None
+        }
+
+        fn get_camera(&self) -> Option<String> {
+            self.camera.clone()
+        }
+
+        fn get_dimensions(&self) -> Option<(u32, u32)> {
+            self.dimensions
+        }
+
+        fn get_invalid_date(&self) -> Option<String> {
+            self.invalid_date.clone()
+        }
+
+        fn has_orientation_mismatch(&self) -> bool {
+            self.orientation_mismatch
+        }
+
+        fn has_file_date(&self) -> bool {
+            false
+        }
     }
 
     #[test]
@@ -371,6 +899,49 @@ mod tests {
         FileItem::new(PathBuf::from("tests/test"), resolver, true, "");
     }
 
+    #[test]
+    fn test_dimensions() {
+        let mut resolver = MockResolver::new(10, None);
+        resolver.dimensions = Some((1170, 2532));
+        let file_item = FileItem::new(PathBuf::from("portrait.jpg"), Box::new(resolver), true, "");
+        assert_eq!(file_item.get_dimensions(), Some((1170, 2532)));
+        assert_eq!(file_item.get_long_side(), 2532);
+
+        let file_item = FileItem::dummy("video.mp4", 0, true);
+        assert_eq!(file_item.get_dimensions(), None);
+        assert_eq!(file_item.get_long_side(), 0);
+    }
+
+    #[test]
+    fn test_is_screenshot() {
+        let mut resolver = MockResolver::new(10, None);
+        resolver.dimensions = Some((1170, 2532));
+        let file_item = FileItem::new(
+            PathBuf::from("screenshot.png"),
+            Box::new(resolver),
+            true,
+            "",
+        );
+        assert!(file_item.is_screenshot());
+        assert_eq!(file_item.get_item_string(Path::new("")), "🖥 screenshot.png");
+
+        // Photos taken by a camera or with other sizes are no screenshots
+        let mut resolver = MockResolver::new(10, None);
+        resolver.dimensions = Some((1920, 1080));
+        resolver.camera = Some(String::from("Canon EOS"));
+        let file_item = FileItem::new(PathBuf::from("photo.jpg"), Box::new(resolver), true, "");
+        assert!(!file_item.is_screenshot());
+        let mut resolver = MockResolver::new(10, None);
+        resolver.dimensions = Some((4000, 3000));
+        let file_item = FileItem::new(PathBuf::from("photo.jpg"), Box::new(resolver), true, "");
+        assert!(!file_item.is_screenshot());
+        let mut resolver = MockResolver::new(10, None);
+        resolver.dimensions = Some((1920, 1080));
+        let file_item = FileItem::new(PathBuf::from("video.mp4"), Box::new(resolver), true, "");
+        assert!(!file_item.is_screenshot());
+        assert!(!FileItem::dummy("test.png", 0, true).is_screenshot());
+    }
+
     #[test]
     fn test_hashes() {
         let resolver = Box::new(MockResolver::new(10, Some(Orientation::Landscape180)));
@@ -410,6 +981,85 @@ mod tests {
             file_item2.get_hash_distance(&file_item)
         );
         assert_eq!(file_item.get_hash_distance(&file_item2), 0);
+
+        file_item2.clear_hashes();
+        assert!(!file_item2.has_hash());
+        assert!(!file_item2.has_rotated_hashes());
+    }
+
+    #[test]
+    fn test_rotated_hashes() {
+        let mut file_item = FileItem::dummy("test.jpg", 0, true);
+        let mut file_item2 = FileItem::dummy("test2.jpg", 0, true);
+        file_item.set_hash(HashType::from_bytes(&[0x00, 0x00]).unwrap());
+        file_item2.set_hash(HashType::from_bytes(&[0xFF, 0xFF]).unwrap());
+        assert!(!file_item2.has_rotated_hashes());
+        assert_eq!(file_item.get_hash_distance(&file_item2), 16);
+
+        file_item2.set_rotated_hashes(vec![
+            HashType::from_bytes(&[0xFF, 0x00]).unwrap(),
+            HashType::from_bytes(&[0x01, 0x00]).unwrap(),
+            HashType::from_bytes(&[0x0F, 0x0F]).unwrap(),
+        ]);
+        assert!(file_item2.has_rotated_hashes());
+        assert_eq!(file_item.get_hash_distance(&file_item2), 1);
+
+        let serialized = serde_json::to_string(&file_item2).unwrap();
+        let deserialized: FileItem = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.rotated_hashes, file_item2.rotated_hashes);
+    }
+
+    #[test]
+    fn test_hash_size() {
+        let mut file_item = FileItem::dummy("test.jpg", 0, true);
+        let mut file_item2 = FileItem::dummy("test2.jpg", 0, true);
+        file_item.set_hash(HashType::from_bytes(&[0x00, 0x00]).unwrap());
+        file_item2.set_hash(HashType::from_bytes(&[0x0F, 0x00]).unwrap());
+        assert_eq!(file_item.get_hash_size(), DEFAULT_HASH_SIZE);
+        assert_eq!(file_item.get_hash_distance(&file_item2), 4);
+
+        // Hashes of different hash sizes are never similar
+        file_item2.set_hash_size(16);
+        assert_eq!(file_item.get_hash_distance(&file_item2), u32::MAX);
+
+        // Items of projects saved without a hash size have the default hash size
+        let mut serialized = serde_json::to_value(&file_item2).unwrap();
+        assert_eq!(serialized["hash_size"], 16);
+        serialized.as_object_mut().unwrap().remove("hash_size");
+        let deserialized: FileItem = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized.get_hash_size(), DEFAULT_HASH_SIZE);
+
+        assert_eq!(scale_hash_max_diff(14, 8), 14);
+        assert_eq!(scale_hash_max_diff(14, 4), 3);
+        assert_eq!(scale_hash_max_diff(14, 16), 56);
+        assert_eq!(scale_hash_max_diff(0, 2), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"tests/non_utf8_\xff.jpg")).to_path_buf();
+        assert!(path.to_str().is_none());
+        let file_item = FileItem::dummy("test.jpg", 0, true);
+        let file_item = FileItem { path, ..file_item };
+
+        assert_eq!(
+            file_item.get_item_string(Path::new("tests")),
+            "📷 non_utf8_\u{FFFD}.jpg"
+        );
+
+        let serialized = serde_json::to_string(&file_item).unwrap();
+        let deserialized: FileItem = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.path, file_item.path);
+
+        // Valid paths are still stored as strings
+        let file_item = FileItem::dummy("tests/test.jpg", 0, true);
+        let serialized = serde_json::to_string(&file_item).unwrap();
+        assert!(serialized.contains("\"path\":\"tests/test.jpg\""));
+        let deserialized: FileItem = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.path, file_item.path);
     }
 
     #[test]
@@ -422,7 +1072,158 @@ mod tests {
             "Wrong_hash",
         );
 
+        assert!(!file_item.is_decided());
+        file_item.set_take_over(false);
+        assert!(!file_item.get_take_over());
+        assert!(file_item.is_decided());
+
+        // Unsure items are kept until a decision is made
+        file_item.set_unsure(true);
+        assert!(file_item.is_unsure());
+        assert!(file_item.get_take_over());
+        assert_eq!(
+            file_item.get_item_string(Path::new("tests")),
+            "📷 ❓ test.jpg"
+        );
+        file_item.set_unsure(false);
+        assert!(file_item.get_take_over());
+        file_item.set_unsure(true);
         file_item.set_take_over(false);
+        assert!(!file_item.is_unsure());
+
+        // Items tagged for deletion are discarded until kept or marked as unsure again
+        file_item.set_take_over(true);
+        file_item.set_pending_delete(true);
+        assert!(file_item.is_pending_delete());
+        assert!(!file_item.get_take_over());
+
+        // Favorites are independent of the take over state
+        file_item.set_favorite(true);
+        assert!(file_item.is_favorite());
+        assert!(file_item.is_pending_delete());
+        assert_eq!(
+            file_item.get_item_string(Path::new("tests")),
+            "📷 ❌ ⭐ test.jpg"
+        );
+        file_item.set_favorite(false);
+        assert!(!file_item.is_favorite());
+        assert_eq!(
+            file_item.get_item_string(Path::new("tests")),
+            "📷 ❌ test.jpg"
+        );
+        file_item.set_corrupt(true);
+        assert!(file_item.is_corrupt());
+        assert_eq!(
+            file_item.get_item_string(Path::new("tests")),
+            "📷 ❌ 💔 test.jpg"
+        );
+        file_item.set_corrupt(false);
+        file_item.set_pending_delete(false);
+        assert!(!file_item.is_pending_delete());
         assert!(!file_item.get_take_over());
+        file_item.set_pending_delete(true);
+        file_item.set_take_over(true);
+        assert!(!file_item.is_pending_delete());
+        file_item.set_pending_delete(true);
+        file_item.set_unsure(true);
+        assert!(!file_item.is_pending_delete());
+        assert!(file_item.get_take_over());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut file_item = FileItem::dummy("test.jpg", 0, true);
+        assert_eq!(file_item.get_rotation(), 0);
+        file_item.rotate(90);
+        assert_eq!(file_item.get_user_rotation(), 90);
+        assert_eq!(file_item.get_rotation(), 90);
+
+        // The rotation is added to the orientation of the image
+        file_item.orientation = Some(Orientation::Portrait270);
+        assert_eq!(file_item.get_rotation(), 0);
+        file_item.rotate(270);
+        assert_eq!(file_item.get_user_rotation(), 0);
+        assert_eq!(file_item.get_rotation(), 270);
+        file_item.rotate(-90);
+        assert_eq!(file_item.get_user_rotation(), 270);
+        assert_eq!(file_item.get_rotation(), 180);
+    }
+
+    #[test]
+    fn test_ignore_orientation() {
+        let mut resolver = MockResolver::new(10, Some(Orientation::Portrait90));
+        resolver.orientation_mismatch = true;
+        let mut file_item = FileItem::new(PathBuf::from("test.jpg"), Box::new(resolver), true, "");
+        assert!(file_item.has_orientation_mismatch());
+        assert!(!file_item.is_orientation_ignored());
+        assert_eq!(file_item.get_rotation(), 90);
+
+        // Only the orientation is ignored, the rotation set by the user is kept
+        file_item.set_ignore_orientation(true);
+        file_item.rotate(180);
+        assert!(file_item.is_orientation_ignored());
+        assert_eq!(file_item.get_rotation(), 180);
+        assert_eq!(file_item.get_orientation(), Some(&Orientation::Portrait90));
+
+        let resolver = Box::new(MockResolver::new(10, Some(Orientation::Portrait90)));
+        let file_item = FileItem::new(PathBuf::from("test.jpg"), resolver, true, "");
+        assert!(!file_item.has_orientation_mismatch());
+    }
+
+    #[test]
+    fn test_timestamp_in() {
+        use chrono_tz::{America::New_York, Europe::Berlin};
+
+        let timestamp = |date_time: &str| {
+            NaiveDateTime::parse_from_str(date_time, "%Y-%m-%d %H:%M")
+                .unwrap()
+                .timestamp()
+        };
+        let mut file_item = FileItem::dummy("test.jpg", timestamp("2021-03-27 23:30"), true);
+
+        // Without an offset, the capture time is used for all time zones
+        assert_eq!(
+            file_item.get_timestamp_in(&Berlin),
+            timestamp("2021-03-27 23:30")
+        );
+
+        // Before the DST transition in Berlin
+        file_item.set_utc_offset(Some(3600));
+        assert_eq!(
+            file_item.get_timestamp_in(&Berlin),
+            timestamp("2021-03-27 23:30")
+        );
+        assert_eq!(
+            file_item.get_timestamp_in(&chrono::Utc),
+            timestamp("2021-03-27 22:30")
+        );
+        assert_eq!(
+            file_item.get_timestamp_in(&New_York),
+            timestamp("2021-03-27 18:30")
+        );
+
+        // After the DST transition in Berlin
+        let mut file_item = FileItem::dummy("test.jpg", timestamp("2021-03-28 03:30"), true);
+        file_item.set_utc_offset(Some(7200));
+        assert_eq!(
+            file_item.get_timestamp_in(&Berlin),
+            timestamp("2021-03-28 03:30")
+        );
+        assert_eq!(
+            file_item.get_timestamp_in(&chrono::Utc),
+            timestamp("2021-03-28 01:30")
+        );
+
+        // Crossing midnight after the end of DST in Berlin, but not in New York
+        let mut file_item = FileItem::dummy("test.jpg", timestamp("2021-10-30 23:30"), true);
+        file_item.set_utc_offset(Some(-4 * 3600));
+        assert_eq!(
+            file_item.get_timestamp_in(&Berlin),
+            timestamp("2021-10-31 04:30")
+        );
+        assert_eq!(
+            file_item.get_timestamp_in(&New_York),
+            timestamp("2021-10-30 23:30")
+        );
     }
 }
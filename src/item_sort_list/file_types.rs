@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 const IMAGE: &[&str] = &[
     "jpg", "png", "tif", "jpeg", "jpe", "gif", "bmp", "webp", "tiff",
@@ -28,7 +28,8 @@ pub fn is_video(path: &Path) -> bool {
 pub fn is_any(path: &Path) -> bool {
     if let Some(extension) = path.extension() {
         let extension = extension.to_ascii_lowercase();
-        let extension = &extension.to_str().unwrap();
+        let extension = extension.to_string_lossy();
+        let extension = &extension.as_ref();
         IMAGE.contains(extension) || VIDEO.contains(extension) || RAW.contains(extension)
     } else {
         false
@@ -37,12 +38,73 @@ pub fn is_any(path: &Path) -> bool {
 
 fn is_extension_in(path: &Path, extensions: &[&str]) -> bool {
     if let Some(extension) = path.extension() {
-        extensions.contains(&extension.to_ascii_lowercase().to_str().unwrap())
+        extensions.contains(&extension.to_ascii_lowercase().to_string_lossy().as_ref())
     } else {
         false
     }
 }
 
+/// Checks if a folder is excluded from scanning. An absolute excluded path excludes the folder and all its subfolders,
+/// otherwise the excluded path is a pattern for folder names that may contain the wildcards * and ?.
+pub fn is_excluded(path: &Path, excluded_paths: &[String]) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    excluded_paths.iter().any(|excluded_path| {
+        if Path::new(excluded_path).is_absolute() {
+            path.starts_with(excluded_path)
+        } else {
+            matches_pattern(&name, &excluded_path.to_lowercase())
+        }
+    })
+}
+
+/// Gets the path of a directory inside a source directory as it is found when scanning the source directory. Returns None
+/// if the directory is not inside the source directory or the source directory itself. Links and relative parts of both
+/// paths are resolved before comparing them.
+pub fn get_nested_directory(source: &Path, directory: &Path) -> Option<PathBuf> {
+    if directory.as_os_str().is_empty() {
+        return None;
+    }
+    let resolve = |path: &Path| {
+        path.canonicalize()
+            .or_else(|_| std::path::absolute(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    };
+    let relative = resolve(directory)
+        .strip_prefix(resolve(source))
+        .ok()?
+        .to_path_buf();
+    Some(source.join(relative))
+}
+
+/// Matches a name against a pattern with the wildcards * (any number of characters) and ? (a single character)
+fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    // Position in the pattern after the last *, and the position in the name where it matched
+    let mut star: Option<(usize, usize)> = None;
+    let (mut n, mut p) = (0, 0);
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            n += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last * match one more character
+            star = Some((star_p, star_n + 1));
+            p = star_p;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -51,6 +113,7 @@ mod test {
     fn test_extensions() {
         assert!(is_image(Path::new("/path/to/image.jpg")));
         assert!(is_image(Path::new("/path/to/image.PNG")));
+        assert!(is_image(Path::new("/path/to/image.webp")));
         assert!(!is_image(Path::new("/path/to/image")));
 
         assert!(is_raw_image(Path::new("/path/to/image.mrw")));
@@ -66,4 +129,53 @@ mod test {
         assert!(is_any(Path::new("/path/to/video.mov")));
         assert!(!is_any(Path::new("/path/to/video.zip")));
     }
+
+    #[test]
+    fn test_nested_directory() {
+        let source = std::env::current_dir().unwrap().join("tests");
+        assert_eq!(
+            get_nested_directory(&source, &source.join("subdir")),
+            Some(source.join("subdir"))
+        );
+        // Directories that do not exist yet are found as well
+        assert_eq!(
+            get_nested_directory(&source, &source.join("sieved")),
+            Some(source.join("sieved"))
+        );
+        assert_eq!(get_nested_directory(&source, &source), Some(source.clone()));
+        assert_eq!(get_nested_directory(&source.join("subdir"), &source), None);
+        assert_eq!(get_nested_directory(&source, Path::new("")), None);
+    }
+
+    #[test]
+    fn test_excluded() {
+        let root = std::env::current_dir().unwrap();
+        let excluded_paths = vec![
+            String::from("exports"),
+            String::from("tmp*"),
+            String::from("backup-????"),
+            root.join("target").to_string_lossy().to_string(),
+        ];
+        assert!(is_excluded(Path::new("photos/exports"), &excluded_paths));
+        assert!(is_excluded(Path::new("photos/Exports"), &excluded_paths));
+        assert!(!is_excluded(Path::new("photos/exports2"), &excluded_paths));
+        assert!(is_excluded(Path::new("photos/tmp"), &excluded_paths));
+        assert!(is_excluded(Path::new("photos/tmp_1"), &excluded_paths));
+        assert!(is_excluded(
+            Path::new("photos/backup-2022"),
+            &excluded_paths
+        ));
+        assert!(!is_excluded(Path::new("photos/backup-22"), &excluded_paths));
+        assert!(is_excluded(&root.join("target"), &excluded_paths));
+        assert!(is_excluded(&root.join("target/debug"), &excluded_paths));
+        assert!(!is_excluded(&root.join("targets"), &excluded_paths));
+        assert!(!is_excluded(Path::new("target"), &excluded_paths));
+        assert!(!is_excluded(Path::new("photos"), &[]));
+
+        assert!(matches_pattern("abc", "a*c"));
+        assert!(matches_pattern("abbbc", "a*b*c"));
+        assert!(matches_pattern("", "*"));
+        assert!(!matches_pattern("abd", "a*c"));
+        assert!(!matches_pattern("ab", "a?c"));
+    }
 }
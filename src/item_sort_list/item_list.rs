@@ -1,17 +1,23 @@
 extern crate chrono;
 
-use self::chrono::NaiveDateTime;
+use self::chrono::{NaiveDate, NaiveDateTime};
 use num_derive::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 use std::path::PathBuf;
 
 use super::event;
 use super::file_item;
-use super::file_types::is_any;
+use super::file_types::{is_any, is_excluded};
 use super::resolvers;
 use super::sieve;
+use super::timestamp::{timestamp_to_string, Format};
+
+/// Maximum image hash difference of items with identical timestamps to still be similar, which equals the lowest
+/// similarity sensitivity
+const SAME_TIMESTAMP_MAX_HASH_DIFF: u32 = 20;
 
 /// Method how to perform sieve of sieved images
 #[derive(PartialEq, Eq, FromPrimitive, ToPrimitive, Clone, Debug, Serialize, Deserialize)]
@@ -25,6 +31,8 @@ pub enum SieveMethod {
     MoveAndDelete,
     /// Delete the discarded files
     Delete,
+    /// Copy the images to be taken over to a zip archive in the target directory
+    Zip,
 }
 
 #[derive(PartialEq, Eq, FromPrimitive, ToPrimitive, Clone, Debug, Serialize, Deserialize)]
@@ -40,6 +48,167 @@ pub enum DirectoryNames {
     YearAndQuarter,
     /// Directories are named by year and subdirectory by month
     YearAndMonthInSubdirectory,
+    /// Directories are the same as the subdirectories of the source directory, events are not used
+    SourceSubdirectories,
+}
+
+/// What to do when a sieved file already exists in the target directory
+#[derive(
+    PartialEq, Eq, FromPrimitive, ToPrimitive, Clone, Debug, Default, Serialize, Deserialize,
+)]
+#[repr(i32)]
+pub enum CollisionPolicy {
+    /// Sieve the file with a number appended to its name, like "name (1).jpg"
+    #[default]
+    Rename = 0,
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and do not sieve the file
+    Skip,
+}
+
+/// Time zone in which the capture times of items are compared to events
+#[derive(
+    PartialEq, Eq, FromPrimitive, ToPrimitive, Clone, Debug, Default, Serialize, Deserialize,
+)]
+#[repr(i32)]
+pub enum EventTimeZone {
+    /// Use the wall clock time at which the item was captured
+    #[default]
+    CaptureTime = 0,
+    /// Convert the capture time to the local time zone
+    Local,
+    /// Convert the capture time to UTC
+    Utc,
+}
+
+/// Statistics of the groups of similar items in an item list
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct GroupStatistics {
+    /// Number of groups
+    pub groups: usize,
+    /// Number of items that belong to a group
+    pub grouped_items: usize,
+    /// Number of items in the largest group
+    pub largest_group: usize,
+}
+
+/// Counts of the items in a part of an item list, like the whole project or an event
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ItemStatistics {
+    /// Name of the counted part
+    pub name: String,
+    /// Number of items
+    pub items: usize,
+    /// Number of items that are taken over
+    pub kept: usize,
+    /// Number of items that are marked as favorite
+    pub favorites: usize,
+    /// Size of all items in bytes
+    pub bytes: u64,
+    /// Size of the items that are taken over in bytes
+    pub kept_bytes: u64,
+    /// Date of the first and the last item, None if there are no items
+    pub date_range: Option<(NaiveDate, NaiveDate)>,
+}
+
+impl ItemStatistics {
+    /// Creates empty statistics with a name
+    fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            ..Default::default()
+        }
+    }
+
+    /// Adds an item with its size and date to the statistics
+    fn add(&mut self, item: &file_item::FileItem, size: u64, date: NaiveDate) {
+        self.items += 1;
+        self.bytes += size;
+        if item.get_take_over() {
+            self.kept += 1;
+            self.kept_bytes += size;
+        }
+        if item.is_favorite() {
+            self.favorites += 1;
+        }
+        self.date_range = match self.date_range {
+            Some((first, last)) => Some((first.min(date), last.max(date))),
+            None => Some((date, date)),
+        };
+    }
+
+    /// Gets the number of items that are discarded
+    pub fn discarded(&self) -> usize {
+        self.items - self.kept
+    }
+
+    /// Gets the statistics as a line of comma separated values
+    fn to_csv(&self, kind: &str) -> String {
+        let (first, last) = match self.date_range {
+            Some((first, last)) => (
+                first.format(event::EVENT_DATE_FORMAT).to_string(),
+                last.format(event::EVENT_DATE_FORMAT).to_string(),
+            ),
+            None => (String::new(), String::new()),
+        };
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            kind,
+            quote_csv(&self.name),
+            self.items,
+            self.kept,
+            self.discarded(),
+            self.favorites,
+            self.bytes,
+            self.kept_bytes,
+            first,
+            last
+        )
+    }
+}
+
+/// Quotes a text as a field of comma separated values
+fn quote_csv(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Statistics of a whole project with the counts per event
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ProjectStatistics {
+    /// Counts of all items of the project
+    pub total: ItemStatistics,
+    /// Number of items that were not decided yet
+    pub undecided: usize,
+    /// Counts of the items of each event, in the order of the events
+    pub events: Vec<ItemStatistics>,
+}
+
+impl ProjectStatistics {
+    /// Gets the statistics as comma separated values with a header line, one line for the project and one per event
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "Type,Name,Items,Kept,Discarded,Favorites,Bytes,Kept bytes,First date,Last date\n",
+        );
+        csv += &self.total.to_csv("Project");
+        for event in &self.events {
+            csv += &event.to_csv("Event");
+        }
+        csv
+    }
+}
+
+/// Settings stored in a project, which override the global settings while the project is open
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    /// Target directory of the sieve
+    pub target_directory: String,
+    /// Method used to sieve the items
+    pub sieve_method: SieveMethod,
+    /// Maximum difference of the timestamps of similar items in seconds
+    pub timestamp_max_diff: i64,
+    /// Maximum difference of the image hashes of similar items
+    pub hash_max_diff: u32,
 }
 
 /// Item list containing all file items and all events
@@ -50,7 +219,15 @@ pub struct ItemList {
     /// List of events
     pub events: Vec<event::Event>,
     /// Base path that was used to create the item list
+    #[serde(serialize_with = "file_item::serialize_path")]
+    #[serde(deserialize_with = "file_item::deserialize_path")]
     pub path: PathBuf,
+    /// Time zone used to match items to events, taken from the settings
+    #[serde(skip)]
+    pub event_time_zone: EventTimeZone,
+    /// Settings overriding the global settings while the project is open, None if the global settings are used
+    #[serde(default)]
+    pub project_settings: Option<ProjectSettings>,
 }
 
 impl Default for ItemList {
@@ -66,18 +243,77 @@ impl ItemList {
             items: vec![],
             events: vec![],
             path: PathBuf::new(),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         }
     }
 
+    /// Marks the items of empty files as corrupt, e.g. after an interrupted download, and returns their number. Other
+    /// items are not corrupt anymore, as their files may have been replaced. Truncated files are detected again when
+    /// their images are loaded.
+    pub fn check_empty_files(&mut self) -> usize {
+        let mut empty_files = 0;
+        for item in &mut self.items {
+            let empty = item.get_size() == 0;
+            item.set_corrupt(empty);
+            if empty {
+                empty_files += 1;
+            }
+        }
+        empty_files
+    }
+
+    /// Removes the stored hashes of all items, e.g. because they are stale, and returns the number of items which had
+    /// a hash. The hashes are calculated again when the similarities are checked the next time.
+    pub fn clear_hashes(&mut self) -> usize {
+        let mut cleared = 0;
+        for item in &mut self.items {
+            if item.has_hash() {
+                cleared += 1;
+            }
+            item.clear_hashes();
+        }
+        cleared
+    }
+
+    /// Get the number of items with a stored hash
+    pub fn get_hash_count(&self) -> usize {
+        self.items.iter().filter(|item| item.has_hash()).count()
+    }
+
     /// Remove all missing files from the item list
     pub fn drain_missing(&mut self) {
         self.items = self.items.drain(..).filter(|i| i.path.exists()).collect();
     }
 
-    /// Check if a path can be added
-    pub fn check_and_add(&mut self, path: &Path) {
+    /// Remove all files in excluded folders below the base path from the item list
+    pub fn drain_excluded(&mut self, excluded_paths: &[String]) {
+        let base_path = self.path.clone();
+        self.items.retain(|item| {
+            !item
+                .path
+                .ancestors()
+                .skip(1)
+                .take_while(|folder| folder.starts_with(&base_path) && *folder != base_path)
+                .any(|folder| is_excluded(folder, excluded_paths))
+        });
+    }
+
+    /// Moves the item list to a new base path. Items are remapped by their path relative to the old base path,
+    /// items outside of the old base path are kept unchanged.
+    pub fn relocate(&mut self, base_path: &Path) {
+        for item in &mut self.items {
+            if let Ok(relative_path) = item.path.strip_prefix(&self.path) {
+                item.path = base_path.join(relative_path);
+            }
+        }
+        self.path = base_path.to_path_buf();
+    }
+
+    /// Check if a path can be added and add it with a default take over state
+    pub fn check_and_add(&mut self, path: &Path, default_take_over: bool) {
         if is_any(path) && !self.items.iter().any(|i| i.path == path) {
-            let item = Self::create_item(path.to_path_buf(), true, "");
+            let item = Self::create_item(path.to_path_buf(), default_take_over, "");
             self.items.push(item);
         }
     }
@@ -108,8 +344,21 @@ impl ItemList {
         file_item::FileItem::new(item_path, resolver, take_over, encoded_hash)
     }
 
-    /// Go through all images and find similar ones by comparing the timestamp
-    pub fn find_similar(&mut self, max_diff_seconds: i64) {
+    /// Gets the index of the item representing a group of similar items. This is the first item of the group,
+    /// i.e. the one captured first or with the lowest file name if captured at the same time.
+    pub fn get_group_representative(&self, group_id: usize) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|item| item.get_group_id() == Some(group_id))
+    }
+
+    /// Go through all images and find similar ones by comparing the timestamp.
+    /// By default, the difference to the previous item is compared, so a group grows as long as the items follow each
+    /// other closely. If only consecutive items are grouped, the difference to the first item of the group is compared
+    /// instead, so a group never spans more than the maximum difference.
+    /// Items with identical timestamps are only similar if their image hashes show the same content.
+    /// The items need to be sorted, which is done when finishing the synchronization.
+    pub fn find_similar(&mut self, max_diff_seconds: i64, consecutive_only: bool) {
         // Find similars based on the taken time
         if self.items.is_empty() {
             return;
@@ -123,14 +372,282 @@ impl ItemList {
 
                 start_similar_index = index;
             }
-            timestamp = self.items[index].get_timestamp();
+            if !consecutive_only || start_similar_index == index {
+                timestamp = self.items[index].get_timestamp();
+            }
         }
         // Set all the remaining indices
         self.set_similar_range(start_similar_index..self.items.len());
+        self.remove_same_file_date_similars();
         // Now remove the own index from all items
         for index in 0..self.items.len() {
             self.items[index].clean_similars(index);
         }
+        self.assign_group_ids();
+    }
+
+    /// Removes the similarity of items with identical file dates whose image hashes show different content.
+    /// Files copied in bulk often share the same modification time, which is used as timestamp if there is no capture
+    /// time. Items with a capture time, like burst shots taken within the same second, and items without a hash stay
+    /// similar.
+    fn remove_same_file_date_similars(&mut self) {
+        for indices in self.same_file_date_indices() {
+            for index in &indices {
+                let different: Vec<usize> = indices
+                    .iter()
+                    .copied()
+                    .filter(|other_index| self.is_different_content(*index, *other_index))
+                    .collect();
+                self.items[*index]
+                    .retain_similars(|similar_index| !different.contains(&similar_index));
+            }
+        }
+    }
+
+    /// Separates items with identical file dates whose image hashes show different content. This is needed after the
+    /// hashes were calculated, as `find_similar` keeps such items similar as long as they have no hashes.
+    pub fn separate_same_file_date_items(&mut self) {
+        self.remove_same_file_date_similars();
+        self.assign_group_ids();
+    }
+
+    /// Gets the indices of all items with file dates grouped by identical timestamps, single items are left out
+    fn same_file_date_indices(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut start_index = 0;
+        while start_index < self.items.len() {
+            let timestamp = self.items[start_index].get_timestamp();
+            let end_index = self.items[start_index..]
+                .iter()
+                .position(|item| item.get_timestamp() != timestamp)
+                .map_or(self.items.len(), |position| start_index + position);
+            let indices: Vec<usize> = (start_index..end_index)
+                .filter(|index| self.items[*index].has_file_date())
+                .collect();
+            if indices.len() > 1 {
+                groups.push(indices);
+            }
+            start_index = end_index;
+        }
+        groups
+    }
+
+    /// Checks if the image hashes of two items show different content, items without comparable hashes are not
+    /// different
+    fn is_different_content(&self, index: usize, other_index: usize) -> bool {
+        let item = &self.items[index];
+        let other = &self.items[other_index];
+        item.has_hash()
+            && other.has_hash()
+            && item.get_hash_size() == other.get_hash_size()
+            && item
+                .get_hash_distance(other)
+                .min(other.get_hash_distance(item))
+                > file_item::scale_hash_max_diff(SAME_TIMESTAMP_MAX_HASH_DIFF, item.get_hash_size())
+    }
+
+    /// Assigns a group id to all items that have similar items. All items that are connected by being similar
+    /// share a group id. Groups are numbered starting from 1 in the order of their first item, so the ids
+    /// stay the same as long as the items and their similarities do not change.
+    fn assign_group_ids(&mut self) {
+        let mut group_ids: Vec<Option<usize>> = vec![None; self.items.len()];
+        let mut group_count = 0;
+        for start_index in 0..self.items.len() {
+            if group_ids[start_index].is_some() || self.items[start_index].get_similars().is_empty()
+            {
+                continue;
+            }
+            group_count += 1;
+            group_ids[start_index] = Some(group_count);
+            let mut pending = vec![start_index];
+            while let Some(index) = pending.pop() {
+                for similar_index in self.items[index].get_similars() {
+                    if group_ids[*similar_index].is_none() {
+                        group_ids[*similar_index] = Some(group_count);
+                        pending.push(*similar_index);
+                    }
+                }
+            }
+        }
+        for (item, group_id) in self.items.iter_mut().zip(group_ids) {
+            item.set_group_id(group_id);
+        }
+        self.apply_keep_all_groups();
+    }
+
+    /// Applies the decision to keep all items of a group to items that joined the group, e.g. after calculating the
+    /// similarities with another threshold. Undecided items are kept, items that were decided explicitly stay as they are.
+    fn apply_keep_all_groups(&mut self) {
+        let keep_all_groups: HashSet<usize> = self
+            .items
+            .iter()
+            .filter(|item| item.is_keep_all())
+            .filter_map(|item| item.get_group_id())
+            .collect();
+        for item in &mut self.items {
+            match item.get_group_id() {
+                Some(group_id) if !item.is_keep_all() && keep_all_groups.contains(&group_id) => {
+                    item.set_keep_all(true);
+                    if !item.is_decided() {
+                        item.set_take_over(true);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Splits groups of similar items with more items than the maximum group size, 0 means no limit.
+    /// An oversized group is split at the largest time difference between its items until all parts are small
+    /// enough and the similarities between the parts are removed. Returns the number of groups that were split.
+    pub fn split_large_groups(&mut self, max_group_size: usize) -> usize {
+        if max_group_size == 0 {
+            return 0;
+        }
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(group_id) = item.get_group_id() {
+                groups.entry(group_id).or_default().push(index);
+            }
+        }
+        let mut split_groups = 0;
+        for group in groups.into_values() {
+            if group.len() <= max_group_size {
+                continue;
+            }
+            split_groups += 1;
+            for part in self.split_at_largest_gaps(&group, max_group_size) {
+                for index in part {
+                    self.items[*index]
+                        .retain_similars(|similar_index| part.contains(&similar_index));
+                }
+            }
+        }
+        if split_groups > 0 {
+            self.assign_group_ids();
+        }
+        split_groups
+    }
+
+    /// Splits the sorted indices of a group at the largest time difference between neighboring items until all parts
+    /// have at most the maximum size. For equal differences, the split closest to the middle is taken.
+    fn split_at_largest_gaps<'a>(
+        &self,
+        group: &'a [usize],
+        max_group_size: usize,
+    ) -> Vec<&'a [usize]> {
+        if group.len() <= max_group_size {
+            return vec![group];
+        }
+        let split_position = (1..group.len())
+            .max_by_key(|position| {
+                let gap = self.items[group[*position]].get_timestamp()
+                    - self.items[group[*position - 1]].get_timestamp();
+                (gap, std::cmp::Reverse((2 * position).abs_diff(group.len())))
+            })
+            .unwrap();
+        let (first, second) = group.split_at(split_position);
+        let mut parts = self.split_at_largest_gaps(first, max_group_size);
+        parts.extend(self.split_at_largest_gaps(second, max_group_size));
+        parts
+    }
+
+    /// Finds items with exactly the same content that are stored in different directories, e.g. the same photo
+    /// imported twice. Only items with the same size as an item in another directory are read.
+    /// Returns the paths of each set of duplicates.
+    pub fn find_duplicates(&self) -> Vec<Vec<PathBuf>> {
+        let mut items_by_size: BTreeMap<u64, Vec<&Path>> = BTreeMap::new();
+        for item in &self.items {
+            items_by_size
+                .entry(item.get_size())
+                .or_default()
+                .push(&item.path);
+        }
+        let mut duplicates = vec![];
+        for (size, paths) in items_by_size {
+            if size == 0 || !in_different_directories(&paths) {
+                continue;
+            }
+            let mut paths_by_content: HashMap<u64, Vec<&Path>> = HashMap::new();
+            for path in paths {
+                match std::fs::read(path) {
+                    Ok(content) => {
+                        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                        content.hash(&mut hasher);
+                        paths_by_content
+                            .entry(hasher.finish())
+                            .or_default()
+                            .push(path);
+                    }
+                    Err(e) => log::warn!("Error reading {}: {}", path.display(), e),
+                }
+            }
+            duplicates.extend(
+                paths_by_content
+                    .into_values()
+                    .filter(|paths| in_different_directories(paths))
+                    .map(|paths| {
+                        let mut paths: Vec<PathBuf> =
+                            paths.iter().map(|path| path.to_path_buf()).collect();
+                        paths.sort();
+                        paths
+                    }),
+            );
+        }
+        duplicates.sort();
+        duplicates
+    }
+
+    /// Gets statistics of the current groups of similar items
+    pub fn get_group_statistics(&self) -> GroupStatistics {
+        let mut group_sizes: HashMap<usize, usize> = HashMap::new();
+        for group_id in self.items.iter().filter_map(|item| item.get_group_id()) {
+            *group_sizes.entry(group_id).or_insert(0) += 1;
+        }
+        GroupStatistics {
+            groups: group_sizes.len(),
+            grouped_items: group_sizes.values().sum(),
+            largest_group: group_sizes.values().copied().max().unwrap_or(0),
+        }
+    }
+
+    /// Finds similar items by their timestamp with each of the given maximum differences and returns the
+    /// resulting group statistics. This works on a copy, so the current groups are not changed.
+    pub fn preview_similar(
+        &self,
+        max_diffs_seconds: &[i64],
+        consecutive_only: bool,
+    ) -> Vec<GroupStatistics> {
+        max_diffs_seconds
+            .iter()
+            .map(|max_diff_seconds| {
+                let mut item_list = self.without_similars();
+                item_list.find_similar(*max_diff_seconds, consecutive_only);
+                item_list.get_group_statistics()
+            })
+            .collect()
+    }
+
+    /// Finds similar items by their hash with each of the given maximum differences and returns the
+    /// resulting group statistics. This works on a copy, so the current groups are not changed.
+    pub fn preview_similar_hashes(&self, max_diffs_hash: &[u32]) -> Vec<GroupStatistics> {
+        max_diffs_hash
+            .iter()
+            .map(|max_diff_hash| {
+                let mut item_list = self.without_similars();
+                item_list.find_similar_hashes(*max_diff_hash, |_| true).ok();
+                item_list.get_group_statistics()
+            })
+            .collect()
+    }
+
+    /// Creates a copy of the item list without any similar items
+    fn without_similars(&self) -> ItemList {
+        let mut item_list = self.clone();
+        for item in &mut item_list.items {
+            item.reset_similars();
+        }
+        item_list
     }
 
     /// Sets a range of similar indices for all items in that range
@@ -140,17 +657,43 @@ impl ItemList {
         }
     }
 
-    /// Go through all images and find similar ones by comparing the hash
-    pub fn find_similar_hashes(&mut self, max_diff_hash: u32) {
+    /// Go through all images and find similar ones by comparing the hash. The maximum difference is given for the
+    /// default hash size and scaled to the hash size of the items.
+    /// The progress callback receives the percentage of comparisons done whenever it changes and aborts the
+    /// search by returning false. In that case, the similarities of the items are left untouched.
+    pub fn find_similar_hashes(
+        &mut self,
+        max_diff_hash: u32,
+        mut progress: impl FnMut(usize) -> bool,
+    ) -> Result<(), ()> {
         let mut similar_lists: HashMap<usize, Vec<usize>> = HashMap::new();
         for index in 0..self.items.len() {
             similar_lists.insert(index, vec![]);
         }
+        let item_count = self.items.len();
+        let total_comparisons = item_count * item_count.saturating_sub(1) / 2;
+        let mut comparisons: usize = 0;
+        let mut reported_percent = None;
         for index in 0..self.items.len() {
+            let percent = (comparisons * 100)
+                .checked_div(total_comparisons)
+                .unwrap_or(100);
+            if reported_percent != Some(percent) {
+                reported_percent = Some(percent);
+                if !progress(percent) {
+                    return Err(());
+                }
+            }
+            comparisons += item_count - index - 1;
             for other_index in index + 1..self.items.len() {
                 if other_index != index {
                     let distance = self.items[index].get_hash_distance(&self.items[other_index]);
-                    if distance < max_diff_hash {
+                    if distance
+                        < file_item::scale_hash_max_diff(
+                            max_diff_hash,
+                            self.items[index].get_hash_size(),
+                        )
+                    {
                         similar_lists.get_mut(&index).unwrap().push(other_index);
                         similar_lists.get_mut(&other_index).unwrap().push(index);
                     }
@@ -162,47 +705,399 @@ impl ItemList {
             self.items[index].add_similar_vec(similar_list);
             self.items[index].clean_similars(index);
         }
+        self.assign_group_ids();
+        Ok(())
+    }
+
+    /// Go through all items and find similar ones by comparing the position at which they were captured.
+    /// Items captured within the maximum distance are similar. If one of two items has no GPS position,
+    /// they are similar if they were captured within the maximum time difference instead.
+    pub fn find_similar_locations(&mut self, max_distance_meters: u32, max_diff_seconds: i64) {
+        let mut similar_lists: Vec<Vec<usize>> = vec![vec![]; self.items.len()];
+        for index in 0..self.items.len() {
+            for other_index in index + 1..self.items.len() {
+                let item = &self.items[index];
+                let other_item = &self.items[other_index];
+                let similar = match (item.get_gps(), other_item.get_gps()) {
+                    (Some(gps), Some(other_gps)) => {
+                        gps.distance(&other_gps) <= max_distance_meters as f64
+                    }
+                    _ => {
+                        (item.get_timestamp() - other_item.get_timestamp()).abs()
+                            <= max_diff_seconds
+                    }
+                };
+                if similar {
+                    similar_lists[index].push(other_index);
+                    similar_lists[other_index].push(index);
+                }
+            }
+        }
+        for (index, similar_list) in similar_lists.iter().enumerate() {
+            self.items[index].add_similar_vec(similar_list);
+            self.items[index].clean_similars(index);
+        }
+        self.assign_group_ids();
+    }
+
+    /// Go through all items and find similar ones by comparing their file names. Items with the same file name in
+    /// different folders, like exports of an image in several sizes, are similar regardless of their timestamps.
+    /// The comparison ignores the case of the file names.
+    pub fn find_similar_names(&mut self) {
+        let mut names: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(file_name) = item.path.file_name() {
+                names
+                    .entry(file_name.to_string_lossy().to_lowercase())
+                    .or_default()
+                    .push(index);
+            }
+        }
+        for indices in names.values().filter(|indices| indices.len() > 1) {
+            for index in indices {
+                self.items[*index].add_similar_vec(indices);
+                self.items[*index].clean_similars(*index);
+            }
+        }
+        self.assign_group_ids();
+    }
+
+    /// Counts the items that are taken over and the items that are discarded
+    pub fn count_take_over(&self) -> (usize, usize) {
+        let kept = self
+            .items
+            .iter()
+            .filter(|item| item.get_take_over())
+            .count();
+        (kept, self.items.len() - kept)
+    }
+
+    /// Gets the statistics of the whole project and of each event, the sizes are read from the files
+    pub fn get_project_statistics(&self) -> ProjectStatistics {
+        let mut statistics = ProjectStatistics {
+            total: ItemStatistics::new(&self.path.to_string_lossy()),
+            undecided: self.count_undecided(),
+            events: self
+                .events
+                .iter()
+                .map(|event| ItemStatistics::new(&event.name))
+                .collect(),
+        };
+        for item in &self.items {
+            let date = self.get_event_date(item);
+            let size = item.get_size();
+            statistics.total.add(item, size, date);
+            if let Some(event) = self.get_event(item) {
+                if let Some(index) = self.events.iter().position(|e| e == event) {
+                    statistics.events[index].add(item, size, date);
+                }
+            }
+        }
+        statistics
+    }
+
+    /// Gets a description of sieved files as comma separated values with a header line and one line per file.
+    /// The files are given with the items they were sieved from, paths in the target directory are written relative
+    /// to it and original paths relative to the source directory.
+    pub fn get_sieved_csv(
+        &self,
+        target: &Path,
+        files: &[(&file_item::FileItem, PathBuf)],
+    ) -> String {
+        let mut csv = String::from("File,Original path,Date,Event,Favorite,Bytes\n");
+        for (item, file) in files {
+            let file = file.strip_prefix(target).unwrap_or(file);
+            let original = item.path.strip_prefix(&self.path).unwrap_or(&item.path);
+            csv += &format!(
+                "{},{},{},{},{},{}\n",
+                quote_csv(&file.to_string_lossy()),
+                quote_csv(&original.to_string_lossy()),
+                timestamp_to_string(item.get_timestamp(), Format::DateTime),
+                quote_csv(self.get_event(item).map_or("", |event| &event.name)),
+                if item.is_favorite() { "yes" } else { "no" },
+                item.get_size()
+            );
+        }
+        csv
+    }
+
+    /// Counts the items that were neither kept, discarded nor marked as unsure explicitly
+    pub fn count_undecided(&self) -> usize {
+        self.items.iter().filter(|item| !item.is_decided()).count()
     }
 
     /// Sieves an item list taking the take_over flag into account to a new directory.
+    /// Files that were already copied there and are unchanged are only copied again if forced.
+    /// Files with the name of an existing file are handled according to the collision policy of the sieve IO.
     /// The progress is reported by calling a callback function with the file that is currently processed.
+    /// Returns the copied files, which should be passed to set_sieved_copies.
     pub fn sieve(
         &self,
         path: &Path,
         sieve_method: SieveMethod,
         sieve_directory_names: DirectoryNames,
+        sieve_io: &sieve::FileSieveIO,
+        force: bool,
         progress_callback: impl Fn(String),
-    ) {
-        let sieve_io = sieve::FileSieveIO {};
+    ) -> Vec<sieve::ManifestEntry> {
         sieve::sieve(
             self,
             path,
             sieve_method,
             sieve_directory_names,
+            sieve_io,
+            force,
+            progress_callback,
+        )
+    }
+
+    /// Removes the kept items smaller than the minimum size in bytes, e.g. icons and thumbnails of web pages, so that
+    /// they are not sieved. Discarded items stay in the list to be handled by the sieve method as usual. Returns the
+    /// number of removed items.
+    pub fn remove_small_kept_items(&mut self, min_size: u64) -> usize {
+        let count = self.items.len();
+        self.items
+            .retain(|item| !item.get_take_over() || item.get_size() >= min_size);
+        count - self.items.len()
+    }
+
+    /// Remembers the copies made by a sieve for the copied items
+    pub fn set_sieved_copies(&mut self, copies: &[sieve::ManifestEntry]) {
+        for copy in copies {
+            if let Some(item) = self.items.iter_mut().find(|i| i.path == copy.source) {
+                item.set_sieved_copy(Some(sieve::SievedCopy {
+                    target: copy.target.clone(),
+                    stamp: copy.stamp.clone(),
+                }));
+            }
+        }
+    }
+
+    /// Gets the indices of the items that are tagged to be deleted
+    pub fn get_pending_deletes(&self) -> Vec<usize> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.is_pending_delete())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Deletes the files of the items tagged to be deleted in the source directory, other discarded items are kept.
+    /// The progress is reported by calling a callback function with the file that is currently processed.
+    pub fn delete_pending(&self, progress_callback: impl Fn(String)) {
+        let mut item_list = self.clone();
+        for item in &mut item_list.items {
+            let pending_delete = item.is_pending_delete();
+            item.set_take_over(!pending_delete);
+        }
+        let sieve_io = sieve::FileSieveIO::for_source_operations();
+        sieve::sieve(
+            &item_list,
+            &self.path,
+            SieveMethod::Delete,
+            DirectoryNames::YearAndMonth,
             &sieve_io,
+            false,
             progress_callback,
         );
     }
 
-    /// Gets the event which a file item belongs to
-    pub fn get_event(&self, item: &file_item::FileItem) -> Option<&event::Event> {
-        let naive_date = NaiveDateTime::from_timestamp_opt(item.get_timestamp(), 0)
+    /// Removes the deletion tag of the items whose files were deleted
+    pub fn clear_deleted_pending(&mut self) {
+        for item in &mut self.items {
+            if item.is_pending_delete() && !item.path.exists() {
+                item.set_pending_delete(false);
+            }
+        }
+    }
+
+    /// Reverts the last copy or move sieve to a directory.
+    /// The progress is reported by calling a callback function with the file that is currently processed.
+    pub fn undo_sieve(path: &Path, progress_callback: impl Fn(String)) {
+        let sieve_io = sieve::FileSieveIO::for_source_operations();
+        sieve::undo_sieve(path, &sieve_io, progress_callback);
+    }
+
+    /// Compares the items kept now with the last sieve to the given target directory
+    pub fn compare_with_last_sieve(
+        &self,
+        path: &Path,
+    ) -> Result<sieve::SieveComparison, std::io::Error> {
+        let sieve_io = sieve::FileSieveIO::for_source_operations();
+        sieve::compare_with_last_sieve(self, path, &sieve_io)
+    }
+
+    /// Gets the time stamp of a file item in the time zone used to match events
+    pub fn get_event_timestamp(&self, item: &file_item::FileItem) -> i64 {
+        match self.event_time_zone {
+            EventTimeZone::CaptureTime => item.get_timestamp(),
+            EventTimeZone::Local => item.get_timestamp_in(&chrono::Local),
+            EventTimeZone::Utc => item.get_timestamp_in(&chrono::Utc),
+        }
+    }
+
+    /// Gets the day of a file item in the time zone used to match events
+    pub fn get_event_date(&self, item: &file_item::FileItem) -> NaiveDate {
+        NaiveDateTime::from_timestamp_opt(self.get_event_timestamp(item), 0)
             .unwrap()
-            .date();
+            .date()
+    }
+
+    /// Gets the event which a file item belongs to. An event of the folder of the item takes precedence over the
+    /// events matching the date of the item.
+    pub fn get_event(&self, item: &file_item::FileItem) -> Option<&event::Event> {
+        if let Some(folder) = self.get_item_folder(item) {
+            let folder_event = self
+                .events
+                .iter()
+                .find(|event| event.is_folder_event() && Path::new(&event.folder) == folder);
+            if folder_event.is_some() {
+                return folder_event;
+            }
+        }
+        let naive_date = self.get_event_date(item);
         self.events
             .iter()
-            .find(|&event| event.contains(&naive_date))
+            .find(|&event| !event.is_folder_event() && event.contains(&naive_date))
+    }
+
+    /// Gets the folder of a file item relative to the source directory, None if it is directly in the source directory
+    fn get_item_folder<'a>(&self, item: &'a file_item::FileItem) -> Option<&'a Path> {
+        item.path
+            .parent()
+            .and_then(|folder| folder.strip_prefix(&self.path).ok())
+            .filter(|folder| !folder.as_os_str().is_empty())
+    }
+
+    /// Creates an event for each folder of the items that has none yet, so that a folder based organization is taken
+    /// over. The items of the folder belong to its event regardless of their dates. The dates of the events are set to
+    /// the days of their items. Returns the number of created events.
+    pub fn create_folder_events(&mut self) -> usize {
+        let mut folder_dates: BTreeMap<PathBuf, (NaiveDate, NaiveDate)> = BTreeMap::new();
+        for item in &self.items {
+            if let Some(folder) = self.get_item_folder(item) {
+                let date = self.get_event_date(item);
+                folder_dates
+                    .entry(folder.to_path_buf())
+                    .and_modify(|(start, end)| {
+                        *start = (*start).min(date);
+                        *end = (*end).max(date);
+                    })
+                    .or_insert((date, date));
+            }
+        }
+        let mut created = 0;
+        for (folder, (start_date, end_date)) in folder_dates {
+            match self
+                .events
+                .iter_mut()
+                .find(|event| event.is_folder_event() && Path::new(&event.folder) == folder)
+            {
+                Some(event) => {
+                    event.start_date = start_date;
+                    event.end_date = end_date;
+                }
+                None => {
+                    self.events
+                        .push(event::Event::from_folder(&folder, start_date, end_date));
+                    created += 1;
+                }
+            }
+        }
+        created
+    }
+
+    /// Counts the items per day, sorted by date. Days without items are not contained.
+    pub fn count_per_day(&self) -> Vec<(NaiveDate, usize)> {
+        let mut counts = BTreeMap::new();
+        for item in &self.items {
+            *counts.entry(self.get_event_date(item)).or_insert(0) += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    /// Sets the take over state of all items belonging to an event and returns the number of changed items.
+    /// All items of the event count as decided afterwards.
+    pub fn set_take_over_of_event(&mut self, event_index: usize, take_over: bool) -> usize {
+        let event = match self.events.get(event_index) {
+            Some(event) => event,
+            None => return 0,
+        };
+        let indices: Vec<usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| self.get_event(item) == Some(event))
+            .map(|(index, _)| index)
+            .collect();
+        let mut changed = 0;
+        for index in indices {
+            if self.items[index].get_take_over() != take_over {
+                changed += 1;
+            }
+            self.items[index].set_take_over(take_over);
+        }
+        changed
+    }
+
+    /// Keeps only the largest or the smallest file of each group of similar items, e.g. to keep the export in the best
+    /// quality, and discards the others. Groups whose items are all kept as intentional variations are left unchanged.
+    /// All items of the changed groups count as decided afterwards. Returns the number of changed items.
+    pub fn keep_by_size_in_groups(&mut self, keep_largest: bool) -> usize {
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(group_id) = item.get_group_id() {
+                groups.entry(group_id).or_default().push(index);
+            }
+        }
+        let mut changed = 0;
+        for indices in groups.values() {
+            if indices.len() < 2 || indices.iter().any(|index| self.items[*index].is_keep_all()) {
+                continue;
+            }
+            let sizes: Vec<u64> = indices
+                .iter()
+                .map(|index| self.items[*index].get_size())
+                .collect();
+            // The first item is kept if several items have the same size
+            let mut keep = 0;
+            for (position, size) in sizes.iter().enumerate() {
+                if (keep_largest && *size > sizes[keep]) || (!keep_largest && *size < sizes[keep]) {
+                    keep = position;
+                }
+            }
+            for (position, index) in indices.iter().enumerate() {
+                let take_over = position == keep;
+                if self.items[*index].get_take_over() != take_over {
+                    changed += 1;
+                }
+                self.items[*index].set_take_over(take_over);
+            }
+        }
+        changed
     }
 }
 
+/// Checks if the paths are stored in at least two different directories
+fn in_different_directories(paths: &[&Path]) -> bool {
+    paths
+        .iter()
+        .map(|path| path.parent())
+        .collect::<HashSet<Option<&Path>>>()
+        .len()
+        > 1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::item_sort_list::item_traits::PropertyResolver;
+    use crate::item_sort_list::item_traits::{GpsPosition, PropertyResolver};
     use std::cell::RefCell;
     use std::rc::Rc;
     extern crate base64;
     use base64::{engine::general_purpose, Engine as _};
+    use img_hash::ImageHash;
 
     struct MockResolver {
         call_count: Rc<RefCell<usize>>,
@@ -222,9 +1117,37 @@ mod tests {
             return_values[call_count]
         }
 
+        fn get_utc_offset(&self) -> Option<i32> {
+            None
+        }
+
         fn get_orientation(&self) -> Option<crate::item_sort_list::Orientation> {
             None
         }
+
+        fn get_gps(&self) -> Option<GpsPosition> {
+            None
+        }
+
+        fn get_camera(&self) -> Option<String> {
+            None
+        }
+
+        fn get_dimensions(&self) -> Option<(u32, u32)> {
+            None
+        }
+
+        fn get_invalid_date(&self) -> Option<String> {
+            None
+        }
+
+        fn has_orientation_mismatch(&self) -> bool {
+            false
+        }
+
+        fn has_file_date(&self) -> bool {
+            false
+        }
     }
 
     #[test]
@@ -244,9 +1167,11 @@ mod tests {
             items,
             events: vec![],
             path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
 
-        item_list.find_similar(5);
+        item_list.find_similar(5, false);
 
         assert_eq!(2, item_list.items[0].get_similars().len());
         assert_eq!(2, item_list.items[1].get_similars().len());
@@ -254,6 +1179,256 @@ mod tests {
         assert_eq!(0, item_list.items[3].get_similars().len());
         assert_eq!(1, item_list.items[4].get_similars().len());
         assert_eq!(1, item_list.items[5].get_similars().len());
+
+        let group_ids: Vec<Option<usize>> =
+            item_list.items.iter().map(|i| i.get_group_id()).collect();
+        assert_eq!(
+            group_ids,
+            vec![Some(1), Some(1), Some(1), None, Some(2), Some(2)]
+        );
+        item_list.items[0].reset_similars();
+        assert_eq!(item_list.items[0].get_group_id(), None);
+    }
+
+    #[test]
+    fn find_similar_consecutive_only() {
+        let create_item_list = || {
+            let mut item_list = ItemList::new();
+            // Each item is close to the previous one, but the last one is far from the first one
+            for (path, timestamp) in [("a.jpg", 0), ("b.jpg", 4), ("c.jpg", 8), ("d.jpg", 12)] {
+                item_list
+                    .items
+                    .push(file_item::FileItem::dummy(path, timestamp, true));
+            }
+            item_list
+        };
+        let group_ids = |item_list: &ItemList| -> Vec<Option<usize>> {
+            item_list.items.iter().map(|i| i.get_group_id()).collect()
+        };
+
+        // The time window chains from item to item
+        let mut item_list = create_item_list();
+        item_list.find_similar(5, false);
+        assert_eq!(
+            group_ids(&item_list),
+            vec![Some(1), Some(1), Some(1), Some(1)]
+        );
+
+        // The time window starts at the first item of each group
+        let mut item_list = create_item_list();
+        item_list.find_similar(5, true);
+        assert_eq!(
+            group_ids(&item_list),
+            vec![Some(1), Some(1), Some(2), Some(2)]
+        );
+        assert_eq!(item_list.items[1].get_similars(), &vec![0]);
+        item_list.find_similar(12, true);
+        assert_eq!(group_ids(&item_list), vec![Some(1); 4]);
+
+        let previews = create_item_list().preview_similar(&[5, 12], true);
+        assert_eq!(previews[0].groups, 2);
+        assert_eq!(previews[1].groups, 1);
+    }
+
+    #[test]
+    fn keep_all_groups() {
+        let mut item_list = ItemList::new();
+        for (path, timestamp) in [("a.jpg", 0), ("b.jpg", 4), ("c.jpg", 8), ("d.jpg", 12)] {
+            item_list
+                .items
+                .push(file_item::FileItem::dummy(path, timestamp, false));
+        }
+        item_list.find_similar(5, true);
+        for item in &mut item_list.items[0..2] {
+            item.set_keep_all(true);
+            item.set_take_over(true);
+        }
+        item_list.items[3].set_take_over(false);
+
+        // Finding similar items again keeps the decision
+        for item in &mut item_list.items {
+            item.reset_similars();
+        }
+        item_list.find_similar(5, true);
+        let keep_all: Vec<bool> = item_list.items.iter().map(|i| i.is_keep_all()).collect();
+        assert_eq!(keep_all, vec![true, true, false, false]);
+
+        // Undecided items joining the group are kept, decided items keep their state
+        for item in &mut item_list.items {
+            item.reset_similars();
+        }
+        item_list.find_similar(12, true);
+        assert!(item_list.items.iter().all(|item| item.is_keep_all()));
+        let take_over: Vec<bool> = item_list.items.iter().map(|i| i.get_take_over()).collect();
+        assert_eq!(take_over, vec![true, true, true, false]);
+        assert_eq!(item_list.count_undecided(), 0);
+    }
+
+    #[test]
+    fn find_similar_same_timestamp() {
+        // Distinct images copied in bulk share the same modification time
+        let hashes: [&[u8]; 4] = [
+            &[0x00; 16],
+            &[0xFF; 16],
+            &[
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x01,
+            ],
+            &[0x0F; 16],
+        ];
+        let mut item_list = ItemList::new();
+        for index in 0..hashes.len() + 1 {
+            let mut item = file_item::FileItem::dummy(&format!("{}.jpg", index), 100, true);
+            item.set_file_date(true);
+            item_list.items.push(item);
+        }
+        // An item at another time is not related to them
+        item_list
+            .items
+            .push(file_item::FileItem::dummy("5.jpg", 1000, true));
+
+        // Before the hashes are calculated, items with identical file dates are grouped by time
+        item_list.find_similar(5, false);
+        let group_ids: Vec<Option<usize>> =
+            item_list.items.iter().map(|i| i.get_group_id()).collect();
+        assert_eq!(
+            group_ids,
+            vec![Some(1), Some(1), Some(1), Some(1), Some(1), None]
+        );
+
+        // Once the hashes are known, the different images are separated, the item without a hash cannot be compared
+        for (index, hash) in hashes.iter().enumerate() {
+            item_list.items[index].set_hash(ImageHash::from_bytes(hash).unwrap());
+        }
+        item_list.separate_same_file_date_items();
+        assert_eq!(item_list.items[0].get_similars(), &vec![2, 4]);
+        assert_eq!(item_list.items[1].get_similars(), &vec![4]);
+        assert_eq!(item_list.items[2].get_similars(), &vec![0, 4]);
+        assert_eq!(item_list.items[3].get_similars(), &vec![4]);
+        assert_eq!(item_list.items[4].get_similars(), &vec![0, 1, 2, 3]);
+        assert!(item_list.items[5].get_similars().is_empty());
+
+        // Finding similar items again with the hashes gives the same result
+        for item in &mut item_list.items {
+            item.reset_similars();
+        }
+        item_list.find_similar(5, true);
+        assert_eq!(item_list.items[0].get_similars(), &vec![2, 4]);
+        assert_eq!(item_list.items[1].get_similars(), &vec![4]);
+        assert_eq!(item_list.items[4].get_similars(), &vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn find_similar_same_capture_time() {
+        // Burst shots taken within the same second share their capture time, but not their content
+        let mut item_list = ItemList::new();
+        for (index, hash) in [[0x00; 16], [0xFF; 16]].iter().enumerate() {
+            let mut item = file_item::FileItem::dummy(&format!("{}.jpg", index), 100, true);
+            item.set_hash(ImageHash::from_bytes(hash).unwrap());
+            item_list.items.push(item);
+        }
+
+        // Without using the hashes, the images stay grouped by their capture time
+        item_list.find_similar(5, false);
+        assert_eq!(item_list.items[0].get_similars(), &vec![1]);
+        assert_eq!(item_list.items[1].get_similars(), &vec![0]);
+        assert_eq!(item_list.items[0].get_group_id(), Some(1));
+        assert_eq!(item_list.items[1].get_group_id(), Some(1));
+    }
+
+    #[test]
+    fn find_similar_deterministic() {
+        let create_item_list = |paths: &[(&str, i64)]| {
+            let mut item_list = ItemList::new();
+            for (path, timestamp) in paths {
+                item_list
+                    .items
+                    .push(file_item::FileItem::dummy(path, *timestamp, true));
+            }
+            item_list.finish_synchronizing(Path::new(""));
+            item_list.find_similar(5, false);
+            item_list
+        };
+        let paths = [
+            ("b/b.jpg", 10),
+            ("a/b.jpg", 10),
+            ("c.jpg", 10),
+            ("a.jpg", 12),
+            ("d.jpg", 0),
+            ("e.jpg", 100),
+        ];
+        let item_list = create_item_list(&paths);
+        let mut reversed_paths = paths;
+        reversed_paths.reverse();
+        let other_item_list = create_item_list(&reversed_paths);
+
+        let result = |item_list: &ItemList| -> Vec<(PathBuf, Option<usize>, Vec<usize>)> {
+            item_list
+                .items
+                .iter()
+                .map(|i| (i.path.clone(), i.get_group_id(), i.get_similars().clone()))
+                .collect()
+        };
+        assert_eq!(result(&item_list), result(&other_item_list));
+        let paths: Vec<&Path> = item_list.items.iter().map(|i| i.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("d.jpg"),
+                Path::new("a/b.jpg"),
+                Path::new("b/b.jpg"),
+                Path::new("c.jpg"),
+                Path::new("a.jpg"),
+                Path::new("e.jpg")
+            ]
+        );
+        assert_eq!(item_list.get_group_representative(1), Some(1));
+        assert_eq!(item_list.get_group_representative(2), None);
+    }
+
+    #[test]
+    fn preview_similar() {
+        let mut item_list = ItemList::new();
+        for (path, timestamp) in [("a.jpg", 0), ("b.jpg", 3), ("c.jpg", 8), ("d.jpg", 100)] {
+            item_list
+                .items
+                .push(file_item::FileItem::dummy(path, timestamp, true));
+        }
+        item_list.find_similar(2, false);
+        let statistics = item_list.get_group_statistics();
+        assert_eq!(statistics, GroupStatistics::default());
+
+        let previews = item_list.preview_similar(&[2, 5, 10, 100], false);
+        assert_eq!(
+            previews,
+            vec![
+                GroupStatistics::default(),
+                GroupStatistics {
+                    groups: 1,
+                    grouped_items: 3,
+                    largest_group: 3
+                },
+                GroupStatistics {
+                    groups: 1,
+                    grouped_items: 3,
+                    largest_group: 3
+                },
+                GroupStatistics {
+                    groups: 1,
+                    grouped_items: 4,
+                    largest_group: 4
+                },
+            ]
+        );
+        // The groups of the item list itself are unchanged
+        assert_eq!(item_list.get_group_statistics(), statistics);
+        assert!(item_list.items.iter().all(|i| i.get_similars().is_empty()));
+
+        // Without hashes, no items are similar
+        assert_eq!(
+            item_list.preview_similar_hashes(&[10, 20]),
+            vec![GroupStatistics::default(), GroupStatistics::default()]
+        );
     }
 
     #[test]
@@ -275,12 +1450,204 @@ mod tests {
             items,
             events: vec![],
             path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
 
-        item_list.find_similar_hashes(2);
+        // Aborting the search leaves the items without similarities
+        assert!(item_list
+            .find_similar_hashes(2, |percent| percent < 50)
+            .is_err());
+        assert!(item_list
+            .items
+            .iter()
+            .all(|item| item.get_similars().is_empty()));
+
+        let mut reported = vec![];
+        assert!(item_list
+            .find_similar_hashes(2, |percent| {
+                reported.push(percent);
+                true
+            })
+            .is_ok());
+        assert_eq!(reported, vec![0, 33, 60, 80, 93, 100]);
 
         assert_eq!(2, item_list.items[0].get_similars().len());
         assert_eq!(2, item_list.items[4].get_similars().len());
+        // All items are connected by a chain of similar hashes
+        assert!(item_list
+            .items
+            .iter()
+            .all(|item| item.get_group_id() == Some(1)));
+    }
+
+    #[test]
+    fn find_duplicates() {
+        let mut item_list = ItemList::new();
+        for path in [
+            "tests/test.jpg",
+            "tests/test2.JPG",
+            "tests/test_invalid.jpg",
+            "tests/subdir/test.jpg",
+            "tests/subdir/test2.JPG",
+            "tests/subdir/test3.jpg",
+        ] {
+            item_list
+                .items
+                .push(file_item::FileItem::dummy(path, 0, true));
+        }
+
+        // Files with the same name but different content are not reported
+        assert_eq!(
+            item_list.find_duplicates(),
+            vec![
+                vec![
+                    PathBuf::from("tests/subdir/test.jpg"),
+                    PathBuf::from("tests/test.jpg")
+                ],
+                vec![
+                    PathBuf::from("tests/subdir/test2.JPG"),
+                    PathBuf::from("tests/test_invalid.jpg")
+                ],
+            ]
+        );
+
+        // Duplicates in the same directory are not reported
+        item_list.items.remove(0);
+        item_list
+            .items
+            .push(file_item::FileItem::dummy("tests/subdir/test.jpg", 0, true));
+        assert_eq!(item_list.find_duplicates().len(), 1);
+    }
+
+    #[test]
+    fn split_large_groups() {
+        let mut item_list = ItemList::new();
+        // One group of burst shots with two pauses and a single image
+        for (index, timestamp) in [0, 1, 2, 3, 10, 11, 12, 20, 21, 100]
+            .into_iter()
+            .enumerate()
+        {
+            item_list.items.push(file_item::FileItem::dummy(
+                &format!("{}.jpg", index),
+                timestamp,
+                true,
+            ));
+        }
+        item_list.find_similar(10, false);
+        assert_eq!(item_list.get_group_statistics().largest_group, 9);
+
+        assert_eq!(item_list.split_large_groups(0), 0);
+        assert_eq!(item_list.split_large_groups(9), 0);
+        assert_eq!(item_list.get_group_statistics().groups, 1);
+
+        // The group is split at the pauses
+        assert_eq!(item_list.split_large_groups(4), 1);
+        let group_ids: Vec<Option<usize>> = item_list
+            .items
+            .iter()
+            .map(|item| item.get_group_id())
+            .collect();
+        assert_eq!(
+            group_ids,
+            vec![
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(2),
+                Some(2),
+                Some(3),
+                Some(3),
+                None
+            ]
+        );
+        assert_eq!(item_list.items[4].get_similars(), &vec![5, 6]);
+
+        // Without gaps, the group is split in the middle
+        assert_eq!(item_list.split_large_groups(2), 2);
+        assert_eq!(item_list.get_group_statistics().largest_group, 2);
+        assert_eq!(item_list.get_group_statistics().grouped_items, 8);
+    }
+
+    #[test]
+    fn find_similar_locations() {
+        let venue = |latitude: f64| {
+            Some(GpsPosition {
+                latitude,
+                longitude: 13.405,
+            })
+        };
+        let mut item_list = ItemList::new();
+        // Two venues about 1 km apart, the last items have no position
+        for (index, (gps, timestamp)) in [
+            (venue(52.520), 0),
+            (venue(52.5205), 1000),
+            (venue(52.529), 2000),
+            (venue(52.5292), 3000),
+            (None, 3003),
+            (None, 5000),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut item = file_item::FileItem::dummy(&format!("{}.jpg", index), timestamp, true);
+            item.set_gps(gps);
+            item_list.items.push(item);
+        }
+
+        item_list.find_similar_locations(100, 5);
+
+        assert_eq!(item_list.items[0].get_similars(), &vec![1]);
+        assert_eq!(item_list.items[2].get_similars(), &vec![3]);
+        // Items without a position are grouped by time
+        assert_eq!(item_list.items[3].get_similars(), &vec![2, 4]);
+        assert_eq!(item_list.items[4].get_similars(), &vec![3]);
+        assert!(item_list.items[5].get_similars().is_empty());
+        let group_ids: Vec<Option<usize>> = item_list
+            .items
+            .iter()
+            .map(|item| item.get_group_id())
+            .collect();
+        assert_eq!(
+            group_ids,
+            vec![Some(1), Some(1), Some(2), Some(2), Some(2), None]
+        );
+    }
+
+    #[test]
+    fn find_similar_names() {
+        let mut item_list = ItemList::new();
+        for (path, timestamp) in [
+            ("export/large/IMG_001.jpg", 0),
+            ("export/small/IMG_001.jpg", 5000),
+            ("export/large/IMG_002.jpg", 10000),
+            ("img_001.JPG", 20000),
+            ("export/small/IMG_003.jpg", 10001),
+        ] {
+            item_list
+                .items
+                .push(file_item::FileItem::dummy(path, timestamp, true));
+        }
+
+        item_list.find_similar_names();
+
+        assert_eq!(item_list.items[0].get_similars(), &vec![1, 3]);
+        assert_eq!(item_list.items[1].get_similars(), &vec![0, 3]);
+        assert_eq!(item_list.items[3].get_similars(), &vec![0, 1]);
+        // Different names are not similar, even if captured at the same time
+        assert!(item_list.items[2].get_similars().is_empty());
+        assert!(item_list.items[4].get_similars().is_empty());
+        assert_eq!(item_list.items[0].get_group_id(), Some(1));
+        assert_eq!(item_list.items[3].get_group_id(), Some(1));
+        assert_eq!(item_list.items[2].get_group_id(), None);
+
+        // Similarities found by other means are kept
+        item_list.items[2].add_similar_vec(&[4]);
+        item_list.items[4].add_similar_vec(&[2]);
+        item_list.find_similar_names();
+        assert_eq!(item_list.items[2].get_group_id(), Some(2));
     }
 
     #[test]
@@ -289,15 +1656,24 @@ mod tests {
             items: vec![],
             events: vec![],
             path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
 
-        item_list.check_and_add(Path::new("tests/test_no_date.jpg"));
-        item_list.check_and_add(Path::new("tests/test_no_exif.jpg"));
-        item_list.check_and_add(Path::new("tests/test.jpg"));
-        item_list.check_and_add(Path::new("tests/test_no_date.jpg"));
-        item_list.check_and_add(Path::new("tests/test_invalid.jpg"));
-        item_list.check_and_add(Path::new("tests/test"));
+        item_list.check_and_add(Path::new("tests/test_no_date.jpg"), true);
+        item_list.check_and_add(Path::new("tests/test_no_exif.jpg"), true);
+        item_list.check_and_add(Path::new("tests/test.jpg"), false);
+        item_list.check_and_add(Path::new("tests/test_no_date.jpg"), false);
+        item_list.check_and_add(Path::new("tests/test_invalid.jpg"), true);
+        item_list.check_and_add(Path::new("tests/test"), true);
         assert_eq!(4, item_list.items.len());
+        assert!(item_list.items[0].get_take_over());
+        assert!(!item_list.items[2].get_take_over());
+
+        assert_eq!((3, 1), item_list.count_take_over());
+        assert_eq!(item_list.count_undecided(), 4);
+        item_list.items[0].set_take_over(true);
+        assert_eq!(item_list.count_undecided(), 3);
 
         item_list.finish_synchronizing(Path::new("tests"));
         assert_eq!("tests", item_list.path.to_str().unwrap());
@@ -307,4 +1683,415 @@ mod tests {
         item_list.drain_missing();
         assert_eq!(4, item_list.items.len());
     }
+
+    #[test]
+    fn drain_excluded() {
+        let mut item_list = ItemList {
+            items: vec![
+                file_item::FileItem::dummy("exports/test.jpg", 0, true),
+                file_item::FileItem::dummy("exports/sub/test.jpg", 0, true),
+                file_item::FileItem::dummy("exports/exports/test.jpg", 0, true),
+                file_item::FileItem::dummy("exports/other/test.jpg", 0, true),
+            ],
+            events: vec![],
+            path: PathBuf::from("exports"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+
+        item_list.drain_excluded(&[]);
+        assert_eq!(item_list.items.len(), 4);
+        // The base path itself is never excluded
+        item_list.drain_excluded(&[String::from("exports"), String::from("sub")]);
+        let paths: Vec<&Path> = item_list.items.iter().map(|i| i.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("exports/test.jpg"),
+                Path::new("exports/other/test.jpg")
+            ]
+        );
+    }
+
+    #[test]
+    fn relocate() {
+        let mut item_list = ItemList {
+            items: vec![
+                file_item::FileItem::dummy("old/test.jpg", 0, false),
+                file_item::FileItem::dummy("old/sub/test.mp4", 0, true),
+                file_item::FileItem::dummy("other/test.jpg", 0, true),
+            ],
+            events: vec![],
+            path: PathBuf::from("old"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+
+        item_list.relocate(Path::new("new"));
+        assert_eq!(item_list.path, Path::new("new"));
+        assert_eq!(item_list.items[0].path, Path::new("new/test.jpg"));
+        assert!(!item_list.items[0].get_take_over());
+        assert_eq!(item_list.items[1].path, Path::new("new/sub/test.mp4"));
+        assert_eq!(item_list.items[2].path, Path::new("other/test.jpg"));
+    }
+
+    #[test]
+    fn count_per_day() {
+        let day = 24 * 3600;
+        let item_list = ItemList {
+            items: vec![
+                file_item::FileItem::dummy("test1.jpg", 2 * day + 10, true),
+                file_item::FileItem::dummy("test2.jpg", 10, true),
+                file_item::FileItem::dummy("test3.jpg", 2 * day + 20, false),
+                file_item::FileItem::dummy("test4.jpg", 20, true),
+                file_item::FileItem::dummy("test5.jpg", 3 * day - 1, true),
+            ],
+            events: vec![],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+
+        let counts = item_list.count_per_day();
+        assert_eq!(
+            counts,
+            vec![
+                (NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(), 2),
+                (NaiveDate::from_ymd_opt(1970, 1, 3).unwrap(), 3)
+            ]
+        );
+        assert!(ItemList::new().count_per_day().is_empty());
+    }
+
+    #[test]
+    fn delete_pending() {
+        let directory = Path::new("tests/target/pending");
+        let _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory).unwrap();
+        let mut item_list = ItemList::new();
+        for name in ["tagged.jpg", "discarded.jpg", "kept.jpg"] {
+            std::fs::copy("tests/test.jpg", directory.join(name)).unwrap();
+            item_list.items.push(file_item::FileItem::dummy(
+                directory.join(name).to_str().unwrap(),
+                0,
+                true,
+            ));
+        }
+        item_list.items[0].set_pending_delete(true);
+        item_list.items[1].set_take_over(false);
+        assert_eq!(item_list.get_pending_deletes(), vec![0]);
+
+        let messages = RefCell::new(vec![]);
+        item_list.delete_pending(|message| messages.borrow_mut().push(message));
+        assert_eq!(messages.borrow().len(), 2);
+        assert_eq!(messages.borrow()[1], "Done");
+
+        // Only the tagged file is deleted, discarded files are kept
+        assert!(!directory.join("tagged.jpg").exists());
+        assert!(directory.join("discarded.jpg").exists());
+        assert!(directory.join("kept.jpg").exists());
+        assert!(!item_list.items[1].get_take_over());
+        assert!(item_list.items[0].is_pending_delete());
+
+        item_list.clear_deleted_pending();
+        assert!(item_list.get_pending_deletes().is_empty());
+        assert!(!item_list.items[0].get_take_over());
+    }
+
+    #[test]
+    fn project_statistics() {
+        let day = 24 * 3600;
+        let mut item_list = ItemList {
+            items: vec![
+                file_item::FileItem::dummy("tests/test.jpg", day + 10, true),
+                file_item::FileItem::dummy("tests/test_no_exif.jpg", 10, false),
+                file_item::FileItem::dummy("tests/not_there.jpg", 3 * day, true),
+            ],
+            events: vec![
+                event::Event::new("Event \"1\"", "1970-01-02", "1970-01-02"),
+                event::Event::new("Event 2", "1970-01-05", "1970-01-06"),
+            ],
+            path: PathBuf::from("tests"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let size = Path::new("tests/test.jpg").metadata().unwrap().len();
+        let size_no_exif = Path::new("tests/test_no_exif.jpg")
+            .metadata()
+            .unwrap()
+            .len();
+
+        let statistics = item_list.get_project_statistics();
+        assert_eq!(statistics.total.name, "tests");
+        assert_eq!(statistics.total.items, 3);
+        assert_eq!(statistics.total.kept, 2);
+        assert_eq!(statistics.total.discarded(), 1);
+        assert_eq!(statistics.total.bytes, size + size_no_exif);
+        assert_eq!(statistics.total.kept_bytes, size);
+        assert_eq!(
+            statistics.total.date_range,
+            Some((
+                NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(1970, 1, 4).unwrap()
+            ))
+        );
+        assert_eq!(statistics.undecided, 3);
+        assert_eq!(statistics.events.len(), 2);
+        assert_eq!(statistics.events[0].items, 1);
+        assert_eq!(statistics.events[0].kept, 1);
+        assert_eq!(statistics.events[0].kept_bytes, size);
+        // Events without items are contained as well
+        assert_eq!(statistics.events[1].name, "Event 2");
+        assert_eq!(statistics.events[1].items, 0);
+        assert_eq!(statistics.events[1].date_range, None);
+
+        // Statistics follow the decisions
+        item_list.items[0].set_take_over(false);
+        item_list.items[0].set_favorite(true);
+        let statistics = item_list.get_project_statistics();
+        assert_eq!(statistics.total.favorites, 1);
+        assert_eq!(statistics.total.kept, 1);
+        assert_eq!(statistics.total.kept_bytes, 0);
+        assert_eq!(statistics.undecided, 2);
+        assert_eq!(statistics.events[0].discarded(), 1);
+
+        let csv = statistics.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "Type,Name,Items,Kept,Discarded,Favorites,Bytes,Kept bytes,First date,Last date"
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "Project,\"tests\",3,1,2,1,{},0,1970-01-01,1970-01-04",
+                size + size_no_exif
+            )
+        );
+        assert_eq!(
+            lines[2],
+            format!(
+                "Event,\"Event \"\"1\"\"\",1,0,1,1,{},0,1970-01-02,1970-01-02",
+                size
+            )
+        );
+        assert_eq!(lines[3], "Event,\"Event 2\",0,0,0,0,0,0,,");
+    }
+
+    #[test]
+    fn event_time_zones() {
+        let timestamp = NaiveDateTime::parse_from_str("2021-09-13 23:30", "%Y-%m-%d %H:%M")
+            .unwrap()
+            .timestamp();
+        let mut item = file_item::FileItem::dummy("test.jpg", timestamp, true);
+        item.set_utc_offset(Some(-4 * 3600));
+        let mut item_list = ItemList {
+            items: vec![item],
+            events: vec![event::Event::new("Event", "2021-09-14", "2021-09-14")],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+
+        // Captured shortly before midnight, but already on the day of the event in UTC
+        assert!(item_list.get_event(&item_list.items[0]).is_none());
+        item_list.event_time_zone = EventTimeZone::Utc;
+        assert_eq!(
+            item_list.get_event(&item_list.items[0]).unwrap().name,
+            "Event"
+        );
+        assert_eq!(
+            item_list.get_event_timestamp(&item_list.items[0]),
+            timestamp + 4 * 3600
+        );
+    }
+
+    #[test]
+    fn take_over_of_event() {
+        let day = 24 * 3600;
+        let mut item_list = ItemList {
+            items: vec![
+                file_item::FileItem::dummy("test1.jpg", 10, true),
+                file_item::FileItem::dummy("test2.jpg", day + 10, true),
+                file_item::FileItem::dummy("test3.jpg", day + 20, false),
+                file_item::FileItem::dummy("test4.jpg", 3 * day, true),
+            ],
+            events: vec![
+                event::Event::new("Event 1", "1970-01-02", "1970-01-02"),
+                event::Event::new("Event 2", "1970-01-03", "1970-01-03"),
+            ],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+
+        assert_eq!(item_list.set_take_over_of_event(0, false), 1);
+        let take_over: Vec<bool> = item_list.items.iter().map(|i| i.get_take_over()).collect();
+        assert_eq!(take_over, vec![true, false, false, true]);
+        // Unchanged items of the event are decided as well
+        assert_eq!(item_list.count_undecided(), 2);
+
+        assert_eq!(item_list.set_take_over_of_event(0, true), 2);
+        let take_over: Vec<bool> = item_list.items.iter().map(|i| i.get_take_over()).collect();
+        assert_eq!(take_over, vec![true, true, true, true]);
+
+        // No items on the day of the second event and no third event
+        assert_eq!(item_list.set_take_over_of_event(1, false), 0);
+        assert_eq!(item_list.set_take_over_of_event(2, false), 0);
+    }
+
+    #[test]
+    fn create_folder_events() {
+        let day = 24 * 3600;
+        let mut item_list = ItemList {
+            items: vec![
+                file_item::FileItem::dummy("photos/Holidays/test1.jpg", day + 10, true),
+                file_item::FileItem::dummy("photos/Holidays/test2.jpg", 3 * day, true),
+                file_item::FileItem::dummy("photos/Party/test3.jpg", day + 20, true),
+                file_item::FileItem::dummy("photos/test4.jpg", day + 30, true),
+            ],
+            events: vec![event::Event::new("Event 1", "1970-01-02", "1970-01-02")],
+            path: PathBuf::from("photos"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+
+        assert_eq!(item_list.create_folder_events(), 2);
+        assert_eq!(item_list.events.len(), 3);
+        assert_eq!(item_list.events[1].name, "Holidays");
+        assert_eq!(item_list.events[1].start_date_as_string(), "1970-01-02");
+        assert_eq!(item_list.events[1].end_date_as_string(), "1970-01-04");
+        assert_eq!(item_list.events[2].name, "Party");
+
+        // Items in folders belong to the events of their folders, other items are matched by their dates
+        let event_names: Vec<&str> = item_list
+            .items
+            .iter()
+            .map(|item| item_list.get_event(item).unwrap().name.as_str())
+            .collect();
+        assert_eq!(
+            event_names,
+            vec!["Holidays", "Holidays", "Party", "Event 1"]
+        );
+
+        // Existing folder events are kept and only their dates are updated
+        item_list.events[1].name = String::from("Summer holidays");
+        item_list.items.remove(1);
+        assert_eq!(item_list.create_folder_events(), 0);
+        assert_eq!(item_list.events[1].name, "Summer holidays");
+        assert_eq!(item_list.events[1].end_date_as_string(), "1970-01-02");
+    }
+
+    #[test]
+    fn check_empty_files() {
+        let directory = std::env::temp_dir().join("image_sieve_test_empty_files");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("empty.jpg");
+        std::fs::write(&path, []).unwrap();
+
+        let mut item_list = ItemList::new();
+        item_list
+            .items
+            .push(file_item::FileItem::dummy(&path.to_string_lossy(), 0, true));
+        item_list
+            .items
+            .push(file_item::FileItem::dummy("tests/test.jpg", 0, true));
+        item_list.items[1].set_corrupt(true);
+        assert_eq!(item_list.check_empty_files(), 1);
+        assert!(item_list.items[0].is_corrupt());
+        // A replaced file is not corrupt anymore
+        assert!(!item_list.items[1].is_corrupt());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn remove_small_kept_items() {
+        let mut item_list = ItemList::new();
+        // Sizes of 7383, 631 and 1247 bytes
+        for file_name in [
+            "tests/test.jpg",
+            "tests/test_no_exif.jpg",
+            "tests/test_upright.jpg",
+        ] {
+            item_list
+                .items
+                .push(file_item::FileItem::dummy(file_name, 0, true));
+        }
+        item_list.items[1].set_take_over(false);
+
+        assert_eq!(item_list.remove_small_kept_items(0), 0);
+        assert_eq!(item_list.remove_small_kept_items(1024), 0);
+        // The small discarded item is kept in the list
+        assert_eq!(item_list.remove_small_kept_items(1248), 1);
+        assert_eq!(item_list.items.len(), 2);
+        assert_eq!(item_list.items[0].path, PathBuf::from("tests/test.jpg"));
+        assert_eq!(
+            item_list.items[1].path,
+            PathBuf::from("tests/test_no_exif.jpg")
+        );
+    }
+
+    #[test]
+    fn clear_hashes() {
+        let mut item_list = ItemList::new();
+        item_list
+            .items
+            .push(file_item::FileItem::dummy("tests/test.jpg", 0, true));
+        item_list.items.push(file_item::FileItem::dummy(
+            "tests/test_no_exif.jpg",
+            0,
+            true,
+        ));
+        item_list.items[0].set_hash(ImageHash::from_bytes(&[0x61, 0x62, 0x63]).unwrap());
+        assert_eq!(item_list.get_hash_count(), 1);
+        assert_eq!(item_list.clear_hashes(), 1);
+        assert_eq!(item_list.get_hash_count(), 0);
+        assert_eq!(item_list.clear_hashes(), 0);
+    }
+
+    #[test]
+    fn keep_by_size_in_groups() {
+        let mut item_list = ItemList::new();
+        // Sizes of 7383, 631, 1247, 1712 and 5511 bytes
+        for file_name in [
+            "tests/test.jpg",
+            "tests/test_no_exif.jpg",
+            "tests/test_upright.jpg",
+            "tests/test_thumbnail.jpg",
+            "tests/test_portrait.jpg",
+        ] {
+            item_list
+                .items
+                .push(file_item::FileItem::dummy(file_name, 0, true));
+        }
+        for (item, group_id) in
+            item_list
+                .items
+                .iter_mut()
+                .zip([Some(0), Some(0), Some(1), Some(1), None])
+        {
+            item.set_group_id(group_id);
+        }
+        let take_over = |item_list: &ItemList| -> Vec<bool> {
+            item_list.items.iter().map(|i| i.get_take_over()).collect()
+        };
+
+        assert_eq!(item_list.keep_by_size_in_groups(true), 2);
+        assert_eq!(take_over(&item_list), vec![true, false, false, true, true]);
+        // Items without a group are not decided
+        assert_eq!(item_list.count_undecided(), 1);
+
+        assert_eq!(item_list.keep_by_size_in_groups(false), 4);
+        assert_eq!(take_over(&item_list), vec![false, true, true, false, true]);
+
+        // Groups kept as intentional variations are not changed
+        item_list.items[2].set_keep_all(true);
+        item_list.items[3].set_keep_all(true);
+        item_list.items[2].set_take_over(true);
+        item_list.items[3].set_take_over(true);
+        assert_eq!(item_list.keep_by_size_in_groups(true), 2);
+        assert_eq!(take_over(&item_list), vec![true, false, true, true, true]);
+    }
 }
@@ -9,8 +9,86 @@ pub enum Orientation {
     Portrait270,
 }
 
-/// Trait to get a timestamp and an optional orientation from a file
+impl Orientation {
+    /// Gets the rotation in degrees that is needed to display an image upright
+    pub fn get_rotation(&self) -> i32 {
+        match self {
+            Orientation::Landscape => 0,
+            Orientation::Portrait90 => 90,
+            Orientation::Landscape180 => 180,
+            Orientation::Portrait270 => 270,
+        }
+    }
+}
+
+/// Mean radius of the earth in meters
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Position at which an item was captured in decimal degrees
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct GpsPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+// Positions are only created from finite coordinates, so comparing them is reflexive
+impl Eq for GpsPosition {}
+
+impl GpsPosition {
+    /// Gets the distance to another position in meters using the haversine formula
+    pub fn distance(&self, other: &GpsPosition) -> f64 {
+        let latitude = self.latitude.to_radians();
+        let other_latitude = other.latitude.to_radians();
+        let half_chord = ((other_latitude - latitude) / 2.0).sin().powi(2)
+            + latitude.cos()
+                * other_latitude.cos()
+                * ((other.longitude - self.longitude).to_radians() / 2.0)
+                    .sin()
+                    .powi(2);
+        2.0 * EARTH_RADIUS * half_chord.sqrt().min(1.0).asin()
+    }
+}
+
+/// Trait to get a timestamp, an optional offset to UTC, an optional orientation, an optional GPS position, the optional
+/// camera, the optional width and height in pixels and an optional invalid date from a file
 pub trait PropertyResolver {
     fn get_timestamp(&self) -> i64;
+    fn get_utc_offset(&self) -> Option<i32>;
     fn get_orientation(&self) -> Option<Orientation>;
+    fn get_gps(&self) -> Option<GpsPosition>;
+    fn get_camera(&self) -> Option<String>;
+    fn get_dimensions(&self) -> Option<(u32, u32)>;
+    /// Gets the raw date that could not be parsed or is implausible, None if the date of the file is fine
+    fn get_invalid_date(&self) -> Option<String>;
+    /// Checks if the timestamp is the date of the file system, because the file has no capture date
+    fn has_file_date(&self) -> bool;
+    /// Checks if the orientation disagrees with the pixels, so that applying it would rotate the image twice
+    fn has_orientation_mismatch(&self) -> bool;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let berlin = GpsPosition {
+            latitude: 52.5200,
+            longitude: 13.4050,
+        };
+        let paris = GpsPosition {
+            latitude: 48.8566,
+            longitude: 2.3522,
+        };
+        assert_eq!(berlin.distance(&berlin), 0.0);
+        assert!((berlin.distance(&paris) - 877_500.0).abs() < 1000.0);
+        assert_eq!(berlin.distance(&paris), paris.distance(&berlin));
+
+        // One thousandth of a degree of latitude is about 111 meters
+        let nearby = GpsPosition {
+            latitude: 52.5210,
+            longitude: 13.4050,
+        };
+        assert!((berlin.distance(&nearby) - 111.2).abs() < 0.1);
+    }
 }
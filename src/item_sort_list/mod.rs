@@ -1,18 +1,28 @@
 mod event;
+mod exif_orientation;
 mod file_item;
 mod file_types;
 mod item_list;
 mod item_traits;
 mod resolvers;
 mod sieve;
+mod strip_metadata;
 mod timestamp;
 
 pub use event::parse_date;
 pub use event::Event;
 pub use event::EVENT_DATE_FORMAT;
 pub use file_item::FileItem;
+pub use file_item::DEFAULT_HASH_SIZE;
+pub use file_types::{get_nested_directory, is_excluded};
+pub use item_list::CollisionPolicy;
 pub use item_list::DirectoryNames;
+pub use item_list::EventTimeZone;
 pub use item_list::ItemList;
+pub use item_list::ProjectSettings;
 pub use item_list::SieveMethod;
 pub use item_traits::Orientation;
+pub use resolvers::get_exif_metadata;
+pub use sieve::FileSieveIO;
+pub use sieve::FILE_PROGRESS_PREFIX;
 pub use timestamp::{timestamp_to_string, Format};
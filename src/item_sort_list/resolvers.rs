@@ -2,14 +2,21 @@ extern crate chrono;
 extern crate exif;
 extern crate ffmpeg_next as ffmpeg;
 
-use self::chrono::NaiveDateTime;
+use self::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
 use self::exif::{In, Tag};
 
 use super::file_types::{is_image, is_raw_image, is_video};
-use super::item_traits::{Orientation, PropertyResolver};
+use super::item_traits::{GpsPosition, Orientation, PropertyResolver};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::SystemTime;
 
+/// Set once a warning about unreadable EXIF data was logged, further errors are only logged in debug level
+static EXIF_ERROR_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Earliest year of a plausible capture date, earlier dates are usually unset camera clocks or zeroed metadata
+const MIN_PLAUSIBLE_YEAR: i32 = 1980;
+
 pub fn get_resolver(path: &Path) -> Box<dyn PropertyResolver> {
     if ExifResolver::supports(path) {
         Box::new(ExifResolver::new(path))
@@ -26,6 +33,15 @@ pub fn init_resolvers() {
     FFmpegResolver::init();
 }
 
+/// Gets the EXIF metadata of an image as a list of names and values, fields not found in the image are left out
+pub fn get_exif_metadata(path: &Path) -> Vec<(String, String)> {
+    if ExifResolver::supports(path) || RawResolver::supports(path) {
+        ExifResolver::new(path).get_metadata()
+    } else {
+        vec![]
+    }
+}
+
 pub struct FileResolver {
     path: PathBuf,
 }
@@ -38,26 +54,91 @@ impl FileResolver {
     }
 }
 
-impl PropertyResolver for FileResolver {
-    fn get_timestamp(&self) -> i64 {
+impl FileResolver {
+    /// Gets the UTC timestamp of the file from the file system
+    fn get_utc_timestamp(&self) -> Option<i64> {
         match std::fs::metadata(&self.path) {
             Ok(metadata) => {
                 let created = metadata.created().unwrap_or_else(|_| SystemTime::now());
                 let modified = metadata.modified().unwrap_or_else(|_| SystemTime::now());
-                created
-                    .min(modified)
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as i64
-                    + chrono::Local::now().offset().local_minus_utc() as i64
+                Some(
+                    created
+                        .min(modified)
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs() as i64,
+                )
             }
-            Err(_) => -1,
+            Err(_) => None,
+        }
+    }
+}
+
+/// Checks if a timestamp is a plausible capture date: not before 1980 and not in the future.
+/// A day is added to the current time, as the timestamps are local times of any time zone.
+fn is_plausible_timestamp(timestamp: i64) -> bool {
+    let earliest = NaiveDate::from_ymd_opt(MIN_PLAUSIBLE_YEAR, 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map_or(0, |date_time| date_time.timestamp());
+    let latest = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(i64::MAX, |duration| {
+            duration.as_secs() as i64 + 24 * 60 * 60
+        });
+    (earliest..=latest).contains(&timestamp)
+}
+
+impl PropertyResolver for FileResolver {
+    fn get_timestamp(&self) -> i64 {
+        match (self.get_utc_timestamp(), self.get_utc_offset()) {
+            (Some(timestamp), Some(utc_offset)) => timestamp + utc_offset as i64,
+            _ => -1,
         }
     }
 
+    fn get_utc_offset(&self) -> Option<i32> {
+        // Use the offset of the local time zone at the time of the file, which respects DST
+        let timestamp = self.get_utc_timestamp()?;
+        let local = chrono::Local.timestamp_opt(timestamp, 0).single()?;
+        Some(local.offset().local_minus_utc())
+    }
+
     fn get_orientation(&self) -> Option<Orientation> {
         None
     }
+
+    fn get_gps(&self) -> Option<GpsPosition> {
+        None
+    }
+
+    fn get_camera(&self) -> Option<String> {
+        None
+    }
+
+    fn get_dimensions(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn has_orientation_mismatch(&self) -> bool {
+        false
+    }
+
+    fn get_invalid_date(&self) -> Option<String> {
+        let timestamp = self.get_utc_timestamp()?;
+        if is_plausible_timestamp(timestamp) {
+            None
+        } else {
+            let date_time = NaiveDateTime::from_timestamp_opt(timestamp, 0)?;
+            Some(format!(
+                "{} (file date)",
+                date_time.format("%Y-%m-%d %H:%M:%S")
+            ))
+        }
+    }
+
+    fn has_file_date(&self) -> bool {
+        true
+    }
 }
 
 struct ExifResolver {
@@ -72,7 +153,15 @@ impl ExifResolver {
             Ok(file) => {
                 let mut bufreader = std::io::BufReader::new(&file);
                 let exif_reader = exif::Reader::new();
-                exif_reader.read_from_container(&mut bufreader).ok()
+                match exif_reader.read_from_container(&mut bufreader) {
+                    Ok(exif) => Some(exif),
+                    // Images without EXIF data are common, only log malformed EXIF data
+                    Err(exif::Error::NotFound(_)) => None,
+                    Err(e) => {
+                        log_exif_error(path, &e);
+                        None
+                    }
+                }
             }
             Err(_) => None,
         };
@@ -85,29 +174,163 @@ impl ExifResolver {
     pub fn supports(path: &Path) -> bool {
         is_image(path)
     }
+
+    /// Gets the capture date and time from the EXIF data, None if it is missing, malformed or implausible
+    fn get_date_time(&self) -> Option<NaiveDateTime> {
+        let field = self
+            .exif
+            .as_ref()?
+            .get_field(Tag::DateTimeOriginal, In::PRIMARY)?;
+        let date_time_str = field.display_value().to_string();
+        NaiveDateTime::parse_from_str(&date_time_str, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .filter(|date_time| is_plausible_timestamp(date_time.timestamp()))
+    }
+
+    /// Gets the capture date and time as stored in the EXIF data without parsing it
+    fn get_raw_date_time(&self) -> Option<String> {
+        let field = self
+            .exif
+            .as_ref()?
+            .get_field(Tag::DateTimeOriginal, In::PRIMARY)?;
+        match &field.value {
+            exif::Value::Ascii(strings) => Some(
+                String::from_utf8_lossy(strings.first()?)
+                    .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .to_string(),
+            ),
+            _ => Some(field.display_value().to_string()),
+        }
+    }
+
+    fn get_metadata(&self) -> Vec<(String, String)> {
+        let mut metadata = vec![];
+        if let Some(exif) = &self.exif {
+            let value = |tag: Tag| {
+                exif.get_field(tag, In::PRIMARY)
+                    .map(|field| match &field.value {
+                        exif::Value::Ascii(strings) => strings
+                            .iter()
+                            .map(|string| String::from_utf8_lossy(string).trim().to_string())
+                            .collect::<Vec<String>>()
+                            .join(" "),
+                        _ => field.display_value().with_unit(exif).to_string(),
+                    })
+                    .filter(|value| !value.is_empty())
+            };
+
+            if let Some(field) = exif.get_field(Tag::DateTimeOriginal, In::PRIMARY) {
+                metadata.push((
+                    String::from("Date taken"),
+                    field.display_value().to_string(),
+                ));
+            }
+            let camera: Vec<String> = [Tag::Make, Tag::Model]
+                .into_iter()
+                .filter_map(value)
+                .collect();
+            if !camera.is_empty() {
+                metadata.push((String::from("Camera"), camera.join(" ")));
+            }
+            for (name, tag) in [
+                ("Lens", Tag::LensModel),
+                ("Exposure time", Tag::ExposureTime),
+                ("Aperture", Tag::FNumber),
+                ("Focal length", Tag::FocalLength),
+                ("ISO", Tag::PhotographicSensitivity),
+            ] {
+                if let Some(value) = value(tag) {
+                    metadata.push((String::from(name), value));
+                }
+            }
+            if let (Some(latitude), Some(longitude)) = (
+                get_gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef),
+                get_gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef),
+            ) {
+                metadata.push((
+                    String::from("GPS"),
+                    format!("{:.6}, {:.6}", latitude, longitude),
+                ));
+            }
+        }
+        metadata
+    }
+}
+
+/// Logs that the EXIF data of a file could not be read. The warning is only logged for the first file, so that a
+/// general problem reading EXIF data does not flood the log. The file system dates are used for these files instead.
+fn log_exif_error(path: &Path, error: &exif::Error) {
+    if !EXIF_ERROR_LOGGED.swap(true, Ordering::Relaxed) {
+        log::warn!(
+            "Error reading EXIF data of {}: {}. File dates are used instead for this and other files with unreadable EXIF data",
+            path.display(),
+            error
+        );
+    } else {
+        log::debug!("Error reading EXIF data of {}: {}", path.display(), error);
+    }
+}
+
+/// Parses an offset to UTC in the EXIF format +HH:MM to seconds
+fn parse_utc_offset(offset: &str) -> Option<i32> {
+    let offset = offset.trim();
+    let sign = match offset.get(0..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = offset.get(1..)?.split_once(':')?;
+    Some(sign * (hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60))
+}
+
+/// Gets a GPS coordinate in decimal degrees from the degrees, minutes and seconds stored in EXIF data
+fn get_gps_coordinate(exif: &exif::Exif, tag: Tag, ref_tag: Tag) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    match &field.value {
+        exif::Value::Rational(values) if values.len() == 3 => {
+            let coordinate =
+                values[0].to_f64() + values[1].to_f64() / 60.0 + values[2].to_f64() / 3600.0;
+            // Rationals with a denominator of zero are found in broken EXIF data
+            if !coordinate.is_finite() {
+                return None;
+            }
+            let reference = exif
+                .get_field(ref_tag, In::PRIMARY)
+                .map(|field| field.display_value().to_string())
+                .unwrap_or_default();
+            if reference.contains('S') || reference.contains('W') {
+                Some(-coordinate)
+            } else {
+                Some(coordinate)
+            }
+        }
+        _ => None,
+    }
 }
 
 impl PropertyResolver for ExifResolver {
     fn get_timestamp(&self) -> i64 {
-        let file_resolver = FileResolver::new(&self.path);
-        match &self.exif {
-            Some(exif) => {
-                let date_time_field = exif.get_field(Tag::DateTimeOriginal, In::PRIMARY);
-                match date_time_field {
-                    Some(field) => {
-                        let date_time_str = field.display_value().to_string();
-                        if let Ok(date_time) =
-                            NaiveDateTime::parse_from_str(&date_time_str, "%Y-%m-%d %H:%M:%S")
-                        {
-                            date_time.timestamp()
-                        } else {
-                            file_resolver.get_timestamp()
-                        }
-                    }
-                    None => file_resolver.get_timestamp(),
+        match self.get_date_time() {
+            Some(date_time) => date_time.timestamp(),
+            None => FileResolver::new(&self.path).get_timestamp(),
+        }
+    }
+
+    fn get_utc_offset(&self) -> Option<i32> {
+        if self.get_date_time().is_some() {
+            // Only newer cameras store the offset of the capture time
+            let field = self
+                .exif
+                .as_ref()?
+                .get_field(Tag::OffsetTimeOriginal, In::PRIMARY)?;
+            match &field.value {
+                exif::Value::Ascii(strings) => {
+                    parse_utc_offset(&String::from_utf8_lossy(strings.first()?))
                 }
+                _ => None,
             }
-            None => file_resolver.get_timestamp(),
+        } else {
+            FileResolver::new(&self.path).get_utc_offset()
         }
     }
 
@@ -117,32 +340,140 @@ impl PropertyResolver for ExifResolver {
                 let orientation_field: Option<&exif::Field> =
                     exif.get_field(Tag::Orientation, In::PRIMARY);
 
-                if let Some(orientation_value) = orientation_field {
-                    let orientation_value = orientation_value.value.get_uint(0).unwrap();
-                    Some(match orientation_value {
+                // A malformed orientation field is treated like a missing one
+                orientation_field
+                    .and_then(|field| field.value.get_uint(0))
+                    .map(|orientation_value| match orientation_value {
                         1 => Orientation::Landscape,
                         6 => Orientation::Portrait90,
                         8 => Orientation::Portrait270,
                         3 => Orientation::Landscape180,
                         _ => Orientation::Landscape,
                     })
-                } else {
-                    None
-                }
             }
             None => None,
         }
     }
+
+    fn get_gps(&self) -> Option<GpsPosition> {
+        let exif = self.exif.as_ref()?;
+        Some(GpsPosition {
+            latitude: get_gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef)?,
+            longitude: get_gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef)?,
+        })
+    }
+
+    fn get_camera(&self) -> Option<String> {
+        let exif = self.exif.as_ref()?;
+        let camera: Vec<String> = [Tag::Make, Tag::Model]
+            .iter()
+            .filter_map(|tag| match &exif.get_field(*tag, In::PRIMARY)?.value {
+                exif::Value::Ascii(strings) => Some(
+                    String::from_utf8_lossy(strings.first()?)
+                        .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                        .to_string(),
+                ),
+                _ => None,
+            })
+            .filter(|name| !name.is_empty())
+            .collect();
+        if camera.is_empty() {
+            None
+        } else {
+            Some(camera.join(" "))
+        }
+    }
+
+    fn get_dimensions(&self) -> Option<(u32, u32)> {
+        // Only the header of the image is read
+        image::image_dimensions(&self.path).ok()
+    }
+
+    fn has_orientation_mismatch(&self) -> bool {
+        let exif = match &self.exif {
+            Some(exif) => exif,
+            None => return false,
+        };
+        let exif_dimension = |tag: Tag| {
+            exif.get_field(tag, In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        };
+        let exif_dimensions =
+            exif_dimension(Tag::PixelXDimension).zip(exif_dimension(Tag::PixelYDimension));
+        match (
+            self.get_orientation(),
+            exif_dimensions,
+            self.get_dimensions(),
+        ) {
+            (Some(orientation), Some(exif_dimensions), Some(pixel_dimensions))
+                if is_orientation_mismatch(&orientation, exif_dimensions, pixel_dimensions) =>
+            {
+                log::warn!(
+                    "Orientation {:?} of {} does not match its pixels of {}x{}, the image was likely rotated by an editor",
+                    orientation,
+                    self.path.display(),
+                    pixel_dimensions.0,
+                    pixel_dimensions.1
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn get_invalid_date(&self) -> Option<String> {
+        match self.get_raw_date_time() {
+            Some(raw_date_time) if self.get_date_time().is_none() => Some(raw_date_time),
+            Some(_) => None,
+            None => FileResolver::new(&self.path).get_invalid_date(),
+        }
+    }
+
+    fn has_file_date(&self) -> bool {
+        self.get_date_time().is_none()
+    }
+}
+
+/// Minimum ratio of the longer to the shorter side of an image that is clearly landscape or portrait
+const CLEAR_ASPECT_RATIO: f64 = 1.1;
+
+/// Checks if an orientation that swaps width and height disagrees with the pixels of an image. The dimensions stored
+/// in the EXIF data are those of the image as captured. If the pixels have the other shape, an editor already rotated
+/// them upright without resetting the orientation, so applying it would rotate the image twice.
+fn is_orientation_mismatch(
+    orientation: &Orientation,
+    exif_dimensions: (u32, u32),
+    pixel_dimensions: (u32, u32),
+) -> bool {
+    let is_landscape =
+        |(width, height): (u32, u32)| width as f64 >= height as f64 * CLEAR_ASPECT_RATIO;
+    let is_portrait =
+        |(width, height): (u32, u32)| height as f64 >= width as f64 * CLEAR_ASPECT_RATIO;
+    matches!(
+        orientation,
+        Orientation::Portrait90 | Orientation::Portrait270
+    ) && ((is_landscape(exif_dimensions) && is_portrait(pixel_dimensions))
+        || (is_portrait(exif_dimensions) && is_landscape(pixel_dimensions)))
 }
 
 struct FFmpegResolver {
     path: PathBuf,
+    creation_time: Option<DateTime<FixedOffset>>,
+    /// Creation time from the metadata that could not be parsed or is implausible
+    invalid_creation_time: Option<String>,
 }
 
 impl FFmpegResolver {
     pub fn new(path: &Path) -> Self {
+        let creation_time = Self::get_creation_time(path);
+        let invalid_creation_time = match creation_time {
+            Some(_) => None,
+            None => Self::get_raw_creation_time(path),
+        };
         Self {
             path: PathBuf::from(path),
+            creation_time,
+            invalid_creation_time,
         }
     }
 
@@ -153,41 +484,137 @@ impl FFmpegResolver {
     pub fn supports(path: &Path) -> bool {
         is_video(path)
     }
+
+    /// Gets the capture date and time from the video metadata with the offset to UTC at the place of capture.
+    /// Apple devices store the local capture time, otherwise the UTC creation time of the container or the
+    /// video stream (the MP4 movie header) is converted to the local time zone.
+    fn get_creation_time(path: &Path) -> Option<DateTime<FixedOffset>> {
+        let context = ffmpeg::format::input(&path).ok()?;
+        let metadata = context.metadata();
+        if let Some(date_time) = metadata
+            .get("com.apple.quicktime.creationdate")
+            .and_then(parse_creation_time)
+        {
+            return Some(date_time);
+        }
+        let date_time = metadata
+            .get("creation_time")
+            .and_then(parse_creation_time)
+            .or_else(|| {
+                let video_stream = context.streams().best(ffmpeg::media::Type::Video)?;
+                let stream_metadata = video_stream.metadata();
+                stream_metadata
+                    .get("creation_time")
+                    .and_then(parse_creation_time)
+            })?;
+        let local = date_time.with_timezone(&chrono::Local);
+        Some(date_time.with_timezone(local.offset()))
+    }
+
+    /// Gets the creation time as stored in the video metadata without parsing it
+    fn get_raw_creation_time(path: &Path) -> Option<String> {
+        let context = ffmpeg::format::input(&path).ok()?;
+        let metadata = context.metadata();
+        let raw_creation_time = metadata
+            .get("com.apple.quicktime.creationdate")
+            .or_else(|| metadata.get("creation_time"))
+            .map(String::from);
+        raw_creation_time.or_else(|| {
+            let video_stream = context.streams().best(ffmpeg::media::Type::Video)?;
+            let stream_metadata = video_stream.metadata();
+            stream_metadata.get("creation_time").map(String::from)
+        })
+    }
+}
+
+/// Parses a creation time from video metadata in ISO 8601 format with an offset to UTC
+fn parse_creation_time(creation_time: &str) -> Option<DateTime<FixedOffset>> {
+    let date_time = DateTime::parse_from_rfc3339(creation_time)
+        .or_else(|_| DateTime::parse_from_str(creation_time, "%Y-%m-%dT%H:%M:%S%z"))
+        .ok()?;
+    // A creation time that was never set is stored as zero, which is the start of 1904 or 1970
+    if is_plausible_timestamp(date_time.timestamp()) {
+        Some(date_time)
+    } else {
+        None
+    }
 }
 
 impl PropertyResolver for FFmpegResolver {
     fn get_timestamp(&self) -> i64 {
-        let file_resolver = FileResolver::new(&self.path);
-        if let Ok(context) = ffmpeg::format::input(&self.path) {
-            for (k, v) in context.metadata().iter() {
-                if k == "creation_time" {
-                    if let Ok(date_time) = NaiveDateTime::parse_from_str(v, "%+") {
-                        return date_time.timestamp();
-                    }
-                }
-            }
+        match self.creation_time {
+            Some(date_time) => date_time.naive_local().timestamp(),
+            None => FileResolver::new(&self.path).get_timestamp(),
+        }
+    }
+
+    fn get_utc_offset(&self) -> Option<i32> {
+        match self.creation_time {
+            Some(date_time) => Some(date_time.offset().local_minus_utc()),
+            None => FileResolver::new(&self.path).get_utc_offset(),
         }
-        file_resolver.get_timestamp()
     }
 
     fn get_orientation(&self) -> Option<Orientation> {
-        if let Ok(context) = ffmpeg::format::input(&self.path) {
-            if let Some(video_stream) = context.streams().best(ffmpeg::media::Type::Video) {
-                for (k, v) in video_stream.metadata().iter() {
-                    if k == "rotate" {
-                        return Some(match v {
-                            "0" => Orientation::Landscape,
-                            "90" => Orientation::Portrait90,
-                            "270" => Orientation::Portrait270,
-                            "180" => Orientation::Landscape180,
-                            _ => Orientation::Landscape,
-                        });
-                    }
-                }
-            }
+        let context = ffmpeg::format::input(&self.path).ok()?;
+        let video_stream = context.streams().best(ffmpeg::media::Type::Video)?;
+        // Newer FFmpeg versions only provide the display matrix, older ones a rotate tag
+        let rotation = video_stream
+            .side_data()
+            .find(|side_data| {
+                side_data.kind() == ffmpeg::codec::packet::side_data::Type::DisplayMatrix
+            })
+            .and_then(|side_data| get_display_matrix_rotation(side_data.data()))
+            .or_else(|| {
+                let metadata = video_stream.metadata();
+                metadata
+                    .get("rotate")
+                    .and_then(|rotate| rotate.parse().ok())
+            })?;
+        match rotation {
+            90 => Some(Orientation::Portrait90),
+            180 => Some(Orientation::Landscape180),
+            270 => Some(Orientation::Portrait270),
+            _ => None,
         }
+    }
+
+    fn get_gps(&self) -> Option<GpsPosition> {
+        None
+    }
+
+    fn get_camera(&self) -> Option<String> {
         None
     }
+
+    fn get_dimensions(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn has_orientation_mismatch(&self) -> bool {
+        false
+    }
+
+    fn get_invalid_date(&self) -> Option<String> {
+        self.invalid_creation_time
+            .clone()
+            .or_else(|| FileResolver::new(&self.path).get_invalid_date())
+    }
+
+    fn has_file_date(&self) -> bool {
+        self.creation_time.is_none()
+    }
+}
+
+/// Gets the clockwise rotation of a video in degrees, rounded to multiples of 90, from its display matrix.
+/// The display matrix consists of 3x3 32 bit values, the first two columns are 16.16 fixed point numbers.
+fn get_display_matrix_rotation(display_matrix: &[u8]) -> Option<i32> {
+    let value = |index: usize| -> Option<f64> {
+        let bytes = display_matrix.get(index * 4..index * 4 + 4)?;
+        Some(i32::from_ne_bytes(bytes.try_into().ok()?) as f64)
+    };
+    let degrees = value(1)?.atan2(value(0)?).to_degrees();
+    Some(((degrees / 90.0).round() as i32 * 90).rem_euclid(360))
 }
 
 struct RawResolver {
@@ -211,6 +638,10 @@ impl PropertyResolver for RawResolver {
         ExifResolver::new(&self.path).get_timestamp()
     }
 
+    fn get_utc_offset(&self) -> Option<i32> {
+        ExifResolver::new(&self.path).get_utc_offset()
+    }
+
     fn get_orientation(&self) -> Option<Orientation> {
         match rawloader::decode_file(&self.path) {
             Ok(raw) => match raw.orientation {
@@ -223,6 +654,31 @@ impl PropertyResolver for RawResolver {
             Err(_) => None,
         }
     }
+
+    fn get_gps(&self) -> Option<GpsPosition> {
+        ExifResolver::new(&self.path).get_gps()
+    }
+
+    fn get_camera(&self) -> Option<String> {
+        ExifResolver::new(&self.path).get_camera()
+    }
+
+    fn get_dimensions(&self) -> Option<(u32, u32)> {
+        // Raw images are taken by cameras, so their dimensions are not needed to detect screenshots
+        None
+    }
+
+    fn has_orientation_mismatch(&self) -> bool {
+        false
+    }
+
+    fn get_invalid_date(&self) -> Option<String> {
+        ExifResolver::new(&self.path).get_invalid_date()
+    }
+
+    fn has_file_date(&self) -> bool {
+        ExifResolver::new(&self.path).has_file_date()
+    }
 }
 
 #[cfg(test)]
@@ -233,6 +689,10 @@ mod tests {
         get_resolver(Path::new(path)).get_timestamp()
     }
 
+    fn get_utc_offset_from(path: &str) -> Option<i32> {
+        get_resolver(Path::new(path)).get_utc_offset()
+    }
+
     fn get_orientation_from(path: &str) -> Option<Orientation> {
         get_resolver(Path::new(path)).get_orientation()
     }
@@ -241,6 +701,16 @@ mod tests {
         FileResolver::new(Path::new(path)).get_timestamp()
     }
 
+    #[test]
+    fn file_date() {
+        init_resolvers();
+
+        assert!(!get_resolver(Path::new("tests/test.jpg")).has_file_date());
+        assert!(get_resolver(Path::new("tests/test_no_date.jpg")).has_file_date());
+        assert!(get_resolver(Path::new("tests/test_no_exif.jpg")).has_file_date());
+        assert!(get_resolver(Path::new("tests/test_invalid_date.jpg")).has_file_date());
+    }
+
     #[test]
     fn resolvers() {
         init_resolvers();
@@ -275,13 +745,28 @@ mod tests {
             Some(Orientation::Landscape180),
             get_orientation_from("tests/test.png")
         );
+        assert_eq!(
+            Some(Orientation::Portrait90),
+            get_orientation_from("tests/test_lossless.webp")
+        );
+        assert_eq!(None, get_orientation_from("tests/test_lossy.webp"));
 
         assert_eq!(
             get_file_timestamp("tests/test.mp4"),
             get_timestamp_from("tests/test.mp4")
         );
         assert_eq!(None, get_orientation_from("tests/test.mp4"));
-        assert_eq!(1640790497, get_timestamp_from("tests/test2.MP4"));
+        // The UTC creation time of the video is converted to local time
+        let utc_offset = chrono::Local
+            .timestamp_opt(1640790497, 0)
+            .unwrap()
+            .offset()
+            .local_minus_utc();
+        assert_eq!(
+            1640790497 + utc_offset as i64,
+            get_timestamp_from("tests/test2.MP4")
+        );
+        assert_eq!(Some(utc_offset), get_utc_offset_from("tests/test2.MP4"));
         assert_eq!(
             Some(Orientation::Landscape180),
             get_orientation_from("tests/test2.MP4")
@@ -297,4 +782,199 @@ mod tests {
         assert_eq!(-1, get_timestamp_from("not_there"));
         assert_eq!(get_file_timestamp("LICENSE"), get_timestamp_from("LICENSE"));
     }
+
+    #[test]
+    fn camera_and_dimensions() {
+        // The test images do not contain the make and model of a camera
+        let resolver = get_resolver(Path::new("tests/test_no_exif.jpg"));
+        assert!(resolver.get_camera().is_none());
+        assert!(resolver.get_dimensions().is_some());
+        let resolver = get_resolver(Path::new("tests/test.png"));
+        assert!(resolver.get_camera().is_none());
+        assert!(resolver.get_dimensions().is_some());
+        assert!(get_resolver(Path::new("not_there.jpg"))
+            .get_dimensions()
+            .is_none());
+        let resolver = get_resolver(Path::new("tests/test.mp4"));
+        assert!(resolver.get_camera().is_none());
+        assert!(resolver.get_dimensions().is_none());
+    }
+
+    #[test]
+    fn malformed_exif() {
+        // Corrupt the byte order of the EXIF data and cut off the image data
+        let mut jpeg = std::fs::read("tests/test.jpg").unwrap();
+        let exif_start = jpeg.windows(6).position(|w| w == b"Exif\0\0").unwrap() + 6;
+        jpeg[exif_start..exif_start + 2].copy_from_slice(b"XX");
+        let path = Path::new("test_malformed_exif.jpg");
+        std::fs::write(path, &jpeg[..exif_start + 100]).unwrap();
+
+        assert!(get_exif_metadata(path).is_empty());
+        let file_item = super::super::file_item::FileItem::new(
+            path.to_path_buf(),
+            get_resolver(path),
+            true,
+            "",
+        );
+        assert_eq!(
+            file_item.get_timestamp(),
+            get_file_timestamp("test_malformed_exif.jpg")
+        );
+        assert!(file_item.get_timestamp() > 0);
+        assert!(file_item.get_orientation().is_none());
+        assert!(EXIF_ERROR_LOGGED.load(Ordering::Relaxed));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert!(is_plausible_timestamp(1631461311));
+        assert!(!is_plausible_timestamp(0));
+        assert!(!is_plausible_timestamp(-62135596800));
+        assert!(!is_plausible_timestamp(i64::MAX));
+
+        let invalid_date_from = |path: &str| get_resolver(Path::new(path)).get_invalid_date();
+        assert_eq!(invalid_date_from("tests/test.jpg"), None);
+        assert_eq!(invalid_date_from("tests/test_no_date.jpg"), None);
+        // The raw value is kept if the date cannot be parsed
+        assert_eq!(
+            invalid_date_from("tests/test_invalid_date.jpg"),
+            Some(String::from("2021:13:12 15:41:51"))
+        );
+
+        // An implausible date is reported and the file date is used instead
+        let mut jpeg = std::fs::read("tests/test.jpg").unwrap();
+        for position in 0..jpeg.len() - 19 {
+            if &jpeg[position..position + 19] == b"2021:09:12 15:41:51" {
+                jpeg[position..position + 19].copy_from_slice(b"1970:01:01 00:00:00");
+            }
+        }
+        let path = Path::new("test_implausible_date.jpg");
+        std::fs::write(path, &jpeg).unwrap();
+        assert_eq!(
+            invalid_date_from("test_implausible_date.jpg"),
+            Some(String::from("1970:01:01 00:00:00"))
+        );
+        assert_eq!(
+            get_timestamp_from("test_implausible_date.jpg"),
+            get_file_timestamp("test_implausible_date.jpg")
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn orientation_mismatches() {
+        // Pixels rotated upright by an editor, but the orientation still rotates them
+        assert!(is_orientation_mismatch(
+            &Orientation::Portrait90,
+            (4000, 3000),
+            (3000, 4000)
+        ));
+        assert!(is_orientation_mismatch(
+            &Orientation::Portrait270,
+            (3000, 4000),
+            (4000, 3000)
+        ));
+        // Orientation matching the pixels as captured
+        assert!(!is_orientation_mismatch(
+            &Orientation::Portrait90,
+            (4000, 3000),
+            (4000, 3000)
+        ));
+        // Orientations that do not swap width and height, e.g. after an editor reset the orientation
+        assert!(!is_orientation_mismatch(
+            &Orientation::Landscape,
+            (4000, 3000),
+            (3000, 4000)
+        ));
+        assert!(!is_orientation_mismatch(
+            &Orientation::Landscape180,
+            (4000, 3000),
+            (3000, 4000)
+        ));
+        // Almost square images are not clearly portrait or landscape
+        assert!(!is_orientation_mismatch(
+            &Orientation::Portrait90,
+            (1000, 1050),
+            (1050, 1000)
+        ));
+    }
+
+    #[test]
+    fn utc_offsets() {
+        assert_eq!(parse_utc_offset("+02:00"), Some(7200));
+        assert_eq!(parse_utc_offset("-05:30"), Some(-19800));
+        assert_eq!(parse_utc_offset("+00:00"), Some(0));
+        assert_eq!(parse_utc_offset("   :  "), None);
+        assert_eq!(parse_utc_offset("02:00"), None);
+
+        // The test image does not contain an offset
+        assert_eq!(get_utc_offset_from("tests/test.jpg"), None);
+        assert_eq!(
+            FileResolver::new(Path::new("tests/test_no_exif.jpg")).get_utc_offset(),
+            get_utc_offset_from("tests/test_no_exif.jpg")
+        );
+        assert_eq!(get_utc_offset_from("not_there"), None);
+    }
+
+    #[test]
+    fn display_matrix_rotations() {
+        let display_matrix = |a: i32, b: i32, c: i32, d: i32| -> Vec<u8> {
+            [a, b, 0, c, d, 0, 0, 0, 1 << 30]
+                .iter()
+                .flat_map(|value| value.to_ne_bytes())
+                .collect()
+        };
+        let one = 1 << 16;
+        assert_eq!(
+            get_display_matrix_rotation(&display_matrix(one, 0, 0, one)),
+            Some(0)
+        );
+        // Portrait video of a phone
+        assert_eq!(
+            get_display_matrix_rotation(&display_matrix(0, one, -one, 0)),
+            Some(90)
+        );
+        assert_eq!(
+            get_display_matrix_rotation(&display_matrix(-one, 0, 0, -one)),
+            Some(180)
+        );
+        assert_eq!(
+            get_display_matrix_rotation(&display_matrix(0, -one, one, 0)),
+            Some(270)
+        );
+        // Slightly skewed matrices are rounded
+        assert_eq!(
+            get_display_matrix_rotation(&display_matrix(1000, one, -one, 1000)),
+            Some(90)
+        );
+        assert_eq!(get_display_matrix_rotation(&[0, 1, 2]), None);
+    }
+
+    #[test]
+    fn creation_times() {
+        let date_time = parse_creation_time("2021-12-29T15:08:17.000000Z").unwrap();
+        assert_eq!(date_time.timestamp(), 1640790497);
+        assert_eq!(date_time.offset().local_minus_utc(), 0);
+        let date_time = parse_creation_time("2021-12-29T16:08:17+0100").unwrap();
+        assert_eq!(date_time.timestamp(), 1640790497);
+        assert_eq!(date_time.naive_local().timestamp(), 1640794097);
+        assert_eq!(date_time.offset().local_minus_utc(), 3600);
+        assert!(parse_creation_time("1970-01-01T00:00:00.000000Z").is_none());
+        assert!(parse_creation_time("1904-01-01T00:00:00.000000Z").is_none());
+        assert!(parse_creation_time("2999-01-01T00:00:00.000000Z").is_none());
+        assert!(parse_creation_time("2021-12-29").is_none());
+    }
+
+    #[test]
+    fn exif_metadata() {
+        let metadata = get_exif_metadata(Path::new("tests/test.jpg"));
+        assert!(metadata
+            .iter()
+            .any(|(name, value)| name == "Date taken" && value == "2021-09-12 15:41:51"));
+
+        assert!(get_exif_metadata(Path::new("tests/test_no_exif.jpg")).is_empty());
+        assert!(get_exif_metadata(Path::new("tests/test.mp4")).is_empty());
+    }
 }
@@ -1,142 +1,663 @@
 use std::{
-    fs::{copy, create_dir_all, metadata, remove_file, rename, File},
-    io::{Error, ErrorKind, Read},
+    cell::Cell,
+    collections::HashSet,
+    fs::{self, create_dir_all, metadata, remove_file, rename, File},
+    hash::Hasher,
+    io::{self, Error, ErrorKind, Read, Write},
     path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use chrono::Datelike;
+use chrono::{DateTime, Datelike, Local, Timelike};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
 
-use super::{file_item, timestamp_to_string, DirectoryNames, Format, ItemList, SieveMethod};
+use super::{
+    exif_orientation, file_item, strip_metadata, timestamp_to_string, CollisionPolicy,
+    DirectoryNames, Format, ItemList, SieveMethod,
+};
 
 /// Trait to encapsulate sieve file IO operations
 pub trait SieveIO {
-    fn copy(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error>;
+    fn copy(&self, src: &Path, dest: &Path) -> Result<(), Error>;
     fn remove_file(&self, path: &Path) -> Result<(), Error>;
-    fn r#move(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error>;
+    fn r#move(&self, src: &Path, dest: &Path) -> Result<(), Error>;
     fn create_dir_all(&self, path: &Path) -> Result<(), Error>;
+    fn create_archive(&self, path: &Path) -> Result<Box<dyn SieveArchive>, Error>;
+    fn resolve_collision(&self, dest: &mut PathBuf) -> Option<CollisionPolicy>;
+    fn file_stamp(&self, path: &Path) -> Result<FileStamp, Error>;
+    fn write_orientation(&self, path: &Path, rotation: i32) -> Result<bool, Error>;
+    fn rotate_pixels(&self, path: &Path, rotation: i32) -> Result<bool, Error>;
+    fn strip_metadata(&self, path: &Path, rotation: i32) -> Result<bool, Error>;
+    fn save_manifest(&self, path: &Path, manifest: &SieveManifest) -> Result<(), Error>;
+    fn load_manifest(&self, path: &Path) -> Result<SieveManifest, Error>;
+    fn save_manifest_csv(&self, path: &Path, csv: &str) -> Result<bool, Error>;
+
+    /// Copies a file like copy, calling the progress with the copied and the total bytes while copying
+    fn copy_with_progress(
+        &self,
+        src: &Path,
+        dest: &Path,
+        _progress: &dyn Fn(u64, u64),
+    ) -> Result<(), Error> {
+        self.copy(src, dest)
+    }
+
+    /// Moves a file like move, calling the progress with the copied and the total bytes if the file is copied
+    fn move_with_progress(
+        &self,
+        src: &Path,
+        dest: &Path,
+        _progress: &dyn Fn(u64, u64),
+    ) -> Result<(), Error> {
+        self.r#move(src, dest)
+    }
 }
 
-/// Struct with implementation for std::fs implementation of SieveIO
-pub struct FileSieveIO;
+/// Name of the manifest file written to the target directory
+pub const MANIFEST_FILE: &str = "image_sieve_manifest.json";
 
-impl FileSieveIO {
-    fn different(&self, f1: &Path, f2: &Path) -> Result<bool, Error> {
-        if metadata(f1)?.len() == metadata(f2)?.len() {
-            let mut content1 = vec![];
-            let mut fh1 = File::open(f1)?;
-            fh1.read_to_end(&mut content1)?;
-            let mut content2 = vec![];
-            let mut fh2 = File::open(f2)?;
-            fh2.read_to_end(&mut content2)?;
-            Ok(content1 != content2)
+/// Name of the file describing the sieved files written to the target directory
+pub const MANIFEST_CSV_FILE: &str = "manifest.csv";
+
+/// Name of the directory in the target directory for items marked as unsure
+pub const REVIEW_DIRECTORY: &str = "Review";
+
+/// Start of the messages reporting the progress of a single large file, which replace each other
+pub const FILE_PROGRESS_PREFIX: &str = "⏳";
+
+/// Size of the chunks in which files are copied
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Files of at least this size report their progress while they are copied
+const LARGE_FILE_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Percentage after which the progress of a large file is reported again
+const FILE_PROGRESS_STEP: u64 = 5;
+
+/// Size and modification time of a file used to detect changes
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: i64,
+}
+
+/// Record of a single file operation of a sieve
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ManifestEntry {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub stamp: FileStamp,
+}
+
+/// Copy of a file item in a target directory together with the state of the copy right after sieving
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SievedCopy {
+    pub target: PathBuf,
+    pub stamp: FileStamp,
+}
+
+/// Record of all files copied or moved by a sieve, used to undo it
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SieveManifest {
+    pub method: SieveMethod,
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// Difference between the items kept now and the items copied or moved by the last sieve
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct SieveComparison {
+    /// Kept items which were not sieved by the last sieve
+    pub added: Vec<PathBuf>,
+    /// Items sieved by the last sieve which are not kept anymore
+    pub removed: Vec<PathBuf>,
+}
+
+/// Trait to encapsulate writing files to an archive
+pub trait SieveArchive {
+    /// Adds a file to the archive, images pass their rotation, which is needed to remove their metadata
+    fn add_file(
+        &mut self,
+        src: &Path,
+        name: &str,
+        image_rotation: Option<i32>,
+    ) -> Result<(), Error>;
+    fn finish(&mut self) -> Result<(), Error>;
+}
+
+/// Zip archive implementation of SieveArchive
+struct ZipSieveArchive {
+    writer: ZipWriter<File>,
+    preserve_timestamps: bool,
+    preserve_permissions: bool,
+    strip_metadata: bool,
+    jpeg_quality: u8,
+}
+
+impl SieveArchive for ZipSieveArchive {
+    fn add_file(
+        &mut self,
+        src: &Path,
+        name: &str,
+        image_rotation: Option<i32>,
+    ) -> Result<(), Error> {
+        let mut file = File::open(src)?;
+        let metadata = file.metadata()?;
+        let modified = if self.preserve_timestamps {
+            metadata.modified()?
         } else {
-            Ok(true)
+            SystemTime::now()
+        };
+        // Images and videos are already compressed, so they are only stored
+        let mut options = FileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .last_modified_time(to_zip_date_time(modified))
+            .large_file(metadata.len() >= u32::MAX as u64);
+        // Zip archives only store Unix permissions
+        #[cfg(unix)]
+        if self.preserve_permissions {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(metadata.permissions().mode());
+        }
+        self.writer.start_file(name, options)?;
+        if let (true, Some(rotation)) = (self.strip_metadata, image_rotation) {
+            let mut data = vec![];
+            file.read_to_end(&mut data)?;
+            let stripped = strip_image_metadata(&data, rotation, self.jpeg_quality)?;
+            self.writer
+                .write_all(stripped.as_deref().unwrap_or(&data))?;
+        } else {
+            io::copy(&mut file, &mut self.writer)?;
         }
+        Ok(())
     }
 
-    fn check_target(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error> {
-        if dest.exists() {
-            if self.different(src, dest)? {
-                let mut new_file_name = dest.file_stem().unwrap().to_os_string();
-                new_file_name.push("_.");
-                new_file_name.push(dest.extension().unwrap());
-                let parent = dest.parent().unwrap().to_path_buf();
-                dest.clear();
-                dest.push(parent);
-                dest.push(new_file_name);
+    fn finish(&mut self) -> Result<(), Error> {
+        self.writer.finish()?;
+        Ok(())
+    }
+}
 
-                self.check_target(src, dest)
-            } else {
-                let e = Error::new(
-                    ErrorKind::AlreadyExists,
-                    format!("Destination file already exists: {}", dest.display()),
-                );
-                Err(e)
-            }
-        } else {
-            Ok(())
+/// Removes the metadata from the data of a JPEG or PNG image, JPEG images are rotated upright as their orientation is removed as well.
+/// Returns None if the data is neither a JPEG nor a PNG image.
+fn strip_image_metadata(
+    data: &[u8],
+    rotation: i32,
+    jpeg_quality: u8,
+) -> Result<Option<Vec<u8>>, Error> {
+    // Without the EXIF orientation, the image is only displayed correctly if its pixels are upright
+    if rotation != 0 && data.starts_with(&[0xFF, 0xD8]) {
+        let upright = exif_orientation::rotate_jpeg_upright(data, rotation, jpeg_quality)?;
+        return Ok(strip_metadata::strip_metadata(&upright));
+    }
+    Ok(strip_metadata::strip_metadata(data))
+}
+
+/// Converts a system time to the local date and time stored in zip archives.
+/// Zip archives can only store dates from 1980 to 2107, other dates are replaced by the earliest possible date.
+fn to_zip_date_time(time: SystemTime) -> zip::DateTime {
+    let time: DateTime<Local> = time.into();
+    zip::DateTime::from_date_and_time(
+        time.year().clamp(0, u16::MAX as i32) as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second().min(59) as u8,
+    )
+    .unwrap_or_default()
+}
+
+/// Struct with implementation for std::fs implementation of SieveIO
+#[derive(Debug, Clone)]
+pub struct FileSieveIO {
+    /// Set the access and modification times of copied files to those of the source file
+    pub preserve_timestamps: bool,
+    /// Write the rotations made while sorting to the EXIF orientation of copied and moved JPEG images, which is lossless
+    pub correct_orientation: bool,
+    /// Rotate the pixels of copied JPEG images upright for programs that ignore the EXIF orientation, which encodes
+    /// them again and is lossy. Originals and moved files are never encoded again.
+    pub rotate_pixels: bool,
+    /// Quality from 1 to 100 used when a JPEG image has to be encoded again
+    pub jpeg_quality: u8,
+    /// Remove the metadata like the capture time and the GPS position from copied JPEG and PNG images
+    pub strip_metadata: bool,
+    /// What to do when a file or archive already exists in the target directory
+    pub collision_policy: CollisionPolicy,
+    /// Move files by copying them, verifying the copy and deleting the original afterwards instead of renaming them,
+    /// so that an interrupted move never loses a file
+    pub safe_move: bool,
+    /// Read back copied files and compare them with the source file to detect copies corrupted by the target drive
+    pub verify_copies: bool,
+    /// Write a description of the copied and moved files to the target directory
+    pub manifest_csv: bool,
+    /// Set the permissions of copied and archived files to those of the source file, which is the read-only attribute
+    /// on Windows
+    pub preserve_permissions: bool,
+}
+
+impl FileSieveIO {
+    /// Creates the file IO for operations in the source directory and on earlier sieves, like deleting tagged files,
+    /// undoing a sieve and comparing with it. The options only matter when files are copied, which keep their dates,
+    /// permissions and contents then.
+    pub fn for_source_operations() -> Self {
+        FileSieveIO {
+            preserve_timestamps: true,
+            correct_orientation: false,
+            rotate_pixels: false,
+            jpeg_quality: 95,
+            strip_metadata: false,
+            collision_policy: CollisionPolicy::Rename,
+            safe_move: true,
+            verify_copies: false,
+            manifest_csv: false,
+            preserve_permissions: true,
+        }
+    }
+
+    /// Copies a file and optionally sets the access and modification times and the permissions of the copy to those
+    /// of the source file. An incomplete copy, e.g. because a network share became unreachable, is removed.
+    fn copy_file(
+        &self,
+        src: &Path,
+        dest: &Path,
+        preserve_timestamps: bool,
+        preserve_permissions: bool,
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<(), Error> {
+        if let Err(e) = copy_buffered(src, dest, progress) {
+            let _ = remove_file(dest);
+            return Err(e);
         }
+        let metadata = metadata(src)?;
+        if preserve_timestamps {
+            filetime::set_file_times(
+                dest,
+                FileTime::from_last_access_time(&metadata),
+                FileTime::from_last_modification_time(&metadata),
+            )?;
+        }
+        if preserve_permissions {
+            fs::set_permissions(dest, metadata.permissions())?;
+        }
+        Ok(())
     }
 }
 
 impl SieveIO for FileSieveIO {
-    fn copy(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error> {
-        self.check_target(src, dest)?;
-        copy(src, dest)?;
-        Ok(())
+    fn copy(&self, src: &Path, dest: &Path) -> Result<(), Error> {
+        self.copy_with_progress(src, dest, &|_, _| ())
     }
 
     fn remove_file(&self, path: &Path) -> Result<(), Error> {
         remove_file(path)
     }
 
-    fn r#move(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error> {
-        self.check_target(src, dest)?;
-        match rename(src, dest.clone()) {
-            Ok(_) => Ok(()),
-            Err(_) => {
-                self.copy(src, dest)?;
-                self.remove_file(src)
+    fn r#move(&self, src: &Path, dest: &Path) -> Result<(), Error> {
+        self.move_with_progress(src, dest, &|_, _| ())
+    }
+
+    fn copy_with_progress(
+        &self,
+        src: &Path,
+        dest: &Path,
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<(), Error> {
+        self.copy_file(
+            src,
+            dest,
+            self.preserve_timestamps,
+            self.preserve_permissions,
+            progress,
+        )?;
+        if self.verify_copies {
+            if let Err(e) = verify_copy(src, dest) {
+                // A corrupted copy must not be mistaken for a sieved file later
+                let _ = self.remove_file(dest);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn move_with_progress(
+        &self,
+        src: &Path,
+        dest: &Path,
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<(), Error> {
+        if !self.safe_move && rename(src, dest).is_ok() {
+            return Ok(());
+        }
+        // Renaming keeps the timestamps and permissions, so a moved file keeps them as well if it has to be copied
+        self.copy_file(src, dest, true, true, progress)?;
+        if self.safe_move || self.verify_copies {
+            if let Err(e) = verify_copy(src, dest) {
+                // The original is kept, so the copy that cannot be trusted is removed
+                let _ = self.remove_file(dest);
+                return Err(e);
             }
         }
+        self.remove_file(src)
     }
 
     fn create_dir_all(&self, path: &Path) -> Result<(), Error> {
         create_dir_all(path)
     }
+
+    fn create_archive(&self, path: &Path) -> Result<Box<dyn SieveArchive>, Error> {
+        let writer = ZipWriter::new(File::create(path)?);
+        Ok(Box::new(ZipSieveArchive {
+            writer,
+            preserve_timestamps: self.preserve_timestamps,
+            preserve_permissions: self.preserve_permissions,
+            strip_metadata: self.strip_metadata,
+            jpeg_quality: self.jpeg_quality,
+        }))
+    }
+
+    fn resolve_collision(&self, dest: &mut PathBuf) -> Option<CollisionPolicy> {
+        resolve_collision(&self.collision_policy, dest, |path| path.exists())
+    }
+
+    fn file_stamp(&self, path: &Path) -> Result<FileStamp, Error> {
+        let metadata = metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as i64);
+        Ok(FileStamp {
+            size: metadata.len(),
+            modified,
+        })
+    }
+
+    fn write_orientation(&self, path: &Path, rotation: i32) -> Result<bool, Error> {
+        if !self.correct_orientation {
+            return Ok(false);
+        }
+        let jpeg = fs::read(path)?;
+        if !jpeg.starts_with(&[0xFF, 0xD8]) {
+            return Ok(false);
+        }
+        let orientation = exif_orientation::orientation_from_rotation(rotation);
+        if exif_orientation::get_jpeg_orientation(&jpeg).unwrap_or(1) == orientation {
+            return Ok(false);
+        }
+        let oriented =
+            exif_orientation::set_jpeg_orientation(&jpeg, orientation).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    "The EXIF data contains no orientation",
+                )
+            })?;
+        let metadata = metadata(path)?;
+        write_keeping_permissions(path, &oriented)?;
+        if self.preserve_timestamps {
+            filetime::set_file_times(
+                path,
+                FileTime::from_last_access_time(&metadata),
+                FileTime::from_last_modification_time(&metadata),
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn rotate_pixels(&self, path: &Path, rotation: i32) -> Result<bool, Error> {
+        if !self.rotate_pixels {
+            return Ok(false);
+        }
+        let jpeg = fs::read(path)?;
+        if !jpeg.starts_with(&[0xFF, 0xD8]) {
+            return Ok(false);
+        }
+        let metadata = metadata(path)?;
+        write_keeping_permissions(
+            path,
+            &exif_orientation::rotate_jpeg_upright(&jpeg, rotation, self.jpeg_quality)?,
+        )?;
+        if self.preserve_timestamps {
+            filetime::set_file_times(
+                path,
+                FileTime::from_last_access_time(&metadata),
+                FileTime::from_last_modification_time(&metadata),
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn strip_metadata(&self, path: &Path, rotation: i32) -> Result<bool, Error> {
+        if !self.strip_metadata {
+            return Ok(false);
+        }
+        let data = fs::read(path)?;
+        let stripped = match strip_image_metadata(&data, rotation, self.jpeg_quality)? {
+            Some(stripped) => stripped,
+            None => return Ok(false),
+        };
+        let metadata = metadata(path)?;
+        write_keeping_permissions(path, &stripped)?;
+        if self.preserve_timestamps {
+            filetime::set_file_times(
+                path,
+                FileTime::from_last_access_time(&metadata),
+                FileTime::from_last_modification_time(&metadata),
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn save_manifest(&self, path: &Path, manifest: &SieveManifest) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(manifest)?)
+    }
+
+    fn load_manifest(&self, path: &Path) -> Result<SieveManifest, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save_manifest_csv(&self, path: &Path, csv: &str) -> Result<bool, Error> {
+        if !self.manifest_csv {
+            return Ok(false);
+        }
+        fs::write(path, csv)?;
+        Ok(true)
+    }
 }
 
 /// Sieves an item list taking the take_over flag into account to a new directory.
+/// Items of an event with its own target directory are copied or moved there instead.
+/// Items marked as unsure are put into a review directory to decide about them later.
+/// When copying, items whose copy from an earlier sieve is still unchanged in the directory are skipped unless forced.
+/// Items with the name of an existing file are renamed, overwrite it or are skipped depending on the collision policy.
 /// The progress is reported by calling a callback function with the file that is currently processed.
+/// Returns the files that were copied.
 pub fn sieve<T>(
     item_list: &ItemList,
     path: &Path,
     sieve_method: SieveMethod,
     sieve_directory_names: DirectoryNames,
     sieve_io: &T,
+    force: bool,
     progress_callback: impl Fn(String),
-) where
+) -> Vec<ManifestEntry>
+where
     T: SieveIO,
 {
-    if sieve_method != SieveMethod::Delete {
-        prepare_path(path, sieve_io);
-
+    let mut copies = vec![];
+    if sieve_method != SieveMethod::Delete && !prepare_path(path, sieve_io, &progress_callback) {
+        progress_callback(String::from("Done"));
+        return copies;
+    }
+    if sieve_method == SieveMethod::Zip {
+        sieve_to_archive(
+            item_list,
+            path,
+            &sieve_directory_names,
+            sieve_io,
+            &progress_callback,
+        );
+    } else if sieve_method != SieveMethod::Delete {
+        let mut manifest = SieveManifest {
+            method: sieve_method.clone(),
+            entries: vec![],
+        };
+        let mut sieved_files = vec![];
+        let mut skipped = 0;
+        let mut progress = SieveProgress::new(
+            item_list
+                .items
+                .iter()
+                .filter(|item| item.get_take_over() || sieve_method == SieveMethod::MoveAndDelete)
+                .count(),
+        );
         for item in &item_list.items {
-            if item.get_take_over() {
-                let sub_path: PathBuf = get_sub_path(item_list, item, &sieve_directory_names)
-                    .iter()
-                    .collect();
-                let full_path = path.join(sub_path);
-                prepare_path(&full_path, sieve_io);
+            let sub_path: PathBuf = get_sub_path(item_list, item, &sieve_directory_names)
+                .iter()
+                .collect();
+            let full_path =
+                get_target_path(item_list, item, path, &sieve_directory_names).join(sub_path);
+            if item.get_take_over()
+                && sieve_method == SieveMethod::Copy
+                && !force
+                && is_sieved(item, &full_path, sieve_io)
+            {
+                skipped += 1;
+                progress.skip();
+            } else if item.get_take_over() {
+                prepare_path(&full_path, sieve_io, &progress_callback);
                 let source = &item.path;
                 let mut target = full_path.join(source.file_name().unwrap());
+                if !resolve_and_report_collision(&mut target, sieve_io, &progress_callback) {
+                    progress.skip();
+                    continue;
+                }
+                // The size is taken before, as the source does not exist anymore after moving
+                let size = item.get_size();
 
-                if sieve_method == SieveMethod::Copy {
-                    match sieve_io.copy(source, &mut target) {
-                        Ok(_) => (),
-                        Err(e) => progress_callback(format!("Error copying {}: {}", item, e)),
+                let file_progress = FileProgress::new(source);
+                let report_file_progress = |copied: u64, total: u64| {
+                    if let Some(message) = file_progress.update(copied, total) {
+                        progress_callback(message);
                     }
+                };
+                let result = if sieve_method == SieveMethod::Copy {
+                    sieve_io
+                        .copy_with_progress(source, &target, &report_file_progress)
+                        .map_err(|e| {
+                            progress_callback(format!(
+                                "Error copying {}: {}",
+                                item,
+                                describe_error(&e)
+                            ));
+                        })
                 } else {
-                    match sieve_io.r#move(source, &mut target) {
-                        Ok(_) => (),
-                        Err(e) => progress_callback(format!("Error moving {}: {}", item, e)),
-                    }
+                    sieve_io
+                        .move_with_progress(source, &target, &report_file_progress)
+                        .map_err(|e| {
+                            progress_callback(format!(
+                                "Error moving {}: {}",
+                                item,
+                                describe_error(&e)
+                            ));
+                        })
                 };
-                progress_callback(format!("{:?} -> {:?}", source, target));
+                if result.is_ok() {
+                    let mut rotation = item.get_rotation();
+                    // Only copies are encoded again, moved files are the originals
+                    let mut rotated = false;
+                    if item.is_image() && rotation != 0 && sieve_method == SieveMethod::Copy {
+                        match sieve_io.rotate_pixels(&target, rotation) {
+                            Ok(true) => {
+                                rotation = 0;
+                                rotated = true;
+                            }
+                            Ok(false) => (),
+                            Err(e) => progress_callback(format!("Error rotating {}: {}", item, e)),
+                        }
+                    }
+                    // The orientation in the file only differs from the displayed one if the user changed it
+                    if item.is_image()
+                        && !rotated
+                        && (item.get_user_rotation() != 0 || item.is_orientation_ignored())
+                    {
+                        if let Err(e) = sieve_io.write_orientation(&target, rotation) {
+                            progress_callback(format!("Error rotating {}: {}", item, e));
+                        }
+                    }
+                    // The metadata is only removed from copies, moved files are the originals
+                    if item.is_image() && sieve_method == SieveMethod::Copy {
+                        if let Err(e) = sieve_io.strip_metadata(&target, rotation) {
+                            progress_callback(format!(
+                                "Error removing metadata of {}: {}",
+                                item, e
+                            ));
+                        }
+                    }
+                    if let Ok(stamp) = sieve_io.file_stamp(&target) {
+                        manifest.entries.push(ManifestEntry {
+                            source: source.clone(),
+                            target: target.clone(),
+                            stamp,
+                        });
+                    }
+                    sieved_files.push((item, target.clone()));
+                }
+                progress_callback(format!(
+                    "{:?} -> {:?} {}",
+                    source,
+                    target,
+                    progress.step(size)
+                ));
             } else if sieve_method == SieveMethod::MoveAndDelete {
                 let source = &item.path;
-                progress_callback(format!("Delete {:?}", source));
+                progress_callback(format!("Delete {:?} {}", source, progress.step(0)));
                 match sieve_io.remove_file(source) {
                     Ok(_) => (),
                     Err(e) => progress_callback(format!("Error deleting {}: {}", item, e)),
                 }
             }
         }
+
+        if skipped > 0 {
+            progress_callback(format!(
+                "Skipped {} files already sieved to {}",
+                skipped,
+                path.display()
+            ));
+        }
+        if !manifest.entries.is_empty() {
+            let manifest_path = path.join(MANIFEST_FILE);
+            if let Err(e) = sieve_io.save_manifest(&manifest_path, &manifest) {
+                progress_callback(format!(
+                    "Error writing manifest {}: {}",
+                    manifest_path.display(),
+                    e
+                ));
+            }
+        }
+        if !sieved_files.is_empty() {
+            let csv_path = path.join(MANIFEST_CSV_FILE);
+            let csv = item_list.get_sieved_csv(path, &sieved_files);
+            if let Err(e) = sieve_io.save_manifest_csv(&csv_path, &csv) {
+                progress_callback(format!("Error writing {}: {}", csv_path.display(), e));
+            }
+        }
+        if sieve_method == SieveMethod::Copy {
+            copies = manifest.entries;
+        }
     } else {
+        let mut progress = SieveProgress::new(
+            item_list
+                .items
+                .iter()
+                .filter(|item| !item.get_take_over())
+                .count(),
+        );
         for item in &item_list.items {
             if !item.get_take_over() {
                 let source = &item.path;
-                progress_callback(format!("Delete {:?}", source));
+                progress_callback(format!("Delete {:?} {}", source, progress.step(0)));
                 match sieve_io.remove_file(source) {
                     Ok(_) => (),
                     Err(e) => progress_callback(format!("Error deleting {:?}: {}", item, e)),
@@ -146,361 +667,2061 @@ pub fn sieve<T>(
     }
 
     progress_callback(String::from("Done"));
+    copies
 }
 
-/// Gets the sub path of a file item taking the file item's timestamp and possible events into account.
-/// If a fileitem is part of an event, its sub path is the event's span and name.
-/// If it is not part of an event, its sub path is the file item's timestamp in the given format.
-fn get_sub_path(
-    item_list: &ItemList,
-    item: &file_item::FileItem,
-    directory_names: &DirectoryNames,
-) -> Vec<String> {
-    // TODO: This is a bit ugly.
-
-    let mut directories = Vec::<String>::new();
-    let event = item_list.get_event(item);
-    if let Some(event) = event {
-        if *directory_names == DirectoryNames::YearAndMonthInSubdirectory {
-            directories.push(event.start_date.format("%Y").to_string());
-            if event.start_date != event.end_date {
-                directories.push(format!(
-                    "{} - {} {}",
-                    event.start_date.format("%m-%d"),
-                    event
-                        .end_date
-                        .format(if event.start_date.year() != event.end_date.year() {
-                            "%Y-%m-%d"
-                        } else {
-                            "%m-%d"
-                        }),
-                    event.name
-                ));
-            } else {
-                directories.push(format!(
-                    "{} {}",
-                    event.start_date.format("%m-%d"),
-                    event.name
-                ));
-            }
-        } else if event.start_date != event.end_date {
-            directories.push(format!(
-                "{} - {} {}",
-                event.start_date.format("%Y-%m-%d"),
-                event.end_date.format("%Y-%m-%d"),
-                event.name
+/// Resolves a collision of a target path with an existing file using the collision policy of the sieve IO and
+/// reports which policy was applied. Returns false if the file shall not be sieved.
+fn resolve_and_report_collision<T>(
+    target: &mut PathBuf,
+    sieve_io: &T,
+    progress_callback: &impl Fn(String),
+) -> bool
+where
+    T: SieveIO,
+{
+    let existing = target.clone();
+    match sieve_io.resolve_collision(target) {
+        Some(CollisionPolicy::Rename) => {
+            progress_callback(format!(
+                "{} already exists, renamed to {}",
+                existing.display(),
+                target.display()
             ));
-        } else {
-            directories.push(format!(
-                "{} {}",
-                event.start_date.format("%Y-%m-%d"),
-                event.name
+            true
+        }
+        Some(CollisionPolicy::Overwrite) => {
+            progress_callback(format!(
+                "{} already exists, overwritten",
+                existing.display()
             ));
+            true
         }
-    } else {
-        let format = match directory_names {
-            DirectoryNames::YearAndMonth => Format::YearAndMonth,
-            DirectoryNames::Year => Format::Year,
-            DirectoryNames::YearMonthAndDay => Format::Date,
-            DirectoryNames::YearAndQuarter => Format::YearAndQuarter,
-            DirectoryNames::YearAndMonthInSubdirectory => Format::Year,
-        };
-        directories.push(timestamp_to_string(item.get_timestamp(), format));
-        if *directory_names == DirectoryNames::YearAndMonthInSubdirectory {
-            directories.push(timestamp_to_string(item.get_timestamp(), Format::Month))
+        Some(CollisionPolicy::Skip) => {
+            progress_callback(format!("{} already exists, skipped", existing.display()));
+            false
         }
+        None => true,
     }
+}
 
-    directories
+/// Applies a collision policy if the destination exists. A renamed destination is changed to a path that does not exist.
+/// Returns the applied policy or None if there is no collision.
+fn resolve_collision(
+    collision_policy: &CollisionPolicy,
+    dest: &mut PathBuf,
+    exists: impl Fn(&Path) -> bool,
+) -> Option<CollisionPolicy> {
+    if !exists(dest) {
+        return None;
+    }
+    if *collision_policy == CollisionPolicy::Rename {
+        *dest = get_free_path(dest, exists);
+    }
+    Some(collision_policy.clone())
 }
 
-/// Prepares the path by creating it if it does not exist
-fn prepare_path<T>(path: &Path, sieve_io: &T)
-where
-    T: SieveIO,
-{
-    if !path.exists() {
-        match sieve_io.create_dir_all(path) {
-            Ok(_) => (),
-            Err(e) => println!("Error creating path {}: {}", e, path.display()),
+/// Copies a file in chunks, calling the progress with the copied and the total bytes after each chunk. The copy is
+/// synchronized to the disk, so that write errors of network shares are reported instead of being lost when closing it.
+fn copy_buffered(src: &Path, dest: &Path, progress: &dyn Fn(u64, u64)) -> Result<(), Error> {
+    let mut reader = File::open(src)?;
+    let source_metadata = reader.metadata()?;
+    let total = source_metadata.len();
+    let mut writer = File::create(dest)?;
+    let mut buffer = vec![0; COPY_CHUNK_SIZE];
+    let mut copied = 0;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
         }
+        writer.write_all(&buffer[..read])?;
+        copied += read as u64;
+        progress(copied, total);
     }
+    writer.sync_all()
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::item_sort_list::sieve::SieveIO;
-    use crate::item_sort_list::{sieve::get_sub_path, Event, FileItem, ItemList};
-    use num_traits::FromPrimitive;
-    use std::cell::RefCell;
-    use std::path::PathBuf;
+/// Writes the contents of a file, which may be a read-only copy of a read-only source file. The permissions of the
+/// file are kept.
+fn write_keeping_permissions(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let permissions = metadata(path)?.permissions();
+    if permissions.readonly() {
+        let mut writable = permissions.clone();
+        #[allow(clippy::permissions_set_readonly_false)]
+        writable.set_readonly(false);
+        fs::set_permissions(path, writable)?;
+        let result = fs::write(path, contents);
+        fs::set_permissions(path, permissions)?;
+        result
+    } else {
+        fs::write(path, contents)
+    }
+}
 
-    struct TestSieveIO {
-        pub copies: RefCell<Vec<(PathBuf, PathBuf)>>,
-        pub renames: RefCell<Vec<(PathBuf, PathBuf)>>,
-        pub removes: RefCell<Vec<PathBuf>>,
-        pub creates: RefCell<Vec<PathBuf>>,
+/// Describes an IO error with a hint if it is typical for a network share that is slow or not reachable anymore
+fn describe_error(e: &Error) -> String {
+    match e.kind() {
+        ErrorKind::TimedOut
+        | ErrorKind::NotConnected
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::ConnectionReset
+        | ErrorKind::BrokenPipe => format!(
+            "{} (the target directory may be on a network share that is not reachable)",
+            e
+        ),
+        _ => e.to_string(),
     }
+}
 
-    impl TestSieveIO {
-        pub fn new() -> Self {
-            TestSieveIO {
-                copies: RefCell::new(vec![]),
-                renames: RefCell::new(vec![]),
-                removes: RefCell::new(vec![]),
-                creates: RefCell::new(vec![]),
-            }
-        }
+/// Progress of copying or moving a single file, which is reported in steps for large files, so that the sieve does not
+/// appear to hang while a large video is copied to a slow target like a network share
+struct FileProgress<'a> {
+    source: &'a Path,
+    reported_percent: Cell<u64>,
+}
 
-        pub fn reset(&mut self) {
-            self.copies.get_mut().clear();
-            self.renames.get_mut().clear();
-            self.removes.get_mut().clear();
-            self.creates.get_mut().clear();
+impl<'a> FileProgress<'a> {
+    fn new(source: &'a Path) -> Self {
+        Self {
+            source,
+            reported_percent: Cell::new(0),
         }
     }
 
-    impl SieveIO for TestSieveIO {
-        fn copy(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error> {
-            self.copies
-                .borrow_mut()
-                .push((src.to_path_buf(), dest.to_path_buf()));
-            Ok(())
+    /// Returns the progress as text if the file is large and the next step of the progress was reached
+    fn update(&self, copied: u64, total: u64) -> Option<String> {
+        if total < LARGE_FILE_SIZE || copied >= total {
+            return None;
         }
+        let percent = copied * 100 / total;
+        if percent < self.reported_percent.get() + FILE_PROGRESS_STEP {
+            return None;
+        }
+        self.reported_percent.set(percent);
+        Some(format!(
+            "{} {:?} {}% ({:.1} of {:.1} MB)",
+            FILE_PROGRESS_PREFIX,
+            self.source,
+            percent,
+            copied as f64 / 1_000_000.0,
+            total as f64 / 1_000_000.0
+        ))
+    }
+}
 
-        fn remove_file(&self, path: &Path) -> Result<(), Error> {
-            self.removes.borrow_mut().push(path.to_path_buf());
-            Ok(())
+/// Checks that a copy has the same size and content as the original file
+fn verify_copy(src: &Path, dest: &Path) -> Result<(), Error> {
+    if metadata(src)?.len() != metadata(dest)?.len() || content_hash(src)? != content_hash(dest)? {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "verification of the copy {} failed, the original is kept",
+                dest.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Calculates a hash of the content of a file, reading it in chunks
+fn content_hash(path: &Path) -> Result<u64, Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finish());
         }
+        hasher.write(&buffer[..read]);
+    }
+}
 
-        fn r#move(&self, src: &Path, dest: &mut PathBuf) -> Result<(), Error> {
-            self.renames
-                .borrow_mut()
-                .push((src.to_path_buf(), dest.to_path_buf()));
-            Ok(())
+/// Gets a path that does not exist by appending a number to the file stem, like "name (1).jpg"
+fn get_free_path(path: &Path, exists: impl Fn(&Path) -> bool) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map_or(String::new(), |extension| {
+        format!(".{}", extension.to_string_lossy())
+    });
+    let mut number = 1;
+    loop {
+        let free_path = path.with_file_name(format!("{} ({}){}", stem, number, extension));
+        if !exists(&free_path) {
+            return free_path;
         }
+        number += 1;
+    }
+}
 
-        fn create_dir_all(&self, path: &Path) -> Result<(), Error> {
-            self.creates.borrow_mut().push(path.to_path_buf());
-            Ok(())
+/// Progress of a sieve counting the processed files and the transferred bytes
+struct SieveProgress {
+    total: usize,
+    done: usize,
+    bytes: u64,
+    start: Instant,
+}
+
+impl SieveProgress {
+    fn new(total: usize) -> Self {
+        Self {
+            total,
+            done: 0,
+            bytes: 0,
+            start: Instant::now(),
         }
     }
 
-    #[test]
-    fn test_get_sub_path() {
-        use chrono::NaiveDate;
-        use chrono::NaiveDateTime;
+    /// Counts a processed file with the number of transferred bytes and returns the progress as text
+    fn step(&mut self, bytes: u64) -> String {
+        self.done += 1;
+        self.bytes += bytes;
+        format_progress(self.done, self.total, self.bytes, self.start.elapsed())
+    }
 
-        let item_list = ItemList {
-            items: vec![],
-            events: vec![
-                Event {
+    /// Counts a file that did not need to be processed
+    fn skip(&mut self) {
+        self.done += 1;
+    }
+}
+
+/// Formats the number of processed files and the transfer rate in MB/s, which is left out until bytes were transferred
+fn format_progress(done: usize, total: usize, bytes: u64, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64();
+    if bytes > 0 && seconds > 0.0 {
+        format!(
+            "({}/{}, {:.1} MB/s)",
+            done,
+            total,
+            bytes as f64 / 1_000_000.0 / seconds
+        )
+    } else {
+        format!("({}/{})", done, total)
+    }
+}
+
+/// Gets the directory to sieve an item to, which is the target directory of its event if set or the given path.
+/// Events are not used if the subdirectories of the source directory are mirrored.
+fn get_target_path(
+    item_list: &ItemList,
+    item: &file_item::FileItem,
+    path: &Path,
+    directory_names: &DirectoryNames,
+) -> PathBuf {
+    match item_list.get_event(item) {
+        Some(event)
+            if !event.target_directory.is_empty()
+                && *directory_names != DirectoryNames::SourceSubdirectories =>
+        {
+            PathBuf::from(&event.target_directory)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Checks if an item was copied to a directory by an earlier sieve and the copy is still unchanged
+fn is_sieved<T>(item: &file_item::FileItem, path: &Path, sieve_io: &T) -> bool
+where
+    T: SieveIO,
+{
+    match item.get_sieved_copy() {
+        Some(sieved_copy) => {
+            sieved_copy.target.parent() == Some(path)
+                && sieve_io
+                    .file_stamp(&sieved_copy.target)
+                    .is_ok_and(|stamp| stamp == sieved_copy.stamp)
+        }
+        None => false,
+    }
+}
+
+/// Reverts the last copy or move sieve to the given directory using the manifest written by the sieve.
+/// Copies are deleted and moved files are moved back. Nothing is changed if any target file was modified after sieving.
+pub fn undo_sieve<T>(path: &Path, sieve_io: &T, progress_callback: impl Fn(String))
+where
+    T: SieveIO,
+{
+    undo_manifest(path, sieve_io, &progress_callback);
+    progress_callback(String::from("Done"));
+}
+
+/// Compares the items kept now with the items recorded in the manifest of the last sieve to the given directory
+pub fn compare_with_last_sieve<T>(
+    item_list: &ItemList,
+    path: &Path,
+    sieve_io: &T,
+) -> Result<SieveComparison, Error>
+where
+    T: SieveIO,
+{
+    let manifest = sieve_io.load_manifest(&path.join(MANIFEST_FILE))?;
+    let sieved: HashSet<&PathBuf> = manifest.entries.iter().map(|entry| &entry.source).collect();
+    let kept: HashSet<&PathBuf> = item_list
+        .items
+        .iter()
+        .filter(|item| item.get_take_over())
+        .map(|item| &item.path)
+        .collect();
+    Ok(SieveComparison {
+        added: item_list
+            .items
+            .iter()
+            .filter(|item| item.get_take_over() && !sieved.contains(&item.path))
+            .map(|item| item.path.clone())
+            .collect(),
+        removed: manifest
+            .entries
+            .iter()
+            .filter(|entry| !kept.contains(&entry.source))
+            .map(|entry| entry.source.clone())
+            .collect(),
+    })
+}
+
+/// Reverts the operations recorded in the manifest in the given directory
+fn undo_manifest<T>(path: &Path, sieve_io: &T, progress_callback: &impl Fn(String))
+where
+    T: SieveIO,
+{
+    let manifest_path = path.join(MANIFEST_FILE);
+    let manifest = match sieve_io.load_manifest(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            progress_callback(format!(
+                "Error reading manifest {}: {}",
+                manifest_path.display(),
+                e
+            ));
+            return;
+        }
+    };
+
+    for entry in &manifest.entries {
+        if sieve_io.file_stamp(&entry.target).ok().as_ref() != Some(&entry.stamp) {
+            progress_callback(format!(
+                "Error: {} was modified or removed after sieving, nothing was undone",
+                entry.target.display()
+            ));
+            return;
+        }
+    }
+
+    for entry in &manifest.entries {
+        if manifest.method == SieveMethod::Copy {
+            progress_callback(format!("Delete {:?}", entry.target));
+            if let Err(e) = sieve_io.remove_file(&entry.target) {
+                progress_callback(format!("Error deleting {}: {}", entry.target.display(), e));
+            }
+        } else {
+            let mut source = entry.source.clone();
+            if let Some(parent) = source.parent() {
+                prepare_path(parent, sieve_io, progress_callback);
+            }
+            if !resolve_and_report_collision(&mut source, sieve_io, progress_callback) {
+                continue;
+            }
+            match sieve_io.r#move(&entry.target, &source) {
+                Ok(_) => progress_callback(format!("{:?} -> {:?}", entry.target, source)),
+                Err(e) => {
+                    progress_callback(format!("Error moving {}: {}", entry.target.display(), e))
+                }
+            }
+        }
+    }
+
+    if let Err(e) = sieve_io.remove_file(&manifest_path) {
+        progress_callback(format!(
+            "Error deleting manifest {}: {}",
+            manifest_path.display(),
+            e
+        ));
+    }
+}
+
+/// Writes all items to be taken over to a zip archive in the given directory.
+/// The archive is named after the source directory and the entries are organized like the directories of the other sieve methods.
+fn sieve_to_archive<T>(
+    item_list: &ItemList,
+    path: &Path,
+    directory_names: &DirectoryNames,
+    sieve_io: &T,
+    progress_callback: &impl Fn(String),
+) where
+    T: SieveIO,
+{
+    let mut archive_path = path.join(get_archive_file_name(item_list));
+    if !resolve_and_report_collision(&mut archive_path, sieve_io, progress_callback) {
+        return;
+    }
+    let mut archive = match sieve_io.create_archive(&archive_path) {
+        Ok(archive) => archive,
+        Err(e) => {
+            progress_callback(format!(
+                "Error creating archive {}: {}",
+                archive_path.display(),
+                e
+            ));
+            return;
+        }
+    };
+
+    let mut names = HashSet::new();
+    let mut progress = SieveProgress::new(
+        item_list
+            .items
+            .iter()
+            .filter(|item| item.get_take_over())
+            .count(),
+    );
+    for item in &item_list.items {
+        if item.get_take_over() {
+            let mut sub_path = get_sub_path(item_list, item, directory_names);
+            sub_path.push(item.path.file_name().unwrap().to_string_lossy().to_string());
+            let name = get_unique_archive_name(&sub_path.join("/"), &names);
+            let image_rotation = item.is_image().then(|| item.get_rotation());
+            match archive.add_file(&item.path, &name, image_rotation) {
+                Ok(_) => (),
+                Err(e) => progress_callback(format!("Error archiving {}: {}", item, e)),
+            }
+            progress_callback(format!(
+                "{:?} -> {:?} {}",
+                item.path,
+                archive_path.join(&name),
+                progress.step(item.get_size())
+            ));
+            names.insert(name);
+        }
+    }
+
+    if let Err(e) = archive.finish() {
+        progress_callback(format!(
+            "Error finishing archive {}: {}",
+            archive_path.display(),
+            e
+        ));
+    }
+}
+
+/// Gets the file name of the archive from the name of the source directory
+fn get_archive_file_name(item_list: &ItemList) -> String {
+    match item_list.path.file_name() {
+        Some(name) => format!("{}.zip", name.to_string_lossy()),
+        None => String::from("ImageSieve.zip"),
+    }
+}
+
+/// Gets a name for an archive entry that is not yet used by appending underscores to the file stem
+fn get_unique_archive_name(name: &str, names: &HashSet<String>) -> String {
+    let mut name = name.to_string();
+    while names.contains(&name) {
+        name = match name.rfind('.') {
+            Some(index) if index > name.rfind('/').map_or(0, |slash| slash + 1) => {
+                format!("{}_{}", &name[..index], &name[index..])
+            }
+            _ => name + "_",
+        };
+    }
+    name
+}
+
+/// Gets the sub path of a file item taking the file item's timestamp and possible events into account.
+/// If a fileitem is part of an event, its sub path is the event's span and name.
+/// If it is not part of an event, its sub path is the file item's timestamp in the given format. This is the capture
+/// time from the metadata, the file date is only used for files without one, as it is reset by copying.
+/// When mirroring the source directory, the sub path is the item's directory relative to the source directory.
+/// Unsure items are put below the review directory.
+fn get_sub_path(
+    item_list: &ItemList,
+    item: &file_item::FileItem,
+    directory_names: &DirectoryNames,
+) -> Vec<String> {
+    // TODO: This is a bit ugly.
+
+    let mut directories = Vec::<String>::new();
+    if item.is_unsure() {
+        directories.push(String::from(REVIEW_DIRECTORY));
+    }
+    if *directory_names == DirectoryNames::SourceSubdirectories {
+        if let Some(relative_path) = item
+            .path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(&item_list.path).ok())
+        {
+            directories.extend(
+                relative_path
+                    .iter()
+                    .map(|directory| directory.to_string_lossy().to_string()),
+            );
+        }
+        return directories;
+    }
+    let event = item_list.get_event(item);
+    if let Some(event) = event {
+        if *directory_names == DirectoryNames::YearAndMonthInSubdirectory {
+            directories.push(event.start_date.format("%Y").to_string());
+            if event.start_date != event.end_date {
+                directories.push(format!(
+                    "{} - {} {}",
+                    event.start_date.format("%m-%d"),
+                    event
+                        .end_date
+                        .format(if event.start_date.year() != event.end_date.year() {
+                            "%Y-%m-%d"
+                        } else {
+                            "%m-%d"
+                        }),
+                    event.name
+                ));
+            } else {
+                directories.push(format!(
+                    "{} {}",
+                    event.start_date.format("%m-%d"),
+                    event.name
+                ));
+            }
+        } else if event.start_date != event.end_date {
+            directories.push(format!(
+                "{} - {} {}",
+                event.start_date.format("%Y-%m-%d"),
+                event.end_date.format("%Y-%m-%d"),
+                event.name
+            ));
+        } else {
+            directories.push(format!(
+                "{} {}",
+                event.start_date.format("%Y-%m-%d"),
+                event.name
+            ));
+        }
+    } else {
+        let format = match directory_names {
+            DirectoryNames::YearAndMonth => Format::YearAndMonth,
+            DirectoryNames::Year => Format::Year,
+            DirectoryNames::YearMonthAndDay => Format::Date,
+            DirectoryNames::YearAndQuarter => Format::YearAndQuarter,
+            DirectoryNames::YearAndMonthInSubdirectory => Format::Year,
+            DirectoryNames::SourceSubdirectories => unreachable!(),
+        };
+        let timestamp = item_list.get_event_timestamp(item);
+        directories.push(timestamp_to_string(timestamp, format));
+        if *directory_names == DirectoryNames::YearAndMonthInSubdirectory {
+            directories.push(timestamp_to_string(timestamp, Format::Month))
+        }
+    }
+
+    directories
+}
+
+/// Prepares the path by creating it and its parents if it does not exist. Returns false if it could not be created.
+fn prepare_path<T>(path: &Path, sieve_io: &T, progress_callback: &impl Fn(String)) -> bool
+where
+    T: SieveIO,
+{
+    if !path.exists() {
+        if let Err(e) = sieve_io.create_dir_all(path) {
+            progress_callback(format!(
+                "Error creating directory {}: {}",
+                path.display(),
+                e
+            ));
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::item_sort_list::sieve::SieveIO;
+    use crate::item_sort_list::{sieve::get_sub_path, Event, EventTimeZone, FileItem, ItemList};
+    use chrono::TimeZone;
+    use num_traits::FromPrimitive;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    struct TestSieveIO {
+        pub copies: RefCell<Vec<(PathBuf, PathBuf)>>,
+        pub renames: RefCell<Vec<(PathBuf, PathBuf)>>,
+        pub removes: RefCell<Vec<PathBuf>>,
+        pub creates: RefCell<Vec<PathBuf>>,
+        pub archives: RefCell<Vec<PathBuf>>,
+        pub archived: Rc<RefCell<Vec<(PathBuf, String, Option<i32>)>>>,
+        pub manifests: RefCell<Vec<(PathBuf, SieveManifest)>>,
+        pub manifest_csvs: RefCell<Vec<(PathBuf, String)>>,
+        pub modified: RefCell<Vec<PathBuf>>,
+        pub rotations: RefCell<Vec<(PathBuf, i32)>>,
+        pub pixel_rotations: RefCell<Vec<(PathBuf, i32)>>,
+        pub strips: RefCell<Vec<(PathBuf, i32)>>,
+        pub create_error: bool,
+        pub rotate_pixels: bool,
+        pub existing: Vec<PathBuf>,
+        pub collision_policy: CollisionPolicy,
+    }
+
+    struct TestSieveArchive {
+        pub archived: Rc<RefCell<Vec<(PathBuf, String, Option<i32>)>>>,
+    }
+
+    impl TestSieveIO {
+        pub fn new() -> Self {
+            TestSieveIO {
+                copies: RefCell::new(vec![]),
+                renames: RefCell::new(vec![]),
+                removes: RefCell::new(vec![]),
+                creates: RefCell::new(vec![]),
+                archives: RefCell::new(vec![]),
+                archived: Rc::new(RefCell::new(vec![])),
+                manifests: RefCell::new(vec![]),
+                manifest_csvs: RefCell::new(vec![]),
+                modified: RefCell::new(vec![]),
+                rotations: RefCell::new(vec![]),
+                pixel_rotations: RefCell::new(vec![]),
+                strips: RefCell::new(vec![]),
+                create_error: false,
+                rotate_pixels: false,
+                existing: vec![],
+                collision_policy: CollisionPolicy::Rename,
+            }
+        }
+
+        pub fn reset(&mut self) {
+            self.copies.get_mut().clear();
+            self.renames.get_mut().clear();
+            self.removes.get_mut().clear();
+            self.creates.get_mut().clear();
+            self.archives.get_mut().clear();
+            self.archived.borrow_mut().clear();
+            self.manifests.get_mut().clear();
+            self.manifest_csvs.get_mut().clear();
+            self.modified.get_mut().clear();
+            self.rotations.get_mut().clear();
+            self.pixel_rotations.get_mut().clear();
+            self.strips.get_mut().clear();
+        }
+    }
+
+    impl SieveArchive for TestSieveArchive {
+        fn add_file(
+            &mut self,
+            src: &Path,
+            name: &str,
+            image_rotation: Option<i32>,
+        ) -> Result<(), Error> {
+            self.archived
+                .borrow_mut()
+                .push((src.to_path_buf(), name.to_string(), image_rotation));
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    impl SieveIO for TestSieveIO {
+        fn copy(&self, src: &Path, dest: &Path) -> Result<(), Error> {
+            self.copies
+                .borrow_mut()
+                .push((src.to_path_buf(), dest.to_path_buf()));
+            Ok(())
+        }
+
+        fn remove_file(&self, path: &Path) -> Result<(), Error> {
+            self.removes.borrow_mut().push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn r#move(&self, src: &Path, dest: &Path) -> Result<(), Error> {
+            self.renames
+                .borrow_mut()
+                .push((src.to_path_buf(), dest.to_path_buf()));
+            Ok(())
+        }
+
+        fn create_dir_all(&self, path: &Path) -> Result<(), Error> {
+            if self.create_error {
+                return Err(Error::new(ErrorKind::PermissionDenied, "Access denied"));
+            }
+            self.creates.borrow_mut().push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn create_archive(&self, path: &Path) -> Result<Box<dyn SieveArchive>, Error> {
+            self.archives.borrow_mut().push(path.to_path_buf());
+            Ok(Box::new(TestSieveArchive {
+                archived: self.archived.clone(),
+            }))
+        }
+
+        fn resolve_collision(&self, dest: &mut PathBuf) -> Option<CollisionPolicy> {
+            resolve_collision(&self.collision_policy, dest, |path| {
+                self.existing.contains(&path.to_path_buf())
+            })
+        }
+
+        fn file_stamp(&self, path: &Path) -> Result<FileStamp, Error> {
+            let modified = if self.modified.borrow().contains(&path.to_path_buf()) {
+                1
+            } else {
+                0
+            };
+            Ok(FileStamp { size: 1, modified })
+        }
+
+        fn write_orientation(&self, path: &Path, rotation: i32) -> Result<bool, Error> {
+            self.rotations
+                .borrow_mut()
+                .push((path.to_path_buf(), rotation));
+            Ok(true)
+        }
+
+        fn rotate_pixels(&self, path: &Path, rotation: i32) -> Result<bool, Error> {
+            self.pixel_rotations
+                .borrow_mut()
+                .push((path.to_path_buf(), rotation));
+            Ok(self.rotate_pixels)
+        }
+
+        fn strip_metadata(&self, path: &Path, rotation: i32) -> Result<bool, Error> {
+            self.strips
+                .borrow_mut()
+                .push((path.to_path_buf(), rotation));
+            Ok(true)
+        }
+
+        fn save_manifest(&self, path: &Path, manifest: &SieveManifest) -> Result<(), Error> {
+            self.manifests
+                .borrow_mut()
+                .push((path.to_path_buf(), manifest.clone()));
+            Ok(())
+        }
+
+        fn load_manifest(&self, path: &Path) -> Result<SieveManifest, Error> {
+            self.manifests
+                .borrow()
+                .iter()
+                .rev()
+                .find(|(manifest_path, _)| manifest_path == path)
+                .map(|(_, manifest)| manifest.clone())
+                .ok_or_else(|| Error::from(ErrorKind::NotFound))
+        }
+
+        fn save_manifest_csv(&self, path: &Path, csv: &str) -> Result<bool, Error> {
+            self.manifest_csvs
+                .borrow_mut()
+                .push((path.to_path_buf(), String::from(csv)));
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn test_get_sub_path() {
+        use chrono::NaiveDate;
+        use chrono::NaiveDateTime;
+
+        let item_list = ItemList {
+            items: vec![],
+            events: vec![
+                Event {
                     name: String::from("Test1"),
                     start_date: NaiveDate::from_ymd_opt(2021, 9, 14).unwrap(),
                     end_date: NaiveDate::from_ymd_opt(2021, 9, 14).unwrap(),
+                    target_directory: String::new(),
+                    folder: String::new(),
                 },
                 Event {
                     name: String::from("Test2"),
                     start_date: NaiveDate::from_ymd_opt(2021, 9, 20).unwrap(),
                     end_date: NaiveDate::from_ymd_opt(2021, 9, 21).unwrap(),
+                    target_directory: String::new(),
+                    folder: String::new(),
                 },
                 Event {
                     name: String::from("Test3"),
                     start_date: NaiveDate::from_ymd_opt(2021, 9, 24).unwrap(),
                     end_date: NaiveDate::from_ymd_opt(2022, 9, 27).unwrap(),
+                    target_directory: String::new(),
+                    folder: String::new(),
                 },
             ],
             path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let test_cases = [
+            (
+                "2021-09-14 00:00",
+                [
+                    "2021-09-14 Test1",
+                    "2021-09-14 Test1",
+                    "2021-09-14 Test1",
+                    "2021-09-14 Test1",
+                    "202109-14 Test1",
+                ],
+            ),
+            (
+                "2021-09-13 23:59",
+                ["2021-09", "2021", "2021-09-13", "2021-Q3", "202109"],
+            ),
+            (
+                "2021-09-15 00:00",
+                ["2021-09", "2021", "2021-09-15", "2021-Q3", "202109"],
+            ),
+            (
+                "2021-09-20 00:00",
+                [
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "202109-20 - 09-21 Test2",
+                ],
+            ),
+            (
+                "2021-09-21 16:00",
+                [
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "2021-09-20 - 2021-09-21 Test2",
+                    "202109-20 - 09-21 Test2",
+                ],
+            ),
+            (
+                "2021-09-25 16:00",
+                [
+                    "2021-09-24 - 2022-09-27 Test3",
+                    "2021-09-24 - 2022-09-27 Test3",
+                    "2021-09-24 - 2022-09-27 Test3",
+                    "2021-09-24 - 2022-09-27 Test3",
+                    "202109-24 - 2022-09-27 Test3",
+                ],
+            ),
+            (
+                "2020-01-13 23:59",
+                ["2020-01", "2020", "2020-01-13", "2020-Q1", "202001"],
+            ),
+            (
+                "2021-04-15 00:00",
+                ["2021-04", "2021", "2021-04-15", "2021-Q2", "202104"],
+            ),
+        ];
+
+        for (input, results) in test_cases {
+            for (i, result) in results.into_iter().enumerate() {
+                let sub_path: String = get_sub_path(
+                    &item_list,
+                    &FileItem::dummy(
+                        "test.jpg",
+                        NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+                            .unwrap()
+                            .timestamp(),
+                        false,
+                    ),
+                    &FromPrimitive::from_usize(i).unwrap(),
+                )
+                .join("");
+                assert_eq!(sub_path, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sieve_methods() {
+        let item_list = ItemList {
+            items: vec![
+                FileItem::dummy("test/test1.jpg", 0, true),
+                FileItem::dummy("test/test2.jpg", 0, false),
+            ],
+            events: vec![],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let mut sieve_io = TestSieveIO::new();
+
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Delete,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.copies.borrow().len(), 0);
+        assert_eq!(sieve_io.creates.borrow().len(), 0);
+        assert_eq!(sieve_io.renames.borrow().len(), 0);
+        assert_eq!(sieve_io.removes.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.removes.borrow()[0].to_str().unwrap(),
+            "test/test2.jpg"
+        );
+
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.copies.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.copies.borrow()[0].0.to_str().unwrap(),
+            "test/test1.jpg"
+        );
+        assert_eq!(
+            sieve_io.copies.borrow()[0].1.to_str().unwrap(),
+            "target/1970-01/test1.jpg"
+        );
+        assert_eq!(sieve_io.creates.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.creates.borrow()[0].to_str().unwrap(),
+            "target/1970-01"
+        );
+        assert_eq!(sieve_io.renames.borrow().len(), 0);
+        assert_eq!(sieve_io.removes.borrow().len(), 0);
+
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Move,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.copies.borrow().len(), 0);
+        assert_eq!(sieve_io.creates.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.creates.borrow()[0].to_str().unwrap(),
+            "target/1970-01"
+        );
+        assert_eq!(sieve_io.renames.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.renames.borrow()[0].0.to_str().unwrap(),
+            "test/test1.jpg"
+        );
+        assert_eq!(
+            sieve_io.renames.borrow()[0].1.to_str().unwrap(),
+            "target/1970-01/test1.jpg"
+        );
+        assert_eq!(sieve_io.removes.borrow().len(), 0);
+
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::MoveAndDelete,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.copies.borrow().len(), 0);
+        assert_eq!(sieve_io.creates.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.creates.borrow()[0].to_str().unwrap(),
+            "target/1970-01"
+        );
+        assert_eq!(sieve_io.renames.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.renames.borrow()[0].0.to_str().unwrap(),
+            "test/test1.jpg"
+        );
+        assert_eq!(
+            sieve_io.renames.borrow()[0].1.to_str().unwrap(),
+            "target/1970-01/test1.jpg"
+        );
+        assert_eq!(sieve_io.removes.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.removes.borrow()[0].to_str().unwrap(),
+            "test/test2.jpg"
+        );
+
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Zip,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.copies.borrow().len(), 0);
+        assert_eq!(sieve_io.renames.borrow().len(), 0);
+        assert_eq!(sieve_io.removes.borrow().len(), 0);
+        assert_eq!(sieve_io.archives.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.archives.borrow()[0].to_str().unwrap(),
+            "target/ImageSieve.zip"
+        );
+        assert_eq!(sieve_io.archived.borrow().len(), 1);
+        assert_eq!(
+            sieve_io.archived.borrow()[0].0.to_str().unwrap(),
+            "test/test1.jpg"
+        );
+        assert_eq!(sieve_io.archived.borrow()[0].1, "1970-01/test1.jpg");
+    }
+
+    #[test]
+    fn test_progress() {
+        assert_eq!(format_progress(1, 3, 0, Duration::from_secs(1)), "(1/3)");
+        assert_eq!(format_progress(1, 3, 100, Duration::ZERO), "(1/3)");
+        assert_eq!(
+            format_progress(2, 3, 5_000_000, Duration::from_secs(2)),
+            "(2/3, 2.5 MB/s)"
+        );
+
+        let item_list = ItemList {
+            items: vec![
+                FileItem::dummy("test/test1.jpg", 0, true),
+                FileItem::dummy("test/test2.jpg", 0, false),
+                FileItem::dummy("test/test3.jpg", 0, true),
+            ],
+            events: vec![],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
-        let test_cases = [
-            (
-                "2021-09-14 00:00",
-                [
-                    "2021-09-14 Test1",
-                    "2021-09-14 Test1",
-                    "2021-09-14 Test1",
-                    "2021-09-14 Test1",
-                    "202109-14 Test1",
-                ],
-            ),
-            (
-                "2021-09-13 23:59",
-                ["2021-09", "2021", "2021-09-13", "2021-Q3", "202109"],
-            ),
-            (
-                "2021-09-15 00:00",
-                ["2021-09", "2021", "2021-09-15", "2021-Q3", "202109"],
-            ),
-            (
-                "2021-09-20 00:00",
-                [
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "202109-20 - 09-21 Test2",
-                ],
-            ),
-            (
-                "2021-09-21 16:00",
-                [
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "2021-09-20 - 2021-09-21 Test2",
-                    "202109-20 - 09-21 Test2",
-                ],
-            ),
-            (
-                "2021-09-25 16:00",
-                [
-                    "2021-09-24 - 2022-09-27 Test3",
-                    "2021-09-24 - 2022-09-27 Test3",
-                    "2021-09-24 - 2022-09-27 Test3",
-                    "2021-09-24 - 2022-09-27 Test3",
-                    "202109-24 - 2022-09-27 Test3",
-                ],
-            ),
-            (
-                "2020-01-13 23:59",
-                ["2020-01", "2020", "2020-01-13", "2020-Q1", "202001"],
-            ),
-            (
-                "2021-04-15 00:00",
-                ["2021-04", "2021", "2021-04-15", "2021-Q2", "202104"],
-            ),
-        ];
+        let progress = RefCell::new(vec![]);
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::MoveAndDelete,
+            DirectoryNames::YearAndMonth,
+            &TestSieveIO::new(),
+            false,
+            |p: String| progress.borrow_mut().push(p),
+        );
+        let progress = progress.into_inner();
+        assert!(progress[0].ends_with("(1/3)"));
+        assert_eq!(progress[1], "Delete \"test/test2.jpg\" (2/3)");
+        assert!(progress[2].ends_with("(3/3)"));
+    }
+
+    #[test]
+    fn test_skip_sieved() {
+        let mut item_list = ItemList {
+            items: vec![
+                FileItem::dummy("test/test1.jpg", 0, true),
+                FileItem::dummy("test/test2.jpg", 0, false),
+            ],
+            events: vec![],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let mut sieve_io = TestSieveIO::new();
+        let sieve_copy = |item_list: &ItemList, sieve_io: &TestSieveIO, path: &str, force: bool| {
+            let progress = RefCell::new(vec![]);
+            let copies = sieve(
+                item_list,
+                Path::new(path),
+                SieveMethod::Copy,
+                DirectoryNames::YearAndMonth,
+                sieve_io,
+                force,
+                |p: String| progress.borrow_mut().push(p),
+            );
+            (copies, progress.into_inner())
+        };
+
+        let (copies, _) = sieve_copy(&item_list, &sieve_io, "target", false);
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].source, PathBuf::from("test/test1.jpg"));
+        item_list.set_sieved_copies(&copies);
+        assert_eq!(
+            item_list.items[0].get_sieved_copy().unwrap().target,
+            PathBuf::from("target/1970-01/test1.jpg")
+        );
+
+        // An unchanged copy is skipped
+        sieve_io.reset();
+        let (copies, progress) = sieve_copy(&item_list, &sieve_io, "target", false);
+        assert!(copies.is_empty());
+        assert_eq!(sieve_io.copies.borrow().len(), 0);
+        assert!(progress.contains(&String::from("Skipped 1 files already sieved to target")));
+
+        // Forced, to another directory or with a changed copy, the file is copied again
+        let (copies, _) = sieve_copy(&item_list, &sieve_io, "target", true);
+        assert_eq!(copies.len(), 1);
+        let (copies, _) = sieve_copy(&item_list, &sieve_io, "other", false);
+        assert_eq!(copies.len(), 1);
+        sieve_io.reset();
+        sieve_io
+            .modified
+            .borrow_mut()
+            .push(PathBuf::from("target/1970-01/test1.jpg"));
+        let (copies, _) = sieve_copy(&item_list, &sieve_io, "target", false);
+        assert_eq!(copies.len(), 1);
+        assert_eq!(sieve_io.copies.borrow().len(), 1);
+
+        // Other methods do not return copies
+        let copies = sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Move,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert!(copies.is_empty());
+    }
+
+    #[test]
+    fn test_event_target_directory() {
+        let mut event = Event::new("Trip", "1970-01-01", "1970-01-01");
+        event.target_directory = String::from("other");
+        let mut item_list = ItemList {
+            items: vec![
+                FileItem::dummy("test/test1.jpg", 0, true),
+                FileItem::dummy("test/test2.jpg", 100000, true),
+            ],
+            events: vec![event],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::Utc,
+            project_settings: None,
+        };
+        let sieve_io = TestSieveIO::new();
+        let copies = sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(
+            *sieve_io.copies.borrow(),
+            vec![
+                (
+                    PathBuf::from("test/test1.jpg"),
+                    PathBuf::from("other/1970-01-01 Trip/test1.jpg")
+                ),
+                (
+                    PathBuf::from("test/test2.jpg"),
+                    PathBuf::from("target/1970-01/test2.jpg")
+                )
+            ]
+        );
+        // The manifest is written to the target directory and lists both copies
+        let manifests = sieve_io.manifests.borrow();
+        assert_eq!(manifests[0].0, PathBuf::from("target").join(MANIFEST_FILE));
+        assert_eq!(manifests[0].1.entries.len(), 2);
+        // The description lists the files relative to the target directory with their original path and event
+        let manifest_csvs = sieve_io.manifest_csvs.borrow();
+        assert_eq!(
+            manifest_csvs[0].0,
+            PathBuf::from("target").join(MANIFEST_CSV_FILE)
+        );
+        let lines: Vec<&str> = manifest_csvs[0].1.lines().collect();
+        assert_eq!(lines[0], "File,Original path,Date,Event,Favorite,Bytes");
+        assert_eq!(
+            lines[1],
+            format!(
+                "\"{}\",\"{}\",1970-01-01 00:00:00,\"Trip\",no,0",
+                Path::new("other/1970-01-01 Trip/test1.jpg").display(),
+                Path::new("test/test1.jpg").display()
+            )
+        );
+        assert_eq!(
+            lines[2],
+            format!(
+                "\"{}\",\"{}\",1970-01-02 03:46:40,\"\",no,0",
+                Path::new("1970-01/test2.jpg").display(),
+                Path::new("test/test2.jpg").display()
+            )
+        );
+
+        // A copy in the event target directory is recognized as already sieved
+        item_list.set_sieved_copies(&copies);
+        let copies = sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert!(copies.is_empty());
+    }
+
+    #[test]
+    fn test_source_subdirectories() {
+        let mut unsure_item = FileItem::dummy("tests/subdir/test2.JPG", 0, true);
+        unsure_item.set_unsure(true);
+        let item_list = ItemList {
+            items: vec![
+                FileItem::dummy("tests/test.jpg", 0, true),
+                FileItem::dummy("tests/subdir/test.jpg", 0, true),
+                unsure_item,
+                FileItem::dummy("tests/subdir/test3.jpg", 0, false),
+            ],
+            events: vec![Event::new("Trip", "1970-01-01", "1970-01-01")],
+            path: PathBuf::from("tests"),
+            event_time_zone: EventTimeZone::Utc,
+            project_settings: None,
+        };
+        let sieve_io = TestSieveIO::new();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::SourceSubdirectories,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        // The event is not used, the subdirectories of the source directory are mirrored instead
+        assert_eq!(
+            *sieve_io.copies.borrow(),
+            vec![
+                (
+                    PathBuf::from("tests/test.jpg"),
+                    PathBuf::from("target/test.jpg")
+                ),
+                (
+                    PathBuf::from("tests/subdir/test.jpg"),
+                    PathBuf::from("target/subdir/test.jpg")
+                ),
+                (
+                    PathBuf::from("tests/subdir/test2.JPG"),
+                    PathBuf::from("target/Review/subdir/test2.JPG")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sieve_unsure() {
+        let mut item_list = ItemList {
+            items: vec![
+                FileItem::dummy("test/test1.jpg", 0, true),
+                FileItem::dummy("test/test2.jpg", 0, false),
+            ],
+            events: vec![Event::new("Trip", "1970-01-01", "1970-01-01")],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::Utc,
+            project_settings: None,
+        };
+        item_list.items[1].set_unsure(true);
+        let sieve_io = TestSieveIO::new();
+        let copies = sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(
+            sieve_io.copies.borrow()[1].1,
+            PathBuf::from("target/Review/1970-01-01 Trip/test2.jpg")
+        );
+
+        // A reviewed item is copied again to the regular directory
+        item_list.set_sieved_copies(&copies);
+        item_list.items[1].set_take_over(true);
+        sieve_io.copies.borrow_mut().clear();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(
+            *sieve_io.copies.borrow(),
+            vec![(
+                PathBuf::from("test/test2.jpg"),
+                PathBuf::from("target/1970-01-01 Trip/test2.jpg")
+            )]
+        );
+
+        // Unsure items are archived to the review directory as well
+        item_list.items[0].set_unsure(true);
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Zip,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(
+            sieve_io.archived.borrow()[0].1,
+            "Review/1970-01-01 Trip/test1.jpg"
+        );
+    }
+
+    #[test]
+    fn test_create_target_error() {
+        let item_list = ItemList {
+            items: vec![FileItem::dummy("test/test1.jpg", 0, true)],
+            events: vec![],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::Utc,
+            project_settings: None,
+        };
+        let mut sieve_io = TestSieveIO::new();
+        sieve_io.create_error = true;
+
+        // Nothing is sieved if the target directory cannot be created
+        for sieve_method in [SieveMethod::Copy, SieveMethod::Zip] {
+            let messages = RefCell::new(vec![]);
+            sieve(
+                &item_list,
+                Path::new("missing_target"),
+                sieve_method,
+                DirectoryNames::YearAndMonth,
+                &sieve_io,
+                false,
+                |message: String| messages.borrow_mut().push(message),
+            );
+            assert_eq!(
+                *messages.borrow(),
+                vec![
+                    String::from("Error creating directory missing_target: Access denied"),
+                    String::from("Done")
+                ]
+            );
+            assert!(sieve_io.copies.borrow().is_empty());
+            assert!(sieve_io.archives.borrow().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_undo_sieve() {
+        let item_list = ItemList {
+            items: vec![
+                FileItem::dummy("test/test1.jpg", 0, true),
+                FileItem::dummy("test/test2.jpg", 0, false),
+            ],
+            events: vec![],
+            path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let mut sieve_io = TestSieveIO::new();
+        let manifest_path = Path::new("target").join(MANIFEST_FILE);
+
+        // Nothing to undo
+        let messages = RefCell::new(vec![]);
+        undo_sieve(Path::new("target"), &sieve_io, |message: String| {
+            messages.borrow_mut().push(message)
+        });
+        assert_eq!(messages.borrow().len(), 2);
+        assert!(messages.borrow()[0].starts_with("Error reading manifest"));
+        assert_eq!(messages.borrow()[1], "Done");
+
+        // Copies are deleted
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.manifests.borrow().len(), 1);
+        {
+            let manifest = &sieve_io.manifests.borrow()[0];
+            assert_eq!(manifest.0, manifest_path);
+            assert_eq!(manifest.1.method, SieveMethod::Copy);
+            assert_eq!(manifest.1.entries.len(), 1);
+            assert_eq!(manifest.1.entries[0].source, Path::new("test/test1.jpg"));
+            assert_eq!(
+                manifest.1.entries[0].target,
+                Path::new("target/1970-01/test1.jpg")
+            );
+        }
+        undo_sieve(Path::new("target"), &sieve_io, |_: String| {});
+        assert_eq!(
+            *sieve_io.removes.borrow(),
+            vec![
+                PathBuf::from("target/1970-01/test1.jpg"),
+                manifest_path.clone()
+            ]
+        );
+
+        // Moved files are moved back
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::MoveAndDelete,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        assert_eq!(sieve_io.manifests.borrow()[0].1.entries.len(), 1);
+        sieve_io.renames.borrow_mut().clear();
+        undo_sieve(Path::new("target"), &sieve_io, |_: String| {});
+        assert_eq!(
+            *sieve_io.renames.borrow(),
+            vec![(
+                PathBuf::from("target/1970-01/test1.jpg"),
+                PathBuf::from("test/test1.jpg")
+            )]
+        );
+
+        // Modified targets prevent the undo
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Move,
+            DirectoryNames::YearAndMonth,
+            &sieve_io,
+            false,
+            |_: String| {},
+        );
+        sieve_io.renames.borrow_mut().clear();
+        sieve_io
+            .modified
+            .borrow_mut()
+            .push(PathBuf::from("target/1970-01/test1.jpg"));
+        undo_sieve(Path::new("target"), &sieve_io, |_: String| {});
+        assert!(sieve_io.renames.borrow().is_empty());
+        assert!(sieve_io.removes.borrow().is_empty());
 
-        for (input, results) in test_cases {
-            for (i, result) in results.into_iter().enumerate() {
-                let sub_path: String = get_sub_path(
-                    &item_list,
-                    &FileItem::dummy(
-                        "test.jpg",
-                        NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
-                            .unwrap()
-                            .timestamp(),
-                        false,
-                    ),
-                    &FromPrimitive::from_usize(i).unwrap(),
-                )
-                .join("");
-                assert_eq!(sub_path, result);
-            }
+        // Deleting and archiving write no manifest
+        sieve_io.reset();
+        for sieve_method in [SieveMethod::Delete, SieveMethod::Zip] {
+            sieve(
+                &item_list,
+                Path::new("target"),
+                sieve_method,
+                DirectoryNames::YearAndMonth,
+                &sieve_io,
+                false,
+                |_: String| {},
+            );
         }
+        assert!(sieve_io.manifests.borrow().is_empty());
     }
 
     #[test]
-    fn test_sieve_methods() {
-        let item_list = ItemList {
+    fn test_compare_with_last_sieve() {
+        let mut item_list = ItemList {
             items: vec![
                 FileItem::dummy("test/test1.jpg", 0, true),
                 FileItem::dummy("test/test2.jpg", 0, false),
+                FileItem::dummy("test/test3.jpg", 0, true),
             ],
             events: vec![],
             path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
-        let mut sieve_io = TestSieveIO::new();
+        let sieve_io = TestSieveIO::new();
+
+        // Without a sieve, there is nothing to compare with
+        assert_eq!(
+            compare_with_last_sieve(&item_list, Path::new("target"), &sieve_io)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound
+        );
 
         sieve(
             &item_list,
             Path::new("target"),
-            SieveMethod::Delete,
+            SieveMethod::Copy,
             DirectoryNames::YearAndMonth,
             &sieve_io,
+            false,
             |_: String| {},
         );
-        assert_eq!(sieve_io.copies.borrow().len(), 0);
-        assert_eq!(sieve_io.creates.borrow().len(), 0);
-        assert_eq!(sieve_io.renames.borrow().len(), 0);
-        assert_eq!(sieve_io.removes.borrow().len(), 1);
         assert_eq!(
-            sieve_io.removes.borrow()[0].to_str().unwrap(),
-            "test/test2.jpg"
+            compare_with_last_sieve(&item_list, Path::new("target"), &sieve_io).unwrap(),
+            SieveComparison::default()
         );
 
-        sieve_io.reset();
+        // Newly kept and newly found items are added, discarded items are removed
+        item_list.items[1].set_take_over(true);
+        item_list.items[2].set_take_over(false);
+        item_list
+            .items
+            .push(FileItem::dummy("test/test4.jpg", 0, true));
+        let comparison =
+            compare_with_last_sieve(&item_list, Path::new("target"), &sieve_io).unwrap();
+        assert_eq!(
+            comparison.added,
+            vec![
+                PathBuf::from("test/test2.jpg"),
+                PathBuf::from("test/test4.jpg")
+            ]
+        );
+        assert_eq!(comparison.removed, vec![PathBuf::from("test/test3.jpg")]);
+    }
+
+    #[test]
+    fn test_save_manifest_csv() {
+        let mut file_io = FileSieveIO::default();
+        let path = Path::new("tests/test_manifest.csv");
+        assert!(!file_io.save_manifest_csv(path, "File\n").unwrap());
+        assert!(!path.exists());
+
+        file_io.manifest_csv = true;
+        assert!(file_io.save_manifest_csv(path, "File\n").unwrap());
+        assert_eq!(fs::read_to_string(path).unwrap(), "File\n");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_undo_file_sieve() {
+        let item_list = ItemList {
+            items: vec![FileItem::dummy("tests/test.jpg", 0, true)],
+            events: vec![],
+            path: PathBuf::from("tests"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let file_io = FileSieveIO::for_source_operations();
+        let target = Path::new("tests/target/undo");
+        let copy = target.join("1970-01/test.jpg");
+        let _ = std::fs::remove_dir_all(target);
+
         sieve(
             &item_list,
-            Path::new("target"),
+            target,
             SieveMethod::Copy,
             DirectoryNames::YearAndMonth,
-            &sieve_io,
+            &file_io,
+            false,
             |_: String| {},
         );
-        assert_eq!(sieve_io.copies.borrow().len(), 1);
+        assert!(copy.exists());
+        assert!(target.join(MANIFEST_FILE).exists());
+        undo_sieve(target, &file_io, |_: String| {});
+        assert!(!copy.exists());
+        assert!(!target.join(MANIFEST_FILE).exists());
+
+        sieve(
+            &item_list,
+            target,
+            SieveMethod::Copy,
+            DirectoryNames::YearAndMonth,
+            &file_io,
+            false,
+            |_: String| {},
+        );
+        std::fs::write(&copy, "modified").unwrap();
+        undo_sieve(target, &file_io, |_: String| {});
+        assert!(copy.exists());
+        assert!(target.join(MANIFEST_FILE).exists());
+        std::fs::remove_dir_all(target).unwrap();
+    }
+
+    #[test]
+    fn test_unique_archive_name() {
+        let mut names = HashSet::new();
+        assert_eq!(get_unique_archive_name("a/test.jpg", &names), "a/test.jpg");
+        names.insert(String::from("a/test.jpg"));
+        assert_eq!(get_unique_archive_name("a/test.jpg", &names), "a/test_.jpg");
+        names.insert(String::from("a/test_.jpg"));
         assert_eq!(
-            sieve_io.copies.borrow()[0].0.to_str().unwrap(),
-            "test/test1.jpg"
+            get_unique_archive_name("a/test.jpg", &names),
+            "a/test__.jpg"
+        );
+        names.insert(String::from("a.b/test"));
+        assert_eq!(get_unique_archive_name("a.b/test", &names), "a.b/test_");
+    }
+
+    #[test]
+    fn test_zip_archive() {
+        let item_list = ItemList {
+            items: vec![
+                FileItem::dummy("tests/test.jpg", 0, true),
+                FileItem::dummy("tests/test2.JPG", 0, false),
+                FileItem::dummy("tests/subdir/test.jpg", 0, true),
+            ],
+            events: vec![],
+            path: PathBuf::from("tests"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let file_io = FileSieveIO::for_source_operations();
+        let _ = std::fs::remove_file("tests/target/tests.zip");
+
+        sieve(
+            &item_list,
+            Path::new("tests/target"),
+            SieveMethod::Zip,
+            DirectoryNames::YearAndMonth,
+            &file_io,
+            false,
+            |_: String| {},
+        );
+
+        let archive = zip::ZipArchive::new(File::open("tests/target/tests.zip").unwrap());
+        let archive = archive.unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["1970-01/test.jpg", "1970-01/test_.jpg"]);
+    }
+
+    #[test]
+    fn test_sub_path_uses_capture_time() {
+        create_dir_all("tests/target/capture_time").unwrap();
+        let file_time = FileTime::from_unix_time(
+            chrono::NaiveDate::from_ymd_opt(2015, 3, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .timestamp(),
+            0,
         );
+        let mut item_list = ItemList::new();
+        item_list.path = PathBuf::from("tests/target/capture_time");
+        for source in ["tests/test.jpg", "tests/test_no_exif.jpg"] {
+            let target = item_list.path.join(Path::new(source).file_name().unwrap());
+            fs::copy(source, &target).unwrap();
+            filetime::set_file_times(&target, file_time, file_time).unwrap();
+            item_list.check_and_add(&target, true);
+        }
+        item_list.finish_synchronizing(&PathBuf::from("tests/target/capture_time"));
+
+        let sub_path = |file_name: &str| {
+            let item = item_list
+                .items
+                .iter()
+                .find(|item| item.path.ends_with(file_name))
+                .unwrap();
+            get_sub_path(&item_list, item, &DirectoryNames::YearAndMonth)
+        };
+        // The capture time of the EXIF data wins over the file date, which was changed by copying
+        assert_eq!(sub_path("test.jpg"), vec!["2021-09"]);
+        // Without EXIF data, the file date is used
+        assert_eq!(sub_path("test_no_exif.jpg"), vec!["2015-03"]);
+    }
+
+    #[test]
+    fn test_preserve_timestamps() {
+        let source = Path::new("tests/test.jpg");
+        let source_modified = metadata(source).unwrap().modified().unwrap();
+        create_dir_all("tests/target/timestamps").unwrap();
+
+        for preserve_timestamps in [true, false] {
+            let target = PathBuf::from("tests/target/timestamps/test.jpg");
+            let _ = remove_file(&target);
+            let file_io = FileSieveIO {
+                preserve_timestamps,
+                ..FileSieveIO::for_source_operations()
+            };
+            file_io.copy(source, &target).unwrap();
+            let target_modified = metadata(&target).unwrap().modified().unwrap();
+            assert_eq!(target_modified == source_modified, preserve_timestamps);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::permissions_set_readonly_false)]
+    fn test_preserve_permissions() {
+        let directory = Path::new("tests/target/permissions");
+        create_dir_all(directory).unwrap();
+        let source = directory.join("source.jpg");
+        let _ = remove_file(&source);
+        fs::copy("tests/test.jpg", &source).unwrap();
+        let mut permissions = metadata(&source).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&source, permissions).unwrap();
+
+        for preserve_permissions in [true, false] {
+            let target = directory.join("target.jpg");
+            if let Ok(metadata) = metadata(&target) {
+                let mut permissions = metadata.permissions();
+                permissions.set_readonly(false);
+                fs::set_permissions(&target, permissions).unwrap();
+                remove_file(&target).unwrap();
+            }
+            let file_io = FileSieveIO {
+                correct_orientation: true,
+                preserve_permissions,
+                ..FileSieveIO::for_source_operations()
+            };
+            file_io.copy(&source, &target).unwrap();
+            assert_eq!(
+                metadata(&target).unwrap().permissions().readonly(),
+                preserve_permissions
+            );
+            // The orientation of a read-only copy can still be changed
+            assert!(file_io.write_orientation(&target, 180).unwrap());
+            assert_eq!(
+                metadata(&target).unwrap().permissions().readonly(),
+                preserve_permissions
+            );
+        }
+
+        // Read-only files cannot be removed on Windows
+        for path in [source, directory.join("target.jpg")] {
+            let mut permissions = metadata(&path).unwrap().permissions();
+            permissions.set_readonly(false);
+            fs::set_permissions(&path, permissions).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_copy_with_progress() {
+        let source = Path::new("tests/test.mp4");
+        let target = Path::new("tests/target/progress/test.mp4");
+        create_dir_all(target.parent().unwrap()).unwrap();
+        let file_io = FileSieveIO::default();
+        let progress = RefCell::new(vec![]);
+        file_io
+            .copy_with_progress(source, target, &|copied, total| {
+                progress.borrow_mut().push((copied, total))
+            })
+            .unwrap();
+        let size = metadata(source).unwrap().len();
+        assert_eq!(progress.into_inner().last(), Some(&(size, size)));
+        assert_eq!(fs::read(target).unwrap(), fs::read(source).unwrap());
+
+        // An incomplete copy is removed
+        assert!(file_io
+            .copy(Path::new("tests/not_there.mp4"), target)
+            .is_err());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_file_progress() {
+        let file_progress = FileProgress::new(Path::new("video.mp4"));
+        assert!(file_progress.update(1, LARGE_FILE_SIZE - 1).is_none());
+        let total = 100_000_000;
+        assert!(file_progress.update(1_000_000, total).is_none());
         assert_eq!(
-            sieve_io.copies.borrow()[0].1.to_str().unwrap(),
-            "target/1970-01/test1.jpg"
+            file_progress.update(5_000_000, total).unwrap(),
+            "⏳ \"video.mp4\" 5% (5.0 of 100.0 MB)"
         );
-        assert_eq!(sieve_io.creates.borrow().len(), 1);
+        // Only the next step is reported and a finished file is reported by the sieve
+        assert!(file_progress.update(9_000_000, total).is_none());
+        assert!(file_progress.update(10_000_000, total).is_some());
+        assert!(file_progress.update(total, total).is_none());
+
+        assert!(describe_error(&Error::from(ErrorKind::TimedOut)).contains("network share"));
+        assert!(!describe_error(&Error::from(ErrorKind::NotFound)).contains("network share"));
+    }
+
+    #[test]
+    fn test_safe_move() {
+        let original = Path::new("tests/test.jpg");
+        let directory = Path::new("tests/target/safe_move");
+        create_dir_all(directory).unwrap();
+
+        for safe_move in [true, false] {
+            let source = directory.join("source.jpg");
+            let target = directory.join("moved.jpg");
+            let _ = remove_file(&target);
+            fs::copy(original, &source).unwrap();
+            let source_modified = metadata(&source).unwrap().modified().unwrap();
+            let file_io = FileSieveIO {
+                safe_move,
+                ..FileSieveIO::for_source_operations()
+            };
+            file_io.r#move(&source, &target).unwrap();
+            assert!(!source.exists());
+            assert_eq!(fs::read(&target).unwrap(), fs::read(original).unwrap());
+            assert_eq!(
+                metadata(&target).unwrap().modified().unwrap(),
+                source_modified
+            );
+        }
+
+        // Verified copies are read back and kept if they are identical
+        let file_io = FileSieveIO {
+            verify_copies: true,
+            ..FileSieveIO::for_source_operations()
+        };
+        let copy = directory.join("verified.jpg");
+        let _ = remove_file(&copy);
+        file_io.copy(original, &copy).unwrap();
+        assert_eq!(fs::read(&copy).unwrap(), fs::read(original).unwrap());
+        assert!(file_io
+            .copy(
+                &directory.join("not_there.jpg"),
+                &directory.join("not_copied.jpg")
+            )
+            .is_err());
+        assert!(!directory.join("not_copied.jpg").exists());
+
+        // Copies with a different size or content are not accepted
+        assert!(verify_copy(original, original).is_ok());
+        assert!(verify_copy(original, Path::new("tests/test2.JPG")).is_err());
+        let changed = directory.join("changed.jpg");
+        let mut content = fs::read(original).unwrap();
+        let last = content.len() - 1;
+        content[last] ^= 0xFF;
+        fs::write(&changed, content).unwrap();
         assert_eq!(
-            sieve_io.creates.borrow()[0].to_str().unwrap(),
-            "target/1970-01"
+            metadata(&changed).unwrap().len(),
+            metadata(original).unwrap().len()
         );
-        assert_eq!(sieve_io.renames.borrow().len(), 0);
-        assert_eq!(sieve_io.removes.borrow().len(), 0);
+        let error = verify_copy(original, &changed).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(verify_copy(original, &directory.join("not_there.jpg")).is_err());
+    }
 
-        sieve_io.reset();
+    #[test]
+    fn test_write_orientation() {
+        let mut item_list = ItemList::new();
+        item_list.add_item(Path::new("tests/test.jpg"), true, "");
+        item_list.add_item(Path::new("tests/test2.JPG"), true, "");
+        for item in item_list.items.iter_mut() {
+            if item.path.ends_with("test.jpg") {
+                item.set_ignore_orientation(true);
+            } else {
+                item.rotate(270);
+            }
+        }
+        let sieve_io = TestSieveIO::new();
         sieve(
             &item_list,
             Path::new("target"),
-            SieveMethod::Move,
-            DirectoryNames::YearAndMonth,
+            SieveMethod::Copy,
+            DirectoryNames::Year,
             &sieve_io,
+            false,
             |_: String| {},
         );
-        assert_eq!(sieve_io.copies.borrow().len(), 0);
-        assert_eq!(sieve_io.creates.borrow().len(), 1);
-        assert_eq!(
-            sieve_io.creates.borrow()[0].to_str().unwrap(),
-            "target/1970-01"
+        // Only images whose orientation was changed by the user are written
+        let mut rotations = sieve_io.rotations.borrow().clone();
+        rotations.sort();
+        assert_eq!(rotations.len(), 2);
+        assert_eq!(rotations[0], (PathBuf::from("target/2021/test.jpg"), 0));
+        assert_eq!(rotations[1].1, 270);
+
+        let source = Path::new("tests/test.jpg");
+        let (width, height) = image::image_dimensions(source).unwrap();
+        create_dir_all("tests/target/upright").unwrap();
+        for correct_orientation in [true, false] {
+            let target = PathBuf::from("tests/target/upright/test.jpg");
+            let _ = remove_file(&target);
+            let file_io = FileSieveIO {
+                correct_orientation,
+                ..FileSieveIO::for_source_operations()
+            };
+            file_io.copy(source, &target).unwrap();
+            // The orientation already matches
+            assert!(!file_io.write_orientation(&target, 90).unwrap());
+            assert_eq!(
+                file_io.write_orientation(&target, 0).unwrap(),
+                correct_orientation
+            );
+            let oriented = fs::read(&target).unwrap();
+            let original = fs::read(source).unwrap();
+            if correct_orientation {
+                assert_eq!(exif_orientation::get_jpeg_orientation(&oriented), Some(1));
+                // Only the orientation is changed, the pixels are not encoded again
+                assert_eq!(oriented.len(), original.len());
+                assert_eq!(image::image_dimensions(&target).unwrap(), (width, height));
+                assert_eq!(
+                    metadata(&target).unwrap().modified().unwrap(),
+                    metadata(source).unwrap().modified().unwrap()
+                );
+            } else {
+                assert_eq!(oriented, original);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_pixels() {
+        let mut item_list = ItemList::new();
+        item_list.add_item(Path::new("tests/test.jpg"), true, "");
+        let mut sieve_io = TestSieveIO::new();
+        sieve_io.rotate_pixels = true;
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::Year,
+            &sieve_io,
+            false,
+            |_: String| {},
         );
-        assert_eq!(sieve_io.renames.borrow().len(), 1);
         assert_eq!(
-            sieve_io.renames.borrow()[0].0.to_str().unwrap(),
-            "test/test1.jpg"
+            *sieve_io.pixel_rotations.borrow(),
+            vec![(PathBuf::from("target/2021/test.jpg"), 90)]
         );
+        // The copy is upright now
         assert_eq!(
-            sieve_io.renames.borrow()[0].1.to_str().unwrap(),
-            "target/1970-01/test1.jpg"
+            *sieve_io.strips.borrow(),
+            vec![(PathBuf::from("target/2021/test.jpg"), 0)]
         );
-        assert_eq!(sieve_io.removes.borrow().len(), 0);
-
+        // Moved files are the originals, which are never encoded again
         sieve_io.reset();
         sieve(
             &item_list,
             Path::new("target"),
-            SieveMethod::MoveAndDelete,
-            DirectoryNames::YearAndMonth,
+            SieveMethod::Move,
+            DirectoryNames::Year,
             &sieve_io,
+            false,
             |_: String| {},
         );
-        assert_eq!(sieve_io.copies.borrow().len(), 0);
-        assert_eq!(sieve_io.creates.borrow().len(), 1);
-        assert_eq!(
-            sieve_io.creates.borrow()[0].to_str().unwrap(),
-            "target/1970-01"
+        assert!(sieve_io.pixel_rotations.borrow().is_empty());
+
+        let source = Path::new("tests/test.jpg");
+        let (width, height) = image::image_dimensions(source).unwrap();
+        create_dir_all("tests/target/pixels").unwrap();
+        for rotate_pixels in [true, false] {
+            let target = PathBuf::from("tests/target/pixels/test.jpg");
+            let _ = remove_file(&target);
+            let file_io = FileSieveIO {
+                rotate_pixels,
+                jpeg_quality: 90,
+                ..FileSieveIO::for_source_operations()
+            };
+            file_io.copy(source, &target).unwrap();
+            assert_eq!(file_io.rotate_pixels(&target, 90).unwrap(), rotate_pixels);
+            if rotate_pixels {
+                assert_eq!(image::image_dimensions(&target).unwrap(), (height, width));
+                assert_eq!(
+                    exif_orientation::get_jpeg_orientation(&fs::read(&target).unwrap()),
+                    Some(1)
+                );
+            } else {
+                assert_eq!(fs::read(&target).unwrap(), fs::read(source).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_metadata() {
+        let mut item_list = ItemList::new();
+        item_list.add_item(Path::new("tests/test.jpg"), true, "");
+        let mut sieve_io = TestSieveIO::new();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Copy,
+            DirectoryNames::Year,
+            &sieve_io,
+            false,
+            |_: String| {},
         );
-        assert_eq!(sieve_io.renames.borrow().len(), 1);
+        // The rotation is passed on, as the orientation is removed with the metadata
         assert_eq!(
-            sieve_io.renames.borrow()[0].0.to_str().unwrap(),
-            "test/test1.jpg"
+            *sieve_io.strips.borrow(),
+            vec![(PathBuf::from("target/2021/test.jpg"), 90)]
         );
-        assert_eq!(
-            sieve_io.renames.borrow()[0].1.to_str().unwrap(),
-            "target/1970-01/test1.jpg"
+        // Moved files are never changed
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Move,
+            DirectoryNames::Year,
+            &sieve_io,
+            false,
+            |_: String| {},
         );
-        assert_eq!(sieve_io.removes.borrow().len(), 1);
-        assert_eq!(
-            sieve_io.removes.borrow()[0].to_str().unwrap(),
-            "test/test2.jpg"
+        assert!(sieve_io.strips.borrow().is_empty());
+        // Images written to archives pass their rotation on as well
+        sieve_io.reset();
+        sieve(
+            &item_list,
+            Path::new("target"),
+            SieveMethod::Zip,
+            DirectoryNames::Year,
+            &sieve_io,
+            false,
+            |_: String| {},
         );
+        assert_eq!(sieve_io.archived.borrow()[0].2, Some(90));
+
+        let source = Path::new("tests/test.jpg");
+        let (width, height) = image::image_dimensions(source).unwrap();
+        create_dir_all("tests/target/stripped").unwrap();
+        for strip_metadata in [true, false] {
+            let target = PathBuf::from("tests/target/stripped/test.jpg");
+            let _ = remove_file(&target);
+            let file_io = FileSieveIO {
+                strip_metadata,
+                ..FileSieveIO::for_source_operations()
+            };
+            file_io.copy(source, &target).unwrap();
+            assert_eq!(file_io.strip_metadata(&target, 90).unwrap(), strip_metadata);
+            let exif = exif::Reader::new()
+                .read_from_container(&mut io::BufReader::new(File::open(&target).unwrap()));
+            if strip_metadata {
+                assert!(exif.is_err());
+                // The orientation is lost with the metadata, so the pixels are rotated upright
+                assert_eq!(image::image_dimensions(&target).unwrap(), (height, width));
+            } else {
+                assert!(exif.is_ok());
+                assert_eq!(fs::read(&target).unwrap(), fs::read(source).unwrap());
+            }
+
+            // The metadata is removed from images written to zip archives the same way
+            let archive_path = Path::new("tests/target/stripped/ImageSieve.zip");
+            let _ = remove_file(archive_path);
+            sieve(
+                &item_list,
+                Path::new("tests/target/stripped"),
+                SieveMethod::Zip,
+                DirectoryNames::Year,
+                &file_io,
+                false,
+                |_: String| {},
+            );
+            let mut archive = zip::ZipArchive::new(File::open(archive_path).unwrap()).unwrap();
+            let mut data = vec![];
+            archive
+                .by_name("2021/test.jpg")
+                .unwrap()
+                .read_to_end(&mut data)
+                .unwrap();
+            let exif = exif::Reader::new().read_from_container(&mut io::Cursor::new(&data));
+            assert_eq!(exif.is_err(), strip_metadata);
+            if strip_metadata {
+                let dimensions = image::io::Reader::new(io::Cursor::new(&data))
+                    .with_guessed_format()
+                    .unwrap()
+                    .into_dimensions()
+                    .unwrap();
+                assert_eq!(dimensions, (height, width));
+            } else {
+                assert_eq!(data, fs::read(source).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_zip_date_time() {
+        let time = Local
+            .with_ymd_and_hms(2021, 9, 12, 13, 14, 15)
+            .unwrap()
+            .into();
+        let date_time = to_zip_date_time(time);
+        assert_eq!(date_time.year(), 2021);
+        assert_eq!(date_time.month(), 9);
+        assert_eq!(date_time.day(), 12);
+        assert_eq!(date_time.hour(), 13);
+        assert_eq!(date_time.minute(), 14);
+        assert_eq!(date_time.second(), 15);
+
+        // Dates before 1980 cannot be stored
+        assert_eq!(to_zip_date_time(SystemTime::UNIX_EPOCH).year(), 1980);
     }
 
     #[test]
@@ -516,23 +2737,115 @@ mod test {
             ],
             events: vec![],
             path: PathBuf::from(""),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
-        let file_io = FileSieveIO {};
 
-        sieve(
-            &item_list,
-            Path::new("tests/target"),
-            SieveMethod::Copy,
-            DirectoryNames::YearAndMonth,
-            &file_io,
-            |_: String| {},
+        for collision_policy in [
+            CollisionPolicy::Rename,
+            CollisionPolicy::Overwrite,
+            CollisionPolicy::Skip,
+        ] {
+            let target = PathBuf::from(format!("tests/target/{:?}", collision_policy));
+            let _ = std::fs::remove_dir_all(&target);
+            let file_io = FileSieveIO {
+                collision_policy: collision_policy.clone(),
+                ..FileSieveIO::for_source_operations()
+            };
+
+            sieve(
+                &item_list,
+                &target,
+                SieveMethod::Copy,
+                DirectoryNames::YearAndMonth,
+                &file_io,
+                false,
+                |_: String| {},
+            );
+
+            let directory = target.join("1970-01");
+            for name in ["test.jpg", "test2.JPG", "test3.jpg"] {
+                assert!(directory.join(name).exists());
+            }
+            let renamed = directory.join("test2 (1).JPG");
+            assert_eq!(
+                renamed.exists(),
+                collision_policy == CollisionPolicy::Rename
+            );
+            // The file from the subdirectory is the last one written to the target when overwriting
+            let test2 = fs::read(directory.join("test2.JPG")).unwrap();
+            assert_eq!(
+                test2 == fs::read("tests/subdir/test2.JPG").unwrap(),
+                collision_policy == CollisionPolicy::Overwrite
+            );
+            std::fs::remove_dir_all(&target).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_collision_policy() {
+        let item_list = ItemList {
+            items: vec![FileItem::dummy("test/test1.jpg", 0, true)],
+            events: vec![],
+            path: PathBuf::from("test"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
+        };
+        let mut sieve_io = TestSieveIO::new();
+        sieve_io.existing = vec![
+            PathBuf::from("target/1970-01/test1.jpg"),
+            PathBuf::from("target/1970-01/test1 (1).jpg"),
+            PathBuf::from("target/test.zip"),
+        ];
+
+        let sieve_with = |sieve_io: &TestSieveIO, sieve_method: SieveMethod| {
+            let messages = RefCell::new(vec![]);
+            sieve(
+                &item_list,
+                Path::new("target"),
+                sieve_method,
+                DirectoryNames::YearAndMonth,
+                sieve_io,
+                false,
+                |message: String| messages.borrow_mut().push(message),
+            );
+            messages.into_inner()
+        };
+
+        // Renamed files get the first free number
+        let messages = sieve_with(&sieve_io, SieveMethod::Move);
+        assert!(messages[0].contains("test1.jpg already exists, renamed to"));
+        assert_eq!(
+            sieve_io.renames.borrow()[0].1,
+            PathBuf::from("target/1970-01/test1 (2).jpg")
+        );
+        let messages = sieve_with(&sieve_io, SieveMethod::Zip);
+        assert!(messages[0].contains("test.zip already exists, renamed to"));
+        assert_eq!(
+            sieve_io.archives.borrow()[0],
+            PathBuf::from("target/test (1).zip")
+        );
+
+        sieve_io.reset();
+        sieve_io.collision_policy = CollisionPolicy::Overwrite;
+        let messages = sieve_with(&sieve_io, SieveMethod::Copy);
+        assert!(messages[0].ends_with("test1.jpg already exists, overwritten"));
+        assert_eq!(
+            sieve_io.copies.borrow()[0].1,
+            PathBuf::from("target/1970-01/test1.jpg")
         );
+        assert_eq!(sieve_io.manifests.borrow()[0].1.entries.len(), 1);
 
-        assert!(Path::new("tests/target/1970-01/test.jpg").exists());
-        assert!(!Path::new("tests/target/1970-01/test_.jpg").exists());
-        assert!(Path::new("tests/target/1970-01/test2.JPG").exists());
-        assert!(Path::new("tests/target/1970-01/test2_.JPG").exists());
-        assert!(Path::new("tests/target/1970-01/test3.jpg").exists());
-        assert!(Path::new("tests/target/1970-01/test3_.jpg").exists());
+        // Skipped files are neither copied nor recorded in the manifest
+        sieve_io.reset();
+        sieve_io.collision_policy = CollisionPolicy::Skip;
+        let messages = sieve_with(&sieve_io, SieveMethod::Copy);
+        assert!(messages[0].ends_with("test1.jpg already exists, skipped"));
+        assert!(sieve_io.copies.borrow().is_empty());
+        assert!(sieve_io.manifests.borrow().is_empty());
+        let messages = sieve_with(&sieve_io, SieveMethod::Zip);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].ends_with("test.zip already exists, skipped"));
+        assert!(sieve_io.archives.borrow().is_empty());
     }
 }
@@ -0,0 +1,118 @@
+/// Markers of the JPEG segments containing metadata: APP1 with EXIF or XMP and APP13 with IPTC
+const JPEG_METADATA_MARKERS: [u8; 2] = [0xE1, 0xED];
+
+/// Types of the PNG chunks containing metadata: EXIF, texts and the modification time
+const PNG_METADATA_CHUNKS: [&[u8; 4]; 5] = [b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// Signature at the start of each PNG file
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Removes the metadata like EXIF, XMP and IPTC from a JPEG or PNG image without encoding it again.
+/// Color profiles and all other data needed to display the image are kept.
+/// Returns None if the data is neither a JPEG nor a PNG image.
+pub fn strip_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        Some(strip_jpeg_metadata(data))
+    } else if data.starts_with(&PNG_SIGNATURE) {
+        Some(strip_png_metadata(data))
+    } else {
+        None
+    }
+}
+
+/// Removes the segments with metadata from a JPEG image, which can only be found before the image data
+fn strip_jpeg_metadata(jpeg: &[u8]) -> Vec<u8> {
+    let mut stripped = jpeg[..2].to_vec();
+    let mut position = 2;
+    while position + 4 <= jpeg.len() && jpeg[position] == 0xFF {
+        let marker = jpeg[position + 1];
+        // Image data starts at the start of scan marker, there is no metadata afterwards
+        if marker == 0xDA {
+            break;
+        }
+        let length = u16::from_be_bytes([jpeg[position + 2], jpeg[position + 3]]) as usize;
+        let end = position + 2 + length;
+        if end > jpeg.len() {
+            break;
+        }
+        if !JPEG_METADATA_MARKERS.contains(&marker) {
+            stripped.extend_from_slice(&jpeg[position..end]);
+        }
+        position = end;
+    }
+    stripped.extend_from_slice(&jpeg[position..]);
+    stripped
+}
+
+/// Removes the chunks with metadata from a PNG image
+fn strip_png_metadata(png: &[u8]) -> Vec<u8> {
+    let mut stripped = PNG_SIGNATURE.to_vec();
+    let mut position = PNG_SIGNATURE.len();
+    // Each chunk consists of its length, its type, the data and a checksum
+    while position + 12 <= png.len() {
+        let length = u32::from_be_bytes([
+            png[position],
+            png[position + 1],
+            png[position + 2],
+            png[position + 3],
+        ]) as usize;
+        let end = position + 12 + length;
+        if end > png.len() {
+            break;
+        }
+        if !PNG_METADATA_CHUNKS
+            .iter()
+            .any(|chunk_type| png[position + 4..position + 8] == chunk_type[..])
+        {
+            stripped.extend_from_slice(&png[position..end]);
+        }
+        position = end;
+    }
+    stripped.extend_from_slice(&png[position..]);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_strip_jpeg_metadata() {
+        let jpeg = std::fs::read("tests/test.jpg").unwrap();
+        assert!(exif::Reader::new()
+            .read_from_container(&mut Cursor::new(&jpeg))
+            .is_ok());
+
+        let stripped = strip_metadata(&jpeg).unwrap();
+        assert!(stripped.len() < jpeg.len());
+        assert!(exif::Reader::new()
+            .read_from_container(&mut Cursor::new(&stripped))
+            .is_err());
+        // The image itself is unchanged
+        assert_eq!(
+            image::load_from_memory(&stripped).unwrap(),
+            image::load_from_memory(&jpeg).unwrap()
+        );
+        // Stripping again changes nothing
+        assert_eq!(strip_metadata(&stripped).unwrap(), stripped);
+    }
+
+    #[test]
+    fn test_strip_png_metadata() {
+        let png = std::fs::read("tests/test.png").unwrap();
+        let stripped = strip_metadata(&png).unwrap();
+        assert!(png.windows(4).any(|window| window == b"eXIf"));
+        for chunk_type in PNG_METADATA_CHUNKS {
+            assert!(!stripped.windows(4).any(|window| window == chunk_type));
+        }
+        // The color profile is kept and the image is unchanged
+        assert!(stripped.windows(4).any(|window| window == b"iCCP"));
+        assert_eq!(
+            image::load_from_memory(&stripped).unwrap(),
+            image::load_from_memory(&png).unwrap()
+        );
+
+        assert!(strip_metadata(b"no image").is_none());
+    }
+}
@@ -4,6 +4,7 @@ extern crate image_sieve;
 extern crate backtrace;
 
 use std::io::Write;
+use std::path::PathBuf;
 use std::{panic, fs::File};
 use backtrace::Backtrace;
 
@@ -23,7 +24,20 @@ fn main() {
         
         prev(panic_info);
     }));
-    let main_window = main_window::MainWindow::new();
+    // A folder or project file can be passed to open it instead of the last source directory and
+    // --config-dir <folder> stores settings, logs and projects in a custom folder
+    let mut source = None;
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config-dir" {
+            if let Some(config_dir) = args.next() {
+                main_window::set_config_dir(PathBuf::from(config_dir));
+            }
+        } else if source.is_none() {
+            source = Some(PathBuf::from(arg));
+        }
+    }
+    let main_window = main_window::MainWindow::with_source(source);
 
     main_window.run();
 }
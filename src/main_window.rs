@@ -3,22 +3,36 @@
 extern crate nfd;
 extern crate slint;
 
-use slint::{Model, ModelRc, SharedString};
+use slint::winit_030::winit::event::WindowEvent;
+use slint::winit_030::{WinitWindowAccessor, WinitWindowEventResult};
+use slint::{ComponentHandle, Model, ModelRc, SharedString};
 use std::cell::RefCell;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 use crate::controller::events_controller::EventsController;
 use crate::controller::items_controller::ItemsController;
-use crate::item_sort_list::ItemList;
+use crate::item_sort_list::{
+    get_nested_directory, FileSieveIO, ItemList, ProjectSettings, SieveMethod, EVENT_DATE_FORMAT,
+    FILE_PROGRESS_PREFIX,
+};
+use crate::misc::combo_values::{
+    COLLISION_POLICIES, DARK_MODES, DIRECTORY_NAMES, HASH_GRIDS, IMAGE_DISPLAYS, SENSITIVITIES,
+    SIEVE_METHODS, SORT_BY, SORT_DIRECTIONS, TIME_ZONES,
+};
 use crate::misc::images::get_empty_image;
-use crate::persistence::json::{get_project_filename, get_settings_filename, JsonPersistence, self};
-use crate::persistence::model_to_enum::model_to_enum;
-use crate::persistence::settings::Settings;
-use crate::synchronize::Synchronizer;
+use crate::misc::logging;
+use crate::misc::translations::{self, tr, tr_format};
+use crate::persistence::json::{
+    self, get_log_filename, get_project_filename, get_settings_filename, JsonPersistence,
+};
+use crate::persistence::model_to_enum::index_to_enum;
+use crate::persistence::settings::{convert_jpeg_quality, Settings};
+use crate::synchronize::{self, Synchronizer};
 
 #[allow(
     clippy::all,
@@ -41,6 +55,9 @@ pub struct MainWindow {
     events_controller: Rc<RefCell<EventsController>>,
     sieve_result_model: Rc<slint::VecModel<SieveResult>>,
     synchronizer: Rc<Synchronizer>,
+    auto_save_timer: Rc<slint::Timer>,
+    /// Global settings replaced by the settings of the open project, None if the project uses the global settings
+    global_settings: Rc<RefCell<Option<ProjectSettings>>>,
 }
 
 impl Default for MainWindow {
@@ -61,9 +78,45 @@ impl Debug for MainWindow {
 impl MainWindow {
     /// Creates a new main window and initializes it from saved settings
     pub fn new() -> Self {
+        Self::with_source(None)
+    }
+
+    /// Creates a new main window that opens the given folder or project file instead of the last source directory
+    pub fn with_source(source: Option<PathBuf>) -> Self {
         // Load settings and item list
-        let settings: Settings =
+        let mut settings: Settings =
             JsonPersistence::load(&get_settings_filename()).unwrap_or_else(Settings::new);
+        logging::init(&get_log_filename(), &settings.log_level);
+        log::info!("ImageSieve {} started", env!("CARGO_PKG_VERSION"));
+        // Construct main window, the translations of the user interface are selected for it
+        let image_sieve = ImageSieve::new().unwrap();
+        translations::set_language(&settings.language);
+        settings.prune_recent_projects();
+
+        let mut source_hint = None;
+        if let Some(source) = source {
+            match json::get_project_folder(&source) {
+                Some(folder) => {
+                    let folder = std::path::absolute(&folder).unwrap_or(folder);
+                    settings.source_directory = folder.to_string_lossy().to_string();
+                }
+                None => {
+                    // Do not open the last source directory either, but keep it in the settings
+                    log::warn!("Invalid source {} given", source.display());
+                    source_hint = Some(tr_format(
+                        "⚠ {} cannot be opened. Pass a folder with images and videos or its image_sieve.json project file.",
+                        &[&source.display()],
+                    ));
+                }
+            }
+        } else if !settings.open_last_project && !settings.source_directory.is_empty() {
+            // Start without a project, e.g. on shared machines, and do not show the last source directory
+            log::info!("Starting without opening the last source directory");
+            settings.source_directory.clear();
+            source_hint = Some(String::from(tr(
+                "📂 Open a folder with images and videos to start",
+            )));
+        }
 
         let item_list = ItemList::new();
 
@@ -73,13 +126,13 @@ impl MainWindow {
         let items_controller = Rc::new(RefCell::new(ItemsController::new(item_list.clone())));
         let sieve_result_model = Rc::new(slint::VecModel::<SieveResult>::default());
 
-        // Construct main window
-        let image_sieve = ImageSieve::new().unwrap();
-
         let synchronizer = Synchronizer::new(item_list.clone(), &image_sieve);
-        if !settings.source_directory.is_empty() {
+        let open_source = source_hint.is_none() && !settings.source_directory.is_empty();
+        if open_source {
+            let source_directory = settings.source_directory.clone();
+            settings.add_recent_project(&source_directory);
             // Start synchronization in a background thread
-            synchronizer.scan_path(Path::new(&settings.source_directory));
+            synchronizer.scan_path(Path::new(&settings.source_directory), settings.clone());
         }
 
         let main_window = Self {
@@ -89,6 +142,8 @@ impl MainWindow {
             events_controller,
             sieve_result_model,
             synchronizer: Rc::new(synchronizer),
+            auto_save_timer: Rc::new(slint::Timer::default()),
+            global_settings: Rc::new(RefCell::new(None)),
         };
 
         // Set initial values
@@ -96,8 +151,20 @@ impl MainWindow {
         main_window
             .window
             .set_window_title(SharedString::from("ImageSieve v") + version);
+        // Fill the combo boxes before the settings select their values
+        combo_values_to_window(&main_window.window);
         settings.to_window(&main_window.window);
-        if settings.source_directory.is_empty() {
+        main_window
+            .events_controller
+            .borrow_mut()
+            .set_sort_events(settings.sort_events);
+        update_target_hint(&main_window.window);
+        if let Some(source_hint) = source_hint {
+            main_window
+                .window
+                .set_source_hint(SharedString::from(source_hint));
+        }
+        if !open_source {
             main_window.window.set_loading(false);
             main_window.window.set_calculating_similarities(false);
         }
@@ -114,6 +181,18 @@ impl MainWindow {
                 .get_list_model()
                 .into(),
         );
+        main_window.window.set_overview_model(
+            main_window
+                .items_controller
+                .borrow()
+                .get_overview_model()
+                .into(),
+        );
+        main_window
+            .items_controller
+            .borrow()
+            .get_overview_model()
+            .set_window(main_window.window.as_weak());
         main_window.window.set_similar_images_model(
             main_window
                 .items_controller
@@ -124,11 +203,25 @@ impl MainWindow {
         main_window
             .window
             .set_events_model(main_window.events_controller.borrow().get_model().into());
+        main_window.window.set_histogram_model(
+            main_window
+                .events_controller
+                .borrow()
+                .get_histogram_model()
+                .into(),
+        );
         main_window
             .window
             .set_sieve_result_model(main_window.sieve_result_model.clone().into());
 
         main_window.setup_callbacks();
+        main_window.setup_drop_handler();
+        start_auto_save(
+            &main_window.auto_save_timer,
+            settings.auto_save_minutes,
+            main_window.item_list.clone(),
+            main_window.window.as_weak(),
+        );
 
         main_window
     }
@@ -139,15 +232,20 @@ impl MainWindow {
 
         self.synchronizer.stop();
 
-        // Save settings when program exits
-        let settings = Settings::from_window(&self.window);
+        // Save settings when program exits, without the settings of the project
+        store_project_settings(&self.window, &self.item_list);
+        let mut settings = Settings::from_window(&self.window);
+        if let Some(global_settings) = self.global_settings.borrow().as_ref() {
+            settings.set_project_settings(global_settings);
+        }
         JsonPersistence::save(&get_settings_filename(), &settings);
 
         // and save item list
         let item_list = self.item_list.lock().unwrap();
         if !item_list.items.is_empty() || !item_list.events.is_empty() {
-            JsonPersistence::save(&get_project_filename(&item_list.path), &item_list.clone());
+            json::save_project(&item_list);
         }
+        json::unlock_project(&item_list.path);
     }
 
     /// Setup slint GUI callbacks
@@ -158,12 +256,73 @@ impl MainWindow {
             let window_weak = self.window.as_weak();
 
             move |i: i32| {
+                let window = window_weak.unwrap();
+                if !window.get_hold_adjustment() {
+                    window.set_preview_brightness(0.0);
+                    window.set_preview_contrast(0.0);
+                }
                 items_controller
                     .borrow_mut()
                     .selected_list_item(i as usize, window_weak.clone());
             }
         });
 
+        self.window.on_update_comparison({
+            // Another similar image is shown or an image of the group was loaded
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                let items_controller = items_controller.borrow();
+                items_controller.cache_metadata(window.get_current_image().local_index);
+                items_controller.update_comparison(&window);
+            }
+        });
+
+        self.window.on_adjust_preview({
+            // Brightness or contrast of the preview was changed
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                items_controller
+                    .borrow()
+                    .adjust_current_image(&window_weak.unwrap());
+            }
+        });
+
+        self.window.on_get_metadata({
+            // Metadata panel shown for an item
+            let items_controller = self.items_controller.clone();
+
+            move |i: i32| {
+                let metadata = items_controller.borrow().get_metadata(i);
+                ModelRc::from(Rc::new(slint::VecModel::from(metadata)))
+            }
+        });
+
+        self.window.on_get_loupe({
+            // Loupe shown while hovering the current image
+            let items_controller = self.items_controller.clone();
+
+            move |i: i32, x: f32, y: f32| items_controller.borrow().get_loupe(i, x, y)
+        });
+
+        self.window.on_get_video_frame_count({
+            // Number of frames of the shown video for the scrub bar
+            let items_controller = self.items_controller.clone();
+
+            move |i: i32| items_controller.borrow().get_video_frame_count(i)
+        });
+
+        self.window.on_get_video_frame({
+            // Frame of the shown video selected with the scrub bar
+            let items_controller = self.items_controller.clone();
+
+            move |i: i32, frame: i32| items_controller.borrow().get_video_frame(i, frame)
+        });
+
         self.window.on_sieve({
             // Sieve pressed - perform selected action
             let window_weak = self.window.as_weak();
@@ -171,21 +330,302 @@ impl MainWindow {
             let sieve_result_model = self.sieve_result_model.clone();
 
             move || {
-                sieve(
-                    &item_list.lock().unwrap(),
+                sieve(&item_list, window_weak.clone(), sieve_result_model.clone());
+            }
+        });
+
+        self.window.on_finalize({
+            // Finalize pressed and confirmed - delete all discarded items of the project
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+            let sieve_result_model = self.sieve_result_model.clone();
+
+            move || {
+                sieve_with_method(
+                    &item_list,
+                    SieveMethod::Delete,
                     window_weak.clone(),
                     sieve_result_model.clone(),
                 );
             }
         });
 
+        self.window.on_undo_sieve({
+            // Undo pressed and confirmed - revert the last sieve to the target directory
+            let window_weak = self.window.as_weak();
+            let sieve_result_model = self.sieve_result_model.clone();
+
+            move || {
+                undo_sieve(window_weak.clone(), sieve_result_model.clone());
+            }
+        });
+
+        self.window.on_compare_sieve({
+            // Compare pressed - show the differences between the kept items and the last sieve
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+            let sieve_result_model = self.sieve_result_model.clone();
+
+            move || {
+                compare_sieve(&window_weak.unwrap(), &item_list, &sieve_result_model);
+            }
+        });
+
+        self.window.on_finalize_summary({
+            // Finalize pressed - summarize what will happen before asking for confirmation
+            let item_list = self.item_list.clone();
+
+            move || -> SharedString {
+                let (kept, discarded) = item_list.lock().unwrap().count_take_over();
+                SharedString::from(tr_format(
+                    "Finalizing the project keeps {} and permanently deletes {} discarded images and videos in the source directory.",
+                    &[&kept, &discarded],
+                ))
+            }
+        });
+
+        self.window.on_export_statistics({
+            // Export of the project statistics requested, ask for the file to write
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+
+            move || -> SharedString {
+                let source_directory = window_weak.unwrap().get_source_directory();
+                let mut path =
+                    match nfd::open_save_dialog(Some("csv"), get_folder(&source_directory)) {
+                        Ok(nfd::Response::Okay(path)) => PathBuf::from(path),
+                        _ => return SharedString::default(),
+                    };
+                if path.extension().is_none() {
+                    path.set_extension("csv");
+                }
+                let csv = item_list.lock().unwrap().get_project_statistics().to_csv();
+                match std::fs::write(&path, csv) {
+                    Ok(()) => SharedString::from(tr_format("Exported to {}", &[&path.display()])),
+                    Err(e) => {
+                        log::warn!("Error exporting statistics to {}: {}", path.display(), e);
+                        SharedString::from(tr_format(
+                            "Error exporting to {}: {}",
+                            &[&path.display(), &e],
+                        ))
+                    }
+                }
+            }
+        });
+
         self.window.on_set_take_over({
             // Image was clicked, toggle take over state
             let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
 
             move |i: i32, take_over: bool| -> SharedString {
                 // Change the state of the SortImage in the items_model
-                items_controller.borrow_mut().set_take_over(i, take_over)
+                let description = items_controller.borrow_mut().set_take_over(i, take_over);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+                description
+            }
+        });
+
+        self.window.on_set_unsure({
+            // Image was marked as unsure or the mark was removed
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |i: i32, unsure: bool| -> SharedString {
+                let description = items_controller.borrow_mut().set_unsure(i, unsure);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+                description
+            }
+        });
+
+        self.window.on_set_pending_delete({
+            // Image was tagged for deletion or the tag was removed
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |i: i32, pending_delete: bool| -> SharedString {
+                let description = items_controller
+                    .borrow_mut()
+                    .set_pending_delete(i, pending_delete);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+                description
+            }
+        });
+
+        self.window.on_set_favorite({
+            // Image was marked as favorite or the mark was removed
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |i: i32, favorite: bool| -> SharedString {
+                let description = items_controller.borrow_mut().set_favorite(i, favorite);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                description
+            }
+        });
+
+        self.window.on_untag_pending_delete({
+            // Deletion tag removed in the list of tagged items, show the change in the current image as well
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move |i: i32| {
+                items_controller.borrow_mut().set_pending_delete(i, false);
+                window_weak.unwrap().invoke_pending_deletes_changed();
+            }
+        });
+
+        self.window.on_pending_deletes_changed({
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                let mut items_controller = items_controller.borrow_mut();
+                items_controller.update_list_model();
+                items_controller.selected_list_item(
+                    window.get_current_list_item() as usize,
+                    window_weak.clone(),
+                );
+                update_statistics(&window, &item_list);
+                update_pending_deletes(&window_weak, &items_controller, &item_list);
+            }
+        });
+
+        self.window.on_delete_pending({
+            // Deletion of the tagged items confirmed
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+            let sieve_result_model = self.sieve_result_model.clone();
+
+            move || {
+                delete_pending(&item_list, window_weak.clone(), sieve_result_model.clone());
+            }
+        });
+
+        self.window.on_cycle_group_take_over({
+            // Keep all, keep the best or discard all items of the shown group
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                items_controller.borrow_mut().cycle_group_take_over();
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+            }
+        });
+
+        self.window.on_set_group_keep_all({
+            // Keep all items of the shown group permanently or remove this decision
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |keep_all| {
+                items_controller.borrow_mut().set_group_keep_all(keep_all);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+            }
+        });
+
+        self.window.on_rotate_image({
+            // Rotate the shown image clockwise
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move |i: i32| {
+                items_controller
+                    .borrow_mut()
+                    .rotate_items(&[i], 90, &window_weak.unwrap());
+            }
+        });
+
+        self.window.on_set_ignore_orientation({
+            // Display the shown image with or without applying its orientation
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move |i: i32, ignore_orientation: bool| {
+                items_controller.borrow_mut().set_ignore_orientation(
+                    i,
+                    ignore_orientation,
+                    &window_weak.unwrap(),
+                );
+            }
+        });
+
+        self.window.on_rotate_group({
+            // Rotate all images of the shown group clockwise
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let local_indices = items_controller.borrow().get_group_local_indices();
+                items_controller.borrow_mut().rotate_items(
+                    &local_indices,
+                    90,
+                    &window_weak.unwrap(),
+                );
+            }
+        });
+
+        self.window.on_next_undecided({
+            // Jump to the next listed item that was not decided yet
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                let row = items_controller
+                    .borrow()
+                    .get_next_undecided_row(window.get_current_list_item() as usize);
+                if let Some(row) = row {
+                    window.set_current_list_item(row as i32);
+                    window.invoke_item_selected(row as i32);
+                }
+            }
+        });
+
+        self.window.on_next_group({
+            // Jump to the first item of the next group of similar items
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                let row = items_controller
+                    .borrow()
+                    .get_next_group_row(window.get_current_list_item() as usize);
+                if let Some(row) = row {
+                    window.set_current_list_item(row as i32);
+                    window.invoke_item_selected(row as i32);
+                }
+            }
+        });
+
+        self.window.on_previous_group({
+            // Jump to the first item of the previous group of similar items
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                let row = items_controller
+                    .borrow()
+                    .get_previous_group_row(window.get_current_list_item() as usize);
+                if let Some(row) = row {
+                    window.set_current_list_item(row as i32);
+                    window.invoke_item_selected(row as i32);
+                }
             }
         });
 
@@ -196,32 +636,56 @@ impl MainWindow {
             let item_list = self.item_list.clone();
             let window_weak = self.window.as_weak();
             let synchronizer = self.synchronizer.clone();
+            let global_settings = self.global_settings.clone();
 
             move || {
                 if let Ok(nfd::Response::Okay(folder)) =
                     nfd::open_pick_folder(get_folder(&window_weak.unwrap().get_source_directory()))
                 {
-                    {
-                        // Save current item list
-                        let item_list = item_list.lock().unwrap();
-                        if !item_list.items.is_empty() {
-                            JsonPersistence::save(
-                                &get_project_filename(&item_list.path),
-                                &item_list.clone(),
-                            );
-                        }
-                    }
-
-                    items_controller.borrow_mut().clear_list();
-                    events_controller.borrow_mut().clear();
+                    open_source_directory(
+                        Path::new(&folder),
+                        &item_list,
+                        &items_controller,
+                        &events_controller,
+                        &synchronizer,
+                        &window_weak,
+                        &global_settings,
+                    );
+                }
+            }
+        });
 
-                    // Synchronize in a background thread
-                    window_weak.unwrap().set_loading(true);
-                    synchronizer.scan_path(Path::new(&folder));
+        self.window.on_open_recent_project({
+            // A recent project was selected, open it again unless its folder disappeared
+            let events_controller = self.events_controller.clone();
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+            let synchronizer = self.synchronizer.clone();
+            let global_settings = self.global_settings.clone();
 
-                    window_weak
-                        .unwrap()
-                        .set_source_directory(SharedString::from(folder));
+            move |folder: SharedString| {
+                let window = window_weak.unwrap();
+                let folder = Path::new(folder.as_str());
+                if folder.is_dir() {
+                    open_source_directory(
+                        folder,
+                        &item_list,
+                        &items_controller,
+                        &events_controller,
+                        &synchronizer,
+                        &window_weak,
+                        &global_settings,
+                    );
+                } else {
+                    log::warn!("Recent project {} does not exist anymore", folder.display());
+                    let mut settings = Settings::from_window(&window);
+                    settings.prune_recent_projects();
+                    settings.recent_projects_to_window(&window);
+                    window.set_source_hint(SharedString::from(tr_format(
+                        "⚠ {} does not exist anymore",
+                        &[&folder.display()],
+                    )));
                 }
             }
         });
@@ -229,6 +693,7 @@ impl MainWindow {
         self.window.on_browse_target({
             // Sieve target path was changed
             let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
 
             move || {
                 if let Ok(nfd::Response::Okay(folder)) =
@@ -237,6 +702,28 @@ impl MainWindow {
                     window_weak
                         .unwrap()
                         .set_target_directory(SharedString::from(folder));
+                    update_target_hint(&window_weak.unwrap());
+                    store_project_settings(&window_weak.unwrap(), &item_list);
+                }
+            }
+        });
+
+        self.window.on_project_settings_toggled({
+            // Settings of the project were enabled or disabled
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+            let global_settings = self.global_settings.clone();
+
+            move |enabled| {
+                let window = window_weak.unwrap();
+                if enabled {
+                    // The current settings become the settings of the project and stay the global settings as well
+                    let project_settings = Settings::from_window(&window).get_project_settings();
+                    *global_settings.borrow_mut() = Some(project_settings.clone());
+                    item_list.lock().unwrap().project_settings = Some(project_settings);
+                } else {
+                    item_list.lock().unwrap().project_settings = None;
+                    restore_global_settings(&window, &global_settings);
                 }
             }
         });
@@ -247,12 +734,17 @@ impl MainWindow {
             let events_controller = self.events_controller.clone();
             let items_controller = self.items_controller.clone();
             let synchronizer = self.synchronizer.clone();
+            let item_list = self.item_list.clone();
+            let global_settings = self.global_settings.clone();
 
             move || {
                 let window = window_weak.unwrap();
+                apply_project_settings(&window, &item_list, &global_settings);
                 let filters = window.get_filters();
                 // First fill the list of items
                 let num_items = items_controller.borrow_mut().populate_list_model(&filters);
+                update_statistics(&window, &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
 
                 // Now fill the events model
                 events_controller.borrow_mut().synchronize();
@@ -264,8 +756,16 @@ impl MainWindow {
                 } else {
                     let empty_image = SortItem {
                         image: get_empty_image(),
+                        thumbnail: get_empty_image(),
                         take_over: true,
-                        text: SharedString::from("No images found"),
+                        unsure: false,
+                        pending_delete: false,
+                        favorite: false,
+                        text: SharedString::from(tr("No images found")),
+                        is_video: false,
+                        orientation_mismatch: false,
+                        ignore_orientation: false,
+                        long_side: 0,
                         local_index: 0,
                     };
                     window.set_current_image(empty_image);
@@ -295,15 +795,35 @@ impl MainWindow {
                 }
                 if finished {
                     window.set_calculating_similarities(false);
+                    window.set_similarities_progress(SharedString::default());
+                    // The groups are known now, so the thumbnails of the similar items can be prepared
+                    items_controller.borrow().warm_thumbnails(
+                        window.get_current_list_item() as usize,
+                        window_weak.clone(),
+                    );
                 }
             }
         });
 
+        self.window.on_cancel_thumbnails({
+            let items_controller = self.items_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                items_controller.borrow().cancel_warming();
+                window_weak
+                    .unwrap()
+                    .set_thumbnails_progress(SharedString::default());
+            }
+        });
+
         self.window.on_add_event({
             // New event was added, return true if the dates are ok
             let events_controller = self.events_controller.clone();
             let items_controller = self.items_controller.clone();
-
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
             move |name: SharedString,
                   start_date: SharedString,
                   end_date: SharedString|
@@ -314,6 +834,7 @@ impl MainWindow {
                         .add_event(&name, &start_date, &end_date);
                 if result.is_empty() {
                     items_controller.borrow_mut().update_list_model();
+                    update_statistics(&window_weak.unwrap(), &item_list);
                 }
                 result
             }
@@ -322,6 +843,8 @@ impl MainWindow {
         self.window.on_update_event({
             let events_controller = self.events_controller.clone();
             let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
             move |index: i32,
                   name: SharedString,
                   start_date: SharedString,
@@ -335,19 +858,161 @@ impl MainWindow {
                 );
                 if result.is_empty() {
                     items_controller.borrow_mut().update_list_model();
+                    update_statistics(&window_weak.unwrap(), &item_list);
                 }
                 result
             }
         });
 
+        self.window.on_browse_event_target({
+            // Target directory of an event shall be picked
+            let window_weak = self.window.as_weak();
+            let events_controller = self.events_controller.clone();
+            move |index: i32| {
+                let event = events_controller
+                    .borrow()
+                    .get_model()
+                    .row_data(index as usize);
+                // Start browsing in the event's target directory or the global one
+                let start_directory = match event {
+                    Some(event) if !event.target_directory.is_empty() => event.target_directory,
+                    _ => window_weak.unwrap().get_target_directory(),
+                };
+                if let Ok(nfd::Response::Okay(folder)) =
+                    nfd::open_pick_folder(get_folder(&start_directory))
+                {
+                    events_controller
+                        .borrow_mut()
+                        .set_target_directory(index, &folder);
+                }
+            }
+        });
+
+        self.window.on_set_event_target({
+            let events_controller = self.events_controller.clone();
+            move |index: i32, target_directory: SharedString| {
+                events_controller
+                    .borrow_mut()
+                    .set_target_directory(index, &target_directory);
+            }
+        });
+
         self.window.on_remove_event({
             // Event was removed
             let events_controller = self.events_controller.clone();
             let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
 
             move |index| {
                 events_controller.borrow_mut().remove_event(index);
                 items_controller.borrow_mut().update_list_model();
+                update_statistics(&window_weak.unwrap(), &item_list);
+            }
+        });
+
+        self.window.on_move_event({
+            // Event was moved up or down in the list of events
+            let events_controller = self.events_controller.clone();
+
+            move |index: i32, new_index: i32| {
+                events_controller.borrow_mut().move_event(index, new_index);
+            }
+        });
+
+        self.window.on_sort_events_changed({
+            // Sorting the events by their start date was turned on or off
+            let events_controller = self.events_controller.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let sort_events = window_weak.unwrap().get_sort_events();
+                events_controller.borrow_mut().set_sort_events(sort_events);
+            }
+        });
+
+        self.window.on_events_from_folders_changed({
+            // Creating events from folder names was turned on or off, existing events are kept when turned off
+            let events_controller = self.events_controller.clone();
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                if window.get_events_from_folders() {
+                    events_controller.borrow_mut().create_folder_events();
+                    items_controller.borrow_mut().update_list_model();
+                    update_statistics(&window, &item_list);
+                }
+            }
+        });
+
+        self.window.on_merge_events({
+            // Two events shall be merged into one
+            let events_controller = self.events_controller.clone();
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |first: i32, second: i32| -> SharedString {
+                let result = events_controller.borrow_mut().merge_events(first, second);
+                if result.is_empty() {
+                    items_controller.borrow_mut().update_list_model();
+                    update_statistics(&window_weak.unwrap(), &item_list);
+                }
+                result
+            }
+        });
+
+        self.window.on_set_event_take_over({
+            // Keep or discard all items of an event
+            let events_controller = self.events_controller.clone();
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |index: i32, take_over: bool| -> SharedString {
+                let result = events_controller
+                    .borrow_mut()
+                    .set_take_over(index, take_over);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+                let mut items_controller = items_controller.borrow_mut();
+                if items_controller.update_list_model() {
+                    // Refresh the take over state of the current and the similar images
+                    let window = window_weak.unwrap();
+                    items_controller.selected_list_item(
+                        window.get_current_list_item() as usize,
+                        window_weak.clone(),
+                    );
+                }
+                result
+            }
+        });
+
+        self.window.on_keep_by_size_in_groups({
+            // Keep only the largest or the smallest item of every group of similar items
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move |keep_largest: bool| -> SharedString {
+                let result = items_controller
+                    .borrow_mut()
+                    .keep_by_size_in_groups(keep_largest);
+                update_statistics(&window_weak.unwrap(), &item_list);
+                update_pending_deletes(&window_weak, &items_controller.borrow(), &item_list);
+                let mut items_controller = items_controller.borrow_mut();
+                if items_controller.update_list_model() {
+                    // Refresh the take over state of the current and the similar images
+                    let window = window_weak.unwrap();
+                    items_controller.selected_list_item(
+                        window.get_current_list_item() as usize,
+                        window_weak.clone(),
+                    );
+                }
+                result
             }
         });
 
@@ -356,13 +1021,47 @@ impl MainWindow {
             move |i: i32| {
                 let item_list = item_list.lock().unwrap();
                 let item = &item_list.items[i as usize];
-                opener::open(&item.path).ok();
+                if let Err(e) = opener::open(&item.path) {
+                    log::warn!("Error opening {}: {}", item.path.display(), e);
+                }
+            }
+        });
+
+        self.window.on_copy_path({
+            // Copy the path of an item to the clipboard, the clipboard is kept to keep serving its contents on Linux
+            let item_list = self.item_list.clone();
+            let clipboard: RefCell<Option<arboard::Clipboard>> = RefCell::new(None);
+
+            move |i: i32| -> SharedString {
+                let path = item_list.lock().unwrap().items[i as usize]
+                    .path
+                    .to_string_lossy()
+                    .to_string();
+                let mut clipboard = clipboard.borrow_mut();
+                if clipboard.is_none() {
+                    *clipboard = arboard::Clipboard::new()
+                        .map_err(|e| log::warn!("Error opening clipboard: {}", e))
+                        .ok();
+                }
+                match clipboard
+                    .as_mut()
+                    .map(|clipboard| clipboard.set_text(&path))
+                {
+                    Some(Ok(_)) => SharedString::from(tr("📋 Path copied to clipboard")),
+                    Some(Err(e)) => {
+                        log::warn!("Error copying {} to clipboard: {}", path, e);
+                        SharedString::from(tr("Path could not be copied to clipboard"))
+                    }
+                    None => SharedString::from(tr("Path could not be copied to clipboard")),
+                }
             }
         });
 
         self.window.on_open_url({
             move |url: SharedString| {
-                opener::open(url.as_str()).ok();
+                if let Err(e) = opener::open(url.as_str()) {
+                    log::warn!("Error opening {}: {}", url, e);
+                }
             }
         });
 
@@ -370,19 +1069,158 @@ impl MainWindow {
             // Browse source was clicked, select new path
             let window_weak = self.window.as_weak();
             let synchronizer = self.synchronizer.clone();
+            let item_list = self.item_list.clone();
 
             move || {
                 let window = window_weak.unwrap();
+                store_project_settings(&window, &item_list);
                 // Synchronize in a background thread
                 window.set_calculating_similarities(true);
                 synchronizer.calculate_similarities(Settings::from_window(&window));
             }
         });
 
+        self.window.on_use_project_anyway({
+            // Project is locked by another instance, but shall be used and saved by this instance
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+
+            move || {
+                json::force_lock_project(&item_list.lock().unwrap().path);
+                let window = window_weak.unwrap();
+                window.set_project_in_use(false);
+                window.set_source_hint(SharedString::new());
+            }
+        });
+
+        self.window.on_preview_thresholds({
+            // Preview of the groups with different thresholds requested
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+
+            move || {
+                let window = window_weak.unwrap();
+                window.set_previewing_thresholds(true);
+                let consecutive_only = Settings::from_window(&window).consecutive_only;
+                let item_list = item_list.lock().unwrap().clone();
+                synchronize::preview_thresholds(item_list, consecutive_only, window_weak.clone());
+            }
+        });
+
+        self.window.on_find_duplicates({
+            // Report of the items with the same content in different folders requested
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+
+            move || {
+                window_weak.unwrap().set_finding_duplicates(true);
+                let item_list = item_list.lock().unwrap().clone();
+                synchronize::find_duplicates(item_list, window_weak.clone());
+            }
+        });
+
+        self.window.on_event_time_zone_changed({
+            // Time zone for events was changed, update the event assignment of all items
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+            let items_controller = self.items_controller.clone();
+            let events_controller = self.events_controller.clone();
+
+            move || {
+                let window = window_weak.unwrap();
+                item_list.lock().unwrap().event_time_zone =
+                    Settings::from_window(&window).event_time_zone;
+                events_controller.borrow_mut().synchronize_histogram();
+                if items_controller.borrow_mut().update_list_model() {
+                    window.invoke_item_selected(window.get_current_list_item());
+                }
+            }
+        });
+
+        self.window.on_log_level_changed({
+            // Log level was changed, apply it immediately
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let window = window_weak.unwrap();
+                logging::set_level(&window.get_log_level());
+            }
+        });
+
+        self.window.on_language_changed({
+            // Language was changed, translate the window immediately
+            let window_weak = self.window.as_weak();
+            let item_list = self.item_list.clone();
+
+            move || {
+                let window = window_weak.unwrap();
+                translations::set_language(&window.get_language());
+                translate_combo_values(&window);
+                update_statistics(&window, &item_list);
+            }
+        });
+
+        self.window.on_update_cache_statistics({
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let statistics = items_controller.borrow().cache_statistics();
+                log::debug!("Image cache: {}", statistics);
+                let window = window_weak.unwrap();
+                window.set_cache_statistics(SharedString::from(statistics.to_string()));
+                window.set_storage_statistics(SharedString::from(storage_statistics(
+                    &item_list.lock().unwrap(),
+                )));
+            }
+        });
+
+        self.window.on_clear_cache({
+            // Remove cached images and hashes, the hashes are calculated again when checking the similarities
+            let items_controller = self.items_controller.clone();
+            let item_list = self.item_list.clone();
+            let window_weak = self.window.as_weak();
+
+            move || {
+                let cleared = items_controller.borrow().clear_cache();
+                log::info!("Image cache and {} stored hashes cleared", cleared);
+                let window = window_weak.unwrap();
+                if !item_list.lock().unwrap().items.is_empty() {
+                    window.invoke_recheck_similarities();
+                }
+                window.invoke_update_cache_statistics();
+            }
+        });
+
+        self.window.on_auto_save_changed({
+            // Auto-save interval was changed, restart the timer
+            let window_weak = self.window.as_weak();
+            let auto_save_timer = self.auto_save_timer.clone();
+            let item_list = self.item_list.clone();
+
+            move || {
+                let settings = Settings::from_window(&window_weak.unwrap());
+                start_auto_save(
+                    &auto_save_timer,
+                    settings.auto_save_minutes,
+                    item_list.clone(),
+                    window_weak.clone(),
+                );
+            }
+        });
+
         self.window.on_cancel_loading({
             let synchronizer = self.synchronizer.clone();
             move || {
-                synchronizer.stop();
+                synchronizer.cancel();
+            }
+        });
+
+        self.window.on_cancel_similarities({
+            let synchronizer = self.synchronizer.clone();
+            move || {
+                synchronizer.cancel();
             }
         });
 
@@ -398,6 +1236,17 @@ impl MainWindow {
             }
         });
 
+        self.window.on_first_item_of_day({
+            let items_controller = self.items_controller.clone();
+
+            move |date| {
+                items_controller
+                    .borrow()
+                    .get_first_list_item_of_day(&date)
+                    .map_or(-1, |row| row as i32)
+            }
+        });
+
         self.window.on_fill_event_cb({
             let items_controller = self.items_controller.clone();
             let window_weak = self.window.as_weak();
@@ -408,77 +1257,617 @@ impl MainWindow {
             }
         });
     }
+
+    /// Setup handling of folders that are dropped onto the window
+    fn setup_drop_handler(&self) {
+        let events_controller = self.events_controller.clone();
+        let items_controller = self.items_controller.clone();
+        let item_list = self.item_list.clone();
+        let window_weak = self.window.as_weak();
+        let synchronizer = self.synchronizer.clone();
+        let global_settings = self.global_settings.clone();
+
+        self.window
+            .window()
+            .on_winit_window_event(move |_, event| match event {
+                WindowEvent::DroppedFile(path) => {
+                    let window = window_weak.unwrap();
+                    // A drop of several folders sends one event per folder, only the first one is opened
+                    if !window.get_loading() && !window.get_read_only() {
+                        if path.is_dir() {
+                            window.set_source_hint(SharedString::new());
+                            open_source_directory(
+                                path,
+                                &item_list,
+                                &items_controller,
+                                &events_controller,
+                                &synchronizer,
+                                &window_weak,
+                                &global_settings,
+                            );
+                        } else {
+                            window.set_source_hint(SharedString::from(tr(
+                                "Only folders can be dropped to select the source",
+                            )));
+                        }
+                    } else if window.get_loading() && path.is_dir() {
+                        // Tell the user about the other folders of a drop, which arrive while the first one is loading
+                        window.set_source_hint(SharedString::from(tr_format(
+                            "⚠ Only one folder can be opened at a time, {} was not opened",
+                            &[&path.display()],
+                        )));
+                    }
+                    WinitWindowEventResult::PreventDefault
+                }
+                _ => WinitWindowEventResult::Propagate,
+            });
+    }
+}
+
+/// Starts saving the project periodically, an interval of 0 minutes disables auto-save.
+/// Saving is skipped while the item list is in use by the synchronization. If another instance took over the project
+/// meanwhile, the window shows that the changes are not saved.
+fn start_auto_save(
+    timer: &slint::Timer,
+    minutes: u32,
+    item_list: Arc<Mutex<ItemList>>,
+    window_weak: slint::Weak<ImageSieve>,
+) {
+    if minutes == 0 {
+        timer.stop();
+        return;
+    }
+    timer.start(
+        slint::TimerMode::Repeated,
+        Duration::from_secs(minutes as u64 * 60),
+        move || {
+            if let Ok(item_list) = item_list.try_lock() {
+                if !item_list.items.is_empty() || !item_list.events.is_empty() {
+                    if json::is_locked_by_other(&item_list.path) {
+                        log::warn!(
+                            "Project {} is in use by another instance and is not auto-saved",
+                            item_list.path.display()
+                        );
+                        let window = window_weak.unwrap();
+                        window.set_project_in_use(true);
+                        window.set_source_hint(SharedString::from(
+                            synchronize::project_in_use_hint(&item_list.path),
+                        ));
+                        return;
+                    }
+                    let project_filename = get_project_filename(&item_list.path);
+                    if json::save_if_changed(&project_filename, &item_list) {
+                        log::debug!("Project auto-saved to {}", project_filename.display());
+                    }
+                }
+            }
+        },
+    );
+}
+
+/// Saves the current project, clears the models and starts synchronizing a new source folder
+fn open_source_directory(
+    folder: &Path,
+    item_list: &Arc<Mutex<ItemList>>,
+    items_controller: &Rc<RefCell<ItemsController>>,
+    events_controller: &Rc<RefCell<EventsController>>,
+    synchronizer: &Synchronizer,
+    window_weak: &slint::Weak<ImageSieve>,
+    global_settings: &RefCell<Option<ProjectSettings>>,
+) {
+    store_project_settings(&window_weak.unwrap(), item_list);
+    let unsaved_path = {
+        // Save current item list and release it
+        let item_list = item_list.lock().unwrap();
+        let saved = item_list.items.is_empty() || json::save_project(&item_list);
+        json::unlock_project(&item_list.path);
+        (!saved).then(|| item_list.path.clone())
+    };
+    restore_global_settings(&window_weak.unwrap(), global_settings);
+
+    items_controller.borrow_mut().clear_list();
+    events_controller.borrow_mut().clear();
+
+    // Synchronize in a background thread
+    window_weak.unwrap().set_source_missing(false);
+    window_weak.unwrap().set_project_in_use(false);
+    window_weak
+        .unwrap()
+        .set_source_hint(SharedString::from(match unsaved_path {
+            // Tell that the changes of the previous project were lost, as it is locked by another instance
+            Some(unsaved_path) => tr_format(
+                "⚠ The changes of {} were not saved, because the project is opened in another ImageSieve instance.",
+                &[&unsaved_path.display()],
+            ),
+            None => String::new(),
+        }));
+    window_weak.unwrap().set_loading(true);
+    synchronizer.scan_path(folder, Settings::from_window(&window_weak.unwrap()));
+
+    // Only used for display and the settings, scanning uses the real path
+    window_weak
+        .unwrap()
+        .set_source_directory(SharedString::from(folder.to_string_lossy().as_ref()));
+    update_target_hint(&window_weak.unwrap());
+
+    let mut settings = Settings::from_window(&window_weak.unwrap());
+    settings.add_recent_project(&folder.to_string_lossy());
+    settings.recent_projects_to_window(&window_weak.unwrap());
+}
+
+/// Applies the settings of a loaded project to the window and keeps the global settings to restore them later.
+/// Without settings of the project, the global settings are restored.
+fn apply_project_settings(
+    window: &ImageSieve,
+    item_list: &Mutex<ItemList>,
+    global_settings: &RefCell<Option<ProjectSettings>>,
+) {
+    let project_settings = item_list.lock().unwrap().project_settings.clone();
+    match project_settings {
+        Some(project_settings) => {
+            let mut settings = Settings::from_window(window);
+            global_settings
+                .borrow_mut()
+                .get_or_insert_with(|| settings.get_project_settings());
+            log::info!("Using the settings of the project");
+            settings.set_project_settings(&project_settings);
+            settings.to_window(window);
+            window.set_project_settings(true);
+            update_target_hint(window);
+        }
+        None => restore_global_settings(window, global_settings),
+    }
+}
+
+/// Restores the global settings which were replaced by the settings of a project
+fn restore_global_settings(
+    window: &ImageSieve,
+    global_settings: &RefCell<Option<ProjectSettings>>,
+) {
+    if let Some(project_settings) = global_settings.borrow_mut().take() {
+        let mut settings = Settings::from_window(window);
+        settings.set_project_settings(&project_settings);
+        settings.to_window(window);
+        update_target_hint(window);
+    }
+    window.set_project_settings(false);
 }
 
-/// Sieves the item list in a background thread
+/// Stores the settings of the window in the project if it has its own settings
+fn store_project_settings(window: &ImageSieve, item_list: &Mutex<ItemList>) {
+    let mut item_list = item_list.lock().unwrap();
+    if item_list.project_settings.is_some() {
+        item_list.project_settings = Some(Settings::from_window(window).get_project_settings());
+    }
+}
+
+/// Warns if the target directory is inside the source directory. It is excluded when scanning the source directory then.
+fn update_target_hint(window: &ImageSieve) {
+    let source_directory = window.get_source_directory().to_string();
+    let target_directory = window.get_target_directory().to_string();
+    let hint = if source_directory.is_empty() {
+        String::new()
+    } else {
+        match get_nested_directory(Path::new(&source_directory), Path::new(&target_directory)) {
+            Some(nested_directory) if nested_directory == Path::new(&source_directory) => String::from(tr(
+                "⚠ The target directory is the source directory. Sieved files are found again when opening it the next time.",
+            )),
+            Some(_) => String::from(tr(
+                "⚠ The target directory is inside the source directory. It is excluded when scanning the source directory.",
+            )),
+            None => String::new(),
+        }
+    };
+    window.set_target_hint(SharedString::from(hint));
+}
+
+/// Sieves the item list in a background thread using the sieve method selected in the window
 pub fn sieve(
-    item_list: &ItemList,
+    item_list: &Arc<Mutex<ItemList>>,
+    window_weak: slint::Weak<ImageSieve>,
+    sieve_result_model: Rc<slint::VecModel<SieveResult>>,
+) {
+    let sieve_method = index_to_enum(window_weak.unwrap().get_sieve_method());
+    store_project_settings(&window_weak.unwrap(), item_list);
+    sieve_with_method(item_list, sieve_method, window_weak, sieve_result_model);
+}
+
+/// Sieves the item list in a background thread using the given sieve method
+fn sieve_with_method(
+    item_list: &Arc<Mutex<ItemList>>,
+    sieve_method: SieveMethod,
     window_weak: slint::Weak<ImageSieve>,
     sieve_result_model: Rc<slint::VecModel<SieveResult>>,
 ) {
-    let item_list_copy = item_list.to_owned();
+    let mut item_list_copy = item_list.lock().unwrap().to_owned();
+    let item_list = item_list.clone();
     let target_path = window_weak.unwrap().get_target_directory().to_string();
-    let methods: ModelRc<SharedString> = window_weak
-        .unwrap()
-        .global::<SieveComboValues>()
-        .get_methods();
-    let sieve_method = model_to_enum(&methods, &window_weak.unwrap().get_sieve_method());
-    let directory_names: ModelRc<SharedString> = window_weak
-        .unwrap()
-        .global::<SieveComboValues>()
-        .get_directory_names();
-    let sieve_directory_names = model_to_enum(
-        &directory_names,
-        &window_weak.unwrap().get_sieve_directory_names(),
-    );
+    let min_size_kb = Settings::from_window(&window_weak.unwrap()).min_sieve_size_kb;
+    let sieve_directory_names = index_to_enum(window_weak.unwrap().get_sieve_directory_names());
+    let sieve_io = FileSieveIO {
+        preserve_timestamps: window_weak.unwrap().get_preserve_timestamps(),
+        correct_orientation: window_weak.unwrap().get_correct_orientation(),
+        rotate_pixels: window_weak.unwrap().get_rotate_pixels(),
+        jpeg_quality: convert_jpeg_quality(&window_weak.unwrap().get_jpeg_quality()),
+        strip_metadata: window_weak.unwrap().get_strip_metadata(),
+        safe_move: window_weak.unwrap().get_safe_move(),
+        verify_copies: window_weak.unwrap().get_verify_copies(),
+        manifest_csv: window_weak.unwrap().get_manifest_csv(),
+        preserve_permissions: window_weak.unwrap().get_preserve_permissions(),
+        collision_policy: index_to_enum(window_weak.unwrap().get_collision_policy()),
+    };
+    let force = window_weak.unwrap().get_force_sieve();
+    let create_target_directory = window_weak.unwrap().get_create_target_directory();
     for _ in 0..sieve_result_model.row_count() {
         sieve_result_model.remove(0);
     }
+    let sieve_info = tr_format(
+        "Sieving using {} method to {} with directories {}, existing files: {}",
+        &[
+            &format!("{:?}", sieve_method),
+            &target_path,
+            &format!("{:?}", sieve_directory_names),
+            &format!("{:?}", sieve_io.collision_policy),
+        ],
+    );
+    log::info!("{}", sieve_info);
     sieve_result_model.push(SieveResult {
-        result: SharedString::from(format!(
-            "Sieving using {:?} method to {} with directories {:?}",
-            sieve_method, target_path, sieve_directory_names
-        )),
+        result: SharedString::from(sieve_info),
         color: SharedString::from("black"),
     });
+    if sieve_method != SieveMethod::Delete && min_size_kb > 0 {
+        let too_small = item_list_copy.remove_small_kept_items(min_size_kb as u64 * 1024);
+        let skip_info = tr_format(
+            "Skipping {} kept files smaller than {} KB",
+            &[&too_small, &min_size_kb],
+        );
+        log::info!("{}", skip_info);
+        sieve_result_model.push(SieveResult {
+            result: SharedString::from(skip_info),
+            color: SharedString::from("black"),
+        });
+    }
 
     thread::spawn(move || {
-        let progress_callback = |progress: String| {
-            let window_weak_copy = window_weak.clone();
-            window_weak_copy
-                .upgrade_in_event_loop(move |handle| {
-                    if progress == "Done" {
-                        handle.set_sieve_running(false);
-                    }
-                    let sieve_result_model = handle.get_sieve_result_model();
-                    let sieve_result_model = sieve_result_model
-                        .as_any()
-                        .downcast_ref::<slint::VecModel<SieveResult>>()
-                        .unwrap();
-                    let color = if progress == "Done" {
-                        SharedString::from("green")
-                    } else if progress.starts_with("Error") {
-                        SharedString::from("red")
-                    } else {
-                        SharedString::from("black")
-                    };
-                    let sieve_result = SieveResult {
-                        result: SharedString::from(progress),
-                        color,
-                    };
-                    sieve_result_model.push(sieve_result);
-                })
-                .unwrap();
-        };
-        item_list_copy.sieve(
+        if sieve_method != SieveMethod::Delete
+            && !create_target_directory
+            && !Path::new(&target_path).is_dir()
+        {
+            report_sieve_progress(
+                &window_weak,
+                format!("Error: the target directory {} does not exist", target_path),
+            );
+            report_sieve_progress(&window_weak, String::from("Done"));
+            return;
+        }
+        let copies = item_list_copy.sieve(
             Path::new(&target_path),
             sieve_method,
             sieve_directory_names,
-            progress_callback,
+            &sieve_io,
+            force,
+            |progress| report_sieve_progress(&window_weak, progress),
         );
+        item_list.lock().unwrap().set_sieved_copies(&copies);
     });
 }
 
+/// Deletes the items tagged for deletion in a background thread
+fn delete_pending(
+    item_list: &Arc<Mutex<ItemList>>,
+    window_weak: slint::Weak<ImageSieve>,
+    sieve_result_model: Rc<slint::VecModel<SieveResult>>,
+) {
+    let item_list_copy = item_list.lock().unwrap().to_owned();
+    let item_list = item_list.clone();
+    for _ in 0..sieve_result_model.row_count() {
+        sieve_result_model.remove(0);
+    }
+    let delete_info = tr_format(
+        "Deleting {} images and videos tagged for deletion",
+        &[&item_list_copy.get_pending_deletes().len()],
+    );
+    log::info!("{}", delete_info);
+    sieve_result_model.push(SieveResult {
+        result: SharedString::from(delete_info),
+        color: SharedString::from("black"),
+    });
+
+    thread::spawn(move || {
+        item_list_copy.delete_pending(|progress| report_sieve_progress(&window_weak, progress));
+        item_list.lock().unwrap().clear_deleted_pending();
+        window_weak
+            .upgrade_in_event_loop(|handle| handle.invoke_pending_deletes_changed())
+            .unwrap();
+    });
+}
+
+/// Reverts the last sieve to the target directory selected in the window in a background thread
+fn undo_sieve(
+    window_weak: slint::Weak<ImageSieve>,
+    sieve_result_model: Rc<slint::VecModel<SieveResult>>,
+) {
+    let target_path = window_weak.unwrap().get_target_directory().to_string();
+    for _ in 0..sieve_result_model.row_count() {
+        sieve_result_model.remove(0);
+    }
+    let undo_info = tr_format("Undoing the last sieve to {}", &[&target_path]);
+    log::info!("{}", undo_info);
+    sieve_result_model.push(SieveResult {
+        result: SharedString::from(undo_info),
+        color: SharedString::from("black"),
+    });
+
+    thread::spawn(move || {
+        ItemList::undo_sieve(Path::new(&target_path), |progress| {
+            report_sieve_progress(&window_weak, progress)
+        });
+    });
+}
+
+/// Shows the items kept since the last sieve to the target directory and the sieved items not kept anymore
+fn compare_sieve(
+    window: &ImageSieve,
+    item_list: &Mutex<ItemList>,
+    sieve_result_model: &slint::VecModel<SieveResult>,
+) {
+    let target_path = window.get_target_directory().to_string();
+    for _ in 0..sieve_result_model.row_count() {
+        sieve_result_model.remove(0);
+    }
+    let push = |result: String, color: &str| {
+        sieve_result_model.push(SieveResult {
+            result: SharedString::from(result),
+            color: SharedString::from(color),
+        })
+    };
+    push(
+        tr_format(
+            "Comparing the kept images and videos with the last sieve to {}",
+            &[&target_path],
+        ),
+        "black",
+    );
+
+    let comparison = item_list
+        .lock()
+        .unwrap()
+        .compare_with_last_sieve(Path::new(&target_path));
+    match comparison {
+        Ok(comparison) => {
+            for path in &comparison.added {
+                push(format!("➕ {}", path.display()), "green");
+            }
+            for path in &comparison.removed {
+                push(format!("➖ {}", path.display()), "red");
+            }
+            push(
+                tr_format(
+                    "{} added and {} removed since the last sieve",
+                    &[&comparison.added.len(), &comparison.removed.len()],
+                ),
+                "black",
+            );
+        }
+        Err(e) => {
+            log::warn!(
+                "Error comparing with the last sieve to {}: {}",
+                target_path,
+                e
+            );
+            push(
+                tr_format(
+                    "Error reading the manifest of the last sieve in {}: {}",
+                    &[&target_path, &e],
+                ),
+                "red",
+            );
+        }
+    }
+}
+
+/// Shows the progress of a sieve in the sieve result list, "Done" finishes the sieve
+fn report_sieve_progress(window_weak: &slint::Weak<ImageSieve>, progress: String) {
+    if progress.starts_with("Error") {
+        log::error!("{}", progress);
+    } else {
+        log::debug!("{}", progress);
+    }
+    window_weak
+        .upgrade_in_event_loop(move |handle| {
+            if progress == "Done" {
+                handle.set_sieve_running(false);
+            }
+            let sieve_result_model = handle.get_sieve_result_model();
+            let sieve_result_model = sieve_result_model
+                .as_any()
+                .downcast_ref::<slint::VecModel<SieveResult>>()
+                .unwrap();
+            let color = if progress == "Done" {
+                SharedString::from("green")
+            } else if progress.starts_with("Error") {
+                SharedString::from("red")
+            } else {
+                SharedString::from("black")
+            };
+            // "Done" is only translated for display, as it finishes the sieve
+            let sieve_result = SieveResult {
+                result: if progress == "Done" {
+                    SharedString::from(tr("Done"))
+                } else {
+                    SharedString::from(progress)
+                },
+                color,
+            };
+            // The progress of a large file is replaced by its next progress or by the result of the file
+            let row_count = sieve_result_model.row_count();
+            let last_is_file_progress = row_count > 0
+                && sieve_result_model
+                    .row_data(row_count - 1)
+                    .is_some_and(|last| last.result.starts_with(FILE_PROGRESS_PREFIX));
+            if last_is_file_progress {
+                sieve_result_model.set_row_data(row_count - 1, sieve_result);
+            } else {
+                sieve_result_model.push(sieve_result);
+            }
+        })
+        .unwrap();
+}
+
+/// Shows the number of items that were not decided yet and the statistics of the project and its events
+fn update_statistics(window: &ImageSieve, item_list: &Mutex<ItemList>) {
+    let statistics = item_list.lock().unwrap().get_project_statistics();
+    window.set_undecided_count(statistics.undecided as i32);
+
+    let total = &statistics.total;
+    let date_range = match total.date_range {
+        Some((first, last)) => tr_format(
+            " from {} to {}",
+            &[
+                &first.format(EVENT_DATE_FORMAT),
+                &last.format(EVENT_DATE_FORMAT),
+            ],
+        ),
+        None => String::new(),
+    };
+    window.set_project_statistics(SharedString::from(tr_format(
+        "🗂 {} images and videos{}, {}\n✔ {} kept ({}), 🗑 {} discarded, ❔ {} undecided, ⭐ {} favorites",
+        &[
+            &total.items,
+            &date_range,
+            &format_size(total.bytes),
+            &total.kept,
+            &format_size(total.kept_bytes),
+            &total.discarded(),
+            &statistics.undecided,
+            &total.favorites,
+        ],
+    )));
+    let events: Vec<EventStatistics> = statistics
+        .events
+        .iter()
+        .map(|event| EventStatistics {
+            name: SharedString::from(&event.name),
+            items: event.items as i32,
+            kept: event.kept as i32,
+            size: SharedString::from(format_size(event.bytes)),
+        })
+        .collect();
+    window.set_event_statistics_model(ModelRc::from(Rc::new(slint::VecModel::from(events))));
+}
+
+/// Shows the items tagged for deletion with their number and total size
+fn update_pending_deletes(
+    window_weak: &slint::Weak<ImageSieve>,
+    items_controller: &ItemsController,
+    item_list: &Mutex<ItemList>,
+) {
+    let pending_deletes = items_controller.get_pending_delete_items(window_weak.clone());
+    let bytes: u64 = {
+        let item_list = item_list.lock().unwrap();
+        pending_deletes
+            .iter()
+            .map(|item| item_list.items[item.local_index as usize].get_size())
+            .sum()
+    };
+    let window = window_weak.unwrap();
+    window.set_pending_delete_summary(SharedString::from(if pending_deletes.is_empty() {
+        String::from(tr("No images and videos are tagged for deletion"))
+    } else {
+        tr_format(
+            "{} images and videos tagged for deletion, {}",
+            &[&pending_deletes.len(), &format_size(bytes)],
+        )
+    }));
+    window.set_pending_delete_model(ModelRc::from(Rc::new(slint::VecModel::from(
+        pending_deletes,
+    ))));
+}
+
+/// Creates the models of the combo boxes with the texts in the selected language
+fn combo_values_to_window(window: &ImageSieve) {
+    let sieve_combo_values = window.global::<SieveComboValues>();
+    sieve_combo_values.set_methods(translated_model(SIEVE_METHODS));
+    sieve_combo_values.set_directory_names(translated_model(DIRECTORY_NAMES));
+    sieve_combo_values.set_collision_policies(translated_model(COLLISION_POLICIES));
+    let settings_combo_values = window.global::<SettingsComboValues>();
+    settings_combo_values.set_sensitivities(translated_model(SENSITIVITIES));
+    settings_combo_values.set_hash_grids(translated_model(HASH_GRIDS));
+    settings_combo_values.set_dark_modes(translated_model(DARK_MODES));
+    settings_combo_values.set_image_displays(translated_model(IMAGE_DISPLAYS));
+    settings_combo_values.set_time_zones(translated_model(TIME_ZONES));
+    let filter_combo_values = window.global::<FilterComboValues>();
+    filter_combo_values.set_sort_by(translated_model(SORT_BY));
+    filter_combo_values.set_directions(translated_model(SORT_DIRECTIONS));
+}
+
+/// Translates the texts of the combo boxes to the selected language, the selected indices are kept
+fn translate_combo_values(window: &ImageSieve) {
+    let sieve_combo_values = window.global::<SieveComboValues>();
+    translate_model(&sieve_combo_values.get_methods(), SIEVE_METHODS);
+    translate_model(&sieve_combo_values.get_directory_names(), DIRECTORY_NAMES);
+    translate_model(
+        &sieve_combo_values.get_collision_policies(),
+        COLLISION_POLICIES,
+    );
+    let settings_combo_values = window.global::<SettingsComboValues>();
+    translate_model(&settings_combo_values.get_sensitivities(), SENSITIVITIES);
+    translate_model(&settings_combo_values.get_hash_grids(), HASH_GRIDS);
+    translate_model(&settings_combo_values.get_dark_modes(), DARK_MODES);
+    translate_model(&settings_combo_values.get_image_displays(), IMAGE_DISPLAYS);
+    translate_model(&settings_combo_values.get_time_zones(), TIME_ZONES);
+    let filter_combo_values = window.global::<FilterComboValues>();
+    translate_model(&filter_combo_values.get_sort_by(), SORT_BY);
+    translate_model(&filter_combo_values.get_directions(), SORT_DIRECTIONS);
+}
+
+/// Creates a combo box model of texts translated to the selected language
+fn translated_model(texts: &[&str]) -> ModelRc<SharedString> {
+    ModelRc::from(Rc::new(slint::VecModel::from(
+        texts
+            .iter()
+            .map(|text| SharedString::from(tr(text)))
+            .collect::<Vec<SharedString>>(),
+    )))
+}
+
+/// Translates the texts of a combo box model in place. Replacing the model would reset the selection of the combo box.
+fn translate_model(model: &ModelRc<SharedString>, texts: &[&str]) {
+    for (row, text) in texts.iter().enumerate() {
+        model.set_row_data(row, SharedString::from(tr(text)));
+    }
+}
+
+/// Formats a size in bytes as megabytes
+fn format_size(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Describes where settings, logs and the project with the stored hashes are saved and how large they are
+fn storage_statistics(item_list: &ItemList) -> String {
+    let config_dir = json::get_config_dir();
+    let mut statistics = format!(
+        "{} ({})",
+        config_dir.display(),
+        format_size(json::get_directory_size(&config_dir))
+    );
+    if !item_list.items.is_empty() {
+        let project_filename = get_project_filename(&item_list.path);
+        let project_size = std::fs::metadata(&project_filename)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        statistics.push_str(&tr_format(
+            "\n{} ({}, {} hashes)",
+            &[
+                &project_filename.display(),
+                &format_size(project_size),
+                &item_list.get_hash_count(),
+            ],
+        ));
+    }
+    statistics
+}
+
 /// Convert a folder setting to an option if the folder exists
 fn get_folder(folder: &SharedString) -> Option<&str> {
     let folder = folder.as_str();
@@ -493,3 +1882,8 @@ fn get_folder(folder: &SharedString) -> Option<&str> {
 pub fn get_trace_filename() -> PathBuf {
     json::get_trace_filename()
 }
+
+/// Sets a custom directory to store settings, logs and projects in, must be called before the main window is created
+pub fn set_config_dir(config_dir: PathBuf) {
+    json::set_config_dir(config_dir)
+}
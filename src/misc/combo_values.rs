@@ -0,0 +1,65 @@
+/// English texts of the combo boxes. Each list is in the order of the enum or setting its combo box selects by index and
+/// is translated with `tr` when the models are shown.
+pub const SIEVE_METHODS: &[&str] = &[
+    "Copy to target directory",
+    "Move to target directory",
+    "Move to target directory and delete in source directory",
+    "Delete in source directory",
+    "Copy to zip archive in target directory",
+];
+
+pub const DIRECTORY_NAMES: &[&str] = &[
+    "Year and month (2021-12)",
+    "Year (2021)",
+    "Year, month and day (2021-12-27)",
+    "Year and quarter (2021-Q4)",
+    "Year and month in subdirectory (2021/12)",
+    "Same subfolders as in source directory",
+];
+
+pub const COLLISION_POLICIES: &[&str] = &[
+    "Rename the new file (name (1).jpg)",
+    "Overwrite the existing file",
+    "Skip the new file",
+];
+
+pub const TIME_ZONES: &[&str] = &["Time of capture", "Local time zone", "UTC"];
+
+pub const SENSITIVITIES: &[&str] = &["Very low", "Low", "Medium", "High", "Very high"];
+
+pub const HASH_GRIDS: &[&str] = &["Fast (8x4)", "Normal (16x8)", "Precise (32x16)"];
+
+pub const DARK_MODES: &[&str] = &["Automatic", "On", "Off"];
+
+pub const IMAGE_DISPLAYS: &[&str] = &["Fit", "Fill"];
+
+pub const SORT_BY: &[&str] = &["Date", "Name", "Size", "Type"];
+
+pub const SORT_DIRECTIONS: &[&str] = &["Asc", "Desc"];
+
+/// Gets the index of a text in a combo box list, 0 if it is not in the list
+pub fn index_of(texts: &[&str], text: &str) -> i32 {
+    texts.iter().position(|other| *other == text).unwrap_or(0) as i32
+}
+
+/// Gets the text of an index in a combo box list, the first text if the index is out of range
+pub fn text_of<'a>(texts: &[&'a str], index: i32) -> &'a str {
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| texts.get(index))
+        .unwrap_or(&texts[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_text() {
+        assert_eq!(index_of(DARK_MODES, "Off"), 2);
+        assert_eq!(index_of(DARK_MODES, "Unknown"), 0);
+        assert_eq!(text_of(DARK_MODES, 1), "On");
+        assert_eq!(text_of(DARK_MODES, 3), "Automatic");
+        assert_eq!(text_of(DARK_MODES, -1), "Automatic");
+    }
+}
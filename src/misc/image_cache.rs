@@ -1,24 +1,45 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
+    fmt,
+    path::PathBuf,
     sync::Arc,
-    sync::{mpsc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Mutex,
+    },
     thread,
 };
 
 use super::lru_map::LruMap;
 use crate::item_sort_list::FileItem;
-use crate::misc::images::ImageBuffer;
+use crate::misc::images::{get_rotation, ImageBuffer, LoadError};
 use slint::{
     private_unstable_api::re_exports::{load_image_from_embedded_data, Slice},
     Image,
 };
 
+/// Maximum number of images in the cache
+const CACHE_SIZE: usize = 64;
 /// The least recently used map used to store the images protected by a mutex.
-type ImagesMapMutex = Mutex<LruMap<ImageBuffer, String, 64>>;
+type ImagesMapMutex = Mutex<LruMap<ImageBuffer, ImageKey, CACHE_SIZE>>;
 /// The queue with images to load protected by a mutex.
 type LoadQueue = Mutex<VecDeque<LoadImageCommand>>;
+/// The paths of files whose images could not be loaded because they are corrupt, shared with the load threads.
+pub type CorruptFiles = Arc<Mutex<HashSet<PathBuf>>>;
 /// The callback which is executed when an image was loaded (is no slint::Image because that is not "Send")
 pub type DoneCallback = Box<dyn Fn(ImageBuffer) + Send + 'static>;
+/// The callback which is executed when a thumbnail was prepared with the number of prepared and of all thumbnails
+pub type WarmProgressCallback = Box<dyn FnMut(usize, usize) + Send + 'static>;
+
+/// Maximum width of thumbnails, twice the displayed size to look sharp on high resolution screens
+const THUMBNAIL_WIDTH: u32 = 360;
+/// Maximum height of thumbnails
+const THUMBNAIL_HEIGHT: u32 = 200;
+/// Maximum number of thumbnails prepared in advance, so that they do not push each other and the shown images
+/// out of the cache
+pub const MAX_WARM_THUMBNAILS: usize = CACHE_SIZE / 2;
+/// Maximum number of images prefetched at once, so that prefetching does not evict the images that are shown
+pub const MAX_PREFETCH: usize = CACHE_SIZE / 4;
 
 /// Purpose of the image to load from the cache
 pub enum Purpose {
@@ -28,6 +49,75 @@ pub enum Purpose {
     SimilarImage,
     /// The image is one of the next in the list and should be loaded to increase the perceived speed, but it is not urgent
     Prefetch,
+    /// The image is shown in the strip of similar images and is loaded in a small size
+    Thumbnail,
+}
+
+/// Statistics about the usage of the image cache
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheStatistics {
+    /// Number of images in the cache
+    pub entries: usize,
+    /// Maximum number of images in the cache
+    pub capacity: usize,
+    /// Estimated memory used by the cached images in bytes
+    pub bytes: usize,
+    /// Number of requested images that were found in the cache
+    pub hits: usize,
+    /// Number of requested images that were not in the cache yet
+    pub misses: usize,
+}
+
+impl CacheStatistics {
+    /// Gets the share of requested images that were found in the cache in percent, None if nothing was requested
+    pub fn hit_rate(&self) -> Option<f64> {
+        let requests = self.hits + self.misses;
+        if requests > 0 {
+            Some(self.hits as f64 * 100.0 / requests as f64)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for CacheStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} images, {:.1} MB",
+            self.entries,
+            self.capacity,
+            self.bytes as f64 / (1024.0 * 1024.0)
+        )?;
+        match self.hit_rate() {
+            Some(hit_rate) => write!(
+                f,
+                ", {:.0} % hits ({} hits, {} misses)",
+                hit_rate, self.hits, self.misses
+            ),
+            None => write!(f, ", no requests yet"),
+        }
+    }
+}
+
+/// Key of an image in the cache. The same file rendered with a different size or rotation is a different image.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+struct ImageKey {
+    path: PathBuf,
+    max_width: u32,
+    max_height: u32,
+    rotation: i32,
+}
+
+impl ImageKey {
+    fn new(item: &FileItem, max_width: u32, max_height: u32) -> Self {
+        Self {
+            path: item.path.clone(),
+            max_width,
+            max_height,
+            rotation: get_rotation(item),
+        }
+    }
 }
 
 struct LoadImageCommand {
@@ -37,9 +127,15 @@ struct LoadImageCommand {
     pub callback: Option<DoneCallback>,
 }
 
+impl LoadImageCommand {
+    fn key(&self) -> ImageKey {
+        ImageKey::new(&self.file_item, self.width, self.height)
+    }
+}
+
 impl PartialEq for LoadImageCommand {
     fn eq(&self, other: &Self) -> bool {
-        self.file_item == other.file_item
+        self.key() == other.key()
     }
 }
 
@@ -49,6 +145,7 @@ impl PartialEq for LoadImageCommand {
 /// The cache implements two separate threads for loading to implement the priorization. Selected images are loaded from one thread,
 /// the other thread loads the similar images and the prefetch images. In order to priorize the similar images, these commands are
 /// added to the front of the load queue, while the prefetch image commands are added to the back.
+/// A third thread prepares thumbnails in advance, its commands are not purged when another image is selected.
 pub struct ImageCache {
     /// Map with the images
     images: Arc<ImagesMapMutex>,
@@ -66,6 +163,16 @@ pub struct ImageCache {
     secondary_queue: Arc<LoadQueue>,
     /// Sender to the secondary load thread
     secondary_sender: mpsc::Sender<()>,
+    /// Queue of thumbnails to prepare in advance
+    warm_queue: Arc<LoadQueue>,
+    /// Sender to the thread preparing thumbnails in advance
+    warm_sender: mpsc::Sender<()>,
+    /// Number of images requested from the cache that were found
+    hits: AtomicUsize,
+    /// Number of images requested from the cache that were missing
+    misses: AtomicUsize,
+    /// Files which are empty or truncated
+    corrupt_files: CorruptFiles,
 }
 
 impl ImageCache {
@@ -73,18 +180,28 @@ impl ImageCache {
     pub fn new() -> Self {
         let images = LruMap::new();
         let mutex = Arc::new(Mutex::new(images));
+        let corrupt_files: CorruptFiles = Arc::new(Mutex::new(HashSet::new()));
 
         let mutex_t = mutex.clone();
+        let corrupt_t = corrupt_files.clone();
         let (primary_sender, rx) = mpsc::channel();
         let primary_queue = Arc::new(LoadQueue::new(VecDeque::new()));
         let queue_t = primary_queue.clone();
-        thread::spawn(move || load_image_thread(mutex_t, queue_t, rx));
+        thread::spawn(move || load_image_thread(mutex_t, queue_t, corrupt_t, rx));
 
         let mutex_t = mutex.clone();
+        let corrupt_t = corrupt_files.clone();
         let (secondary_sender, rx) = mpsc::channel();
         let secondary_queue = Arc::new(LoadQueue::new(VecDeque::new()));
         let queue_t = secondary_queue.clone();
-        thread::spawn(move || load_image_thread(mutex_t, queue_t, rx));
+        thread::spawn(move || load_image_thread(mutex_t, queue_t, corrupt_t, rx));
+
+        let mutex_t = mutex.clone();
+        let corrupt_t = corrupt_files.clone();
+        let (warm_sender, rx) = mpsc::channel();
+        let warm_queue = Arc::new(LoadQueue::new(VecDeque::new()));
+        let queue_t = warm_queue.clone();
+        thread::spawn(move || load_image_thread(mutex_t, queue_t, corrupt_t, rx));
 
         Self {
             images: mutex,
@@ -95,6 +212,11 @@ impl ImageCache {
             primary_sender,
             secondary_queue,
             secondary_sender,
+            warm_queue,
+            warm_sender,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            corrupt_files,
         }
     }
 
@@ -113,6 +235,59 @@ impl ImageCache {
         self.secondary_queue.lock().unwrap().clear();
     }
 
+    /// Removes all cached images and drops the commands which are not running yet, so that all images are loaded again
+    /// from their files. The statistics of hits and misses are kept.
+    pub fn clear(&self) {
+        self.purge();
+        self.cancel_warming();
+        self.images.lock().unwrap().clear();
+        self.corrupt_files.lock().unwrap().clear();
+    }
+
+    /// Gets the files whose images could not be loaded because they are empty or truncated. The set is shared, so
+    /// that it can be checked from the done callback of a load.
+    pub fn get_corrupt_files(&self) -> CorruptFiles {
+        self.corrupt_files.clone()
+    }
+
+    /// Removes all cached images of an item, e.g. because it is displayed differently now
+    pub fn invalidate(&self, item: &FileItem) {
+        self.images
+            .lock()
+            .unwrap()
+            .retain(|key| key.path != item.path);
+    }
+
+    /// Prepares the thumbnails of items in the background in the given order, so that they can be shown immediately.
+    /// Only the first items up to the maximum number of thumbnails are prepared. Thumbnails that are still prepared
+    /// from an earlier call are dropped. Returns the number of thumbnails that are prepared.
+    pub fn warm_thumbnails(&self, items: Vec<FileItem>, progress: WarmProgressCallback) -> usize {
+        let total = items.len().min(MAX_WARM_THUMBNAILS);
+        let progress = Arc::new(Mutex::new((0, progress)));
+        let mut queue = self.warm_queue.lock().unwrap();
+        queue.clear();
+        for item in items.into_iter().take(total) {
+            let progress = progress.clone();
+            queue.push_back(LoadImageCommand {
+                file_item: item,
+                width: THUMBNAIL_WIDTH,
+                height: THUMBNAIL_HEIGHT,
+                callback: Some(Box::new(move |_| {
+                    let (done, progress) = &mut *progress.lock().unwrap();
+                    *done += 1;
+                    progress(*done, total);
+                })),
+            });
+            self.warm_sender.send(()).ok();
+        }
+        total
+    }
+
+    /// Stops preparing thumbnails in advance
+    pub fn cancel_warming(&self) {
+        self.warm_queue.lock().unwrap().clear();
+    }
+
     /// Sets the maximum width and height of the images to load
     pub fn restrict_size(&mut self, max_width: u32, max_height: u32) {
         if max_width > self.max_width || max_height > self.max_height {
@@ -124,12 +299,54 @@ impl ImageCache {
 
     /// Gets an image from the cache
     pub fn get(&self, item: &FileItem) -> Option<Image> {
-        let item_path = item.path.to_str().unwrap();
         let mut map = self.images.lock().unwrap();
-        map.get(String::from(item_path))
+        self.count(map.get(ImageKey::new(item, self.max_width, self.max_height)))
+            .map(crate::misc::images::get_slint_image)
+    }
+
+    /// Gets the image buffer of an image from the cache
+    pub fn get_buffer(&self, item: &FileItem) -> Option<ImageBuffer> {
+        let mut map = self.images.lock().unwrap();
+        self.count(map.get(ImageKey::new(item, self.max_width, self.max_height)))
+            .cloned()
+    }
+
+    /// Gets the thumbnail of an image from the cache
+    pub fn get_thumbnail(&self, item: &FileItem) -> Option<Image> {
+        let mut map = self.images.lock().unwrap();
+        self.count(map.get(ImageKey::new(item, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT)))
             .map(crate::misc::images::get_slint_image)
     }
 
+    /// Checks if an image is already cached without counting it as a request
+    pub fn is_cached(&self, item: &FileItem) -> bool {
+        let map = self.images.lock().unwrap();
+        map.contains(ImageKey::new(item, self.max_width, self.max_height))
+    }
+
+    /// Counts a request of an image as hit or miss
+    fn count<'a>(&self, image: Option<&'a ImageBuffer>) -> Option<&'a ImageBuffer> {
+        let counter = if image.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        image
+    }
+
+    /// Gets statistics about the number of cached images, their memory usage and how often requested images were found
+    pub fn statistics(&self) -> CacheStatistics {
+        let map = self.images.lock().unwrap();
+        CacheStatistics {
+            entries: map.values().count(),
+            capacity: CACHE_SIZE,
+            bytes: map.values().map(|image| image.as_raw().len()).sum(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
     /// Gets the waiting image
     pub fn get_waiting(&self) -> Image {
         self.waiting_image.clone()
@@ -138,24 +355,47 @@ impl ImageCache {
     /// Loads an image from the cache
     /// The purpose of the image needs to be indicated to determine the loading priority. When the image was loaded,
     /// the done callback is executed.
-    pub fn load(&self, item: &FileItem, purpose: Purpose, done_callback: Option<DoneCallback>) {
+    /// For images to be shown right away, the thumbnail embedded in the EXIF data is returned if there is one, so that
+    /// a preview can be shown until the full image is loaded.
+    pub fn load(
+        &self,
+        item: &FileItem,
+        purpose: Purpose,
+        done_callback: Option<DoneCallback>,
+    ) -> Option<Image> {
+        let (width, height) = match purpose {
+            Purpose::Thumbnail => (THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT),
+            _ => (self.max_width, self.max_height),
+        };
         let command = LoadImageCommand {
             file_item: item.clone(),
-            width: self.max_width,
-            height: self.max_height,
+            width,
+            height,
             callback: done_callback,
         };
         match purpose {
             Purpose::CurrentImage => {
-                let mut queue = self.primary_queue.lock().unwrap();
-                queue.clear();
-                queue.push_front(command);
-                self.primary_sender.send(()).ok();
+                {
+                    let mut queue = self.primary_queue.lock().unwrap();
+                    queue.clear();
+                    queue.push_front(command);
+                    self.primary_sender.send(()).ok();
+                }
+                get_exif_preview(item)
             }
             Purpose::SimilarImage => {
+                {
+                    let mut queue = self.secondary_queue.lock().unwrap();
+                    queue.push_back(command);
+                    self.secondary_sender.send(()).ok();
+                }
+                get_exif_preview(item)
+            }
+            Purpose::Thumbnail => {
                 let mut queue = self.secondary_queue.lock().unwrap();
                 queue.push_back(command);
                 self.secondary_sender.send(()).ok();
+                None
             }
             Purpose::Prefetch => {
                 let mut queue = self.secondary_queue.lock().unwrap();
@@ -163,16 +403,24 @@ impl ImageCache {
                     queue.push_back(command);
                 }
                 self.secondary_sender.send(()).ok();
+                None
             }
         }
     }
 }
 
+/// Gets the thumbnail embedded in the EXIF data of an image as preview
+fn get_exif_preview(item: &FileItem) -> Option<Image> {
+    crate::misc::images::get_exif_thumbnail(item)
+        .map(|thumbnail| crate::misc::images::get_slint_image(&thumbnail))
+}
+
 /// Loads images in the background after receiving a trigger message. The message sent to the thread is empty, the actual
 /// commands are contained in the load queue.
 fn load_image_thread(
     cache: Arc<ImagesMapMutex>,
     load_queue: Arc<LoadQueue>,
+    corrupt_files: CorruptFiles,
     receiver: mpsc::Receiver<()>,
 ) {
     for () in receiver {
@@ -181,11 +429,11 @@ fn load_image_thread(
             continue;
         }
         let command = next_item.unwrap();
-        let item_path = command.file_item.path.to_str().unwrap();
+        let key = command.key();
         // First try to get the image from the cache
         let contains_key = {
             let map = cache.lock().unwrap();
-            map.contains(String::from(item_path))
+            map.contains(key.clone())
         };
         // If it is not in the cache, load it from the file and put it into the cache
         if !contains_key {
@@ -196,24 +444,211 @@ fn load_image_thread(
                     command.height,
                 )
             } else {
-                crate::misc::images::get_image_buffer(
+                crate::misc::images::load_image_buffer(
                     &command.file_item,
                     command.width,
                     command.height,
                 )
+                .unwrap_or_else(|error| {
+                    if error == LoadError::Corrupt {
+                        corrupt_files
+                            .lock()
+                            .unwrap()
+                            .insert(command.file_item.path.clone());
+                    }
+                    ImageBuffer::new(1, 1)
+                })
             };
             let mut map = cache.lock().unwrap();
-            map.put(String::from(item_path), image_buffer.clone());
+            map.put(key.clone(), image_buffer.clone());
         }
 
         // If a callback was indicated, execute it passing a clone of the image
         if let Some(callback) = command.callback {
             let image = {
                 let mut map = cache.lock().unwrap();
-                map.get(String::from(item_path)).cloned()
+                map.get(key).cloned()
             }
             .unwrap();
             callback(image);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_key() {
+        let item = FileItem::dummy("test.jpg", 0, true);
+        let key = ImageKey::new(&item, 100, 100);
+        assert_eq!(key, ImageKey::new(&item, 100, 100));
+        assert_eq!(key.rotation, 0);
+        assert_ne!(key, ImageKey::new(&item, 200, 100));
+        assert_ne!(key, ImageKey::new(&item, 100, 200));
+        assert_ne!(
+            key,
+            ImageKey::new(&FileItem::dummy("test2.jpg", 0, true), 100, 100)
+        );
+        let mut rotated_item = item.clone();
+        rotated_item.rotate(90);
+        assert_eq!(ImageKey::new(&rotated_item, 100, 100).rotation, 90);
+        assert_ne!(key, ImageKey::new(&rotated_item, 100, 100));
+
+        let mut map: LruMap<u32, ImageKey, 2> = LruMap::new();
+        map.put(key.clone(), 1);
+        map.put(ImageKey::new(&item, 200, 100), 2);
+        assert_eq!(map.get(key), Some(&1));
+        assert_eq!(map.get(ImageKey::new(&item, 200, 100)), Some(&2));
+    }
+
+    #[test]
+    fn test_thumbnail() {
+        let mut image_cache = ImageCache::new();
+        image_cache.restrict_size(1600, 1000);
+        let item = FileItem::dummy("tests/test.jpg", 0, true);
+        assert!(image_cache.get_thumbnail(&item).is_none());
+
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        image_cache.load(
+            &item,
+            Purpose::Thumbnail,
+            Some(Box::new(move |image_buffer| {
+                sender.lock().unwrap().send(image_buffer).unwrap();
+            })),
+        );
+        let image_buffer = receiver.recv().unwrap();
+        assert!(image_buffer.width() <= THUMBNAIL_WIDTH);
+        assert!(image_buffer.height() <= THUMBNAIL_HEIGHT);
+        assert!(image_cache.get_thumbnail(&item).is_some());
+        assert!(image_cache.get(&item).is_none());
+        assert!(image_cache.get_buffer(&item).is_none());
+        assert!(!image_cache.is_cached(&item));
+
+        let statistics = image_cache.statistics();
+        assert_eq!(statistics.entries, 1);
+        assert_eq!(statistics.capacity, CACHE_SIZE);
+        assert_eq!(
+            statistics.bytes,
+            (image_buffer.width() * image_buffer.height() * 4) as usize
+        );
+        assert_eq!((statistics.hits, statistics.misses), (1, 3));
+
+        // A rotated item is a different image, the cached images of the item can be discarded
+        let mut rotated_item = item.clone();
+        rotated_item.rotate(90);
+        assert!(image_cache.get_thumbnail(&rotated_item).is_none());
+        image_cache.invalidate(&rotated_item);
+        assert!(image_cache.get_thumbnail(&item).is_none());
+        assert_eq!(image_cache.statistics().entries, 0);
+    }
+
+    #[test]
+    fn test_corrupt_files() {
+        let directory = std::env::temp_dir().join("image_sieve_test_corrupt_files");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("empty.jpg");
+        std::fs::write(&path, []).unwrap();
+
+        let image_cache = ImageCache::new();
+        let item = FileItem::dummy(&path.to_string_lossy(), 0, true);
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        image_cache.load(
+            &item,
+            Purpose::CurrentImage,
+            Some(Box::new(move |image_buffer| {
+                sender.lock().unwrap().send(image_buffer).unwrap();
+            })),
+        );
+        // The corrupt file is known before the callback is executed
+        assert_eq!(receiver.recv().unwrap().dimensions(), (1, 1));
+        assert!(image_cache
+            .get_corrupt_files()
+            .lock()
+            .unwrap()
+            .contains(&path));
+
+        image_cache.clear();
+        assert!(image_cache.get_corrupt_files().lock().unwrap().is_empty());
+        assert_eq!(image_cache.statistics().entries, 0);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_cache_statistics() {
+        let mut statistics = CacheStatistics {
+            entries: 3,
+            capacity: 64,
+            bytes: 3 * 1024 * 1024 / 2,
+            hits: 0,
+            misses: 0,
+        };
+        assert_eq!(statistics.hit_rate(), None);
+        assert_eq!(
+            statistics.to_string(),
+            "3/64 images, 1.5 MB, no requests yet"
+        );
+
+        statistics.hits = 3;
+        statistics.misses = 1;
+        assert_eq!(statistics.hit_rate(), Some(75.0));
+        assert_eq!(
+            statistics.to_string(),
+            "3/64 images, 1.5 MB, 75 % hits (3 hits, 1 misses)"
+        );
+    }
+
+    #[test]
+    fn test_exif_preview() {
+        let image_cache = ImageCache::new();
+        let item = FileItem::dummy("tests/test_thumbnail.jpg", 0, true);
+        let preview = image_cache
+            .load(&item, Purpose::CurrentImage, None)
+            .unwrap();
+        assert_eq!((preview.size().width, preview.size().height), (12, 8));
+        assert!(image_cache
+            .load(&item, Purpose::SimilarImage, None)
+            .is_some());
+        // Thumbnails and prefetched images are not shown right away
+        assert!(image_cache.load(&item, Purpose::Thumbnail, None).is_none());
+        assert!(image_cache.load(&item, Purpose::Prefetch, None).is_none());
+
+        let item = FileItem::dummy("tests/test.jpg", 0, true);
+        assert!(image_cache
+            .load(&item, Purpose::CurrentImage, None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_warm_thumbnails() {
+        let image_cache = ImageCache::new();
+        let items = vec![
+            FileItem::dummy("tests/test.jpg", 0, true),
+            FileItem::dummy("tests/test.png", 0, true),
+        ];
+        let (sender, receiver) = mpsc::channel();
+        let total = image_cache.warm_thumbnails(
+            items.clone(),
+            Box::new(move |done, total| sender.send((done, total)).unwrap()),
+        );
+        assert_eq!(total, 2);
+        assert_eq!(receiver.recv().unwrap(), (1, 2));
+        assert_eq!(receiver.recv().unwrap(), (2, 2));
+        assert!(items
+            .iter()
+            .all(|item| image_cache.get_thumbnail(item).is_some()));
+
+        // The number of thumbnails is limited to keep the other images in the cache
+        let items: Vec<FileItem> = (0..MAX_WARM_THUMBNAILS + 5)
+            .map(|index| FileItem::dummy(&format!("test{}.jpg", index), 0, true))
+            .collect();
+        let total = image_cache.warm_thumbnails(items, Box::new(|_, _| {}));
+        assert_eq!(total, MAX_WARM_THUMBNAILS);
+        image_cache.cancel_warming();
+        assert!(image_cache.warm_queue.lock().unwrap().is_empty());
+    }
+}
@@ -1,35 +1,50 @@
 extern crate image;
 extern crate slint;
 
+use std::io::Cursor;
+
+use image::codecs::{jpeg::JpegDecoder, png::PngDecoder, tiff::TiffDecoder, webp::WebPDecoder};
+use image::{DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageResult};
+use moxcms::{ColorProfile, DataColorSpace, Layout, TransformOptions};
+
 use super::resize::{resize_image, restrict_size};
 use crate::item_sort_list::FileItem;
 
 /// Image buffer from the image crate
 pub type ImageBuffer = image::ImageBuffer<image::Rgba<u8>, Vec<u8>>;
 
+/// Reason why an image could not be loaded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// The file is empty or its data is truncated or damaged, e.g. by an interrupted download
+    Corrupt,
+    /// The file could not be read or its format is not supported
+    Unreadable,
+}
+
 /// Get an image buffer from a FileItem with a width and height constraint. If the image contains
-/// an orientation indication, it is rotated accordingly.
+/// an orientation indication, it is rotated accordingly. Images that cannot be loaded result in an image of 1x1 pixels.
 pub fn get_image_buffer(item: &FileItem, max_width: u32, max_height: u32) -> ImageBuffer {
-    let image_buffer = if item.is_image() {
+    load_image_buffer(item, max_width, max_height).unwrap_or_else(|_| ImageBuffer::new(1, 1))
+}
+
+/// Loads an image buffer from a FileItem like get_image_buffer, but tells why the image could not be loaded
+pub fn load_image_buffer(
+    item: &FileItem,
+    max_width: u32,
+    max_height: u32,
+) -> Result<ImageBuffer, LoadError> {
+    if item.is_image() {
         load_image_and_rotate(&item.path, get_rotation(item), max_width, max_height)
     } else {
         load_raw_image_and_rotate(&item.path, get_rotation(item), max_width, max_height)
-    };
-
-    image_buffer.unwrap_or_else(|| ImageBuffer::new(1, 1))
+            .ok_or(LoadError::Unreadable)
+    }
 }
 
-/// Return the rotation in degrees from a file item
+/// Return the rotation in degrees from a file item, including the rotation set by the user
 pub fn get_rotation(item: &FileItem) -> i32 {
-    match item.get_orientation() {
-        Some(orientation) => match orientation {
-            crate::item_sort_list::Orientation::Landscape => 0,
-            crate::item_sort_list::Orientation::Portrait90 => 90,
-            crate::item_sort_list::Orientation::Landscape180 => 180,
-            crate::item_sort_list::Orientation::Portrait270 => 270,
-        },
-        None => 0,
-    }
+    item.get_rotation()
 }
 
 /// Get an empty image of the size 1x1
@@ -52,20 +67,99 @@ pub fn get_slint_image(buffer: &ImageBuffer) -> slint::Image {
     }
 }
 
-/// Loads an image from a path and rotates it by a given angle in degrees
+/// Loads an image from a path and rotates it by a given angle in degrees. The colors are converted to sRGB if the
+/// image contains an ICC profile, otherwise the image is assumed to be sRGB already.
 fn load_image_and_rotate(
     path: &std::path::Path,
     rotate: i32,
     max_width: u32,
     max_height: u32,
-) -> Option<ImageBuffer> {
-    if let Ok(image) = image::open(path) {
-        resize_and_rotate(image.to_rgba8(), rotate, max_width, max_height)
-    } else {
-        None
+) -> Result<ImageBuffer, LoadError> {
+    match open_image(path) {
+        Ok((image, icc_profile)) => {
+            let mut image_buffer =
+                resize_and_rotate(image.to_rgba8(), rotate, max_width, max_height)
+                    .ok_or(LoadError::Unreadable)?;
+            if let Some(icc_profile) = icc_profile {
+                convert_to_srgb(&mut image_buffer, &icc_profile);
+            }
+            Ok(image_buffer)
+        }
+        Err(e) => {
+            log::warn!("Error opening image {}: {}", path.display(), e);
+            Err(get_load_error(&e))
+        }
     }
 }
 
+/// Gets the reason why an image could not be opened from the error of the image crate. Empty files and data that
+/// ends early or cannot be decoded are corrupt.
+fn get_load_error(error: &ImageError) -> LoadError {
+    match error {
+        ImageError::Decoding(_) => LoadError::Corrupt,
+        ImageError::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            LoadError::Corrupt
+        }
+        _ => LoadError::Unreadable,
+    }
+}
+
+/// Opens an image together with its embedded ICC profile, if the format supports one
+fn open_image(path: &std::path::Path) -> ImageResult<(DynamicImage, Option<Vec<u8>>)> {
+    let bytes = std::fs::read(path)?;
+    let format = ImageFormat::from_path(path).or_else(|_| image::guess_format(&bytes))?;
+    let reader = Cursor::new(&bytes);
+    match format {
+        ImageFormat::Jpeg => decode_with_profile(JpegDecoder::new(reader)?),
+        ImageFormat::Png => decode_with_profile(PngDecoder::new(reader)?),
+        ImageFormat::WebP => decode_with_profile(WebPDecoder::new(reader)?),
+        ImageFormat::Tiff => decode_with_profile(TiffDecoder::new(reader)?),
+        _ => Ok((image::load_from_memory_with_format(&bytes, format)?, None)),
+    }
+}
+
+/// Decodes an image and gets its ICC profile from the decoder
+fn decode_with_profile<'a>(
+    mut decoder: impl ImageDecoder<'a>,
+) -> ImageResult<(DynamicImage, Option<Vec<u8>>)> {
+    let icc_profile = decoder.icc_profile();
+    Ok((DynamicImage::from_decoder(decoder)?, icc_profile))
+}
+
+/// Converts the colors of an image from the color space of an ICC profile to sRGB for displaying. Returns false if
+/// the profile is invalid or not an RGB profile, the image is left unchanged then.
+fn convert_to_srgb(buffer: &mut ImageBuffer, icc_profile: &[u8]) -> bool {
+    let profile = match ColorProfile::new_from_slice(icc_profile) {
+        Ok(profile) if profile.color_space == DataColorSpace::Rgb => profile,
+        Ok(_) => return false,
+        Err(e) => {
+            log::debug!("Invalid ICC profile: {:?}", e);
+            return false;
+        }
+    };
+    let transform = match profile.create_transform_8bit(
+        Layout::Rgba,
+        &ColorProfile::new_srgb(),
+        Layout::Rgba,
+        TransformOptions::default(),
+    ) {
+        Ok(transform) => transform,
+        Err(e) => {
+            log::debug!("ICC profile cannot be converted to sRGB: {:?}", e);
+            return false;
+        }
+    };
+    let mut converted = vec![0; buffer.as_raw().len()];
+    if transform
+        .transform(buffer.as_raw(), &mut converted)
+        .is_err()
+    {
+        return false;
+    }
+    buffer.copy_from_slice(&converted);
+    true
+}
+
 fn resize_and_rotate(
     cat_image: ImageBuffer,
     rotate: i32,
@@ -77,12 +171,7 @@ fn resize_and_rotate(
         (max_width, max_height),
     );
     if let Ok(cat_image) = resize_image(cat_image, new_width, new_height) {
-        Some(match rotate {
-            90 => image::imageops::rotate90(&cat_image),
-            180 => image::imageops::rotate180(&cat_image),
-            270 => image::imageops::rotate270(&cat_image),
-            _ => cat_image,
-        })
+        Some(rotate_image(cat_image, rotate))
     } else {
         None
     }
@@ -97,20 +186,29 @@ fn load_raw_image_and_rotate(
 ) -> Option<ImageBuffer> {
     let raw = match rawloader::decode_file(path) {
         Ok(raw) => raw,
-        Err(_) => return None,
+        Err(e) => {
+            log::warn!("Error decoding raw image {}: {}", path.display(), e);
+            return None;
+        }
     };
 
     let source = imagepipe::ImageSource::Raw(raw);
 
     let mut pipeline = match imagepipe::Pipeline::new_from_source(source) {
         Ok(pipeline) => pipeline,
-        Err(_) => return None,
+        Err(e) => {
+            log::warn!("Error processing raw image {}: {}", path.display(), e);
+            return None;
+        }
     };
 
     pipeline.run(None);
     let image = match pipeline.output_8bit(None) {
         Ok(image) => image,
-        Err(_) => return None,
+        Err(e) => {
+            log::warn!("Error processing raw image {}: {}", path.display(), e);
+            return None;
+        }
     };
 
     let image = image::ImageBuffer::<image::Rgb<u8>, Vec<u8>>::from_raw(
@@ -129,8 +227,286 @@ fn load_raw_image_and_rotate(
     resize_and_rotate(rgba_image, rotate, max_width, max_height)
 }
 
+/// Gets the small thumbnail embedded in the EXIF data of an image, rotated like the image. Reading it is much faster
+/// than decoding the whole image, so it can be shown until the image is loaded. Returns None if there is no thumbnail.
+pub fn get_exif_thumbnail(item: &FileItem) -> Option<ImageBuffer> {
+    if !item.is_image() {
+        return None;
+    }
+    let file = std::fs::File::open(&item.path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut std::io::BufReader::new(file))
+        .ok()?;
+    let field_value = |tag| {
+        exif.get_field(tag, exif::In::THUMBNAIL)?
+            .value
+            .get_uint(0)
+            .map(|value| value as usize)
+    };
+    let offset = field_value(exif::Tag::JPEGInterchangeFormat)?;
+    let length = field_value(exif::Tag::JPEGInterchangeFormatLength)?;
+    let thumbnail = exif.buf().get(offset..offset.checked_add(length)?)?;
+    let thumbnail =
+        image::load_from_memory_with_format(thumbnail, image::ImageFormat::Jpeg).ok()?;
+    let thumbnail = thumbnail.into_rgba8();
+    Some(rotate_image(thumbnail, get_rotation(item)))
+}
+
+/// Rotates an image clockwise by a given angle in degrees, other angles than 90, 180 and 270 keep the image unchanged
+pub fn rotate_image(image: ImageBuffer, rotate: i32) -> ImageBuffer {
+    match rotate {
+        90 => image::imageops::rotate90(&image),
+        180 => image::imageops::rotate180(&image),
+        270 => image::imageops::rotate270(&image),
+        _ => image,
+    }
+}
+
 /// Converts a byte buffer to an image buffer
 pub fn image_from_buffer(bytes: &[u8]) -> Result<ImageBuffer, image::ImageError> {
     let cat_image = image::load_from_memory(bytes)?;
     Ok(cat_image.into_rgba8())
 }
+
+/// Changes the brightness and contrast of an image buffer for displaying, a value of 0 leaves the property unchanged
+pub fn adjust_image(buffer: &ImageBuffer, brightness: i32, contrast: f32) -> ImageBuffer {
+    let mut adjusted = if brightness != 0 {
+        image::imageops::brighten(buffer, brightness)
+    } else {
+        buffer.clone()
+    };
+    if contrast != 0.0 {
+        adjusted = image::imageops::contrast(&adjusted, contrast);
+    }
+    adjusted
+}
+
+/// Crops a square of the given size at native resolution around a position given as fraction of the image width and height.
+/// At the borders of the image, the square is moved inside the image.
+pub fn crop_loupe(buffer: &ImageBuffer, x: f32, y: f32, size: u32) -> ImageBuffer {
+    let width = size.min(buffer.width());
+    let height = size.min(buffer.height());
+    let center_x = (x.clamp(0.0, 1.0) * buffer.width() as f32) as u32;
+    let center_y = (y.clamp(0.0, 1.0) * buffer.height() as f32) as u32;
+    let left = center_x
+        .saturating_sub(width / 2)
+        .min(buffer.width() - width);
+    let top = center_y
+        .saturating_sub(height / 2)
+        .min(buffer.height() - height);
+    image::imageops::crop_imm(buffer, left, top, width, height).to_image()
+}
+
+/// Gets the sharpness of an image as the variance of the Laplacian of its brightness.
+/// Sharp images have strong edges and thus a high variance, blurred images a low one.
+pub fn get_sharpness(buffer: &ImageBuffer) -> f64 {
+    let gray = image::imageops::grayscale(buffer);
+    let (width, height) = gray.dimensions();
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+    let value = |x: u32, y: u32| gray.get_pixel(x, y)[0] as f64;
+    let laplacians: Vec<f64> = (1..height - 1)
+        .flat_map(|y| (1..width - 1).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            value(x - 1, y) + value(x + 1, y) + value(x, y - 1) + value(x, y + 1)
+                - 4.0 * value(x, y)
+        })
+        .collect();
+    let count = laplacians.len() as f64;
+    let mean = laplacians.iter().sum::<f64>() / count;
+    laplacians
+        .iter()
+        .map(|laplacian| (laplacian - mean).powi(2))
+        .sum::<f64>()
+        / count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item_sort_list::ItemList;
+    use std::path::Path;
+
+    #[test]
+    fn test_webp() {
+        let mut item_list = ItemList::new();
+        item_list.add_item(Path::new("tests/test_lossy.webp"), true, "");
+        item_list.add_item(Path::new("tests/test_lossless.webp"), true, "");
+
+        let lossy = get_image_buffer(&item_list.items[0], 1000, 1000);
+        assert_eq!(lossy.dimensions(), (100, 100));
+        // The lossless image is stored as landscape and rotated by its EXIF orientation
+        let lossless = get_image_buffer(&item_list.items[1], 1000, 1000);
+        assert_eq!(get_rotation(&item_list.items[1]), 90);
+        assert_eq!(lossless.dimensions(), (32, 48));
+    }
+
+    #[test]
+    fn test_exif_thumbnail() {
+        let mut item_list = ItemList::new();
+        item_list.add_item(Path::new("tests/test_thumbnail.jpg"), true, "");
+        item_list.add_item(Path::new("tests/test.jpg"), true, "");
+        item_list.add_item(Path::new("tests/test_no_exif.jpg"), true, "");
+
+        // The embedded thumbnail of 12x8 pixels is rotated like the image
+        let thumbnail = get_exif_thumbnail(&item_list.items[0]).unwrap();
+        assert_eq!(get_rotation(&item_list.items[0]), 90);
+        assert_eq!(thumbnail.dimensions(), (8, 12));
+        let pixel = thumbnail.get_pixel(4, 6);
+        assert!(pixel[0] > 150 && pixel[1] < 80 && pixel[2] < 80);
+
+        assert!(get_exif_thumbnail(&item_list.items[1]).is_none());
+        assert!(get_exif_thumbnail(&item_list.items[2]).is_none());
+        assert!(get_exif_thumbnail(&FileItem::dummy("tests/test.mp4", 0, true)).is_none());
+    }
+
+    #[test]
+    fn test_rotate_image() {
+        let mut image = ImageBuffer::new(3, 2);
+        image.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+
+        // The top left pixel moves clockwise with the rotation
+        let rotated = rotate_image(image.clone(), 90);
+        assert_eq!(rotated.dimensions(), (2, 3));
+        assert_eq!(rotated.get_pixel(1, 0)[0], 255);
+        let rotated = rotate_image(image.clone(), 180);
+        assert_eq!(rotated.dimensions(), (3, 2));
+        assert_eq!(rotated.get_pixel(2, 1)[0], 255);
+        let rotated = rotate_image(image.clone(), 270);
+        assert_eq!(rotated.dimensions(), (2, 3));
+        assert_eq!(rotated.get_pixel(0, 2)[0], 255);
+        assert_eq!(rotate_image(image.clone(), 45), image);
+    }
+
+    #[test]
+    fn test_sharpness() {
+        let flat = ImageBuffer::from_pixel(8, 8, image::Rgba([100, 100, 100, 255]));
+        assert_eq!(get_sharpness(&flat), 0.0);
+        assert_eq!(get_sharpness(&ImageBuffer::new(2, 2)), 0.0);
+
+        // A checkerboard has stronger edges than a blurred version of it
+        let checkerboard = ImageBuffer::from_fn(8, 8, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgba([255, 255, 255, 255])
+            } else {
+                image::Rgba([0, 0, 0, 255])
+            }
+        });
+        let blurred = image::imageops::blur(&checkerboard, 1.0);
+        assert!(get_sharpness(&checkerboard) > get_sharpness(&blurred));
+    }
+
+    #[test]
+    fn test_crop_loupe() {
+        let buffer = ImageBuffer::from_fn(1000, 500, |x, y| {
+            image::Rgba([
+                (x % 256) as u8,
+                (y % 256) as u8,
+                (x / 256) as u8,
+                (y / 256) as u8,
+            ])
+        });
+        let pixel_at = |x: u32, y: u32| *buffer.get_pixel(x, y);
+
+        let loupe = crop_loupe(&buffer, 0.5, 0.5, 100);
+        assert_eq!(loupe.dimensions(), (100, 100));
+        assert_eq!(loupe.get_pixel(0, 0), &pixel_at(450, 200));
+
+        // The square stays inside the image at the borders
+        let loupe = crop_loupe(&buffer, 0.0, 0.0, 100);
+        assert_eq!(loupe.get_pixel(0, 0), &pixel_at(0, 0));
+        let loupe = crop_loupe(&buffer, 1.0, 1.5, 100);
+        assert_eq!(loupe.get_pixel(99, 99), &pixel_at(999, 499));
+
+        // Images smaller than the loupe are shown completely
+        let loupe = crop_loupe(&buffer, 0.5, 0.5, 600);
+        assert_eq!(loupe.dimensions(), (600, 500));
+        assert_eq!(loupe.get_pixel(0, 0), &pixel_at(200, 0));
+    }
+
+    #[test]
+    fn test_convert_to_srgb() {
+        let original = ImageBuffer::from_pixel(2, 2, image::Rgba([200, 100, 50, 128]));
+
+        // Display P3 colors are more saturated than the same values in sRGB
+        let mut buffer = original.clone();
+        let display_p3 = ColorProfile::new_display_p3().encode().unwrap();
+        assert!(convert_to_srgb(&mut buffer, &display_p3));
+        let pixel = buffer.get_pixel(1, 1);
+        assert!(pixel[0] > 200 && pixel[2] < 50);
+        assert_eq!(pixel[3], 128);
+
+        // An sRGB profile keeps the colors
+        let mut buffer = original.clone();
+        let srgb = ColorProfile::new_srgb().encode().unwrap();
+        assert!(convert_to_srgb(&mut buffer, &srgb));
+        for (converted, original) in buffer.as_raw().iter().zip(original.as_raw()) {
+            assert!(converted.abs_diff(*original) <= 1);
+        }
+
+        // Invalid profiles leave the image unchanged
+        let mut buffer = original.clone();
+        assert!(!convert_to_srgb(&mut buffer, b"no profile"));
+        assert_eq!(buffer, original);
+    }
+
+    #[test]
+    fn test_corrupt_images() {
+        let directory = std::env::temp_dir().join("image_sieve_test_corrupt_images");
+        std::fs::create_dir_all(&directory).unwrap();
+        let empty = directory.join("empty.jpg");
+        let truncated = directory.join("truncated.jpg");
+        std::fs::write(&empty, []).unwrap();
+        let bytes = std::fs::read("tests/test.jpg").unwrap();
+        std::fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+
+        let mut item_list = ItemList::new();
+        item_list.add_item(&empty, true, "");
+        item_list.add_item(&truncated, true, "");
+        item_list.add_item(Path::new("tests/not_there.jpg"), true, "");
+        assert_eq!(
+            load_image_buffer(&item_list.items[0], 100, 100),
+            Err(LoadError::Corrupt)
+        );
+        assert_eq!(
+            load_image_buffer(&item_list.items[1], 100, 100),
+            Err(LoadError::Corrupt)
+        );
+        assert_eq!(
+            load_image_buffer(&item_list.items[2], 100, 100),
+            Err(LoadError::Unreadable)
+        );
+        // The placeholder of images that cannot be loaded has a single pixel
+        assert_eq!(
+            get_image_buffer(&item_list.items[1], 100, 100).dimensions(),
+            (1, 1)
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_open_image() {
+        let (image, icc_profile) = open_image(Path::new("tests/test_no_exif.jpg")).unwrap();
+        assert!(image.width() > 0);
+        assert!(icc_profile.is_none());
+        assert!(open_image(Path::new("tests/not_there.jpg")).is_err());
+    }
+
+    #[test]
+    fn test_adjust_image() {
+        let buffer = ImageBuffer::from_pixel(2, 2, image::Rgba([100, 100, 100, 255]));
+        assert_eq!(adjust_image(&buffer, 0, 0.0), buffer);
+
+        let brighter = adjust_image(&buffer, 50, 0.0);
+        assert_eq!(brighter.get_pixel(0, 0), &image::Rgba([150, 150, 150, 255]));
+        let darker = adjust_image(&buffer, -150, 0.0);
+        assert_eq!(darker.get_pixel(1, 1), &image::Rgba([0, 0, 0, 255]));
+
+        // Contrast moves values away from the middle gray
+        let contrasted = adjust_image(&buffer, 0, 50.0);
+        assert!(contrasted.get_pixel(0, 0)[0] < 100);
+        assert_eq!(contrasted.get_pixel(0, 0)[3], 255);
+    }
+}
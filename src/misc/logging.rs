@@ -0,0 +1,111 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use log::LevelFilter;
+
+/// Maximum size of the log file before it is rotated
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// Environment variable that overrides the log level from the settings
+const LOG_ENV: &str = "RUST_LOG";
+
+/// Initialize logging to a file with the given level. If the RUST_LOG environment variable is set, it takes
+/// precedence over the given level. A log file that grew too large is renamed before and a new file is started.
+pub fn init(log_file: &Path, level: &str) {
+    rotate(log_file, MAX_LOG_SIZE);
+    let file = match OpenOptions::new().create(true).append(true).open(log_file) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Error opening log file {}: {}", log_file.display(), e);
+            return;
+        }
+    };
+
+    let mut builder = env_logger::Builder::new();
+    builder
+        .format(|buf, record| {
+            writeln!(
+                buf,
+                "{} {:<5} [{}] {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.target(),
+                record.args()
+            )
+        })
+        .target(env_logger::Target::Pipe(Box::new(file)));
+    let use_env = std::env::var(LOG_ENV).is_ok();
+    if use_env {
+        builder.parse_env(LOG_ENV);
+    } else {
+        // Only log messages of image_sieve, the level is then restricted by the maximum level
+        builder
+            .filter_level(LevelFilter::Warn)
+            .filter_module("image_sieve", LevelFilter::Trace);
+    }
+    if builder.try_init().is_ok() && !use_env {
+        set_level(level);
+    }
+}
+
+/// Set the log level, unless the level is controlled by the RUST_LOG environment variable
+pub fn set_level(level: &str) {
+    if std::env::var(LOG_ENV).is_err() {
+        log::set_max_level(to_level_filter(level));
+    }
+}
+
+/// Convert a log level name from the settings to a level filter
+fn to_level_filter(level: &str) -> LevelFilter {
+    match level {
+        "Off" => LevelFilter::Off,
+        "Error" => LevelFilter::Error,
+        "Info" => LevelFilter::Info,
+        "Debug" => LevelFilter::Debug,
+        "Trace" => LevelFilter::Trace,
+        _ => LevelFilter::Warn,
+    }
+}
+
+/// Rename a log file exceeding the given size, replacing a previously rotated file
+fn rotate(log_file: &Path, max_size: u64) {
+    if let Ok(metadata) = fs::metadata(log_file) {
+        if metadata.len() > max_size {
+            let mut rotated_file = log_file.as_os_str().to_owned();
+            rotated_file.push(".1");
+            fs::rename(log_file, rotated_file).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(to_level_filter("Off"), LevelFilter::Off);
+        assert_eq!(to_level_filter("Error"), LevelFilter::Error);
+        assert_eq!(to_level_filter("Warn"), LevelFilter::Warn);
+        assert_eq!(to_level_filter("Trace"), LevelFilter::Trace);
+        assert_eq!(to_level_filter("Something"), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn test_rotate() {
+        fs::write("test_rotate.log", "0123456789").unwrap();
+        rotate(Path::new("test_rotate.log"), 20);
+        assert!(Path::new("test_rotate.log").exists());
+
+        rotate(Path::new("test_rotate.log"), 5);
+        assert!(!Path::new("test_rotate.log").exists());
+        assert_eq!(
+            fs::read_to_string("test_rotate.log.1").unwrap(),
+            "0123456789"
+        );
+        fs::remove_file("test_rotate.log.1").unwrap();
+    }
+}
@@ -54,6 +54,16 @@ where
         self.map.insert(key, (t, self.counter));
     }
 
+    /// Iterates over all values of the map without changing when they were last used.
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.map.values().map(|(t, _)| t)
+    }
+
+    /// Removes all items whose key does not fulfill the predicate.
+    pub fn retain(&mut self, predicate: impl Fn(&K) -> bool) {
+        self.map.retain(|key, _| predicate(key));
+    }
+
     /// Clear the map.
     pub fn clear(&mut self) {
         self.map.clear();
@@ -104,6 +114,14 @@ mod tests {
         assert!(list.get(5).is_none());
         assert_eq!(*list.get(4).unwrap(), 4);
 
+        let mut values: Vec<u32> = list.values().copied().collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![4, 12, 14]);
+
+        list.retain(|key| *key != 6);
+        assert!(list.get(6).is_none());
+        assert_eq!(*list.get(7).unwrap(), 14);
+
         list.clear();
         assert!(list.get(4).is_none());
     }
@@ -1,5 +1,8 @@
+pub mod combo_values;
 pub mod image_cache;
 pub mod images;
+pub mod logging;
 mod lru_map;
 mod resize;
-mod video_to_image;
+pub mod translations;
+pub mod video_to_image;
@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{OnceLock, RwLock},
+};
+
+/// Translations of the texts shown from Rust in the current language, none for English. The texts of the user interface
+/// are translated by slint with the bundled translations in the lang folder.
+static TRANSLATIONS: RwLock<Option<&'static HashMap<&'static str, &'static str>>> =
+    RwLock::new(None);
+
+/// Sets the language of the translated texts, "System" uses the language of the system locale and unknown
+/// languages fall back to English
+pub fn set_language(language: &str) {
+    let language = if language == "System" {
+        get_system_language()
+    } else {
+        language
+    };
+    let (translations, bundled_language) = match language {
+        "Deutsch" => (Some(german()), "de"),
+        // The empty language selects the original texts of the user interface
+        _ => (None, ""),
+    };
+    *TRANSLATIONS.write().unwrap() = translations;
+    if let Err(error) = slint::select_bundled_translation(bundled_language) {
+        log::warn!("Cannot select the translation {}: {}", language, error);
+    }
+}
+
+/// Translates an English text to the current language, texts without a translation are returned unchanged
+pub fn tr(text: &str) -> &str {
+    match *TRANSLATIONS.read().unwrap() {
+        Some(translations) => translations.get(text).copied().unwrap_or(text),
+        None => text,
+    }
+}
+
+/// Translates an English text containing {} placeholders and replaces them by the given arguments in order
+pub fn tr_format(text: &'static str, arguments: &[&dyn Display]) -> String {
+    let mut formatted = String::new();
+    let mut parts = tr(text).split("{}");
+    if let Some(first) = parts.next() {
+        formatted.push_str(first);
+    }
+    for (index, part) in parts.enumerate() {
+        if let Some(argument) = arguments.get(index) {
+            formatted.push_str(&argument.to_string());
+        }
+        formatted.push_str(part);
+    }
+    formatted
+}
+
+/// Gets the language of the system locale from the environment
+fn get_system_language() -> &'static str {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|locale| !locale.is_empty())
+        .map_or("English", |locale| language_of_locale(&locale))
+}
+
+/// Gets the language of a locale like "de_DE.UTF-8"
+fn language_of_locale(locale: &str) -> &'static str {
+    if locale.to_lowercase().starts_with("de") {
+        "Deutsch"
+    } else {
+        "English"
+    }
+}
+
+/// German translations looked up by their English text
+fn german() -> &'static HashMap<&'static str, &'static str> {
+    static MAP: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    MAP.get_or_init(|| GERMAN.iter().copied().collect())
+}
+
+/// German translations
+const GERMAN: &[(&str, &str)] = &[
+    // Messages
+    ("No images found", "Keine Bilder gefunden"),
+    ("📋 Path copied to clipboard", "📋 Pfad in die Zwischenablage kopiert"),
+    ("Path could not be copied to clipboard", "Pfad konnte nicht in die Zwischenablage kopiert werden"),
+    ("Exported to {}", "Exportiert nach {}"),
+    ("Error exporting to {}: {}", "Fehler beim Exportieren nach {}: {}"),
+    (
+        "Finalizing the project keeps {} and permanently deletes {} discarded images and videos in the source directory.",
+        "Das Abschließen des Projekts behält {} und löscht {} verworfene Bilder und Videos im Quellverzeichnis endgültig.",
+    ),
+    ("Done", "Fertig"),
+    (" from {} to {}", " vom {} bis {}"),
+    (
+        "🗂 {} images and videos{}, {}\n✔ {} kept ({}), 🗑 {} discarded, ❔ {} undecided, ⭐ {} favorites",
+        "🗂 {} Bilder und Videos{}, {}\n✔ {} behalten ({}), 🗑 {} verworfen, ❔ {} unentschieden, ⭐ {} Favoriten",
+    ),
+    ("No images and videos are tagged for deletion", "Keine Bilder und Videos sind zum Löschen markiert"),
+    ("{} images and videos tagged for deletion, {}", "{} Bilder und Videos zum Löschen markiert, {}"),
+    ("Deleting {} images and videos tagged for deletion", "Lösche {} zum Löschen markierte Bilder und Videos"),
+    ("Undoing the last sieve to {}", "Mache das letzte Sieben nach {} rückgängig"),
+    (
+        "Comparing the kept images and videos with the last sieve to {}",
+        "Vergleiche die behaltenen Bilder und Videos mit dem letzten Sieben nach {}",
+    ),
+    ("{} added and {} removed since the last sieve", "{} hinzugefügt und {} entfernt seit dem letzten Sieben"),
+    (
+        "Error reading the manifest of the last sieve in {}: {}",
+        "Fehler beim Lesen des Protokolls des letzten Siebens in {}: {}",
+    ),
+    ("Checking existing project...", "Prüfe bestehendes Projekt..."),
+    ("Loading project...", "Lade Projekt..."),
+    ("Loading project... {}%", "Lade Projekt... {}%"),
+    ("Checking existing files...", "Prüfe bestehende Dateien..."),
+    ("Searching {}", "Durchsuche {}"),
+    ("Finding similar images: {}%", "Suche ähnliche Bilder: {}%"),
+    ("Preparing thumbnails: {}/{}", "Bereite Vorschaubilder vor: {}/{}"),
+    ("🔀 Group {}", "🔀 Gruppe {}"),
+    ("Select two different events to merge", "Zwei verschiedene Ereignisse zum Zusammenführen auswählen"),
+    ("Start date must be before end date", "Das Startdatum muss vor dem Enddatum liegen"),
+    ("⚠ {} does not exist anymore", "⚠ {} existiert nicht mehr"),
+    (
+        "Kept the largest item of each group, {} changed",
+        "Größtes Element jeder Gruppe behalten, {} geändert",
+    ),
+    (
+        "Kept the smallest item of each group, {} changed",
+        "Kleinstes Element jeder Gruppe behalten, {} geändert",
+    ),
+    (
+        "The file is empty or truncated, please download or copy it again",
+        "Die Datei ist leer oder unvollständig, bitte erneut herunterladen oder kopieren",
+    ),
+    (
+        "📂 Open a folder with images and videos to start",
+        "📂 Zum Starten einen Ordner mit Bildern und Videos öffnen",
+    ),
+    (
+        "Only folders can be dropped to select the source",
+        "Nur Ordner können zum Auswählen der Quelle abgelegt werden",
+    ),
+    (
+        "⚠ Only one folder can be opened at a time, {} was not opened",
+        "⚠ Es kann nur ein Ordner gleichzeitig geöffnet werden, {} wurde nicht geöffnet",
+    ),
+    // Similar images
+    ("⚠ {} groups with more than {} similar items were split", "⚠ {} Gruppen mit mehr als {} ähnlichen Elementen wurden aufgeteilt"),
+    ("Calculating image hashes: {}%", "Berechne Bild-Hashes: {}%"),
+    ("⚠ The folder {} cannot be found. It may have been moved, deleted or be on a removed drive. Relocate the source to keep your selection and events.", "⚠ Der Ordner {} wurde nicht gefunden. Er wurde eventuell verschoben, gelöscht oder befindet sich auf einem entfernten Laufwerk. Die Quelle neu zuordnen, um die Auswahl und die Ereignisse zu behalten."),
+    ("⚠ The project in {} is already opened in another ImageSieve instance. Your changes will not be saved unless you use the project anyway, e.g. if the other instance was not closed properly.", "⚠ Das Projekt in {} ist bereits in einer anderen ImageSieve-Instanz geöffnet. Die Änderungen werden nicht gespeichert, außer das Projekt wird trotzdem verwendet, z.B. wenn die andere Instanz nicht richtig beendet wurde."),
+    ("⚠ The changes of {} were not saved, because the project is opened in another ImageSieve instance.", "⚠ Die Änderungen von {} wurden nicht gespeichert, weil das Projekt in einer anderen ImageSieve-Instanz geöffnet ist."),
+    // Details
+    ("Path", "Pfad"),
+    ("Size", "Größe"),
+    ("Date", "Datum"),
+    ("Invalid date", "Ungültiges Datum"),
+    ("Orientation", "Ausrichtung"),
+    ("Orientation mismatch", "Abweichende Ausrichtung"),
+    ("The pixels are already rotated, the orientation is ignored", "Die Pixel sind bereits gedreht, die Ausrichtung wird ignoriert"),
+    ("The pixels are already rotated, the image is likely rotated twice", "Die Pixel sind bereits gedreht, das Bild wird wahrscheinlich doppelt gedreht"),
+    ("Rotation", "Drehung"),
+    ("Event", "Ereignis"),
+    ("Similar to", "Ähnlich zu"),
+    ("Similarity group", "Ähnlichkeitsgruppe"),
+    ("🔍 Sharpness", "🔍 Schärfe"),
+    ("📐 Resolution", "📐 Auflösung"),
+    ("💾 Size", "💾 Größe"),
+    ("{} of {} similar images", "{} von {} ähnlichen Bildern"),
+    // Events
+    ("Merged event overlaps with {}", "Das zusammengeführte Ereignis überschneidet sich mit {}"),
+    ("✔ Kept {} images and videos", "✔ {} Bilder und Videos behalten"),
+    ("🗑 Discarded {} images and videos", "🗑 {} Bilder und Videos verworfen"),
+    ("Start date: {}", "Startdatum: {}"),
+    ("End date: {}", "Enddatum: {}"),
+    ("Event overlaps with {}", "Das Ereignis überschneidet sich mit {}"),
+    // Sieve
+    ("⚠ {} cannot be opened. Pass a folder with images and videos or its image_sieve.json project file.", "⚠ {} kann nicht geöffnet werden. Einen Ordner mit Bildern und Videos oder seine Projektdatei image_sieve.json angeben."),
+    ("⚠ The target directory is the source directory. Sieved files are found again when opening it the next time.", "⚠ Das Zielverzeichnis ist das Quellverzeichnis. Gesiebte Dateien werden beim nächsten Öffnen wieder gefunden."),
+    ("⚠ The target directory is inside the source directory. It is excluded when scanning the source directory.", "⚠ Das Zielverzeichnis liegt im Quellverzeichnis. Es wird beim Durchsuchen des Quellverzeichnisses ausgelassen."),
+    ("Sieving using {} method to {} with directories {}, existing files: {}", "Sieben mit der Methode {} nach {} mit den Verzeichnissen {}, vorhandene Dateien: {}"),
+    ("Skipping {} kept files smaller than {} KB", "Überspringe {} behaltene Dateien kleiner als {} KB"),
+    ("\n{} ({}, {} hashes)", "\n{} ({}, {} Hashes)"),
+    // Combo boxes
+    ("Type", "Typ"),
+    ("Asc", "Aufsteigend"),
+    ("Desc", "Absteigend"),
+    ("Very low", "Sehr niedrig"),
+    ("Low", "Niedrig"),
+    ("Medium", "Mittel"),
+    ("High", "Hoch"),
+    ("Very high", "Sehr hoch"),
+    ("Fast (8x4)", "Schnell (8x4)"),
+    ("Precise (32x16)", "Genau (32x16)"),
+    ("Automatic", "Automatisch"),
+    ("On", "Ein"),
+    ("Off", "Aus"),
+    ("Fit", "Einpassen"),
+    ("Fill", "Füllen"),
+    ("Copy to target directory", "In das Zielverzeichnis kopieren"),
+    ("Move to target directory", "In das Zielverzeichnis verschieben"),
+    ("Move to target directory and delete in source directory", "In das Zielverzeichnis verschieben und im Quellverzeichnis löschen"),
+    ("Delete in source directory", "Im Quellverzeichnis löschen"),
+    ("Copy to zip archive in target directory", "In ein Zip-Archiv im Zielverzeichnis kopieren"),
+    ("Year and month (2021-12)", "Jahr und Monat (2021-12)"),
+    ("Year (2021)", "Jahr (2021)"),
+    ("Year, month and day (2021-12-27)", "Jahr, Monat und Tag (2021-12-27)"),
+    ("Year and quarter (2021-Q4)", "Jahr und Quartal (2021-Q4)"),
+    ("Year and month in subdirectory (2021/12)", "Jahr und Monat im Unterverzeichnis (2021/12)"),
+    ("Same subfolders as in source directory", "Gleiche Unterordner wie im Quellverzeichnis"),
+    ("Rename the new file (name (1).jpg)", "Die neue Datei umbenennen (name (1).jpg)"),
+    ("Overwrite the existing file", "Die vorhandene Datei überschreiben"),
+    ("Skip the new file", "Die neue Datei überspringen"),
+    ("Time of capture", "Aufnahmezeit"),
+    ("Local time zone", "Lokale Zeitzone"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_fork::rusty_fork_test;
+
+    #[test]
+    fn test_german_translations() {
+        for &(english, german) in GERMAN {
+            assert_eq!(
+                english.matches("{}").count(),
+                german.matches("{}").count(),
+                "Placeholders of {}",
+                english
+            );
+            assert_eq!(
+                GERMAN.iter().filter(|(other, _)| *other == english).count(),
+                1,
+                "Duplicate translation of {}",
+                english
+            );
+        }
+        assert_eq!(language_of_locale("de_DE.UTF-8"), "Deutsch");
+        assert_eq!(language_of_locale("en_US.UTF-8"), "English");
+        assert_eq!(language_of_locale("C"), "English");
+    }
+
+    rusty_fork_test! {
+        #[test]
+        fn test_set_language() {
+            assert_eq!(tr("No images found"), "No images found");
+            assert_eq!(tr_format("Exported to {}", &[&"test.csv"]), "Exported to test.csv");
+
+            set_language("Deutsch");
+            assert_eq!(tr("No images found"), "Keine Bilder gefunden");
+            assert_eq!(tr("Not translated"), "Not translated");
+            assert_eq!(
+                tr_format("Preparing thumbnails: {}/{}", &[&2, &3]),
+                "Bereite Vorschaubilder vor: 2/3"
+            );
+            // Missing arguments leave the placeholders empty
+            assert_eq!(tr_format("Exported to {}", &[]), "Exportiert nach ");
+
+            set_language("English");
+            assert_eq!(tr("No images found"), "No images found");
+        }
+    }
+}
@@ -10,12 +10,93 @@ use image::imageops;
 const SCREENSHOTS_X: u32 = 3;
 const SCREENSHOTS_Y: u32 = 3;
 const VIDEO_PNG: &[u8; 2900] = include_bytes!("video.png");
+const MICROSECONDS_PER_SECOND: f64 = 1_000_000.0;
 
 /// Construct an image for a video by combining 9 frames from the video.
 pub fn get_image_buffer(item: &FileItem, max_width: u32, max_height: u32) -> ImageBuffer {
     create_image_from_video(item, max_width, max_height).unwrap_or_else(|_| get_alternative_image())
 }
 
+/// Gets the number of frames of a video, estimated from its duration and frame rate if the container does not store it
+pub fn get_frame_count(item: &FileItem) -> Option<u32> {
+    let input_context = ffmpeg::format::input(&item.path).ok()?;
+    let video_stream = input_context.streams().best(ffmpeg::media::Type::Video)?;
+    if video_stream.frames() > 0 {
+        return Some(video_stream.frames() as u32);
+    }
+    let frame_rate = f64::from(video_stream.avg_frame_rate());
+    let frames = (input_context.duration() as f64 / MICROSECONDS_PER_SECOND * frame_rate).round();
+    if frames >= 1.0 {
+        Some(frames as u32)
+    } else {
+        None
+    }
+}
+
+/// Gets a single frame of a video by its index. Like for the 3x3 frames image, the video is seeked to a key frame, but
+/// then decoded up to the requested frame, so that the exact frame is shown and not only the nearest key frame.
+/// Indices after the last frame return the last frame.
+pub fn get_frame_buffer(
+    item: &FileItem,
+    frame_index: u32,
+    max_width: u32,
+    max_height: u32,
+) -> Option<ImageBuffer> {
+    let mut input_context = ffmpeg::format::input(&item.path).ok()?;
+    let video_stream = input_context.streams().best(ffmpeg::media::Type::Video)?;
+    let stream_index = video_stream.index();
+    let time_base = f64::from(video_stream.time_base());
+    let frame_rate = f64::from(video_stream.avg_frame_rate());
+    if time_base <= 0.0 || frame_rate <= 0.0 {
+        return None;
+    }
+    let mut decoder = ffmpeg::codec::Context::from_parameters(video_stream.parameters())
+        .ok()?
+        .decoder()
+        .video()
+        .ok()?;
+
+    let frame_seconds = frame_index as f64 / frame_rate;
+    // Accept frames half a frame early, as timestamps are rounded to the time base
+    let frame_timestamp = ((frame_seconds - 0.5 / frame_rate) / time_base) as i64;
+    // Seek to the last key frame before the requested frame
+    let seek_ts = (frame_seconds * MICROSECONDS_PER_SECOND) as i64;
+    input_context.seek(seek_ts, ..=seek_ts).ok()?;
+
+    let mut last_frame = None;
+    for (s, packet) in input_context.packets() {
+        if stream_index != s.index() {
+            continue;
+        }
+        decoder.send_packet(&packet).ok();
+        let mut frame = ffmpeg::util::frame::Video::empty();
+        while decoder.receive_frame(&mut frame).is_ok() {
+            if frame.width() == 0 || frame.height() == 0 {
+                continue;
+            }
+            if frame.timestamp().unwrap_or(0) >= frame_timestamp {
+                return scale_frame(&frame, item.get_orientation(), max_width, max_height);
+            }
+            last_frame = Some(frame);
+            frame = ffmpeg::util::frame::Video::empty();
+        }
+    }
+    last_frame.and_then(|frame| scale_frame(&frame, item.get_orientation(), max_width, max_height))
+}
+
+/// Converts a single frame to an upright image buffer with a width and height constraint
+fn scale_frame(
+    frame: &ffmpeg::util::frame::Video,
+    orientation: Option<&Orientation>,
+    max_width: u32,
+    max_height: u32,
+) -> Option<ImageBuffer> {
+    let buffer = rotate_to_orientation(frame_to_image(frame)?, orientation);
+    let (new_width, new_height) =
+        restrict_size((buffer.width(), buffer.height()), (max_width, max_height));
+    resize_image(buffer, new_width, new_height).ok()
+}
+
 /// Get the alternative image of a video camera
 fn get_alternative_image() -> ImageBuffer {
     crate::misc::images::image_from_buffer(VIDEO_PNG).unwrap()
@@ -99,20 +180,7 @@ fn create_image_from_video(
         }
 
         // Rotate the image if necessary
-        if let Some(orientation) = orientation {
-            match orientation {
-                crate::item_sort_list::Orientation::Landscape => {}
-                crate::item_sort_list::Orientation::Portrait90 => {
-                    buffer = image::imageops::rotate90(&buffer);
-                }
-                crate::item_sort_list::Orientation::Landscape180 => {
-                    buffer = image::imageops::rotate180(&buffer);
-                }
-                crate::item_sort_list::Orientation::Portrait270 => {
-                    buffer = image::imageops::rotate270(&buffer);
-                }
-            };
-        }
+        let buffer = rotate_to_orientation(buffer, orientation);
 
         // Scale to max size
         let (new_width, new_height) =
@@ -127,6 +195,16 @@ fn create_image_from_video(
     }
 }
 
+/// Rotates an image of a video according to the orientation of the video
+fn rotate_to_orientation(buffer: ImageBuffer, orientation: Option<&Orientation>) -> ImageBuffer {
+    match orientation {
+        Some(Orientation::Portrait90) => imageops::rotate90(&buffer),
+        Some(Orientation::Landscape180) => imageops::rotate180(&buffer),
+        Some(Orientation::Portrait270) => imageops::rotate270(&buffer),
+        Some(Orientation::Landscape) | None => buffer,
+    }
+}
+
 /// Gets a frame from a packet.
 fn get_frame(
     packet: ffmpeg::Packet,
@@ -148,19 +226,23 @@ fn frame_to_buffer(
     buffer: &mut ImageBuffer,
     position: (u32, u32),
 ) {
+    if let Some(frame_buffer) = frame_to_image(frame) {
+        imageops::overlay(buffer, &frame_buffer, position.0 as i64, position.1 as i64);
+    }
+}
+
+/// Converts a frame to an RGBA image buffer
+fn frame_to_image(frame: &ffmpeg::util::frame::Video) -> Option<ImageBuffer> {
     let mut output_frame = ffmpeg::util::frame::Video::empty();
     let mut converter = frame
         .converter(ffmpeg::util::format::pixel::Pixel::RGBA)
-        .ok()
-        .unwrap();
-    converter.run(frame, &mut output_frame).ok();
-    let frame_buffer = ImageBuffer::from_raw(
+        .ok()?;
+    converter.run(frame, &mut output_frame).ok()?;
+    ImageBuffer::from_raw(
         output_frame.width(),
         output_frame.height(),
         output_frame.data(0).to_vec(),
     )
-    .unwrap();
-    imageops::overlay(buffer, &frame_buffer, position.0 as i64, position.1 as i64);
 }
 
 #[cfg(test)]
@@ -188,4 +270,28 @@ mod tests {
         assert_eq!(image_buffer.width(), 256);
         assert_eq!(image_buffer.height(), 256);
     }
+
+    #[test]
+    fn test_get_frame_buffer() {
+        let file_item = FileItem::dummy("tests/test.mp4", 0, false);
+        let frame_count = get_frame_count(&file_item).unwrap();
+        assert!(frame_count > 1);
+
+        let first_frame = get_frame_buffer(&file_item, 0, 0, 0).unwrap();
+        assert_eq!(first_frame.dimensions(), (320, 240));
+        let last_frame = get_frame_buffer(&file_item, frame_count - 1, 0, 0).unwrap();
+        assert_eq!(last_frame.dimensions(), (320, 240));
+        assert_ne!(first_frame, last_frame);
+        // Frames after the end show the last frame
+        let after_end = get_frame_buffer(&file_item, frame_count + 100, 0, 0).unwrap();
+        assert_eq!(after_end, last_frame);
+
+        let small_frame = get_frame_buffer(&file_item, 1, 100, 100).unwrap();
+        assert!(small_frame.width() <= 100);
+        assert!(small_frame.height() <= 100);
+
+        let file_item = FileItem::dummy("tests/test_invalid.mp4", 0, false);
+        assert!(get_frame_count(&file_item).is_none());
+        assert!(get_frame_buffer(&file_item, 0, 0, 0).is_none());
+    }
 }
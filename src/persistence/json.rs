@@ -1,6 +1,8 @@
 use std::{
     fs,
+    io::{BufReader, ErrorKind, Read, Write},
     path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
 use super::settings::Settings;
@@ -13,29 +15,245 @@ const SETTINGS_FILE: &str = "image_sieve_settings.json";
 /// Name of the project settings file
 const ITEM_LIST_FILE: &str = "image_sieve.json";
 
+/// Name of the file marking a project as opened by an instance
+const LOCK_FILE: &str = "image_sieve.lock";
+
 /// Name of the trace file
 const TRACE_FILE: &str = "trace.txt";
 
+/// Name of the log file
+const LOG_FILE: &str = "image_sieve.log";
+
+/// Environment variable to store settings, logs and projects in a custom directory
+const CONFIG_DIR_ENV: &str = "IMAGE_SIEVE_CONFIG_DIR";
+
+/// Name of the folder in a custom config directory where projects are stored
+const PROJECTS_DIR: &str = "projects";
+
+/// Custom config directory set on the command line, takes precedence over the environment variable
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets a custom directory to store settings, logs and projects in instead of the .image_sieve folder in the
+/// home directory. Only the first call has an effect, so it must be called before any file is accessed.
+pub fn set_config_dir(config_dir: PathBuf) {
+    CONFIG_DIR.set(config_dir).ok();
+}
+
+/// Gets the custom config directory from the command line or the environment, None if the default is used
+fn get_custom_config_dir() -> Option<PathBuf> {
+    CONFIG_DIR.get().cloned().or_else(|| {
+        std::env::var_os(CONFIG_DIR_ENV)
+            .filter(|config_dir| !config_dir.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
 /// Get the directory and filename where traces are stored
 pub fn get_trace_filename() -> PathBuf {
     get_and_create_home_dir().join(TRACE_FILE)
 }
 
+/// Get the directory and filename where the log is stored
+pub fn get_log_filename() -> PathBuf {
+    get_and_create_home_dir().join(LOG_FILE)
+}
+
 /// Get the directory and filename where the settings are stored
 pub fn get_settings_filename() -> PathBuf {
     get_and_create_home_dir().join(SETTINGS_FILE)
 }
 
-/// Get the directory and filename where the item list is stored
+/// Get the directory and filename where the item list is stored. The project is stored in its directory, unless a
+/// custom config directory is used, which then contains the projects of all directories.
 pub fn get_project_filename(path: &Path) -> PathBuf {
-    Path::new(path).to_path_buf().join(ITEM_LIST_FILE)
+    match get_custom_config_dir() {
+        Some(config_dir) => {
+            let projects_dir = config_dir.join(PROJECTS_DIR);
+            fs::create_dir_all(&projects_dir).ok();
+            get_config_project_filename(&projects_dir, path)
+        }
+        None => Path::new(path).to_path_buf().join(ITEM_LIST_FILE),
+    }
+}
+
+/// Get the filename of the item list of a directory in a folder containing the projects of several directories.
+/// The name is built from the full path of the directory, so that each directory has its own project.
+fn get_config_project_filename(projects_dir: &Path, path: &Path) -> PathBuf {
+    let name: String = path
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    projects_dir.join(format!("{}_{}", name, ITEM_LIST_FILE))
+}
+
+/// Gets the folder of a project from a path to the folder or to its project file, None if the path is neither
+pub fn get_project_folder(path: &Path) -> Option<PathBuf> {
+    if path.is_dir() {
+        Some(path.to_path_buf())
+    } else if path.is_file() && path.file_name() == Some(ITEM_LIST_FILE.as_ref()) {
+        path.parent().map(Path::to_path_buf)
+    } else {
+        None
+    }
+}
+
+/// Get the directory and filename of the lock file of a project
+fn get_lock_filename(path: &Path) -> PathBuf {
+    path.join(LOCK_FILE)
+}
+
+/// Gets the id written to lock files by this instance
+fn get_lock_id() -> String {
+    std::process::id().to_string()
+}
+
+/// Locks the project in a directory for this instance. Returns false if the project is already locked by another
+/// instance, which might also be a lock left over by an instance that was not closed properly.
+/// The lock file is only created if it does not exist yet, so only one of two instances opening a project at the same
+/// time gets the lock.
+pub fn lock_project(path: &Path) -> bool {
+    let lock_file_name = get_lock_filename(path);
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&lock_file_name)
+    {
+        Ok(mut lock_file) => {
+            if let Err(e) = lock_file.write_all(get_lock_id().as_bytes()) {
+                log::warn!("Error writing lock {}: {}", lock_file_name.display(), e);
+            }
+            true
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => !is_locked_by_other(path),
+        Err(e) => {
+            log::warn!("Error writing lock {}: {}", lock_file_name.display(), e);
+            true
+        }
+    }
 }
 
+/// Locks the project in a directory for this instance, even if it is locked by another instance
+pub fn force_lock_project(path: &Path) {
+    let lock_file_name = get_lock_filename(path);
+    if let Err(e) = fs::write(&lock_file_name, get_lock_id()) {
+        log::warn!("Error writing lock {}: {}", lock_file_name.display(), e);
+    }
+}
+
+/// Checks if the project in a directory is locked by another instance
+pub fn is_locked_by_other(path: &Path) -> bool {
+    match fs::read_to_string(get_lock_filename(path)) {
+        Ok(lock_id) => lock_id.trim() != get_lock_id(),
+        Err(_) => false,
+    }
+}
+
+/// Removes the lock of the project in a directory if it is locked by this instance
+pub fn unlock_project(path: &Path) {
+    let lock_file_name = get_lock_filename(path);
+    if lock_file_name.exists() && !is_locked_by_other(path) {
+        fs::remove_file(lock_file_name).ok();
+    }
+}
+
+/// Saves an item list to the project file in its directory unless the project is locked by another instance.
+/// Returns false if the project was not saved because of the lock.
+pub fn save_project(item_list: &ItemList) -> bool {
+    if is_locked_by_other(&item_list.path) {
+        log::warn!(
+            "Project {} is in use by another instance and is not saved",
+            item_list.path.display()
+        );
+        false
+    } else {
+        JsonPersistence::save(&get_project_filename(&item_list.path), item_list);
+        true
+    }
+}
+
+/// Reader reporting the percentage of a file that was read whenever it changes
+struct ProgressReader<R: Read, F: FnMut(u64)> {
+    inner: R,
+    read: u64,
+    total: u64,
+    percent: Option<u64>,
+    progress_callback: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.read += read as u64;
+        let percent = (self.read * 100)
+            .checked_div(self.total)
+            .unwrap_or(100)
+            .min(100);
+        if self.percent != Some(percent) {
+            self.percent = Some(percent);
+            (self.progress_callback)(percent);
+        }
+        Ok(read)
+    }
+}
+
+/// Loads the item list of a project, which is deserialized while the file is read, so that large projects are not
+/// kept in memory twice. The percentage of the file that was read is reported by calling a callback function.
+pub fn load_project(file_name: &Path, progress_callback: impl FnMut(u64)) -> Option<ItemList> {
+    let file = fs::File::open(file_name).ok()?;
+    let total = file.metadata().map_or(0, |metadata| metadata.len());
+    let reader = ProgressReader {
+        inner: file,
+        read: 0,
+        total,
+        percent: None,
+        progress_callback,
+    };
+    match serde_json::from_reader::<_, ItemList>(BufReader::new(reader)) {
+        Ok(mut item_list) => {
+            for file_item in &mut item_list.items {
+                file_item.deserialized();
+            }
+            Some(item_list)
+        }
+        Err(e) => {
+            log::warn!("Error loading project {}: {}", file_name.display(), e);
+            None
+        }
+    }
+}
+
+/// Get the directory where settings, logs and traces are stored
+pub fn get_config_dir() -> PathBuf {
+    get_and_create_home_dir()
+}
+
+/// Gets the total size in bytes of all files in a directory and its subdirectories, 0 if it cannot be read
+pub fn get_directory_size(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.metadata() {
+                    Ok(metadata) if metadata.is_dir() => get_directory_size(&entry.path()),
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Gets the directory to store settings and logs in and creates it if necessary
 fn get_and_create_home_dir() -> PathBuf {
+    if let Some(config_dir) = get_custom_config_dir() {
+        fs::create_dir_all(&config_dir).ok();
+        return config_dir;
+    }
     let home = home::home_dir();
     if let Some(home) = home {
         if !Path::new(&home.join(".image_sieve")).exists() {
-            fs::create_dir_all(home.join(".image_sieve")).unwrap();            
+            fs::create_dir_all(home.join(".image_sieve")).unwrap();
         }
         home.join(".image_sieve")
     } else {
@@ -43,6 +261,33 @@ fn get_and_create_home_dir() -> PathBuf {
     }
 }
 
+/// Writes a file by writing a temporary file first and renaming it, so the file is never left half written
+fn write_atomic(file_name: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp_file_name = file_name.as_os_str().to_owned();
+    temp_file_name.push(".tmp");
+    fs::write(&temp_file_name, contents)?;
+    fs::rename(&temp_file_name, file_name)
+}
+
+/// Saves an item list unless the file already contains the same data or the project is locked by another instance.
+/// Returns true if the file was written.
+pub fn save_if_changed(file_name: &Path, item_list: &ItemList) -> bool {
+    if is_locked_by_other(&item_list.path) {
+        return false;
+    }
+    let contents = serde_json::to_string_pretty(item_list).unwrap_or_default();
+    if fs::read_to_string(file_name).unwrap_or_default() == contents {
+        return false;
+    }
+    match write_atomic(file_name, &contents) {
+        Ok(_) => true,
+        Err(e) => {
+            log::error!("Error saving project to {}: {}", file_name.display(), e);
+            false
+        }
+    }
+}
+
 /// Trait to load and save data from/to a file
 pub trait JsonPersistence
 where
@@ -68,28 +313,22 @@ impl JsonPersistence for Settings {
     /// Try saving the settings to a json file
     fn save(file_name: &Path, settings: &Settings) {
         let settings = serde_json::to_string_pretty(settings).unwrap_or_default();
-        fs::write(file_name, settings).ok();
+        if let Err(e) = write_atomic(file_name, &settings) {
+            log::error!("Error saving settings to {}: {}", file_name.display(), e);
+        }
     }
 }
 
 impl JsonPersistence for ItemList {
     fn load(file_name: &Path) -> Option<ItemList> {
-        let item_list = fs::read_to_string(file_name).unwrap_or_default();
-
-        let contents = serde_json::from_str::<ItemList>(&item_list);
-        if let Ok(mut item_list) = contents {
-            for file_item in &mut item_list.items {
-                file_item.deserialized();
-            }
-            Some(item_list)
-        } else {
-            None
-        }
+        load_project(file_name, |_| {})
     }
 
     fn save(file_name: &Path, item_list: &ItemList) {
         let item_list = serde_json::to_string_pretty(item_list).unwrap_or_default();
-        fs::write(file_name, item_list).ok();
+        if let Err(e) = write_atomic(file_name, &item_list) {
+            log::error!("Error saving project to {}: {}", file_name.display(), e);
+        }
     }
 }
 
@@ -98,7 +337,9 @@ mod tests {
     use super::*;
     use crate::item_sort_list::Event;
     use crate::item_sort_list::FileItem;
-    use crate::item_sort_list::{DirectoryNames, SieveMethod};
+    use crate::item_sort_list::{
+        CollisionPolicy, DirectoryNames, EventTimeZone, ProjectSettings, SieveMethod,
+    };
     use chrono::NaiveDate;
     use img_hash::ImageHash;
 
@@ -110,6 +351,23 @@ mod tests {
         assert!(project_filename_str.contains("test"));
         assert!(project_filename_str.contains(ITEM_LIST_FILE));
         assert!(!get_trace_filename().as_os_str().is_empty());
+        assert!(!get_log_filename().as_os_str().is_empty());
+
+        assert_eq!(
+            get_config_project_filename(Path::new("profile"), Path::new("/photos/2021")),
+            Path::new("profile").join("_photos_2021_image_sieve.json")
+        );
+    }
+
+    #[test]
+    fn test_get_directory_size() {
+        let path = Path::new("test_directory_size");
+        fs::create_dir_all(path.join("sub")).unwrap();
+        fs::write(path.join("a.txt"), "abc").unwrap();
+        fs::write(path.join("sub").join("b.txt"), "defgh").unwrap();
+        assert_eq!(get_directory_size(path), 8);
+        fs::remove_dir_all(path).unwrap();
+        assert_eq!(get_directory_size(path), 0);
     }
 
     #[test]
@@ -123,13 +381,18 @@ mod tests {
                 name: String::from("Test1"),
                 start_date: NaiveDate::from_ymd_opt(2021, 9, 14).unwrap(),
                 end_date: NaiveDate::from_ymd_opt(2021, 9, 14).unwrap(),
+                target_directory: String::new(),
+                folder: String::new(),
             }],
             path: PathBuf::from("test"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            project_settings: None,
         };
         let hash = ImageHash::<Vec<u8>>::from_bytes(&[0x64, 0x65, 0x66, 0x67])
             .unwrap()
             .to_base64();
         item_list.items[0].set_encoded_hash(&hash);
+        item_list.items[1].set_group_id(Some(2));
 
         JsonPersistence::save(Path::new("test_il.json"), &item_list);
 
@@ -137,11 +400,128 @@ mod tests {
         assert_eq!(loaded_item_list.path, item_list.path);
         assert_eq!(loaded_item_list.events, item_list.events);
         assert_eq!(loaded_item_list.items, item_list.items);
+        assert_eq!(loaded_item_list.items[0].get_group_id(), None);
+        assert_eq!(loaded_item_list.items[1].get_group_id(), Some(2));
+        assert_eq!(loaded_item_list.project_settings, None);
+
+        // Settings of the project are stored with it
+        let project_settings = ProjectSettings {
+            target_directory: String::from("target"),
+            sieve_method: SieveMethod::Move,
+            timestamp_max_diff: 10,
+            hash_max_diff: 20,
+        };
+        item_list.project_settings = Some(project_settings.clone());
+        JsonPersistence::save(Path::new("test_il.json"), &item_list);
+        let loaded_item_list: ItemList = JsonPersistence::load(Path::new("test_il.json")).unwrap();
+        assert_eq!(loaded_item_list.project_settings, Some(project_settings));
 
         let loaded_item_list: Option<ItemList> = JsonPersistence::load(Path::new("invalid.json"));
         assert!(loaded_item_list.is_none());
     }
 
+    #[test]
+    fn test_save_if_changed() {
+        let file_name = Path::new("test_auto_save.json");
+        let mut item_list = ItemList::new();
+        item_list
+            .items
+            .push(FileItem::dummy("test/test1.jpg", 0, true));
+
+        assert!(save_if_changed(file_name, &item_list));
+        assert!(!save_if_changed(file_name, &item_list));
+        assert!(!Path::new("test_auto_save.json.tmp").exists());
+
+        item_list.items[0].set_take_over(false);
+        assert!(save_if_changed(file_name, &item_list));
+        let loaded_item_list: ItemList = JsonPersistence::load(file_name).unwrap();
+        assert!(!loaded_item_list.items[0].get_take_over());
+        fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_load_project_progress() {
+        let file_name = Path::new("test_load_progress.json");
+        let mut item_list = ItemList::new();
+        for index in 0..100 {
+            item_list.items.push(FileItem::dummy(
+                &format!("test/test{}.jpg", index),
+                index,
+                true,
+            ));
+        }
+        JsonPersistence::save(file_name, &item_list);
+
+        let mut progress = vec![];
+        let loaded_item_list = load_project(file_name, |percent| progress.push(percent)).unwrap();
+        assert_eq!(loaded_item_list.items.len(), 100);
+        assert!(progress.len() > 1);
+        assert!(progress
+            .windows(2)
+            .all(|percents| percents[0] < percents[1]));
+        assert_eq!(progress.last(), Some(&100));
+        fs::remove_file(file_name).unwrap();
+
+        assert!(load_project(file_name, |_| panic!("No progress expected")).is_none());
+    }
+
+    #[test]
+    fn test_get_project_folder() {
+        let path = Path::new("test_project_folder");
+        fs::create_dir_all(path).unwrap();
+        fs::write(get_project_filename(path), "{}").unwrap();
+        fs::write(path.join("other.json"), "{}").unwrap();
+
+        assert_eq!(get_project_folder(path), Some(path.to_path_buf()));
+        assert_eq!(
+            get_project_folder(&get_project_filename(path)),
+            Some(path.to_path_buf())
+        );
+        assert_eq!(get_project_folder(&path.join("other.json")), None);
+        assert_eq!(get_project_folder(&path.join("not_there")), None);
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_lock_project() {
+        let path = Path::new("test_lock");
+        fs::create_dir_all(path).unwrap();
+        let mut item_list = ItemList::new();
+        item_list.path = path.to_path_buf();
+        item_list
+            .items
+            .push(FileItem::dummy("test_lock/test1.jpg", 0, true));
+
+        assert!(lock_project(path));
+        assert!(lock_project(path));
+        assert!(!is_locked_by_other(path));
+        unlock_project(path);
+        assert!(!get_lock_filename(path).exists());
+
+        // Locked by another instance, the project is not saved and the lock is kept
+        fs::write(get_lock_filename(path), "0").unwrap();
+        assert!(is_locked_by_other(path));
+        assert!(!lock_project(path));
+        assert!(!save_project(&item_list));
+        assert!(!get_project_filename(path).exists());
+        assert!(!save_if_changed(&get_project_filename(path), &item_list));
+        unlock_project(path);
+        assert!(get_lock_filename(path).exists());
+
+        force_lock_project(path);
+        assert!(!is_locked_by_other(path));
+        assert!(save_project(&item_list));
+        assert!(get_project_filename(path).exists());
+        unlock_project(path);
+
+        // A lock file created by another instance that did not write its id yet also locks the project
+        fs::write(get_lock_filename(path), "").unwrap();
+        assert!(!lock_project(path));
+        fs::remove_file(get_lock_filename(path)).unwrap();
+        fs::remove_dir_all(path).unwrap();
+    }
+
     #[test]
     fn test_load_save_settings() {
         let mut settings = Settings::new();
@@ -152,8 +532,51 @@ mod tests {
         settings.timestamp_max_diff += 1;
         settings.use_hash = !settings.use_hash;
         settings.hash_max_diff = 12;
+        settings.use_gps = !settings.use_gps;
+        settings.use_names = !settings.use_names;
+        settings.gps_max_distance = 250;
+        settings.hash_size = 16;
+        settings.max_group_size = 50;
+        settings.read_only = !settings.read_only;
+        settings.open_last_project = !settings.open_last_project;
+        settings.accessibility_mode = !settings.accessibility_mode;
+        settings.consecutive_only = !settings.consecutive_only;
+        settings.overview_columns = 6;
         settings.sieve_directory_names = Some(DirectoryNames::YearAndQuarter);
+        settings.collision_policy = CollisionPolicy::Skip;
         settings.dark_mode = String::from("On");
+        settings.image_display = String::from("Fill");
+        settings.default_take_over = !settings.default_take_over;
+        settings.event_time_zone = EventTimeZone::Utc;
+        settings.log_level = String::from("Debug");
+        settings.preserve_timestamps = !settings.preserve_timestamps;
+        settings.auto_save_minutes = 0;
+        settings.excluded_paths = vec![String::from("exports")];
+        settings.scan_newer_than = String::from("2021-09-01");
+        settings.take_over_shortcut = String::from("k");
+        settings.next_undecided_shortcut = String::from("u");
+        settings.loupe_shortcut = String::from("m");
+        settings.group_take_over_shortcut = String::from("h");
+        settings.pending_delete_shortcut = String::from("e");
+        settings.favorite_shortcut = String::from("v");
+        settings.discard_advance_shortcut = String::from("q");
+        settings.keep_advance_shortcut = String::from("w");
+        settings.correct_orientation = !settings.correct_orientation;
+        settings.rotate_pixels = !settings.rotate_pixels;
+        settings.jpeg_quality = 80;
+        settings.create_target_directory = !settings.create_target_directory;
+        settings.strip_metadata = !settings.strip_metadata;
+        settings.safe_move = !settings.safe_move;
+        settings.verify_copies = !settings.verify_copies;
+        settings.manifest_csv = !settings.manifest_csv;
+        settings.preserve_permissions = !settings.preserve_permissions;
+        settings.min_sieve_size_kb = 10;
+        settings.language = String::from("Deutsch");
+        settings.sort_events = !settings.sort_events;
+        settings.events_from_folders = !settings.events_from_folders;
+        settings.max_recent_projects = 3;
+        settings.low_resolution_pixels = 640;
+        settings.add_recent_project("tests");
 
         JsonPersistence::save(Path::new("test.json"), &settings);
 
@@ -162,5 +585,53 @@ mod tests {
 
         let loaded_settings: Option<Settings> = JsonPersistence::load(Path::new("invalid.json"));
         assert!(loaded_settings.is_none());
+
+        // Settings files written by older versions lack newer fields, these take the default values
+        fs::write("test_old.json", r#"{"source_directory": "source"}"#).unwrap();
+        let loaded_settings: Settings = JsonPersistence::load(Path::new("test_old.json")).unwrap();
+        assert_eq!(loaded_settings.source_directory, "source");
+        assert!(loaded_settings.default_take_over);
+        assert_eq!(loaded_settings.event_time_zone, EventTimeZone::CaptureTime);
+        assert_eq!(loaded_settings.log_level, "Warn");
+        assert!(loaded_settings.preserve_timestamps);
+        assert_eq!(loaded_settings.auto_save_minutes, 5);
+        assert!(loaded_settings.excluded_paths.is_empty());
+        assert!(loaded_settings.scan_newer_than.is_empty());
+        assert_eq!(loaded_settings.take_over_shortcut, "x");
+        assert_eq!(loaded_settings.next_undecided_shortcut, "n");
+        assert_eq!(loaded_settings.loupe_shortcut, "l");
+        assert_eq!(loaded_settings.group_take_over_shortcut, "g");
+        assert_eq!(loaded_settings.pending_delete_shortcut, "d");
+        assert_eq!(loaded_settings.favorite_shortcut, "f");
+        assert_eq!(loaded_settings.discard_advance_shortcut, "r");
+        assert_eq!(loaded_settings.keep_advance_shortcut, "k");
+        assert!(!loaded_settings.correct_orientation);
+        assert!(!loaded_settings.rotate_pixels);
+        assert_eq!(loaded_settings.jpeg_quality, 95);
+        assert!(loaded_settings.create_target_directory);
+        assert!(!loaded_settings.strip_metadata);
+        assert!(loaded_settings.safe_move);
+        assert!(!loaded_settings.verify_copies);
+        assert!(!loaded_settings.manifest_csv);
+        assert!(loaded_settings.preserve_permissions);
+        assert_eq!(loaded_settings.min_sieve_size_kb, 0);
+        assert_eq!(loaded_settings.language, "System");
+        assert!(loaded_settings.sort_events);
+        assert!(!loaded_settings.events_from_folders);
+        assert!(loaded_settings.recent_projects.is_empty());
+        assert_eq!(loaded_settings.max_recent_projects, 10);
+        assert_eq!(loaded_settings.collision_policy, CollisionPolicy::Rename);
+        assert_eq!(loaded_settings.image_display, "Fit");
+        assert!(!loaded_settings.use_gps);
+        assert!(!loaded_settings.use_names);
+        assert_eq!(loaded_settings.gps_max_distance, 100);
+        assert_eq!(loaded_settings.hash_size, 8);
+        assert_eq!(loaded_settings.max_group_size, 0);
+        assert!(!loaded_settings.read_only);
+        assert!(loaded_settings.open_last_project);
+        assert!(!loaded_settings.accessibility_mode);
+        assert_eq!(loaded_settings.low_resolution_pixels, 1024);
+        assert!(!loaded_settings.consecutive_only);
+        assert_eq!(loaded_settings.overview_columns, 4);
     }
 }
@@ -1,27 +1,20 @@
 use num_traits::{FromPrimitive, ToPrimitive};
-use slint::{Model, ModelRc, SharedString};
 
-/// Convert a value from a slint SharedString model to an enum by mapping the model index to the enum value.
-pub fn model_to_enum<Enum>(model: &ModelRc<SharedString>, value: &SharedString) -> Enum
+/// Convert the selected index of a slint combo box to an enum by mapping the index to the enum value. Unknown indices
+/// are mapped to the first enum value.
+pub fn index_to_enum<Enum>(index: i32) -> Enum
 where
     Enum: FromPrimitive,
 {
-    let mut enum_value: Enum = FromPrimitive::from_usize(0).unwrap();
-    for index in 0..model.row_count() {
-        if model.row_data(index).unwrap() == value {
-            enum_value = FromPrimitive::from_usize(index).unwrap()
-        }
-    }
-    enum_value
+    FromPrimitive::from_i32(index).unwrap_or_else(|| FromPrimitive::from_i32(0).unwrap())
 }
 
-/// Convert an enum to a value from a slint SharedString modelby mapping the enum value to the model index.
-pub fn enum_to_model<Enum>(model: &ModelRc<SharedString>, value: &Enum) -> SharedString
+/// Convert an enum to the index selected in a slint combo box by mapping the enum value to the index.
+pub fn enum_to_index<Enum>(value: &Enum) -> i32
 where
     Enum: ToPrimitive,
 {
-    let enum_value: u32 = ToPrimitive::to_u32(value).unwrap();
-    model.row_data(enum_value as usize).unwrap()
+    ToPrimitive::to_i32(value).unwrap()
 }
 
 #[cfg(test)]
@@ -29,7 +22,6 @@ mod tests {
 
     use super::*;
     use num_derive::{FromPrimitive, ToPrimitive};
-    use slint::VecModel;
 
     #[derive(PartialEq, FromPrimitive, ToPrimitive, std::fmt::Debug)]
     enum Test {
@@ -39,50 +31,18 @@ mod tests {
     }
 
     #[test]
-    fn test_model_to_enum() {
-        let model = VecModel::from(vec![
-            SharedString::from("A"),
-            SharedString::from("B"),
-            SharedString::from("C"),
-        ]);
-        let model_rc = ModelRc::new(model);
-        assert_eq!(
-            model_to_enum::<Test>(&model_rc, &SharedString::from("A")),
-            Test::A
-        );
-        assert_eq!(
-            model_to_enum::<Test>(&model_rc, &SharedString::from("B")),
-            Test::B
-        );
-        assert_eq!(
-            model_to_enum::<Test>(&model_rc, &SharedString::from("C")),
-            Test::C
-        );
-        assert_eq!(
-            model_to_enum::<Test>(&model_rc, &SharedString::from("X")),
-            Test::A
-        );
+    fn test_index_to_enum() {
+        assert_eq!(index_to_enum::<Test>(0), Test::A);
+        assert_eq!(index_to_enum::<Test>(1), Test::B);
+        assert_eq!(index_to_enum::<Test>(2), Test::C);
+        assert_eq!(index_to_enum::<Test>(3), Test::A);
+        assert_eq!(index_to_enum::<Test>(-1), Test::A);
     }
 
     #[test]
-    fn test_enum_to_model() {
-        let model = VecModel::from(vec![
-            SharedString::from("A"),
-            SharedString::from("B"),
-            SharedString::from("C"),
-        ]);
-        let model_rc = ModelRc::new(model);
-        assert_eq!(
-            enum_to_model::<Test>(&model_rc, &Test::A),
-            &SharedString::from("A")
-        );
-        assert_eq!(
-            enum_to_model::<Test>(&model_rc, &Test::B),
-            &SharedString::from("B")
-        );
-        assert_eq!(
-            enum_to_model::<Test>(&model_rc, &Test::C),
-            SharedString::from("C")
-        );
+    fn test_enum_to_index() {
+        assert_eq!(enum_to_index(&Test::A), 0);
+        assert_eq!(enum_to_index(&Test::B), 1);
+        assert_eq!(enum_to_index(&Test::C), 2);
     }
 }
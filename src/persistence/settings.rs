@@ -1,21 +1,102 @@
-use crate::item_sort_list::{DirectoryNames, SieveMethod};
-use crate::main_window::{ImageSieve, SieveComboValues};
+use crate::item_sort_list::{
+    CollisionPolicy, DirectoryNames, EventTimeZone, ProjectSettings, SieveMethod, DEFAULT_HASH_SIZE,
+};
+use crate::main_window::{self, Accessibility, ImageSieve};
+use crate::misc::combo_values::{
+    index_of, text_of, DARK_MODES, HASH_GRIDS, IMAGE_DISPLAYS, SENSITIVITIES,
+};
 use serde::{Deserialize, Serialize};
-use slint::{ComponentHandle, ModelRc, SharedString};
+use slint::{ComponentHandle, Model, ModelRc, SharedString};
+use std::path::Path;
+use std::rc::Rc;
 
-use super::model_to_enum::{enum_to_model, model_to_enum};
+use super::model_to_enum::{enum_to_index, index_to_enum};
 
-#[derive(Serialize, Deserialize, std::fmt::Debug, PartialEq, Eq)]
+/// Format of the local time a recent project was opened last
+const LAST_OPENED_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Project folder that was opened recently
+#[derive(Serialize, Deserialize, std::fmt::Debug, PartialEq, Eq, Clone)]
+pub struct RecentProject {
+    pub path: String,
+    /// Local time the project was opened last
+    pub last_opened: String,
+}
+
+#[derive(Serialize, Deserialize, std::fmt::Debug, PartialEq, Eq, Clone)]
+#[serde(default)]
 pub struct Settings {
     pub source_directory: String,
     pub target_directory: String,
     pub sieve_method: SieveMethod,
     pub use_timestamps: bool,
     pub timestamp_max_diff: i64,
+    pub consecutive_only: bool,
     pub use_hash: bool,
     pub hash_max_diff: u32,
+    /// Size of the grid the perceptual hashes are calculated with, larger grids are more precise but slower
+    pub hash_size: u32,
+    pub use_gps: bool,
+    pub gps_max_distance: u32,
+    pub use_names: bool,
+    pub max_group_size: u32,
     pub sieve_directory_names: Option<DirectoryNames>,
+    pub collision_policy: CollisionPolicy,
     pub dark_mode: String,
+    pub image_display: String,
+    pub default_take_over: bool,
+    pub sieve_shortcut: String,
+    pub browse_source_shortcut: String,
+    pub browse_target_shortcut: String,
+    pub take_over_shortcut: String,
+    pub next_undecided_shortcut: String,
+    pub loupe_shortcut: String,
+    pub group_take_over_shortcut: String,
+    pub pending_delete_shortcut: String,
+    pub favorite_shortcut: String,
+    pub discard_advance_shortcut: String,
+    pub keep_advance_shortcut: String,
+    pub event_time_zone: EventTimeZone,
+    pub log_level: String,
+    pub preserve_timestamps: bool,
+    pub correct_orientation: bool,
+    /// Rotates the pixels of copied JPEG images upright, which encodes them again
+    pub rotate_pixels: bool,
+    /// Quality from 1 to 100 used when JPEG images have to be encoded again
+    pub jpeg_quality: u8,
+    pub create_target_directory: bool,
+    pub strip_metadata: bool,
+    pub safe_move: bool,
+    pub verify_copies: bool,
+    pub manifest_csv: bool,
+    /// Keeps the permissions of the source files for copied and archived files
+    pub preserve_permissions: bool,
+    /// Kept files smaller than this size in KB are not sieved, e.g. icons and thumbnails, 0 to sieve all files
+    pub min_sieve_size_kb: u32,
+    pub language: String,
+    pub sort_events: bool,
+    /// Creates an event for each folder of the source directory when scanning, whose items belong to it
+    pub events_from_folders: bool,
+    pub recent_projects: Vec<RecentProject>,
+    pub max_recent_projects: u32,
+    pub auto_save_minutes: u32,
+    pub excluded_paths: Vec<String>,
+    /// Only files modified on or after this date are added when scanning, empty to scan all files
+    pub scan_newer_than: String,
+    pub read_only: bool,
+    /// Opens the last source directory on startup, otherwise ImageSieve starts without a project
+    pub open_last_project: bool,
+    /// Shows larger texts and controls with a high contrast
+    pub accessibility_mode: bool,
+    /// Images whose longer side has fewer pixels are marked as low resolution, 0 to mark none
+    pub low_resolution_pixels: u32,
+    pub overview_columns: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Settings {
@@ -26,54 +107,288 @@ impl Settings {
             sieve_method: SieveMethod::Copy,
             use_timestamps: true,
             timestamp_max_diff: 5,
+            consecutive_only: false,
             use_hash: false,
             hash_max_diff: 14,
+            hash_size: DEFAULT_HASH_SIZE,
+            use_gps: false,
+            gps_max_distance: 100,
+            use_names: false,
+            max_group_size: 0,
             sieve_directory_names: Some(DirectoryNames::YearAndMonth),
+            collision_policy: CollisionPolicy::Rename,
             dark_mode: String::from("Automatic"),
+            image_display: String::from("Fit"),
+            default_take_over: true,
+            sieve_shortcut: String::from("s"),
+            browse_source_shortcut: String::from("o"),
+            browse_target_shortcut: String::from("t"),
+            take_over_shortcut: String::from("x"),
+            next_undecided_shortcut: String::from("n"),
+            loupe_shortcut: String::from("l"),
+            group_take_over_shortcut: String::from("g"),
+            pending_delete_shortcut: String::from("d"),
+            favorite_shortcut: String::from("f"),
+            discard_advance_shortcut: String::from("r"),
+            keep_advance_shortcut: String::from("k"),
+            event_time_zone: EventTimeZone::CaptureTime,
+            log_level: String::from("Warn"),
+            preserve_timestamps: true,
+            correct_orientation: false,
+            rotate_pixels: false,
+            jpeg_quality: 95,
+            create_target_directory: true,
+            strip_metadata: false,
+            safe_move: true,
+            verify_copies: false,
+            manifest_csv: false,
+            preserve_permissions: true,
+            min_sieve_size_kb: 0,
+            language: String::from("System"),
+            sort_events: true,
+            events_from_folders: false,
+            recent_projects: vec![],
+            max_recent_projects: 10,
+            auto_save_minutes: 5,
+            excluded_paths: vec![],
+            scan_newer_than: String::new(),
+            read_only: false,
+            open_last_project: true,
+            accessibility_mode: false,
+            low_resolution_pixels: 1024,
+            overview_columns: 4,
         }
     }
 
     pub fn from_window(window: &ImageSieve) -> Self {
         //TODO: Also save last selected image and restart there
-        let methods: ModelRc<SharedString> = window.global::<SieveComboValues>().get_methods();
-        let directory_names: ModelRc<SharedString> =
-            window.global::<SieveComboValues>().get_directory_names();
         Settings {
             source_directory: window.get_source_directory().to_string(),
             target_directory: window.get_target_directory().to_string(),
-            sieve_method: model_to_enum(&methods, &window.get_sieve_method()),
+            sieve_method: index_to_enum(window.get_sieve_method()),
             use_timestamps: window.get_use_timestamps(),
             timestamp_max_diff: convert_timestamp_difference(&window.get_timestamp_difference())
                 .unwrap_or(5),
+            consecutive_only: window.get_consecutive_only(),
             use_hash: window.get_use_similarity(),
-            hash_max_diff: convert_sensitivity_to_u32(&window.get_similarity_sensitivity()),
-            sieve_directory_names: Some(model_to_enum(
-                &directory_names,
-                &window.get_sieve_directory_names(),
+            hash_max_diff: convert_sensitivity_to_u32(text_of(
+                SENSITIVITIES,
+                window.get_similarity_sensitivity(),
             )),
-            dark_mode: window.get_dark_mode().to_string(),
+            hash_size: convert_hash_grid_to_u32(text_of(HASH_GRIDS, window.get_hash_grid())),
+            use_gps: window.get_use_gps(),
+            gps_max_distance: window
+                .get_gps_distance()
+                .trim()
+                .parse::<u32>()
+                .unwrap_or(100),
+            use_names: window.get_use_names(),
+            max_group_size: window
+                .get_max_group_size()
+                .trim()
+                .parse::<u32>()
+                .unwrap_or(0),
+            sieve_directory_names: Some(index_to_enum(window.get_sieve_directory_names())),
+            collision_policy: index_to_enum(window.get_collision_policy()),
+            dark_mode: text_of(DARK_MODES, window.get_dark_mode()).to_string(),
+            image_display: text_of(IMAGE_DISPLAYS, window.get_image_display()).to_string(),
+            default_take_over: window.get_default_take_over(),
+            sieve_shortcut: convert_shortcut(&window.get_sieve_shortcut(), "s"),
+            browse_source_shortcut: convert_shortcut(&window.get_browse_source_shortcut(), "o"),
+            browse_target_shortcut: convert_shortcut(&window.get_browse_target_shortcut(), "t"),
+            take_over_shortcut: convert_shortcut(&window.get_take_over_shortcut(), "x"),
+            next_undecided_shortcut: convert_shortcut(&window.get_next_undecided_shortcut(), "n"),
+            loupe_shortcut: convert_shortcut(&window.get_loupe_shortcut(), "l"),
+            group_take_over_shortcut: convert_shortcut(&window.get_group_take_over_shortcut(), "g"),
+            pending_delete_shortcut: convert_shortcut(&window.get_pending_delete_shortcut(), "d"),
+            favorite_shortcut: convert_shortcut(&window.get_favorite_shortcut(), "f"),
+            discard_advance_shortcut: convert_shortcut(&window.get_discard_advance_shortcut(), "r"),
+            keep_advance_shortcut: convert_shortcut(&window.get_keep_advance_shortcut(), "k"),
+            event_time_zone: index_to_enum(window.get_event_time_zone()),
+            log_level: window.get_log_level().to_string(),
+            preserve_timestamps: window.get_preserve_timestamps(),
+            correct_orientation: window.get_correct_orientation(),
+            rotate_pixels: window.get_rotate_pixels(),
+            jpeg_quality: convert_jpeg_quality(&window.get_jpeg_quality()),
+            create_target_directory: window.get_create_target_directory(),
+            strip_metadata: window.get_strip_metadata(),
+            safe_move: window.get_safe_move(),
+            verify_copies: window.get_verify_copies(),
+            manifest_csv: window.get_manifest_csv(),
+            preserve_permissions: window.get_preserve_permissions(),
+            min_sieve_size_kb: window
+                .get_min_sieve_size()
+                .trim()
+                .parse::<u32>()
+                .unwrap_or(0),
+            language: window.get_language().to_string(),
+            sort_events: window.get_sort_events(),
+            events_from_folders: window.get_events_from_folders(),
+            recent_projects: window
+                .get_recent_projects()
+                .iter()
+                .map(|recent_project| RecentProject {
+                    path: recent_project.path.to_string(),
+                    last_opened: recent_project.last_opened.to_string(),
+                })
+                .collect(),
+            max_recent_projects: window
+                .get_max_recent_projects()
+                .trim()
+                .parse::<u32>()
+                .unwrap_or(10),
+            auto_save_minutes: window
+                .get_auto_save_interval()
+                .trim()
+                .parse::<u32>()
+                .unwrap_or(5),
+            excluded_paths: convert_excluded_paths(&window.get_excluded_paths()),
+            scan_newer_than: window.get_scan_newer_than().trim().to_string(),
+            read_only: window.get_read_only(),
+            open_last_project: window.get_open_last_project(),
+            accessibility_mode: window.get_accessibility_mode(),
+            low_resolution_pixels: window
+                .get_low_resolution_pixels()
+                .trim()
+                .parse::<u32>()
+                .unwrap_or(0),
+            overview_columns: window.get_overview_columns().max(1) as u32,
+        }
+    }
+
+    /// Gets the settings that can be overridden by a project
+    pub fn get_project_settings(&self) -> ProjectSettings {
+        ProjectSettings {
+            target_directory: self.target_directory.clone(),
+            sieve_method: self.sieve_method.clone(),
+            timestamp_max_diff: self.timestamp_max_diff,
+            hash_max_diff: self.hash_max_diff,
         }
     }
 
+    /// Overrides the settings with the settings of a project
+    pub fn set_project_settings(&mut self, project_settings: &ProjectSettings) {
+        self.target_directory = project_settings.target_directory.clone();
+        self.sieve_method = project_settings.sieve_method.clone();
+        self.timestamp_max_diff = project_settings.timestamp_max_diff;
+        self.hash_max_diff = project_settings.hash_max_diff;
+    }
+
     pub fn to_window(&self, window: &ImageSieve) {
         window.set_source_directory(SharedString::from(self.source_directory.clone()));
         window.set_target_directory(SharedString::from(self.target_directory.clone()));
-        let methods: ModelRc<SharedString> = window.global::<SieveComboValues>().get_methods();
-        window.set_sieve_method(enum_to_model(&methods, &self.sieve_method));
         window.set_use_timestamps(self.use_timestamps);
         window.set_timestamp_difference(SharedString::from(self.timestamp_max_diff.to_string()));
+        window.set_consecutive_only(self.consecutive_only);
         window.set_use_similarity(self.use_hash);
-        window.set_similarity_sensitivity(SharedString::from(convert_u32_to_sensitivity(
-            self.hash_max_diff,
-        )));
-        let directory_names: ModelRc<SharedString> =
-            window.global::<SieveComboValues>().get_directory_names();
-        let directory_name = self
+        window.set_use_gps(self.use_gps);
+        window.set_gps_distance(SharedString::from(self.gps_max_distance.to_string()));
+        window.set_use_names(self.use_names);
+        window.set_max_group_size(SharedString::from(self.max_group_size.to_string()));
+        window.set_default_take_over(self.default_take_over);
+        window.set_sieve_shortcut(SharedString::from(self.sieve_shortcut.clone()));
+        window.set_browse_source_shortcut(SharedString::from(self.browse_source_shortcut.clone()));
+        window.set_browse_target_shortcut(SharedString::from(self.browse_target_shortcut.clone()));
+        window.set_take_over_shortcut(SharedString::from(self.take_over_shortcut.clone()));
+        window
+            .set_next_undecided_shortcut(SharedString::from(self.next_undecided_shortcut.clone()));
+        window.set_loupe_shortcut(SharedString::from(self.loupe_shortcut.clone()));
+        window.set_group_take_over_shortcut(SharedString::from(
+            self.group_take_over_shortcut.clone(),
+        ));
+        window
+            .set_pending_delete_shortcut(SharedString::from(self.pending_delete_shortcut.clone()));
+        window.set_favorite_shortcut(SharedString::from(self.favorite_shortcut.clone()));
+        window.set_discard_advance_shortcut(SharedString::from(
+            self.discard_advance_shortcut.clone(),
+        ));
+        window.set_keep_advance_shortcut(SharedString::from(self.keep_advance_shortcut.clone()));
+        window.set_log_level(SharedString::from(self.log_level.clone()));
+        window.set_preserve_timestamps(self.preserve_timestamps);
+        window.set_correct_orientation(self.correct_orientation);
+        window.set_rotate_pixels(self.rotate_pixels);
+        window.set_jpeg_quality(SharedString::from(self.jpeg_quality.to_string()));
+        window.set_create_target_directory(self.create_target_directory);
+        window.set_strip_metadata(self.strip_metadata);
+        window.set_safe_move(self.safe_move);
+        window.set_verify_copies(self.verify_copies);
+        window.set_manifest_csv(self.manifest_csv);
+        window.set_preserve_permissions(self.preserve_permissions);
+        window.set_min_sieve_size(SharedString::from(self.min_sieve_size_kb.to_string()));
+        window.set_language(SharedString::from(self.language.clone()));
+        window.set_sort_events(self.sort_events);
+        window.set_events_from_folders(self.events_from_folders);
+        self.recent_projects_to_window(window);
+        window.set_max_recent_projects(SharedString::from(self.max_recent_projects.to_string()));
+        window.set_auto_save_interval(SharedString::from(self.auto_save_minutes.to_string()));
+        window.set_excluded_paths(SharedString::from(self.excluded_paths.join("; ")));
+        window.set_scan_newer_than(SharedString::from(&self.scan_newer_than));
+        window.set_read_only(self.read_only);
+        window.set_open_last_project(self.open_last_project);
+        window.set_accessibility_mode(self.accessibility_mode);
+        window
+            .set_low_resolution_pixels(SharedString::from(self.low_resolution_pixels.to_string()));
+        window
+            .global::<Accessibility>()
+            .invoke_set_enabled(self.accessibility_mode);
+        window.set_overview_columns(self.overview_columns as i32);
+        window.set_sieve_method(enum_to_index(&self.sieve_method));
+        window.set_similarity_sensitivity(index_of(
+            SENSITIVITIES,
+            convert_u32_to_sensitivity(self.hash_max_diff),
+        ));
+        window.set_hash_grid(index_of(
+            HASH_GRIDS,
+            convert_u32_to_hash_grid(self.hash_size),
+        ));
+        let directory_names = self
             .sieve_directory_names
             .as_ref()
             .unwrap_or(&DirectoryNames::YearAndMonth);
-        window.set_sieve_directory_names(enum_to_model(&directory_names, directory_name));
-        window.set_dark_mode(SharedString::from(self.dark_mode.clone()))
+        window.set_sieve_directory_names(enum_to_index(directory_names));
+        window.set_collision_policy(enum_to_index(&self.collision_policy));
+        window.set_dark_mode(index_of(DARK_MODES, &self.dark_mode));
+        window.set_image_display(index_of(IMAGE_DISPLAYS, &self.image_display));
+        window.set_event_time_zone(enum_to_index(&self.event_time_zone));
+    }
+}
+
+impl Settings {
+    /// Adds a project folder to the front of the recent projects with the current time. An older entry of the folder
+    /// is removed and only the configured number of projects is kept.
+    pub fn add_recent_project(&mut self, path: &str) {
+        self.recent_projects
+            .retain(|recent_project| recent_project.path != path);
+        self.recent_projects.insert(
+            0,
+            RecentProject {
+                path: String::from(path),
+                last_opened: chrono::Local::now().format(LAST_OPENED_FORMAT).to_string(),
+            },
+        );
+        self.recent_projects
+            .truncate(self.max_recent_projects as usize);
+    }
+
+    /// Removes the recent projects whose folder does not exist anymore
+    pub fn prune_recent_projects(&mut self) {
+        self.recent_projects
+            .retain(|recent_project| Path::new(&recent_project.path).is_dir());
+    }
+
+    /// Shows the recent projects in the window
+    pub fn recent_projects_to_window(&self, window: &ImageSieve) {
+        let recent_projects: Vec<main_window::RecentProject> = self
+            .recent_projects
+            .iter()
+            .map(|recent_project| main_window::RecentProject {
+                path: SharedString::from(&recent_project.path),
+                last_opened: SharedString::from(&recent_project.last_opened),
+            })
+            .collect();
+        window.set_recent_projects(ModelRc::from(Rc::new(slint::VecModel::from(
+            recent_projects,
+        ))));
     }
 }
 
@@ -85,7 +400,31 @@ fn convert_timestamp_difference(timestamp_difference: &str) -> Option<i64> {
     }
 }
 
-fn convert_sensitivity_to_u32(sensitivity: &str) -> u32 {
+fn convert_shortcut(shortcut: &str, default: &str) -> String {
+    match shortcut.trim().chars().next() {
+        Some(key) => key.to_lowercase().to_string(),
+        None => String::from(default),
+    }
+}
+
+fn convert_excluded_paths(excluded_paths: &str) -> Vec<String> {
+    excluded_paths
+        .split(';')
+        .map(|excluded_path| excluded_path.trim().to_string())
+        .filter(|excluded_path| !excluded_path.is_empty())
+        .collect()
+}
+
+/// Converts the entered JPEG quality to a value from 1 to 100, 95 if it is not a number
+pub fn convert_jpeg_quality(jpeg_quality: &str) -> u8 {
+    jpeg_quality
+        .trim()
+        .parse::<u8>()
+        .map_or(95, |quality| quality.clamp(1, 100))
+}
+
+/// Converts the name of a similarity sensitivity to the maximum hash difference
+pub fn convert_sensitivity_to_u32(sensitivity: &str) -> u32 {
     match sensitivity {
         "Very low" => 20,
         "Low" => 16,
@@ -96,6 +435,23 @@ fn convert_sensitivity_to_u32(sensitivity: &str) -> u32 {
     }
 }
 
+/// Converts the name of a hash grid preset to the hash size
+pub fn convert_hash_grid_to_u32(hash_grid: &str) -> u32 {
+    match hash_grid {
+        "Fast (8x4)" => 4,
+        "Precise (32x16)" => 16,
+        _ => DEFAULT_HASH_SIZE,
+    }
+}
+
+fn convert_u32_to_hash_grid(hash_size: u32) -> &'static str {
+    match hash_size {
+        0..=5 => "Fast (8x4)",
+        6..=11 => "Normal (16x8)",
+        12.. => "Precise (32x16)",
+    }
+}
+
 fn convert_u32_to_sensitivity(sensitivity: u32) -> &'static str {
     match sensitivity {
         17.. => "Very low",
@@ -129,6 +485,69 @@ mod tests {
         assert_eq!(convert_u32_to_sensitivity(10), "Very high");
         assert_eq!(convert_u32_to_sensitivity(0), "Very high");
         assert_eq!(convert_u32_to_sensitivity(11), "High");
+
+        assert_eq!(convert_hash_grid_to_u32("Fast (8x4)"), 4);
+        assert_eq!(convert_hash_grid_to_u32("Precise (32x16)"), 16);
+        assert_eq!(convert_hash_grid_to_u32("Something"), DEFAULT_HASH_SIZE);
+        assert_eq!(convert_u32_to_hash_grid(4), "Fast (8x4)");
+        assert_eq!(convert_u32_to_hash_grid(DEFAULT_HASH_SIZE), "Normal (16x8)");
+        assert_eq!(convert_u32_to_hash_grid(16), "Precise (32x16)");
+
+        assert_eq!(convert_jpeg_quality(" 80 "), 80);
+        assert_eq!(convert_jpeg_quality("0"), 1);
+        assert_eq!(convert_jpeg_quality("x"), 95);
+
+        assert_eq!(convert_shortcut("s", "x"), "s");
+        assert_eq!(convert_shortcut(" Save", "x"), "s");
+        assert_eq!(convert_shortcut("", "x"), "x");
+
+        assert_eq!(
+            convert_excluded_paths(" exports;; /tmp/test ;"),
+            vec![String::from("exports"), String::from("/tmp/test")]
+        );
+        assert!(convert_excluded_paths("").is_empty());
+    }
+
+    #[test]
+    fn project_settings() {
+        let mut settings = Settings::new();
+        let project_settings = ProjectSettings {
+            target_directory: String::from("target"),
+            sieve_method: SieveMethod::MoveAndDelete,
+            timestamp_max_diff: 42,
+            hash_max_diff: 12,
+        };
+        assert_ne!(settings.get_project_settings(), project_settings);
+        settings.set_project_settings(&project_settings);
+        assert_eq!(settings.get_project_settings(), project_settings);
+        assert_eq!(settings.target_directory, "target");
+        assert_eq!(settings.hash_max_diff, 12);
+    }
+
+    #[test]
+    fn recent_projects() {
+        let mut settings = Settings::new();
+        settings.max_recent_projects = 2;
+        settings.add_recent_project("tests");
+        settings.add_recent_project("not_there");
+        assert_eq!(settings.recent_projects[0].path, "not_there");
+        assert_eq!(settings.recent_projects[1].path, "tests");
+        assert!(!settings.recent_projects[0].last_opened.is_empty());
+
+        // Opening a project again moves it to the front, the oldest project is removed if the list is full
+        settings.add_recent_project("tests");
+        settings.add_recent_project("src");
+        let paths: Vec<&str> = settings
+            .recent_projects
+            .iter()
+            .map(|recent_project| recent_project.path.as_str())
+            .collect();
+        assert_eq!(paths, ["src", "tests"]);
+
+        settings.add_recent_project("not_there");
+        settings.prune_recent_projects();
+        assert_eq!(settings.recent_projects.len(), 1);
+        assert_eq!(settings.recent_projects[0].path, "src");
     }
 
     rusty_fork_test! {
@@ -1,6 +1,7 @@
-use crate::item_sort_list::ItemList;
-use crate::persistence::settings::Settings;
-use image_23::GenericImageView;
+use crate::item_sort_list::{get_nested_directory, is_excluded, parse_date, ItemList};
+use crate::misc::images::{get_rotation, rotate_image, ImageBuffer};
+use crate::misc::translations::{tr, tr_format};
+use crate::persistence::settings::{convert_sensitivity_to_u32, Settings};
 use img_hash::HashAlg;
 use img_hash::Hasher;
 use img_hash::HasherConfig;
@@ -9,23 +10,56 @@ use slint::ComponentHandle;
 use slint::SharedString;
 use walkdir::WalkDir;
 
-use crate::main_window::ImageSieve;
+use crate::main_window::{DuplicateSet, ImageSieve, ThresholdPreview};
 use crate::persistence::json::get_project_filename;
-use crate::persistence::json::JsonPersistence;
-use std::collections::HashMap;
+use crate::persistence::json::load_project;
+use crate::persistence::json::lock_project;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::thread;
+use std::time::SystemTime;
+
+/// Maximum timestamp differences in seconds for which the resulting groups are previewed
+const PREVIEW_TIMESTAMP_DIFFERENCES: [i64; 6] = [1, 2, 5, 10, 30, 60];
+
+/// Similarity sensitivities for which the resulting groups are previewed
+const PREVIEW_SENSITIVITIES: [&str; 5] = ["Very low", "Low", "Medium", "High", "Very high"];
 
 /// Combined path and settings used to send changes to the synchronize thread.
 enum Command {
     Stop,
-    Scan(PathBuf),
+    Cancel,
+    Scan(PathBuf, Settings),
     Similarities(Settings),
 }
 
+/// Receiver of the commands of the synchronize thread. Commands which arrive while a running command checks for an
+/// abort are kept, so that they are processed afterwards instead of being lost.
+struct CommandReceiver {
+    receiver: Receiver<Command>,
+    pending: RefCell<VecDeque<Command>>,
+}
+
+impl CommandReceiver {
+    fn new(receiver: Receiver<Command>) -> Self {
+        Self {
+            receiver,
+            pending: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    /// Gets the next command, first the kept ones. Returns None if the sender was dropped.
+    fn next(&self) -> Option<Command> {
+        let pending = self.pending.borrow_mut().pop_front();
+        pending.or_else(|| self.receiver.recv().ok())
+    }
+}
+
 /// Synchronize the item list with the state of the file system and calculate similarities in a background thread.
 pub struct Synchronizer {
     channel: Sender<Command>,
@@ -39,16 +73,16 @@ impl Synchronizer {
         std::thread::spawn({
             let handle_weak = image_sieve.as_weak();
             move || {
-                synchronize_run(item_list, &receiver, handle_weak);
+                synchronize_run(item_list, &CommandReceiver::new(receiver), handle_weak);
             }
         });
         Self { channel }
     }
 
     /// Perform synchronization of the item list with a given path in a background thread.
-    pub fn scan_path(&self, path: &Path) {
+    pub fn scan_path(&self, path: &Path, settings: Settings) {
         let path = path.to_path_buf();
-        self.channel.send(Command::Scan(path)).ok();
+        self.channel.send(Command::Scan(path, settings)).ok();
     }
 
     /// Calculate similarities in a background thread.
@@ -60,6 +94,11 @@ impl Synchronizer {
     pub fn stop(&self) {
         self.channel.send(Command::Stop).ok();
     }
+
+    /// Cancel the current scan or calculation of similarities, the thread keeps running
+    pub fn cancel(&self) {
+        self.channel.send(Command::Cancel).ok();
+    }
 }
 
 /// Dropping the object will cause the thread to exit by sending an empty path/settings command.
@@ -76,10 +115,14 @@ impl Drop for Synchronizer {
 /// similarity is calculated and afterwards the image similarity (depending on if it is enabled or not)
 fn synchronize_run(
     item_list: Arc<Mutex<ItemList>>,
-    receiver: &Receiver<Command>,
+    receiver: &CommandReceiver,
     image_sieve: slint::Weak<ImageSieve>,
 ) {
-    for command in receiver {
+    while let Some(command) = receiver.next() {
+        // A cancel request is only relevant while scanning or calculating similarities
+        if let Command::Cancel = command {
+            continue;
+        }
         // In any case, reset similarities first
         {
             let mut item_list_loc = item_list.lock().unwrap();
@@ -90,8 +133,12 @@ fn synchronize_run(
 
         match command {
             Command::Stop => break,
-            Command::Scan(path) => {
-                if scan_files(&path, item_list.clone(), &image_sieve, receiver).is_err() {
+            Command::Cancel => {}
+            Command::Scan(path, settings) => {
+                log::info!("Scanning {}", path.display());
+                if scan_files(&path, &settings, item_list.clone(), &image_sieve, receiver).is_err()
+                {
+                    log::info!("Scanning {} aborted", path.display());
                     let mut item_list_loc = item_list.lock().unwrap();
                     item_list_loc.items.clear();
                 }
@@ -109,12 +156,31 @@ fn synchronize_run(
                 if settings.use_timestamps {
                     calculate_similar_timestamps(item_list.clone(), &settings);
                 }
+                // Find similars based on the capture location, items without one are compared by time
+                if settings.use_gps {
+                    calculate_similar_locations(item_list.clone(), &settings);
+                }
+                // Relate items with the same file name in different folders, independent of their timestamps
+                if settings.use_names {
+                    item_list.lock().unwrap().find_similar_names();
+                }
+                split_large_groups(item_list.clone(), &settings, &image_sieve);
                 // Tell the GUI that this is done
                 similarities_calculated(&image_sieve, !settings.use_hash);
 
                 // Then, if enabled, find similars based on hashes. This takes some time.
                 if settings.use_hash {
-                    calculate_similar_hashes(item_list.clone(), &settings);
+                    if calculate_similar_hashes(
+                        item_list.clone(),
+                        &settings,
+                        &image_sieve,
+                        receiver,
+                    )
+                    .is_err()
+                    {
+                        log::info!("Finding similar images aborted");
+                    }
+                    split_large_groups(item_list.clone(), &settings, &image_sieve);
                     // Finally, update the GUI again with the new found similarities
                     similarities_calculated(&image_sieve, true);
                 }
@@ -138,47 +204,186 @@ fn similarities_calculated(image_sieve: &slint::Weak<ImageSieve>, finished: bool
 /// Scan files in a path, update the item list with those found files and update the GUI models with the new data
 fn scan_files(
     path: &Path,
+    settings: &Settings,
     item_list: Arc<Mutex<ItemList>>,
     image_sieve: &slint::Weak<ImageSieve>,
-    receiver: &Receiver<Command>,
+    receiver: &CommandReceiver,
 ) -> Result<(), ()> {
     let mut item_list_loc = item_list.lock().unwrap();
 
-    item_list_loc.items.clear();
+    if std::fs::read_dir(path).is_err() {
+        log::warn!("Source directory {} cannot be read", path.display());
+        item_list_loc.items.clear();
+        item_list_loc.project_settings = None;
+        source_missing(image_sieve, path);
+        return Ok(());
+    }
 
-    report_progress(image_sieve, String::from("Checking existing project..."));
+    report_progress(
+        image_sieve,
+        String::from(tr("Checking existing project...")),
+    );
     check_abort(receiver)?;
-    // Check if folder already contains an item list
-    let loaded_item_list: Option<ItemList> = JsonPersistence::load(&get_project_filename(path));
+    if !lock_project(path) {
+        log::warn!("Project {} is in use by another instance", path.display());
+        project_in_use(image_sieve, path);
+    }
+    // Check if folder already contains an item list, loading a large one takes a while
+    let project_filename = get_project_filename(path);
+    if project_filename.is_file() {
+        report_progress(image_sieve, String::from(tr("Loading project...")));
+    }
+    let loaded_item_list = load_project(&project_filename, |percent| {
+        report_progress(
+            image_sieve,
+            tr_format("Loading project... {}%", &[&percent]),
+        );
+    });
     if let Some(loaded_item_list) = loaded_item_list {
         item_list_loc.clone_from(&loaded_item_list);
-        item_list_loc.events.sort_unstable();
+        if settings.sort_events {
+            item_list_loc.events.sort_unstable();
+        }
+    } else if !item_list_loc.items.is_empty() && !item_list_loc.path.is_dir() {
+        // The previous source directory disappeared, so keep its selection and events for the new location
+        log::info!(
+            "Taking over project of {} to {}",
+            item_list_loc.path.display(),
+            path.display()
+        );
+    } else {
+        item_list_loc.items.clear();
+        item_list_loc.project_settings = None;
     }
+    if item_list_loc.path != path {
+        // Find items of a moved project relative to the new location
+        item_list_loc.relocate(path);
+    }
+    item_list_loc.event_time_zone = settings.event_time_zone.clone();
+    let excluded_paths = get_excluded_paths(path, settings, &item_list_loc);
+    let scan_newer_than = get_scan_newer_than(settings);
 
     if !item_list_loc.items.is_empty() {
-        report_progress(image_sieve, String::from("Checking existing files..."));
+        report_progress(image_sieve, String::from(tr("Checking existing files...")));
         check_abort(receiver)?;
-        // First, drain missing files
+        // First, drain missing files and files in excluded folders
         item_list_loc.drain_missing();
+        item_list_loc.drain_excluded(&excluded_paths);
     }
 
     // Now, walk dirs and synchronize each
-    for (file_counter, entry) in WalkDir::new(path).into_iter().flatten().enumerate() {
+    let walk_dir = WalkDir::new(path).into_iter().filter_entry(|entry| {
+        entry.depth() == 0
+            || !entry.file_type().is_dir()
+            || !is_excluded(entry.path(), &excluded_paths)
+    });
+    for (file_counter, entry) in walk_dir.flatten().enumerate() {
         if file_counter % 100 == 0 {
-            report_progress(image_sieve, format!("Searching {}", entry.path().display()));
+            report_progress(
+                image_sieve,
+                tr_format("Searching {}", &[&entry.path().display()]),
+            );
         }
         check_abort(receiver)?;
-        item_list_loc.check_and_add(entry.path());
+        // Older files are skipped before reading their metadata, items already in the project are kept anyway
+        if let Some(scan_newer_than) = scan_newer_than {
+            if is_modified_before(&entry, scan_newer_than) {
+                continue;
+            }
+        }
+        item_list_loc.check_and_add(entry.path(), settings.default_take_over);
     }
 
     item_list_loc.finish_synchronizing(path);
+    log::info!(
+        "Found {} items in {}",
+        item_list_loc.items.len(),
+        path.display()
+    );
+    let empty_files = item_list_loc.check_empty_files();
+    if empty_files > 0 {
+        log::warn!("Found {} empty files in {}", empty_files, path.display());
+    }
+    if settings.events_from_folders {
+        let created_events = item_list_loc.create_folder_events();
+        log::info!("Created {} events from folder names", created_events);
+        if settings.sort_events {
+            item_list_loc.events.sort_unstable();
+        }
+    }
     Ok(())
 }
 
-/// Check if an abort command was received
-fn check_abort(receiver: &Receiver<Command>) -> Result<(), ()> {
-    let command = receiver.try_recv();
-    if let Ok(Command::Stop) = command {
+/// Gets the start of the day in local time from which on modified files are scanned, None to scan all files
+fn get_scan_newer_than(settings: &Settings) -> Option<SystemTime> {
+    if settings.scan_newer_than.is_empty() {
+        return None;
+    }
+    match parse_date(&settings.scan_newer_than) {
+        Ok(date) => date
+            .and_hms_opt(0, 0, 0)?
+            .and_local_timezone(chrono::Local)
+            .earliest()
+            .map(SystemTime::from),
+        Err(error) => {
+            log::warn!("Scanning all files: {}", error);
+            None
+        }
+    }
+}
+
+/// Checks if a found file was modified before the given time, directories and files without a modification date are
+/// never skipped
+fn is_modified_before(entry: &walkdir::DirEntry, time: SystemTime) -> bool {
+    entry.file_type().is_file()
+        && entry
+            .metadata()
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .is_some_and(|modified| modified < time)
+}
+
+/// Gets the folders excluded from scanning a source directory. Besides the excluded paths of the settings, these are
+/// the target directories of the sieve and of the events inside the source directory, so that sieved files are not
+/// found again. The target directory of the project settings takes precedence over the one of the settings.
+fn get_excluded_paths(path: &Path, settings: &Settings, item_list: &ItemList) -> Vec<String> {
+    let mut excluded_paths = settings.excluded_paths.clone();
+    let target_directory = match &item_list.project_settings {
+        Some(project_settings) => &project_settings.target_directory,
+        None => &settings.target_directory,
+    };
+    let target_directories = std::iter::once(target_directory)
+        .chain(item_list.events.iter().map(|event| &event.target_directory));
+    for target_directory in target_directories {
+        if let Some(nested_directory) = get_nested_directory(path, Path::new(target_directory)) {
+            // Sieving to the source directory itself cannot be excluded
+            if nested_directory != path {
+                log::info!(
+                    "Excluding target directory {} from scanning",
+                    nested_directory.display()
+                );
+                excluded_paths.push(nested_directory.to_string_lossy().to_string());
+            }
+        }
+    }
+    excluded_paths
+}
+
+/// Check if an abort command was received. Other commands are kept to be processed afterwards, a stop command is kept
+/// as well, so that the thread exits after aborting.
+fn check_abort(receiver: &CommandReceiver) -> Result<(), ()> {
+    let mut aborted = false;
+    while let Ok(command) = receiver.receiver.try_recv() {
+        match command {
+            Command::Cancel => aborted = true,
+            Command::Stop => {
+                aborted = true;
+                receiver.pending.borrow_mut().push_front(Command::Stop);
+            }
+            command => receiver.pending.borrow_mut().push_back(command),
+        }
+    }
+    if aborted {
         Err(())
     } else {
         Ok(())
@@ -190,39 +395,93 @@ fn check_abort(receiver: &Receiver<Command>) -> Result<(), ()> {
 fn calculate_similar_timestamps(item_list: Arc<Mutex<ItemList>>, settings: &Settings) {
     {
         let mut item_list_loc = item_list.lock().unwrap();
-        item_list_loc.find_similar(settings.timestamp_max_diff);
+        item_list_loc.find_similar(settings.timestamp_max_diff, settings.consecutive_only);
     }
 }
 
-/// Calculate the similarity hashes of images in the item list and check for hashes with a given maximum distance. Does not update the GUI
-fn calculate_similar_hashes(item_list: Arc<Mutex<ItemList>>, settings: &Settings) {
-    // Collect file names which need to be hashed (those that are images and have no stored hash yet)
-    let mut image_file_names: Vec<PathBuf> = Vec::new();
+/// Find similar items based on the distance of their GPS positions or their timestamps if a position is missing
+fn calculate_similar_locations(item_list: Arc<Mutex<ItemList>>, settings: &Settings) {
+    let mut item_list_loc = item_list.lock().unwrap();
+    item_list_loc.find_similar_locations(settings.gps_max_distance, settings.timestamp_max_diff);
+}
+
+/// Split groups of similar items that are larger than the maximum group size and tell the GUI about it
+fn split_large_groups(
+    item_list: Arc<Mutex<ItemList>>,
+    settings: &Settings,
+    image_sieve: &slint::Weak<ImageSieve>,
+) {
+    let split_groups = item_list
+        .lock()
+        .unwrap()
+        .split_large_groups(settings.max_group_size as usize);
+    let hint = if split_groups > 0 {
+        log::info!(
+            "Split {} groups with more than {} items",
+            split_groups,
+            settings.max_group_size
+        );
+        tr_format(
+            "⚠ {} groups with more than {} similar items were split",
+            &[&split_groups, &settings.max_group_size],
+        )
+    } else {
+        String::new()
+    };
+    image_sieve
+        .clone()
+        .upgrade_in_event_loop(move |h| {
+            h.set_split_groups_hint(SharedString::from(hint));
+        })
+        .unwrap();
+}
+
+/// Calculate the similarity hashes of images in the item list and check for hashes with a given maximum distance.
+/// Reports the progress to the GUI and aborts if a cancel command was received. Hashes calculated until then are kept.
+fn calculate_similar_hashes(
+    item_list: Arc<Mutex<ItemList>>,
+    settings: &Settings,
+    image_sieve: &slint::Weak<ImageSieve>,
+    receiver: &CommandReceiver,
+) -> Result<(), ()> {
+    // Collect file names which need to be hashed (those that are images and have no stored hashes yet or hashes of
+    // another hash size) together with the rotation of their orientation
+    let mut image_file_names: Vec<(PathBuf, i32)> = Vec::new();
     {
         let item_list_loc = item_list.lock().unwrap();
         for item in &item_list_loc.items {
-            if (item.is_image() || item.is_raw_image()) && !item.has_hash() {
-                image_file_names.push(item.path.clone());
+            if (item.is_image() || item.is_raw_image())
+                && !item.is_corrupt()
+                && (!item.has_hash()
+                    || !item.has_rotated_hashes()
+                    || item.get_hash_size() != settings.hash_size)
+            {
+                image_file_names.push((item.path.clone(), get_rotation(item)));
             }
         }
     }
 
-    // Now calculate the hashes
-    let mut hashes: HashMap<PathBuf, ImageHash<Vec<u8>>> = HashMap::new();
-    for image_file_name in image_file_names {
-        if let Ok(image) = image_23::open(&image_file_name) {
-            // The hash size is dependent on the image orientation to increase the result quality
-            let (hash_width, hash_height) = if image.width() > image.height() {
-                (16, 8)
-            } else {
-                (8, 16)
-            };
-            // We are using the double gradient algorithm
-            let hasher: Hasher<Vec<u8>> = HasherConfig::with_bytes_type()
-                .hash_size(hash_width, hash_height)
-                .hash_alg(HashAlg::DoubleGradient)
-                .to_hasher();
-            hashes.insert(image_file_name, hasher.hash_image(&image));
+    // Now calculate the hashes of the upright image and of the three rotated variants
+    log::debug!("Calculating hashes of {} images", image_file_names.len());
+    let mut hashes: HashMap<PathBuf, Vec<ImageHash<Vec<u8>>>> = HashMap::new();
+    let image_count = image_file_names.len();
+    let mut result = Ok(());
+    for (image_index, (image_file_name, rotation)) in image_file_names.into_iter().enumerate() {
+        report_similarities_progress(
+            image_sieve,
+            tr_format(
+                "Calculating image hashes: {}%",
+                &[&(image_index * 100 / image_count)],
+            ),
+        );
+        result = check_abort(receiver);
+        if result.is_err() {
+            break;
+        }
+        if let Some(rotated_hashes) =
+            hash_image_file(&image_file_name, rotation, settings.hash_size)
+        {
+            hashes.insert(image_file_name, rotated_hashes);
         }
     }
 
@@ -230,15 +489,201 @@ fn calculate_similar_hashes(item_list: Arc<Mutex<ItemList>>, settings: &Settings
     {
         let mut item_list_loc = item_list.lock().unwrap();
         for item in &mut item_list_loc.items {
-            let hash = hashes.remove(&item.path);
-            if let Some(hash) = hash {
-                item.set_hash(hash);
+            let hashes = hashes.remove(&item.path);
+            if let Some(mut hashes) = hashes {
+                item.set_hash(hashes.remove(0));
+                item.set_rotated_hashes(hashes);
+                item.set_hash_size(settings.hash_size);
             }
         }
-        item_list_loc.find_similar_hashes(settings.hash_max_diff);
+        // Items with identical file dates could only be compared once their hashes are known
+        if settings.use_timestamps {
+            item_list_loc.separate_same_file_date_items();
+        }
+        result?;
+        item_list_loc.find_similar_hashes(settings.hash_max_diff, |percent| {
+            report_similarities_progress(
+                image_sieve,
+                tr_format("Finding similar images: {}%", &[&percent]),
+            );
+            check_abort(receiver).is_ok()
+        })
+    }
+}
+
+/// Calculate the perceptual hashes of an image file rotated by 0, 90, 180 and 270 degrees. The image is first rotated
+/// by the rotation of its orientation, so that the hashes are calculated from the image as it is displayed.
+fn hash_image_file(
+    image_file_name: &Path,
+    rotation: i32,
+    hash_size: u32,
+) -> Option<Vec<ImageHash<Vec<u8>>>> {
+    match open_image_for_hashing(image_file_name) {
+        Ok(image) => {
+            let image = rotate_image(image, rotation);
+            Some(
+                [0, 90, 180, 270]
+                    .iter()
+                    .map(|rotation| hash_image(rotate_image(image.clone(), *rotation), hash_size))
+                    .collect(),
+            )
+        }
+        Err(e) => {
+            log::warn!(
+                "Error opening {} for hashing: {}",
+                image_file_name.display(),
+                e
+            );
+            None
+        }
     }
 }
 
+/// Opens an image with the image crate version used by the hashing library. Formats this version cannot decode,
+/// like lossless WebP, are decoded with the current version of the image crate.
+fn open_image_for_hashing(image_file_name: &Path) -> Result<ImageBuffer, image::ImageError> {
+    match image_23::open(image_file_name) {
+        Ok(image) => {
+            let image = image.into_rgba8();
+            let (width, height) = image.dimensions();
+            Ok(ImageBuffer::from_raw(width, height, image.into_raw())
+                .expect("Buffer size matches the image dimensions"))
+        }
+        Err(_) => Ok(image::open(image_file_name)?.into_rgba8()),
+    }
+}
+
+/// Calculate the perceptual hash of an image with a hash grid of the given size. The image is converted to the image
+/// crate version used by the hashing library.
+fn hash_image(image: ImageBuffer, hash_size: u32) -> ImageHash<Vec<u8>> {
+    let (width, height) = image.dimensions();
+    let image = image_23::DynamicImage::ImageRgba8(
+        image_23::RgbaImage::from_raw(width, height, image.into_raw())
+            .expect("Buffer size matches the image dimensions"),
+    );
+    // The hash size is dependent on the image orientation to increase the result quality
+    let (hash_width, hash_height) = if width > height {
+        (hash_size * 2, hash_size)
+    } else {
+        (hash_size, hash_size * 2)
+    };
+    // We are using the double gradient algorithm
+    let hasher: Hasher<Vec<u8>> = HasherConfig::with_bytes_type()
+        .hash_size(hash_width, hash_height)
+        .hash_alg(HashAlg::DoubleGradient)
+        .to_hasher();
+    hasher.hash_image(&image)
+}
+
+/// Finds similar items with several thresholds in a background thread and shows the number of resulting groups
+/// in the main window. The item list is a copy, so the current groups are not changed.
+pub fn preview_thresholds(
+    item_list: ItemList,
+    consecutive_only: bool,
+    image_sieve: slint::Weak<ImageSieve>,
+) {
+    thread::spawn(move || {
+        let timestamp_previews = item_list
+            .preview_similar(&PREVIEW_TIMESTAMP_DIFFERENCES, consecutive_only)
+            .into_iter()
+            .zip(PREVIEW_TIMESTAMP_DIFFERENCES)
+            .map(|(statistics, max_diff)| (format!("⇔ {} s", max_diff), statistics));
+        let max_diffs_hash: Vec<u32> = PREVIEW_SENSITIVITIES
+            .iter()
+            .map(|sensitivity| convert_sensitivity_to_u32(sensitivity))
+            .collect();
+        let hash_previews = item_list
+            .preview_similar_hashes(&max_diffs_hash)
+            .into_iter()
+            .zip(PREVIEW_SENSITIVITIES)
+            .map(|(statistics, sensitivity)| (format!("🔀 {}", tr(sensitivity)), statistics));
+        let previews: Vec<ThresholdPreview> = timestamp_previews
+            .chain(hash_previews)
+            .map(|(threshold, statistics)| ThresholdPreview {
+                threshold: SharedString::from(threshold),
+                groups: statistics.groups as i32,
+                grouped_items: statistics.grouped_items as i32,
+                largest_group: statistics.largest_group as i32,
+            })
+            .collect();
+        image_sieve
+            .upgrade_in_event_loop(move |h| {
+                h.set_threshold_preview_model(slint::ModelRc::from(std::rc::Rc::new(
+                    slint::VecModel::from(previews),
+                )));
+                h.set_previewing_thresholds(false);
+            })
+            .unwrap();
+    });
+}
+
+/// Finds items with the same content in different directories in a background thread and lists them in the main window.
+/// The item list is a copy, so the items can be sorted meanwhile.
+pub fn find_duplicates(item_list: ItemList, image_sieve: slint::Weak<ImageSieve>) {
+    thread::spawn(move || {
+        let duplicates = item_list.find_duplicates();
+        log::info!("Found {} sets of duplicates", duplicates.len());
+        image_sieve
+            .upgrade_in_event_loop(move |h| {
+                let duplicate_sets: Vec<DuplicateSet> = duplicates
+                    .into_iter()
+                    .map(|paths| DuplicateSet {
+                        paths: slint::ModelRc::from(std::rc::Rc::new(slint::VecModel::from(
+                            paths
+                                .iter()
+                                .map(|path| SharedString::from(path.to_string_lossy().as_ref()))
+                                .collect::<Vec<SharedString>>(),
+                        ))),
+                    })
+                    .collect();
+                h.set_duplicates_model(slint::ModelRc::from(std::rc::Rc::new(
+                    slint::VecModel::from(duplicate_sets),
+                )));
+                h.set_finding_duplicates(false);
+            })
+            .unwrap();
+    });
+}
+
+/// Tell the main window that the source directory does not exist or cannot be read
+fn source_missing(image_sieve: &slint::Weak<ImageSieve>, path: &Path) {
+    let hint = tr_format(
+        "⚠ The folder {} cannot be found. It may have been moved, deleted or be on a removed drive. Relocate the source to keep your selection and events.",
+        &[&path.display()],
+    );
+    image_sieve
+        .clone()
+        .upgrade_in_event_loop({
+            move |h| {
+                h.set_source_missing(true);
+                h.set_source_hint(SharedString::from(hint));
+            }
+        })
+        .unwrap();
+}
+
+/// Tell the main window that the project is opened by another instance and will not be saved
+fn project_in_use(image_sieve: &slint::Weak<ImageSieve>, path: &Path) {
+    let hint = project_in_use_hint(path);
+    image_sieve
+        .clone()
+        .upgrade_in_event_loop({
+            move |h| {
+                h.set_project_in_use(true);
+                h.set_source_hint(SharedString::from(hint));
+            }
+        })
+        .unwrap();
+}
+
+/// Gets the hint shown while the project is opened by another instance and the changes are not saved
+pub fn project_in_use_hint(path: &Path) -> String {
+    tr_format(
+        "⚠ The project in {} is already opened in another ImageSieve instance. Your changes will not be saved unless you use the project anyway, e.g. if the other instance was not closed properly.",
+        &[&path.display()],
+    )
+}
+
 /// Report a progress string back to the main window
 fn report_progress(image_sieve: &slint::Weak<ImageSieve>, progress: String) {
     image_sieve
@@ -250,3 +695,177 @@ fn report_progress(image_sieve: &slint::Weak<ImageSieve>, progress: String) {
         })
         .unwrap();
 }
+
+/// Report the progress of calculating similarities, which is shown in the GUI while the items can already be sorted
+fn report_similarities_progress(image_sieve: &slint::Weak<ImageSieve>, progress: String) {
+    image_sieve
+        .clone()
+        .upgrade_in_event_loop({
+            move |h| {
+                h.set_similarities_progress(SharedString::from(progress));
+            }
+        })
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item_sort_list::DEFAULT_HASH_SIZE;
+
+    #[test]
+    fn test_commands_during_scan() {
+        let (sender, receiver) = mpsc::channel();
+        let receiver = CommandReceiver::new(receiver);
+        sender
+            .send(Command::Scan(PathBuf::from("tests"), Settings::new()))
+            .unwrap();
+        let Some(Command::Scan(..)) = receiver.next() else {
+            panic!("Scan expected");
+        };
+
+        // A rescan and a threshold change during the scan do not abort it and are processed afterwards
+        sender
+            .send(Command::Scan(PathBuf::from("other"), Settings::new()))
+            .unwrap();
+        sender.send(Command::Similarities(Settings::new())).unwrap();
+        assert!(check_abort(&receiver).is_ok());
+        assert!(
+            matches!(receiver.next(), Some(Command::Scan(path, _)) if path == Path::new("other"))
+        );
+        assert!(matches!(receiver.next(), Some(Command::Similarities(_))));
+
+        // A cancel aborts the scan and is dropped, a scan requested afterwards is processed
+        sender.send(Command::Cancel).unwrap();
+        sender
+            .send(Command::Scan(PathBuf::from("tests"), Settings::new()))
+            .unwrap();
+        assert!(check_abort(&receiver).is_err());
+        assert!(
+            matches!(receiver.next(), Some(Command::Scan(path, _)) if path == Path::new("tests"))
+        );
+        assert!(check_abort(&receiver).is_ok());
+
+        // A stop aborts and is processed afterwards, so that the thread exits
+        sender.send(Command::Similarities(Settings::new())).unwrap();
+        sender.send(Command::Stop).unwrap();
+        assert!(check_abort(&receiver).is_err());
+        assert!(matches!(receiver.next(), Some(Command::Stop)));
+        assert!(matches!(receiver.next(), Some(Command::Similarities(_))));
+        drop(sender);
+        assert!(receiver.next().is_none());
+    }
+
+    #[test]
+    fn test_hash_image_file() {
+        // The portrait image has an orientation of 90 degrees, the upright image contains the same picture rotated
+        let mut item_list = ItemList::new();
+        item_list.add_item(Path::new("tests/test_portrait.jpg"), true, "");
+        item_list.add_item(Path::new("tests/test_upright.jpg"), true, "");
+        let (rotated_item, upright_item) = (&item_list.items[0], &item_list.items[1]);
+        assert_eq!(get_rotation(rotated_item), 90);
+        assert_eq!(get_rotation(upright_item), 0);
+
+        let rotated_hashes = hash_image_file(
+            &rotated_item.path,
+            get_rotation(rotated_item),
+            DEFAULT_HASH_SIZE,
+        )
+        .unwrap();
+        let upright_hashes = hash_image_file(
+            &upright_item.path,
+            get_rotation(upright_item),
+            DEFAULT_HASH_SIZE,
+        )
+        .unwrap();
+
+        // Both images are hashed as they are displayed, so the hashes match
+        assert!(rotated_hashes[0].dist(&upright_hashes[0]) <= 2);
+        // Without the rotation of the orientation, the hashes differ
+        let unrotated_hashes = hash_image_file(&rotated_item.path, 0, DEFAULT_HASH_SIZE).unwrap();
+        assert!(unrotated_hashes[0].dist(&upright_hashes[0]) > 10);
+
+        // Lossless WebP images are decoded although the hashing library does not support them
+        let webp_hashes =
+            hash_image_file(Path::new("tests/test_lossless.webp"), 90, DEFAULT_HASH_SIZE).unwrap();
+        assert!(webp_hashes[0].dist(&upright_hashes[0]) <= 2);
+
+        // A larger hash size results in more hash bits
+        let precise_hashes = hash_image_file(&upright_item.path, 0, 16).unwrap();
+        assert!(precise_hashes[0].as_bytes().len() > upright_hashes[0].as_bytes().len());
+
+        assert!(
+            hash_image_file(Path::new("tests/not_existing.jpg"), 0, DEFAULT_HASH_SIZE).is_none()
+        );
+    }
+    #[test]
+    fn test_get_excluded_paths() {
+        let source = std::env::current_dir().unwrap().join("tests");
+        let mut settings = Settings::new();
+        settings.excluded_paths = vec![String::from("exports")];
+        let mut item_list = ItemList::new();
+        let mut event = crate::item_sort_list::Event::new("Event", "2021-09-14", "2021-09-15");
+        event.target_directory = source.join("subdir").to_string_lossy().to_string();
+        item_list.events.push(event);
+
+        // Target directories inside the source directory are excluded
+        settings.target_directory = source.join("sieved").to_string_lossy().to_string();
+        let excluded_paths = get_excluded_paths(&source, &settings, &item_list);
+        assert_eq!(excluded_paths.len(), 3);
+        assert!(is_excluded(&source.join("sieved/2021-09"), &excluded_paths));
+        assert!(is_excluded(&source.join("subdir"), &excluded_paths));
+        assert!(!is_excluded(&source.join("other"), &excluded_paths));
+
+        // Target directories outside or equal to the source directory are not excluded
+        item_list.events.clear();
+        settings.target_directory = source.to_string_lossy().to_string();
+        assert_eq!(
+            get_excluded_paths(&source, &settings, &item_list),
+            vec![String::from("exports")]
+        );
+        settings.target_directory = std::env::temp_dir().to_string_lossy().to_string();
+        assert_eq!(get_excluded_paths(&source, &settings, &item_list).len(), 1);
+
+        // The target directory of the project settings is used instead of the one of the settings
+        let mut project_settings = settings.get_project_settings();
+        project_settings.target_directory = source.join("sieved").to_string_lossy().to_string();
+        item_list.project_settings = Some(project_settings);
+        let excluded_paths = get_excluded_paths(&source, &settings, &item_list);
+        assert_eq!(excluded_paths.len(), 2);
+        assert!(is_excluded(&source.join("sieved"), &excluded_paths));
+    }
+
+    #[test]
+    fn test_scan_newer_than() {
+        let mut settings = Settings::new();
+        assert!(get_scan_newer_than(&settings).is_none());
+        settings.scan_newer_than = String::from("not a date");
+        assert!(get_scan_newer_than(&settings).is_none());
+
+        settings.scan_newer_than = String::from("01.09.2021");
+        let scan_newer_than = get_scan_newer_than(&settings).unwrap();
+        let expected = chrono::NaiveDate::from_ymd_opt(2021, 9, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        assert_eq!(scan_newer_than, SystemTime::from(expected));
+
+        let entries: Vec<walkdir::DirEntry> = WalkDir::new("tests/test.jpg")
+            .into_iter()
+            .chain(WalkDir::new("tests").max_depth(0))
+            .flatten()
+            .collect();
+        let (file, directory) = (&entries[0], &entries[1]);
+        assert!(is_modified_before(
+            file,
+            SystemTime::now() + std::time::Duration::from_secs(60)
+        ));
+        assert!(!is_modified_before(file, SystemTime::UNIX_EPOCH));
+        assert!(!is_modified_before(
+            directory,
+            SystemTime::now() + std::time::Duration::from_secs(60)
+        ));
+    }
+}